@@ -7,7 +7,8 @@
 
 use persistence::{load_analytics, save_analytics, AnalyticsData};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use crate::notion_url::canonical_key;
+use std::collections::{HashMap, HashSet};
 use tauri::{AppHandle, Manager, Runtime};
 
 /// Page view analytics.
@@ -82,6 +83,39 @@ pub fn record_page_view<R: Runtime>(
     Ok(())
 }
 
+/// Sets the title of page views for `page_url`.
+///
+/// The most recent view always gets the new title; older views only if they
+/// still carry the title derived from the URL. Returns true if any view changed.
+pub fn apply_page_title(views: &mut [PageView], page_url: &str, title: &str) -> bool {
+    let fallback = crate::handlers::title::slug_title(page_url);
+    let mut changed = false;
+    let mut latest = true;
+
+    for view in views
+        .iter_mut()
+        .rev()
+        .filter(|v| crate::notion_url::same_page(&v.page_url, page_url))
+    {
+        if (latest || view.page_title == fallback) && view.page_title != title {
+            view.page_title = title.to_string();
+            changed = true;
+        }
+        latest = false;
+    }
+
+    changed
+}
+
+/// Updates the title of recorded views once the page's real title is known.
+pub fn update_page_title<R: Runtime>(app: &AppHandle<R>, page_url: &str, title: &str) -> Result<(), String> {
+    let mut analytics = load_analytics(app).unwrap_or_default();
+    if apply_page_title(&mut analytics.views, page_url, title) {
+        save_analytics(app, &analytics).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
 /// Records a page edit.
 #[tauri::command]
 pub fn record_page_edit<R: Runtime>(
@@ -112,32 +146,36 @@ pub fn record_page_edit<R: Runtime>(
     Ok(())
 }
 
+/// Counts views per page, most viewed first.
+///
+/// Views of the same page under different URLs are counted together and
+/// reported under the URL of the most recent view.
+pub fn most_viewed_pages(views: &[PageView], limit: usize) -> Vec<(String, u64)> {
+    let mut view_counts: HashMap<String, (String, u64)> = HashMap::new();
+    for view in views {
+        let entry = view_counts
+            .entry(canonical_key(&view.page_url))
+            .or_insert_with(|| (String::new(), 0));
+        entry.0 = view.page_url.clone();
+        entry.1 += 1;
+    }
+
+    let mut most_viewed: Vec<(String, u64)> = view_counts.into_values().collect();
+    most_viewed.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    most_viewed.truncate(limit);
+    most_viewed
+}
+
 /// Gets usage statistics.
 #[tauri::command]
 pub fn get_usage_stats<R: Runtime>(app: AppHandle<R>) -> Result<UsageStats, String> {
     let analytics = load_analytics(&app).unwrap_or_default();
     
-    // Count views per page
-    let mut view_counts: HashMap<String, (String, u64)> = HashMap::new();
-    for view in &analytics.views {
-        let entry = view_counts.entry(view.page_url.clone()).or_insert_with(|| {
-            (view.page_title.clone(), 0)
-        });
-        entry.1 += 1;
-    }
-    
-    let mut most_viewed: Vec<(String, u64)> = view_counts
-        .into_iter()
-        .map(|(url, (_, count))| (url, count))
-        .collect();
-    most_viewed.sort_by(|a, b| b.1.cmp(&a.1));
-    most_viewed.truncate(10);
-    
-    // Get unique pages count
+    let most_viewed = most_viewed_pages(&analytics.views, 10);
     let unique_pages: HashSet<String> = analytics
         .views
         .iter()
-        .map(|v| v.page_url.clone())
+        .map(|v| canonical_key(&v.page_url))
         .collect();
     
     // Get recent activity
@@ -172,13 +210,6 @@ pub fn get_usage_stats<R: Runtime>(app: AppHandle<R>) -> Result<UsageStats, Stri
     recent_activity.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
     recent_activity.truncate(50);
     
-    // Get unique pages count
-    let unique_pages: std::collections::HashSet<String> = analytics
-        .views
-        .iter()
-        .map(|v| v.page_url.clone())
-        .collect();
-    
     Ok(UsageStats {
         total_views: analytics.views.len() as u64,
         total_edits: analytics.edits.len() as u64,
@@ -1,6 +1,6 @@
 //! Tests for page analytics.
 
-use super::{PageView, PageEdit, ActivityEntry, UsageStats};
+use super::{apply_page_title, PageView, PageEdit, ActivityEntry, UsageStats};
 
 #[test]
 fn test_page_view_creation() {
@@ -60,3 +60,23 @@ fn test_usage_stats_structure() {
     assert_eq!(stats.total_edits, 50);
     assert_eq!(stats.total_pages, 10);
 }
+
+#[test]
+fn test_apply_page_title_keeps_older_real_titles() {
+    let url = "https://www.notion.so/Roadmap-8f3a1b2c4d5e6f708192a3b4c5d6e7f8";
+    let view = |title: &str, viewed_at| PageView {
+        page_id: "roadmap".to_string(),
+        page_url: url.to_string(),
+        page_title: title.to_string(),
+        viewed_at,
+        duration: None,
+    };
+    let mut views = vec![view("Roadmap 2024", 1000), view("Roadmap", 2000), view("Roadmap", 3000)];
+
+    assert!(apply_page_title(&mut views, &format!("{}?pvs=4", url), "Roadmap 2025"));
+    assert_eq!(views[0].page_title, "Roadmap 2024");
+    assert_eq!(views[1].page_title, "Roadmap 2025");
+    assert_eq!(views[2].page_title, "Roadmap 2025");
+
+    assert!(!apply_page_title(&mut views, url, "Roadmap 2025"));
+}
@@ -57,6 +57,21 @@ pub fn init(app: &App) -> Result<(), Box<dyn std::error::Error>> {
     // Register shortcuts from settings
     shortcuts::register_with_settings(app, &settings.shortcuts)?;
     
+    // Track real page titles for history and analytics
+    if let Some(window) = app.get_webview_window("main") {
+        crate::handlers::title::watch_titles(&window);
+    }
+    
+    // Save webview downloads through the download manager
+    if let Some(window) = app.get_webview_window("main") {
+        crate::handlers::downloads::watch_downloads(&window);
+    }
+    
+    // Put the main window back where it was
+    if let Some(window) = app.get_webview_window("main") {
+        crate::monitor::restore_window(&window);
+    }
+    
     // Apply theme
     if let Some(window) = app.get_webview_window("main") {
         let theme = settings.theme.clone();
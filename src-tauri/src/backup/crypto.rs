@@ -0,0 +1,205 @@
+//! Passphrase encryption for backups and settings exports.
+//!
+//! The key is derived from the passphrase with Argon2id and the data is
+//! sealed with XChaCha20-Poly1305. An encrypted file is a fixed header
+//! followed by the ciphertext:
+//!
+//! | bytes | field                                            |
+//! |-------|--------------------------------------------------|
+//! | 8     | magic `NOTIVEEB`                                 |
+//! | 1     | format version                                   |
+//! | 12    | Argon2 memory (KiB), iterations, lanes (u32 LE)  |
+//! | 16    | salt                                             |
+//! | 24    | nonce                                            |
+//! | 16    | key check                                        |
+//!
+//! The whole header is authenticated along with the data. The key check is
+//! derived together with the key, so a wrong passphrase can be told apart
+//! from a file whose contents were modified after it was encrypted. (A
+//! modified salt or cost changes the derived key, so it reads as a wrong
+//! passphrase.)
+
+#[cfg(test)]
+mod tests;
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+const MAGIC: &[u8; 8] = b"NOTIVEEB";
+const VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const CHECK_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+const HEADER_LEN: usize = MAGIC.len() + 1 + 12 + SALT_LEN + NONCE_LEN + CHECK_LEN;
+
+/// Prefix of encrypted data passed around as text.
+const ARMOR_PREFIX: &str = "notive-encrypted:";
+
+/// Shortest passphrase accepted for encrypting.
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+/// Largest Argon2 cost accepted when decrypting, so a crafted file cannot
+/// make Notive allocate gigabytes or spin for minutes.
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 16;
+const MAX_LANES: u32 = 16;
+
+pub const WRONG_PASSPHRASE: &str = "Wrong passphrase";
+pub const TAMPERED: &str = "The encrypted file is damaged or has been tampered with";
+pub const PASSPHRASE_REQUIRED: &str = "This file is encrypted; a passphrase is required";
+
+/// Argon2id cost parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub lanes: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        // OWASP's recommended Argon2id baseline, with room to spare
+        Self {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            lanes: 1,
+        }
+    }
+}
+
+/// Checks whether data is in the encrypted format.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Derives the encryption key and the key check from a passphrase.
+fn derive(passphrase: &str, salt: &[u8], params: KdfParams) -> Result<([u8; KEY_LEN], [u8; CHECK_LEN]), String> {
+    let argon_params = Params::new(params.memory_kib, params.iterations, params.lanes, Some(KEY_LEN + CHECK_LEN))
+        .map_err(|e| format!("Invalid key derivation parameters: {}", e))?;
+    let mut output = [0u8; KEY_LEN + CHECK_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut output)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+
+    let mut key = [0u8; KEY_LEN];
+    let mut check = [0u8; CHECK_LEN];
+    key.copy_from_slice(&output[..KEY_LEN]);
+    check.copy_from_slice(&output[KEY_LEN..]);
+    output.fill(0);
+    Ok((key, check))
+}
+
+/// Encrypts with the given salt, nonce and parameters. Reusing a salt and
+/// nonce is only ever right for test vectors; use [`encrypt`].
+pub fn encrypt_with(
+    plaintext: &[u8],
+    passphrase: &str,
+    params: KdfParams,
+    salt: &[u8; SALT_LEN],
+    nonce: &[u8; NONCE_LEN],
+) -> Result<Vec<u8>, String> {
+    let (mut key, check) = derive(passphrase, salt, params)?;
+
+    let mut out = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+    out.extend_from_slice(MAGIC);
+    out.push(VERSION);
+    for value in [params.memory_kib, params.iterations, params.lanes] {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    out.extend_from_slice(salt);
+    out.extend_from_slice(nonce);
+    out.extend_from_slice(&check);
+
+    let cipher = XChaCha20Poly1305::new((&key).into());
+    key.fill(0);
+    let sealed = cipher
+        .encrypt(XNonce::from_slice(nonce), Payload { msg: plaintext, aad: &out })
+        .map_err(|_| "Encryption failed".to_string())?;
+    out.extend_from_slice(&sealed);
+    Ok(out)
+}
+
+/// Encrypts data with a passphrase, using a fresh salt and nonce.
+pub fn encrypt(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!("The passphrase must be at least {} characters", MIN_PASSPHRASE_LEN));
+    }
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+    encrypt_with(plaintext, passphrase, KdfParams::default(), &salt, &nonce)
+}
+
+/// Decrypts data encrypted with [`encrypt`].
+pub fn decrypt(data: &[u8], passphrase: &str) -> Result<Vec<u8>, String> {
+    if !is_encrypted(data) {
+        return Err("Not an encrypted Notive file".to_string());
+    }
+    if data.len() < HEADER_LEN + 16 {
+        return Err(TAMPERED.to_string());
+    }
+    let (header, sealed) = data.split_at(HEADER_LEN);
+    let version = header[MAGIC.len()];
+    if version != VERSION {
+        return Err(format!("Unsupported encryption version {}; please update Notive", version));
+    }
+
+    let field = |index: usize| {
+        let start = MAGIC.len() + 1 + index * 4;
+        u32::from_le_bytes([header[start], header[start + 1], header[start + 2], header[start + 3]])
+    };
+    let params = KdfParams {
+        memory_kib: field(0),
+        iterations: field(1),
+        lanes: field(2),
+    };
+    if params.memory_kib > MAX_MEMORY_KIB || params.iterations > MAX_ITERATIONS || params.lanes > MAX_LANES {
+        return Err(TAMPERED.to_string());
+    }
+    let salt_start = MAGIC.len() + 1 + 12;
+    let salt = &header[salt_start..salt_start + SALT_LEN];
+    let nonce = &header[salt_start + SALT_LEN..salt_start + SALT_LEN + NONCE_LEN];
+    let stored_check = &header[HEADER_LEN - CHECK_LEN..];
+
+    let (mut key, check) = derive(passphrase, salt, params).map_err(|_| TAMPERED.to_string())?;
+    if check != stored_check {
+        key.fill(0);
+        return Err(WRONG_PASSPHRASE.to_string());
+    }
+    let cipher = XChaCha20Poly1305::new((&key).into());
+    key.fill(0);
+    cipher
+        .decrypt(XNonce::from_slice(nonce), Payload { msg: sealed, aad: header })
+        .map_err(|_| TAMPERED.to_string())
+}
+
+/// Decrypts data if it is encrypted, and passes it through otherwise.
+pub fn open(data: Vec<u8>, passphrase: Option<&str>) -> Result<Vec<u8>, String> {
+    if !is_encrypted(&data) {
+        return Ok(data);
+    }
+    match passphrase {
+        Some(passphrase) if !passphrase.is_empty() => decrypt(&data, passphrase),
+        _ => Err(PASSPHRASE_REQUIRED.to_string()),
+    }
+}
+
+/// Encodes encrypted data as text.
+pub fn armor(data: &[u8]) -> String {
+    format!("{}{}", ARMOR_PREFIX, base64::engine::general_purpose::STANDARD.encode(data))
+}
+
+/// Decodes text made by [`armor`]; `None` if the text is not armored.
+pub fn dearmor(text: &str) -> Option<Result<Vec<u8>, String>> {
+    let encoded = text.trim().strip_prefix(ARMOR_PREFIX)?;
+    Some(
+        base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| TAMPERED.to_string()),
+    )
+}
@@ -0,0 +1,102 @@
+//! Tests for backup encryption.
+
+use super::*;
+
+/// Cheap parameters so the tests run quickly; real files use the defaults.
+const TEST_PARAMS: KdfParams = KdfParams {
+    memory_kib: 64,
+    iterations: 1,
+    lanes: 1,
+};
+
+const PASSPHRASE: &str = "correct horse battery staple";
+
+/// "Meeting notes" encrypted with [`PASSPHRASE`], [`TEST_PARAMS`], a salt of
+/// sixteen 0x07 bytes and a nonce of twenty-four 0x09 bytes. Cross-checked
+/// against an independent Argon2id and XChaCha20-Poly1305 implementation.
+const VECTOR: &str = "4e4f5449564545420140000000010000000100000007070707070707070707070707070707\
+                      090909090909090909090909090909090909090909090909b54603c7dfb9277bc162130064\
+                      b40b1923dc548ac3d6e943f32116105a32ea0182d4002ad38c385c1bb4d999fa";
+
+fn vector() -> Vec<u8> {
+    hex::decode(VECTOR).unwrap()
+}
+
+#[test]
+fn test_encrypt_matches_vector() {
+    let encrypted = encrypt_with(b"Meeting notes", PASSPHRASE, TEST_PARAMS, &[7; 16], &[9; 24]).unwrap();
+    assert_eq!(hex::encode(encrypted), VECTOR);
+}
+
+#[test]
+fn test_decrypt_vector() {
+    assert_eq!(decrypt(&vector(), PASSPHRASE).unwrap(), b"Meeting notes");
+}
+
+#[test]
+fn test_wrong_passphrase() {
+    assert_eq!(decrypt(&vector(), "incorrect horse").unwrap_err(), WRONG_PASSPHRASE);
+}
+
+#[test]
+fn test_tampered_ciphertext_header_and_truncation() {
+    let mut ciphertext = vector();
+    let last = ciphertext.len() - 1;
+    ciphertext[last] ^= 1;
+    assert_eq!(decrypt(&ciphertext, PASSPHRASE).unwrap_err(), TAMPERED);
+
+    // The nonce is covered by the tag even though the key check still matches
+    let mut header = vector();
+    header[MAGIC.len() + 1 + 12 + SALT_LEN] ^= 1;
+    assert_eq!(decrypt(&header, PASSPHRASE).unwrap_err(), TAMPERED);
+
+    let truncated = &vector()[..HEADER_LEN + 4];
+    assert_eq!(decrypt(truncated, PASSPHRASE).unwrap_err(), TAMPERED);
+}
+
+#[test]
+fn test_rejects_excessive_kdf_cost() {
+    let mut data = vector();
+    let memory = MAGIC.len() + 1;
+    data[memory..memory + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(decrypt(&data, PASSPHRASE).unwrap_err(), TAMPERED);
+}
+
+#[test]
+fn test_rejects_unknown_version() {
+    let mut data = vector();
+    data[MAGIC.len()] = VERSION + 1;
+    assert!(decrypt(&data, PASSPHRASE).unwrap_err().contains("Unsupported"));
+}
+
+#[test]
+fn test_encrypt_round_trip_with_fresh_salt_and_nonce() {
+    let first = encrypt(b"{\"zoom_level\":1.0}", PASSPHRASE).unwrap();
+    let second = encrypt(b"{\"zoom_level\":1.0}", PASSPHRASE).unwrap();
+
+    assert!(is_encrypted(&first));
+    assert_ne!(first, second);
+    assert_eq!(decrypt(&first, PASSPHRASE).unwrap(), b"{\"zoom_level\":1.0}");
+}
+
+#[test]
+fn test_encrypt_rejects_short_passphrase() {
+    assert!(encrypt(b"data", "short").is_err());
+}
+
+#[test]
+fn test_open_passes_plain_data_through() {
+    assert_eq!(open(b"{}".to_vec(), None).unwrap(), b"{}");
+    assert_eq!(open(vector(), None).unwrap_err(), PASSPHRASE_REQUIRED);
+    assert_eq!(open(vector(), Some("")).unwrap_err(), PASSPHRASE_REQUIRED);
+    assert_eq!(open(vector(), Some(PASSPHRASE)).unwrap(), b"Meeting notes");
+}
+
+#[test]
+fn test_armor_round_trip() {
+    let text = armor(&vector());
+    assert!(text.starts_with("notive-encrypted:"));
+    assert_eq!(dearmor(&text).unwrap().unwrap(), vector());
+    assert!(dearmor("{\"zoom_level\": 1.0}").is_none());
+    assert_eq!(dearmor("notive-encrypted:!!").unwrap().unwrap_err(), TAMPERED);
+}
@@ -0,0 +1,353 @@
+//! Full-profile backups.
+//!
+//! A backup is a zip archive holding every store file under `stores/`, a
+//! `manifest.json` with the format version and a SHA-256 checksum for each
+//! file, and, only when asked for, the keyring secrets in `secrets.json`.
+//!
+//! Restoring works per category: each category owns whole store files or,
+//! for the parts of `settings.json` managed by other modules, single keys.
+//! Replacing a category makes it match the backup exactly; merging keeps
+//! everything already present and adds what is missing (records in lists
+//! are matched by their `id`).
+//!
+//! Archives can be encrypted with a passphrase; see [`crypto`].
+
+#[cfg(test)]
+mod tests;
+
+pub mod crypto;
+pub mod persistence;
+
+use crate::security::token_storage::StoredToken;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read, Write};
+
+/// Identifies Notive backup archives.
+pub const FORMAT: &str = "notive-backup";
+
+/// Version of the archive layout written by this build.
+pub const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_PATH: &str = "manifest.json";
+const SECRETS_PATH: &str = "secrets.json";
+const STORES_DIR: &str = "stores/";
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Keyring service the secrets are stored under.
+pub const SECRET_SERVICE: &str = "notive";
+
+/// Keyring accounts included in backups on request. The keyring cannot
+/// list its entries, so they are named here.
+pub const SECRET_ACCOUNTS: &[&str] = &["notion_api_key", "oauth_token_google"];
+
+/// Store files describing this device rather than the user's data, left
+/// out of backups.
+pub const DEVICE_FILES: &[&str] = &["sync.json"];
+
+/// Contents of the store files, by file name.
+pub type Stores = BTreeMap<String, Map<String, Value>>;
+
+/// What can be restored from a backup.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    Settings,
+    Bookmarks,
+    History,
+    Workspaces,
+    Tabs,
+    Templates,
+    CaptureTemplates,
+    Notifications,
+    Privacy,
+    Sessions,
+    Analytics,
+    Windows,
+    Downloads,
+}
+
+impl Category {
+    pub const ALL: &'static [Category] = &[
+        Self::Settings,
+        Self::Bookmarks,
+        Self::History,
+        Self::Workspaces,
+        Self::Tabs,
+        Self::Templates,
+        Self::CaptureTemplates,
+        Self::Notifications,
+        Self::Privacy,
+        Self::Sessions,
+        Self::Analytics,
+        Self::Windows,
+        Self::Downloads,
+    ];
+
+    /// Keys of `settings.json` owned by this category rather than `Settings`.
+    fn settings_keys(self) -> &'static [&'static str] {
+        match self {
+            Self::CaptureTemplates => &["quick_capture_config"],
+            Self::Notifications => &["notification_settings"],
+            Self::Privacy => &["privacy_settings"],
+            _ => &[],
+        }
+    }
+
+    /// Store files owned entirely by this category.
+    fn files(self) -> &'static [&'static str] {
+        match self {
+            Self::Bookmarks => &["bookmarks.json"],
+            Self::History => &["history.json"],
+            Self::Workspaces => &["workspaces.json"],
+            Self::Tabs => &["tabs.json"],
+            Self::Templates => &["templates.json"],
+            Self::Sessions => &["sessions.json"],
+            Self::Analytics => &["analytics.json"],
+            Self::Windows => &["windows.json", "popouts.json", "mini.json"],
+            Self::Downloads => &["downloads.json"],
+            _ => &[],
+        }
+    }
+
+    /// Checks whether a key of a store file belongs to this category.
+    pub fn owns(self, file: &str, key: &str) -> bool {
+        if file != SETTINGS_FILE {
+            return self.files().contains(&file);
+        }
+        match self {
+            Self::Settings => !Self::ALL.iter().any(|c| c.settings_keys().contains(&key)),
+            _ => self.settings_keys().contains(&key),
+        }
+    }
+
+    /// Finds the category a key of a store file belongs to.
+    pub fn of(file: &str, key: &str) -> Option<Category> {
+        Self::ALL.iter().copied().find(|c| c.owns(file, key))
+    }
+}
+
+/// How restored data is combined with what is already there.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RestoreMode {
+    /// Keep existing data and add what only the backup has.
+    #[default]
+    Merge,
+    /// Make the restored categories match the backup.
+    Replace,
+}
+
+/// A file in the archive and its checksum.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ManifestFile {
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Describes a backup archive.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    pub app_version: String,
+    pub created_at: String, // ISO 8601 format
+    pub categories: Vec<Category>,
+    pub includes_secrets: bool,
+    pub files: Vec<ManifestFile>,
+}
+
+/// A keyring secret carried in a backup.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Secret {
+    pub account: String,
+    pub token: StoredToken,
+}
+
+/// The contents of a backup archive.
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub manifest: Manifest,
+    pub stores: Stores,
+    pub secrets: Vec<Secret>,
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Lists the categories with data in the stores.
+pub fn categories_in(stores: &Stores) -> Vec<Category> {
+    let mut categories: Vec<Category> = stores
+        .iter()
+        .flat_map(|(file, entries)| entries.keys().filter_map(|key| Category::of(file, key)))
+        .collect();
+    categories.sort();
+    categories.dedup();
+    categories
+}
+
+/// Writes a backup archive of the stores, and of the secrets if given.
+pub fn write_backup(stores: &Stores, secrets: Option<&[Secret]>, app_version: &str) -> Result<Vec<u8>, String> {
+    let mut files = Vec::new();
+    let mut contents = Vec::new();
+    for (name, entries) in stores {
+        let bytes = serde_json::to_vec_pretty(entries).map_err(|e| format!("Failed to serialize {}: {}", name, e))?;
+        contents.push((format!("{}{}", STORES_DIR, name), bytes));
+    }
+    if let Some(secrets) = secrets {
+        let bytes = serde_json::to_vec_pretty(secrets).map_err(|e| format!("Failed to serialize secrets: {}", e))?;
+        contents.push((SECRETS_PATH.to_string(), bytes));
+    }
+    for (path, bytes) in &contents {
+        files.push(ManifestFile {
+            path: path.clone(),
+            size: bytes.len() as u64,
+            sha256: sha256(bytes),
+        });
+    }
+
+    let manifest = Manifest {
+        format: FORMAT.to_string(),
+        version: FORMAT_VERSION,
+        app_version: app_version.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        categories: categories_in(stores),
+        includes_secrets: secrets.is_some(),
+        files,
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest).map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    for (path, bytes) in std::iter::once((MANIFEST_PATH.to_string(), manifest)).chain(contents) {
+        zip.start_file(path.as_str(), options)
+            .and_then(|()| zip.write_all(&bytes).map_err(Into::into))
+            .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    }
+    let cursor = zip.finish().map_err(|e| format!("Failed to write backup: {}", e))?;
+    Ok(cursor.into_inner())
+}
+
+/// Reads a file from the archive.
+fn read_entry(zip: &mut zip::ZipArchive<Cursor<&[u8]>>, path: &str) -> Result<Vec<u8>, String> {
+    let mut entry = zip
+        .by_name(path)
+        .map_err(|_| format!("The backup is missing {}", path))?;
+    let mut bytes = Vec::new();
+    entry
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read {} from the backup: {}", path, e))?;
+    Ok(bytes)
+}
+
+/// Reads a backup archive, checking its format and every checksum.
+pub fn read_backup(bytes: &[u8]) -> Result<Backup, String> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|_| "Not a Notive backup archive".to_string())?;
+    let manifest: Manifest = serde_json::from_slice(&read_entry(&mut zip, MANIFEST_PATH)?)
+        .map_err(|e| format!("Invalid backup manifest: {}", e))?;
+    if manifest.format != FORMAT {
+        return Err("Not a Notive backup archive".to_string());
+    }
+    if manifest.version > FORMAT_VERSION {
+        return Err(format!(
+            "The backup was made by a newer version of Notive ({}); please update first",
+            manifest.app_version
+        ));
+    }
+
+    let mut stores = Stores::new();
+    let mut secrets = Vec::new();
+    for file in &manifest.files {
+        let bytes = read_entry(&mut zip, &file.path)?;
+        if bytes.len() as u64 != file.size || sha256(&bytes) != file.sha256 {
+            return Err(format!("Checksum mismatch for {}; the backup is damaged", file.path));
+        }
+
+        if file.path == SECRETS_PATH {
+            secrets = serde_json::from_slice(&bytes).map_err(|e| format!("Invalid secrets in the backup: {}", e))?;
+        } else if let Some(name) = file.path.strip_prefix(STORES_DIR) {
+            let entries = serde_json::from_slice(&bytes).map_err(|e| format!("Invalid store {} in the backup: {}", name, e))?;
+            stores.insert(name.to_string(), entries);
+        }
+    }
+
+    Ok(Backup { manifest, stores, secrets })
+}
+
+/// Gets the `id` of a list record, if it has one.
+fn record_id(value: &Value) -> Option<&str> {
+    value.get("id").and_then(Value::as_str)
+}
+
+/// Merges `incoming` into `current`, keeping everything `current` has.
+///
+/// Objects are merged key by key. Lists gain the records they lack, matched
+/// by `id` when the records have one and by equality otherwise.
+pub fn merge_values(current: &mut Value, incoming: &Value) {
+    match (current, incoming) {
+        (Value::Object(current), Value::Object(incoming)) => {
+            for (key, value) in incoming {
+                match current.get_mut(key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        current.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (Value::Array(current), Value::Array(incoming)) => {
+            for value in incoming {
+                let present = match record_id(value) {
+                    Some(id) => current.iter().any(|v| record_id(v) == Some(id)),
+                    None => current.contains(value),
+                };
+                if !present {
+                    current.push(value.clone());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Restores the chosen categories of a backup into `current`.
+///
+/// Returns the names of the store files that changed.
+pub fn restore_stores(current: &mut Stores, backup: &Stores, categories: &[Category], mode: RestoreMode) -> Vec<String> {
+    let selected = |file: &str, key: &str| Category::of(file, key).map(|c| categories.contains(&c)).unwrap_or(false);
+    let mut changed = Vec::new();
+
+    let files: Vec<String> = current.keys().chain(backup.keys()).cloned().collect();
+    for file in files {
+        if changed.contains(&file) {
+            continue;
+        }
+        let empty = Map::new();
+        let incoming = backup.get(&file).unwrap_or(&empty);
+        let entries = current.entry(file.clone()).or_default();
+        let before = entries.clone();
+
+        if mode == RestoreMode::Replace {
+            entries.retain(|key, _| !selected(&file, key) || incoming.contains_key(key));
+        }
+        for (key, value) in incoming.iter().filter(|(key, _)| selected(&file, key)) {
+            match (mode, entries.get_mut(key)) {
+                (RestoreMode::Merge, Some(existing)) => merge_values(existing, value),
+                _ => {
+                    entries.insert(key.clone(), value.clone());
+                }
+            }
+        }
+
+        if *entries != before {
+            changed.push(file.clone());
+        }
+    }
+    current.retain(|_, entries| !entries.is_empty());
+    changed
+}
@@ -0,0 +1,93 @@
+//! Reading and writing the stores and secrets covered by backups.
+
+use super::{Secret, Stores, DEVICE_FILES, SECRET_ACCOUNTS, SECRET_SERVICE};
+use crate::security::token_storage;
+use crate::utils::paths::STORE_FILES;
+use serde_json::Map;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_store::{with_store, StoreCollection};
+
+/// Loads the contents of every store file that has data, except the
+/// device-specific ones.
+pub fn load_stores<R: Runtime>(app: &AppHandle<R>) -> Result<Stores, Box<dyn std::error::Error>> {
+    let mut stores = Stores::new();
+    for name in STORE_FILES.iter().filter(|name| !DEVICE_FILES.contains(*name)) {
+        let collection = app.state::<StoreCollection<R>>();
+        let entries = with_store(app, collection, *name, |store| {
+            let mut entries = Map::new();
+            let keys: Vec<String> = store.keys().map(|key| key.to_string()).collect();
+            for key in keys {
+                if let Some(value) = store.get(&key) {
+                    entries.insert(key, value.clone());
+                }
+            }
+            Ok(entries)
+        })
+        .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)?;
+
+        if !entries.is_empty() {
+            stores.insert(name.to_string(), entries);
+        }
+    }
+    log::debug!("Loaded {} stores for backup", stores.len());
+    Ok(stores)
+}
+
+/// Makes the given store files hold exactly their contents in `stores`.
+pub fn save_stores<R: Runtime>(
+    app: &AppHandle<R>,
+    stores: &Stores,
+    files: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let empty = Map::new();
+    for name in files {
+        let entries = stores.get(name).unwrap_or(&empty);
+        let collection = app.state::<StoreCollection<R>>();
+        with_store(app, collection, name, |store| {
+            let stale: Vec<String> = store
+                .keys()
+                .map(|key| key.to_string())
+                .filter(|key| !entries.contains_key(key))
+                .collect();
+            for key in stale {
+                store.delete(&key)?;
+            }
+            for (key, value) in entries {
+                store.insert(key.clone(), value.clone())?;
+            }
+            store.save()?;
+            log::debug!("Restored store {}", name);
+            Ok(())
+        })
+        .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)?;
+    }
+    Ok(())
+}
+
+/// Reads the keyring secrets that are set.
+pub fn load_secrets() -> Result<Vec<Secret>, String> {
+    let mut secrets = Vec::new();
+    for account in SECRET_ACCOUNTS {
+        if let Some(token) = token_storage::get_token(SECRET_SERVICE, account)? {
+            secrets.push(Secret {
+                account: account.to_string(),
+                token,
+            });
+        }
+    }
+    Ok(secrets)
+}
+
+/// Writes secrets back into the keyring. Accounts Notive does not know are skipped.
+pub fn save_secrets(secrets: &[Secret]) -> Result<usize, String> {
+    let mut saved = 0;
+    for secret in secrets {
+        if !SECRET_ACCOUNTS.contains(&secret.account.as_str()) {
+            log::warn!("Skipping unknown secret in backup: {}", secret.account);
+            continue;
+        }
+        token_storage::store_token(SECRET_SERVICE, &secret.account, secret.token.clone())?;
+        saved += 1;
+    }
+    Ok(saved)
+}
@@ -0,0 +1,207 @@
+//! Tests for full-profile backups.
+
+use super::*;
+use crate::utils::paths::STORE_FILES;
+use serde_json::json;
+
+fn stores(value: Value) -> Stores {
+    serde_json::from_value(value).unwrap()
+}
+
+fn sample() -> Stores {
+    stores(json!({
+        "settings.json": {
+            "settings": { "zoom_level": 1.2, "theme": "dark" },
+            "privacy_settings": { "privacy_mode": true },
+            "quick_capture_config": { "templates": [{ "id": "t1", "name": "Inbox" }] }
+        },
+        "bookmarks.json": {
+            "bookmarks": [{ "id": "b1", "title": "Wiki" }, { "id": "b2", "title": "Roadmap" }]
+        },
+        "history.json": { "history": [{ "id": "h1" }] }
+    }))
+}
+
+fn secret() -> Secret {
+    Secret {
+        account: "notion_api_key".to_string(),
+        token: StoredToken {
+            token: "secret_abc".to_string(),
+            expires_at: None,
+            refresh_token: None,
+            created_at: 1,
+        },
+    }
+}
+
+/// Rewrites one file of an archive, keeping the rest as they are.
+fn rewrite(archive: &[u8], path: &str, contents: &[u8]) -> Vec<u8> {
+    let mut source = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for index in 0..source.len() {
+        let mut entry = source.by_index(index).unwrap();
+        let name = entry.name().to_string();
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes).unwrap();
+        zip.start_file(name.as_str(), zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(if name == path { contents } else { &bytes }).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+#[test]
+fn test_every_store_file_has_a_category() {
+    for file in STORE_FILES.iter().filter(|file| !DEVICE_FILES.contains(*file)) {
+        assert!(Category::of(file, "any").is_some(), "{} is not covered by backups", file);
+    }
+}
+
+#[test]
+fn test_settings_keys_belong_to_their_category() {
+    assert_eq!(Category::of("settings.json", "settings"), Some(Category::Settings));
+    assert_eq!(Category::of("settings.json", "download_rules"), Some(Category::Settings));
+    assert_eq!(Category::of("settings.json", "privacy_settings"), Some(Category::Privacy));
+    assert_eq!(Category::of("settings.json", "notification_settings"), Some(Category::Notifications));
+    assert_eq!(Category::of("settings.json", "quick_capture_config"), Some(Category::CaptureTemplates));
+    assert_eq!(Category::of("popouts.json", "popouts"), Some(Category::Windows));
+    assert_eq!(Category::of("unknown.json", "key"), None);
+}
+
+#[test]
+fn test_backup_round_trip() {
+    let archive = write_backup(&sample(), None, "1.2.3").unwrap();
+    let backup = read_backup(&archive).unwrap();
+
+    assert_eq!(backup.stores, sample());
+    assert!(backup.secrets.is_empty());
+    assert_eq!(backup.manifest.format, FORMAT);
+    assert_eq!(backup.manifest.version, FORMAT_VERSION);
+    assert_eq!(backup.manifest.app_version, "1.2.3");
+    assert!(!backup.manifest.includes_secrets);
+    assert_eq!(
+        backup.manifest.categories,
+        vec![
+            Category::Settings,
+            Category::Bookmarks,
+            Category::History,
+            Category::CaptureTemplates,
+            Category::Privacy,
+        ]
+    );
+    assert_eq!(backup.manifest.files.len(), 3);
+    assert!(backup.manifest.files.iter().all(|f| f.sha256.len() == 64));
+}
+
+#[test]
+fn test_secrets_only_when_included() {
+    let archive = write_backup(&sample(), Some(&[secret()]), "1.2.3").unwrap();
+    let backup = read_backup(&archive).unwrap();
+
+    assert!(backup.manifest.includes_secrets);
+    assert_eq!(backup.secrets.len(), 1);
+    assert_eq!(backup.secrets[0].token.token, "secret_abc");
+
+    let without = write_backup(&sample(), None, "1.2.3").unwrap();
+    let mut zip = zip::ZipArchive::new(Cursor::new(without.as_slice())).unwrap();
+    assert!(zip.by_name(SECRETS_PATH).is_err());
+}
+
+#[test]
+fn test_read_backup_detects_tampering() {
+    let archive = write_backup(&sample(), None, "1.2.3").unwrap();
+    let tampered = rewrite(&archive, "stores/history.json", br#"{"history": []}"#);
+
+    let err = read_backup(&tampered).unwrap_err();
+    assert!(err.contains("Checksum mismatch"), "{}", err);
+}
+
+#[test]
+fn test_read_backup_rejects_other_files() {
+    assert!(read_backup(b"not a zip").is_err());
+
+    let archive = write_backup(&sample(), None, "1.2.3").unwrap();
+    let mut manifest: Value = {
+        let mut zip = zip::ZipArchive::new(Cursor::new(archive.as_slice())).unwrap();
+        serde_json::from_reader(zip.by_name(MANIFEST_PATH).unwrap()).unwrap()
+    };
+    manifest["version"] = json!(FORMAT_VERSION + 1);
+    let newer = rewrite(&archive, MANIFEST_PATH, &serde_json::to_vec(&manifest).unwrap());
+    assert!(read_backup(&newer).unwrap_err().contains("newer version"));
+}
+
+#[test]
+fn test_merge_values_matches_records_by_id() {
+    let mut current = json!({
+        "bookmarks": [{ "id": "b1", "title": "Wiki (renamed)" }],
+        "tags": ["work"],
+        "theme": "light"
+    });
+    let incoming = json!({
+        "bookmarks": [{ "id": "b1", "title": "Wiki" }, { "id": "b2", "title": "Roadmap" }],
+        "tags": ["work", "home"],
+        "theme": "dark",
+        "zoom_level": 1.2
+    });
+    merge_values(&mut current, &incoming);
+
+    assert_eq!(
+        current,
+        json!({
+            "bookmarks": [{ "id": "b1", "title": "Wiki (renamed)" }, { "id": "b2", "title": "Roadmap" }],
+            "tags": ["work", "home"],
+            "theme": "light",
+            "zoom_level": 1.2
+        })
+    );
+}
+
+#[test]
+fn test_restore_merge_keeps_current_data() {
+    let mut current = stores(json!({
+        "settings.json": { "settings": { "zoom_level": 1.0 } },
+        "bookmarks.json": { "bookmarks": [{ "id": "b3", "title": "Local" }] }
+    }));
+    let changed = restore_stores(&mut current, &sample(), &[Category::Bookmarks], RestoreMode::Merge);
+
+    assert_eq!(changed, vec!["bookmarks.json".to_string()]);
+    let bookmarks = current["bookmarks.json"]["bookmarks"].as_array().unwrap();
+    assert_eq!(bookmarks.len(), 3);
+    // Unselected categories are left alone
+    assert_eq!(current["settings.json"]["settings"], json!({ "zoom_level": 1.0 }));
+    assert!(!current.contains_key("history.json"));
+}
+
+#[test]
+fn test_restore_replace_only_touches_selected_categories() {
+    let mut current = stores(json!({
+        "settings.json": {
+            "settings": { "zoom_level": 1.0 },
+            "privacy_settings": { "privacy_mode": false },
+            "notification_settings": { "enabled": true }
+        },
+        "history.json": { "history": [{ "id": "h9" }], "extra": true }
+    }));
+    let changed = restore_stores(
+        &mut current,
+        &sample(),
+        &[Category::Privacy, Category::Notifications, Category::History],
+        RestoreMode::Replace,
+    );
+
+    assert_eq!(changed, vec!["history.json".to_string(), "settings.json".to_string()]);
+    assert_eq!(current["history.json"], sample()["history.json"]);
+    let settings = &current["settings.json"];
+    assert_eq!(settings["privacy_settings"], json!({ "privacy_mode": true }));
+    // Not in the backup, so replacing the category removes it
+    assert!(!settings.contains_key("notification_settings"));
+    assert_eq!(settings["settings"], json!({ "zoom_level": 1.0 }));
+    assert!(!settings.contains_key("quick_capture_config"));
+}
+
+#[test]
+fn test_restore_nothing_selected_changes_nothing() {
+    let mut current = sample();
+    let changed = restore_stores(&mut current, &Stores::new(), &[], RestoreMode::Replace);
+    assert!(changed.is_empty());
+    assert_eq!(current, sample());
+}
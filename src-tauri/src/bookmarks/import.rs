@@ -0,0 +1,385 @@
+//! Importing Notion bookmarks from web browsers.
+//!
+//! Firefox keeps its bookmarks in `places.sqlite` and Chromium-based
+//! browsers in a `Bookmarks` JSON file, one per profile. Only links to
+//! Notion hosts are imported; the folders they were filed under (and, for
+//! Firefox, their tags) become tags, and pages that are already bookmarked
+//! are skipped.
+
+#[cfg(test)]
+mod tests;
+
+use super::Bookmark;
+use crate::handlers::navigation::is_notion_url;
+use crate::notion_url::same_page;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Firefox profile directories, relative to the home directory.
+const FIREFOX_DIRS: &[&str] = &[
+    ".mozilla/firefox",
+    "snap/firefox/common/.mozilla/firefox",
+    ".var/app/org.mozilla.firefox/.mozilla/firefox",
+];
+
+/// Chromium-based browsers and their user data directories, relative to the
+/// home directory.
+const CHROMIUM_DIRS: &[(&str, &str)] = &[
+    ("Chromium", ".config/chromium"),
+    ("Chromium", "snap/chromium/common/chromium"),
+    ("Chromium", ".var/app/org.chromium.Chromium/config/chromium"),
+    ("Google Chrome", ".config/google-chrome"),
+    ("Google Chrome", ".var/app/com.google.Chrome/config/google-chrome"),
+    ("Brave", ".config/BraveSoftware/Brave-Browser"),
+    ("Brave", ".var/app/com.brave.Browser/config/BraveSoftware/Brave-Browser"),
+    ("Microsoft Edge", ".config/microsoft-edge"),
+    ("Vivaldi", ".config/vivaldi"),
+];
+
+const PLACES_FILE: &str = "places.sqlite";
+const CHROMIUM_FILE: &str = "Bookmarks";
+
+/// GUIDs of the built-in Firefox folders, which are not turned into tags.
+const FIREFOX_ROOTS: &[&str] = &[
+    "root________",
+    "menu________",
+    "toolbar_____",
+    "unfiled_____",
+    "mobile______",
+    "tags________",
+];
+const FIREFOX_TAGS_ROOT: &str = "tags________";
+
+/// Microseconds between 1601-01-01, where Chromium timestamps start, and
+/// the Unix epoch.
+const CHROMIUM_EPOCH_OFFSET: i64 = 11_644_473_600_000_000;
+
+/// Kind of browser profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BrowserKind {
+    Firefox,
+    Chromium,
+}
+
+/// A browser profile with bookmarks to import.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrowserProfile {
+    pub kind: BrowserKind,
+    /// Browser and profile name, e.g. "Google Chrome (Profile 1)".
+    pub name: String,
+    /// Path of the `places.sqlite` or `Bookmarks` file.
+    pub path: PathBuf,
+}
+
+/// A bookmark as read from a browser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowserBookmark {
+    pub title: String,
+    pub url: String,
+    /// Enclosing folders, outermost first, followed by the browser's tags.
+    pub tags: Vec<String>,
+    pub added: Option<DateTime<Utc>>,
+}
+
+/// Outcome of an import.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// Bookmarks added.
+    pub imported: usize,
+    /// Notion pages that were already bookmarked.
+    pub duplicates: usize,
+    /// Links to other sites.
+    pub skipped: usize,
+    /// Names of the profiles read.
+    pub profiles: Vec<String>,
+}
+
+/// Lists the subdirectories of a directory in name order.
+fn subdirectories(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect())
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+fn dir_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// Finds the Firefox and Chromium profiles in the standard locations under
+/// a home directory.
+pub fn find_profiles(home: &Path) -> Vec<BrowserProfile> {
+    let mut profiles = Vec::new();
+
+    for dir in FIREFOX_DIRS {
+        for profile in subdirectories(&home.join(dir)) {
+            let path = profile.join(PLACES_FILE);
+            if path.is_file() {
+                // Profile directories are named "<salt>.<profile name>"
+                let name = dir_name(&profile);
+                let name = name.split_once('.').map_or(name.as_str(), |(_, name)| name);
+                profiles.push(BrowserProfile {
+                    kind: BrowserKind::Firefox,
+                    name: format!("Firefox ({})", name),
+                    path,
+                });
+            }
+        }
+    }
+
+    for (browser, dir) in CHROMIUM_DIRS {
+        for profile in subdirectories(&home.join(dir)) {
+            let path = profile.join(CHROMIUM_FILE);
+            if path.is_file() {
+                profiles.push(BrowserProfile {
+                    kind: BrowserKind::Chromium,
+                    name: format!("{} ({})", browser, dir_name(&profile)),
+                    path,
+                });
+            }
+        }
+    }
+
+    profiles
+}
+
+/// Gets the profile at a chosen path: a profile directory, a
+/// `places.sqlite` file or a Chromium `Bookmarks` file.
+pub fn profile_at(path: &Path) -> Result<BrowserProfile, String> {
+    let path = if path.is_dir() {
+        [PLACES_FILE, CHROMIUM_FILE]
+            .iter()
+            .map(|file| path.join(file))
+            .find(|file| file.is_file())
+            .ok_or_else(|| format!("No browser bookmarks found in {}", path.display()))?
+    } else {
+        path.to_path_buf()
+    };
+
+    let mut header = Vec::new();
+    fs::File::open(&path)
+        .and_then(|file| file.take(16).read_to_end(&mut header))
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let kind = if header == b"SQLite format 3\0" {
+        BrowserKind::Firefox
+    } else {
+        BrowserKind::Chromium
+    };
+    Ok(BrowserProfile {
+        kind,
+        name: path.display().to_string(),
+        path,
+    })
+}
+
+/// Reads the bookmarks of a profile.
+pub fn read_profile(profile: &BrowserProfile) -> Result<Vec<BrowserBookmark>, String> {
+    match profile.kind {
+        BrowserKind::Firefox => read_firefox(&profile.path),
+        BrowserKind::Chromium => {
+            let json = fs::read_to_string(&profile.path)
+                .map_err(|e| format!("Failed to read {}: {}", profile.path.display(), e))?;
+            parse_chromium(&json)
+        }
+    }
+}
+
+/// Reads a Firefox `places.sqlite`.
+///
+/// A running Firefox keeps the database locked, so it is read from a copy
+/// (together with its write-ahead log, which holds the latest changes).
+fn read_firefox(path: &Path) -> Result<Vec<BrowserBookmark>, String> {
+    let temp = std::env::temp_dir().join(format!("notive-import-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&temp).map_err(|e| format!("Failed to create {}: {}", temp.display(), e))?;
+
+    let result = (|| {
+        let copy = temp.join(PLACES_FILE);
+        fs::copy(path, &copy).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let wal = path.with_file_name(format!("{}-wal", PLACES_FILE));
+        if wal.is_file() {
+            fs::copy(&wal, temp.join(format!("{}-wal", PLACES_FILE)))
+                .map_err(|e| format!("Failed to read {}: {}", wal.display(), e))?;
+        }
+        let connection = Connection::open_with_flags(&copy, OpenFlags::SQLITE_OPEN_READ_WRITE)
+            .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        read_places(&connection).map_err(|e| format!("Failed to read Firefox bookmarks: {}", e))
+    })();
+
+    if let Err(e) = fs::remove_dir_all(&temp) {
+        log::warn!("Failed to remove {}: {}", temp.display(), e);
+    }
+    result
+}
+
+/// Reads the bookmarks from an open Firefox places database.
+pub fn read_places(connection: &Connection) -> rusqlite::Result<Vec<BrowserBookmark>> {
+    struct Item {
+        parent: i64,
+        kind: i64,
+        title: String,
+        guid: String,
+        added: Option<i64>,
+        url: Option<String>,
+    }
+
+    let mut statement = connection.prepare(
+        "SELECT b.id, b.parent, b.type, COALESCE(NULLIF(b.title, ''), p.title, ''), b.guid, b.dateAdded, p.url
+         FROM moz_bookmarks b LEFT JOIN moz_places p ON p.id = b.fk
+         ORDER BY b.parent, b.position",
+    )?;
+    let rows = statement.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            Item {
+                parent: row.get(1)?,
+                kind: row.get(2)?,
+                title: row.get(3)?,
+                guid: row.get(4)?,
+                added: row.get(5)?,
+                url: row.get(6)?,
+            },
+        ))
+    })?;
+    let mut ids = Vec::new();
+    let mut items = HashMap::new();
+    for row in rows {
+        let (id, item) = row?;
+        ids.push(id);
+        items.insert(id, item);
+    }
+
+    let is_root = |id: i64| items.get(&id).map_or(true, |item| FIREFOX_ROOTS.contains(&item.guid.as_str()));
+    let is_tag_folder = |id: i64| {
+        items
+            .get(&id)
+            .and_then(|folder| items.get(&folder.parent))
+            .is_some_and(|root| root.guid == FIREFOX_TAGS_ROOT)
+    };
+
+    // Tags are folders under the tags root holding one entry per tagged URL
+    let mut url_tags: HashMap<&str, Vec<String>> = HashMap::new();
+    for item in items.values() {
+        if let (1, Some(url)) = (item.kind, &item.url) {
+            if is_tag_folder(item.parent) {
+                url_tags.entry(url.as_str()).or_default().push(items[&item.parent].title.clone());
+            }
+        }
+    }
+
+    let mut bookmarks = Vec::new();
+    for id in ids {
+        let item = &items[&id];
+        let Some(url) = item.url.as_deref().filter(|_| item.kind == 1) else {
+            continue;
+        };
+        if is_tag_folder(item.parent) {
+            continue;
+        }
+
+        let mut tags = Vec::new();
+        let mut parent = item.parent;
+        // The depth limit guards against a corrupt, cyclic tree
+        while !is_root(parent) && tags.len() < 32 {
+            let folder = &items[&parent];
+            tags.insert(0, folder.title.clone());
+            parent = folder.parent;
+        }
+        tags.extend(url_tags.get(url).into_iter().flatten().cloned());
+
+        bookmarks.push(BrowserBookmark {
+            title: item.title.clone(),
+            url: url.to_string(),
+            tags,
+            added: item.added.and_then(DateTime::from_timestamp_micros),
+        });
+    }
+    Ok(bookmarks)
+}
+
+/// Parses a Chromium `Bookmarks` file.
+pub fn parse_chromium(json: &str) -> Result<Vec<BrowserBookmark>, String> {
+    fn walk(node: &Value, folders: &mut Vec<String>, bookmarks: &mut Vec<BrowserBookmark>) {
+        let name = node.get("name").and_then(Value::as_str).unwrap_or_default();
+        match node.get("type").and_then(Value::as_str) {
+            Some("url") => {
+                let Some(url) = node.get("url").and_then(Value::as_str) else {
+                    return;
+                };
+                let added = node
+                    .get("date_added")
+                    .and_then(Value::as_str)
+                    .and_then(|micros| micros.parse::<i64>().ok())
+                    .and_then(|micros| DateTime::from_timestamp_micros(micros - CHROMIUM_EPOCH_OFFSET));
+                bookmarks.push(BrowserBookmark {
+                    title: name.to_string(),
+                    url: url.to_string(),
+                    tags: folders.clone(),
+                    added,
+                });
+            }
+            Some("folder") => {
+                folders.push(name.to_string());
+                for child in node.get("children").and_then(Value::as_array).into_iter().flatten() {
+                    walk(child, folders, bookmarks);
+                }
+                folders.pop();
+            }
+            _ => {}
+        }
+    }
+
+    let file: Value = serde_json::from_str(json).map_err(|e| format!("Failed to parse Chromium bookmarks: {}", e))?;
+    let roots = file
+        .get("roots")
+        .and_then(Value::as_object)
+        .ok_or("Failed to parse Chromium bookmarks: no bookmark roots")?;
+
+    let mut bookmarks = Vec::new();
+    // The roots ("Bookmarks bar", "Other bookmarks", ...) are not tags
+    for root in roots.values() {
+        for child in root.get("children").and_then(Value::as_array).into_iter().flatten() {
+            walk(child, &mut Vec::new(), &mut bookmarks);
+        }
+    }
+    Ok(bookmarks)
+}
+
+/// Adds the Notion links among browser bookmarks to a list of bookmarks,
+/// skipping pages that are already in it.
+pub fn merge_into(bookmarks: &mut Vec<Bookmark>, found: Vec<BrowserBookmark>, report: &mut ImportReport) {
+    for entry in found {
+        if !is_notion_url(&entry.url) {
+            report.skipped += 1;
+            continue;
+        }
+        if bookmarks.iter().any(|b| same_page(&b.url, &entry.url)) {
+            report.duplicates += 1;
+            continue;
+        }
+
+        let title = match entry.title.trim() {
+            "" => entry.url.clone(),
+            title => title.to_string(),
+        };
+        let mut bookmark = Bookmark::new(title, entry.url);
+        for tag in entry.tags {
+            let tag = tag.trim();
+            if !tag.is_empty() && !bookmark.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                bookmark.tags.push(tag.to_string());
+            }
+        }
+        if let Some(added) = entry.added {
+            bookmark.created_at = added.to_rfc3339();
+        }
+        bookmarks.push(bookmark);
+        report.imported += 1;
+    }
+}
@@ -0,0 +1,193 @@
+//! Tests for importing browser bookmarks.
+
+use super::*;
+use rusqlite::params;
+use serde_json::json;
+
+const ROADMAP: &str = "https://www.notion.so/acme/Roadmap-0123456789abcdef0123456789abcdef";
+const WIKI: &str = "https://www.notion.so/acme/Wiki-fedcba9876543210fedcba9876543210";
+
+/// Creates the parts of the Firefox places schema the importer reads.
+fn places(connection: &Connection) {
+    connection
+        .execute_batch(
+            "CREATE TABLE moz_places (id INTEGER PRIMARY KEY, url TEXT, title TEXT);
+             CREATE TABLE moz_bookmarks (id INTEGER PRIMARY KEY, type INTEGER, fk INTEGER, parent INTEGER,
+                 position INTEGER, title TEXT, dateAdded INTEGER, guid TEXT);
+             INSERT INTO moz_bookmarks VALUES
+                 (1, 2, NULL, 0, 0, '', 0, 'root________'),
+                 (2, 2, NULL, 1, 0, 'menu', 0, 'menu________'),
+                 (3, 2, NULL, 1, 1, 'toolbar', 0, 'toolbar_____'),
+                 (4, 2, NULL, 1, 2, 'tags', 0, 'tags________'),
+                 (10, 2, NULL, 3, 0, 'Work', 0, 'folder-work'),
+                 (11, 2, NULL, 10, 0, 'Planning', 0, 'folder-plan'),
+                 (20, 2, NULL, 4, 0, 'q3', 0, 'tag-q3');",
+        )
+        .unwrap();
+}
+
+fn add_place(connection: &Connection, id: i64, url: &str, title: &str) {
+    connection
+        .execute("INSERT INTO moz_places VALUES (?1, ?2, ?3)", params![id, url, title])
+        .unwrap();
+}
+
+fn add_bookmark(connection: &Connection, id: i64, place: i64, parent: i64, title: Option<&str>, added: i64) {
+    connection
+        .execute(
+            "INSERT INTO moz_bookmarks VALUES (?1, 1, ?2, ?3, ?1, ?4, ?5, ?6)",
+            params![id, place, parent, title, added, format!("bookmark-{}", id)],
+        )
+        .unwrap();
+}
+
+fn found(title: &str, url: &str, tags: &[&str]) -> BrowserBookmark {
+    BrowserBookmark {
+        title: title.to_string(),
+        url: url.to_string(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        added: None,
+    }
+}
+
+#[test]
+fn test_firefox_folders_and_tags_become_tags() {
+    let connection = Connection::open_in_memory().unwrap();
+    places(&connection);
+    add_place(&connection, 1, ROADMAP, "Roadmap – Notion");
+    add_place(&connection, 2, WIKI, "Wiki");
+    add_bookmark(&connection, 100, 1, 11, Some("Roadmap"), 1_700_000_000_000_000);
+    add_bookmark(&connection, 101, 2, 2, None, 1_700_000_000_000_000);
+    // Tagging the roadmap adds an entry under the tag folder
+    add_bookmark(&connection, 102, 1, 20, None, 0);
+
+    let bookmarks = read_places(&connection).unwrap();
+    assert_eq!(bookmarks.len(), 2);
+    let roadmap = bookmarks.iter().find(|b| b.url == ROADMAP).unwrap();
+    assert_eq!(roadmap.title, "Roadmap");
+    assert_eq!(roadmap.tags, vec!["Work", "Planning", "q3"]);
+    assert_eq!(roadmap.added.unwrap().to_rfc3339(), "2023-11-14T22:13:20+00:00");
+
+    // Untitled bookmarks take the page title; built-in folders are no tags
+    let wiki = bookmarks.iter().find(|b| b.url == WIKI).unwrap();
+    assert_eq!(wiki.title, "Wiki");
+    assert!(wiki.tags.is_empty());
+}
+
+#[test]
+fn test_chromium_folders_become_tags() {
+    let file = json!({
+        "checksum": "0",
+        "roots": {
+            "bookmark_bar": { "type": "folder", "name": "Bookmarks bar", "children": [
+                { "type": "folder", "name": "Work", "children": [
+                    { "type": "url", "name": "Roadmap", "url": ROADMAP, "date_added": "13345473600000000" }
+                ] },
+                { "type": "url", "name": "Example", "url": "https://example.com/" }
+            ] },
+            "other": { "type": "folder", "name": "Other bookmarks", "children": [
+                { "type": "url", "name": "Wiki", "url": WIKI }
+            ] }
+        },
+        "version": 1
+    });
+
+    let bookmarks = parse_chromium(&file.to_string()).unwrap();
+    assert_eq!(bookmarks.len(), 3);
+    assert_eq!(bookmarks[0].url, ROADMAP);
+    assert_eq!(bookmarks[0].tags, vec!["Work"]);
+    assert_eq!(bookmarks[0].added.unwrap().to_rfc3339(), "2023-11-26T12:00:00+00:00");
+    assert!(bookmarks[1].tags.is_empty());
+    assert!(bookmarks[2].tags.is_empty());
+
+    assert!(parse_chromium("{}").is_err());
+    assert!(parse_chromium("not json").is_err());
+}
+
+#[test]
+fn test_merge_keeps_new_notion_pages_only() {
+    let mut bookmarks = vec![Bookmark::new("Wiki".to_string(), WIKI.to_string())];
+    let mut report = ImportReport::default();
+
+    merge_into(
+        &mut bookmarks,
+        vec![
+            found("Roadmap", ROADMAP, &["Work", " Planning ", "work", ""]),
+            // The same page under another URL, from another folder
+            found("Roadmap", "https://notion.so/0123456789abcdef0123456789abcdef?pvs=4", &["Other"]),
+            found("Wiki (old)", WIKI, &[]),
+            found("Example", "https://example.com/", &[]),
+            found("Lookalike", "https://evil-notion.so/page", &[]),
+            found("  ", "https://www.notion.so/acme/Inbox-00112233445566778899aabbccddeeff", &[]),
+        ],
+        &mut report,
+    );
+
+    assert_eq!((report.imported, report.duplicates, report.skipped), (2, 2, 2));
+    assert_eq!(bookmarks.len(), 3);
+    assert_eq!(bookmarks[0].title, "Wiki");
+    assert_eq!(bookmarks[1].title, "Roadmap");
+    assert_eq!(bookmarks[1].tags, vec!["Work", "Planning"]);
+    assert_eq!(bookmarks[2].title, bookmarks[2].url);
+}
+
+#[test]
+fn test_merge_keeps_the_browser_date() {
+    let mut bookmarks = Vec::new();
+    let mut entry = found("Roadmap", ROADMAP, &[]);
+    entry.added = DateTime::from_timestamp(1_700_000_000, 0);
+
+    merge_into(&mut bookmarks, vec![entry], &mut ImportReport::default());
+    assert_eq!(bookmarks[0].created_at, "2023-11-14T22:13:20+00:00");
+}
+
+#[test]
+fn test_finds_profiles_in_standard_locations() {
+    let home = tempfile::tempdir().unwrap();
+    let firefox = home.path().join(".mozilla/firefox/x1y2z3.default-release");
+    let chrome = home.path().join(".config/google-chrome/Profile 1");
+    let empty = home.path().join(".config/chromium/Default");
+    for dir in [&firefox, &chrome, &empty] {
+        fs::create_dir_all(dir).unwrap();
+    }
+    Connection::open(firefox.join(PLACES_FILE)).map(|connection| places(&connection)).unwrap();
+    fs::write(chrome.join(CHROMIUM_FILE), r#"{ "roots": {} }"#).unwrap();
+
+    let profiles = find_profiles(home.path());
+    assert_eq!(
+        profiles,
+        vec![
+            BrowserProfile {
+                kind: BrowserKind::Firefox,
+                name: "Firefox (default-release)".to_string(),
+                path: firefox.join(PLACES_FILE),
+            },
+            BrowserProfile {
+                kind: BrowserKind::Chromium,
+                name: "Google Chrome (Profile 1)".to_string(),
+                path: chrome.join(CHROMIUM_FILE),
+            },
+        ]
+    );
+
+    // A chosen directory or file is recognized by its contents
+    assert_eq!(profile_at(&firefox).unwrap().kind, BrowserKind::Firefox);
+    assert_eq!(profile_at(&chrome.join(CHROMIUM_FILE)).unwrap().kind, BrowserKind::Chromium);
+    assert!(profile_at(&empty).is_err());
+}
+
+#[test]
+fn test_reads_firefox_profile_from_a_copy() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(PLACES_FILE);
+    let connection = Connection::open(&path).unwrap();
+    places(&connection);
+    add_place(&connection, 1, ROADMAP, "Roadmap");
+    add_bookmark(&connection, 100, 1, 10, Some("Roadmap"), 0);
+
+    // Still open, as it would be in a running Firefox
+    let profile = profile_at(&path).unwrap();
+    let bookmarks = read_profile(&profile).unwrap();
+    assert_eq!(bookmarks, vec![BrowserBookmark { added: DateTime::from_timestamp(0, 0), ..found("Roadmap", ROADMAP, &["Work"]) }]);
+    drop(connection);
+}
@@ -1,7 +1,17 @@
 //! Bookmarks and favorites management.
 
+#[cfg(test)]
+mod tests;
+
+pub mod import;
+mod persistence;
+
+use crate::notion_url::same_page;
+use import::ImportReport;
+use persistence::{load_bookmarks, save_bookmarks};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::Path;
 use tauri::{AppHandle, Manager, Runtime};
 
 /// Bookmark information.
@@ -40,8 +50,19 @@ pub fn new() -> Self {
         }
     }
 
-    pub fn add_bookmark(&mut self, bookmark: Bookmark) {
-        self.bookmarks.insert(bookmark.id.clone(), bookmark);
+    /// Adds a bookmark, or updates the existing bookmark of the same page.
+    ///
+    /// Returns the ID of the stored bookmark.
+    pub fn add_bookmark(&mut self, bookmark: Bookmark) -> String {
+        if let Some(existing) = self.bookmarks.values_mut().find(|b| same_page(&b.url, &bookmark.url)) {
+            existing.title = bookmark.title;
+            existing.url = bookmark.url;
+            return existing.id.clone();
+        }
+
+        let id = bookmark.id.clone();
+        self.bookmarks.insert(id.clone(), bookmark);
+        id
     }
 
     pub fn remove_bookmark(&mut self, id: &str) -> Option<Bookmark> {
@@ -78,11 +99,22 @@ pub async fn add_bookmark<R: Runtime>(
 ) -> Result<String, String> {
     log::debug!("Adding bookmark: {} -> {}", title, url);
     
+    let mut bookmarks = load_bookmarks(&app).unwrap_or_default();
+
+    // The same page may already be bookmarked under another URL
+    if let Some(existing) = bookmarks.iter_mut().find(|b| same_page(&b.url, &url)) {
+        existing.title = title;
+        existing.url = url;
+        let id = existing.id.clone();
+        save_bookmarks(&app, &bookmarks).map_err(|e| e.to_string())?;
+        log::info!("Bookmark updated: {}", id);
+        return Ok(id);
+    }
+    
     let bookmark = Bookmark::new(title, url);
     let id = bookmark.id.clone();
     
     // Persist bookmark
-    let mut bookmarks = load_bookmarks(&app).unwrap_or_default();
     bookmarks.push(bookmark.clone());
     if let Err(e) = save_bookmarks(&app, &bookmarks) {
         log::warn!("Failed to save bookmark: {}", e);
@@ -142,3 +174,62 @@ pub fn get_bookmark<R: Runtime>(
     let bookmarks = load_bookmarks(&app).unwrap_or_default();
     Ok(bookmarks.into_iter().find(|b| b.id == bookmark_id))
 }
+
+/// Lists the Firefox and Chromium profiles found in the standard locations.
+#[tauri::command]
+pub fn list_browser_profiles() -> Vec<import::BrowserProfile> {
+    dirs::home_dir().map(|home| import::find_profiles(&home)).unwrap_or_default()
+}
+
+/// Imports the Notion bookmarks of a browser profile, or of every profile
+/// in the standard locations when no path is given.
+#[tauri::command]
+pub async fn import_browser_bookmarks<R: Runtime>(
+    app: AppHandle<R>,
+    path: Option<String>,
+) -> Result<ImportReport, String> {
+    let chosen = path.is_some();
+    // Scanning profiles and copying their databases blocks, so keep it off the runtime
+    let read = tauri::async_runtime::spawn_blocking(move || {
+        let profiles = match path {
+            Some(path) => vec![import::profile_at(Path::new(&path))?],
+            None => list_browser_profiles(),
+        };
+        if profiles.is_empty() {
+            return Err("No Firefox or Chromium profiles found".to_string());
+        }
+        Ok(profiles
+            .into_iter()
+            .map(|profile| {
+                let found = import::read_profile(&profile);
+                (profile.name, found)
+            })
+            .collect::<Vec<_>>())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let mut bookmarks = load_bookmarks(&app).map_err(|e| e.to_string())?;
+    let mut report = ImportReport::default();
+    for (name, found) in read {
+        match found {
+            Ok(found) => {
+                import::merge_into(&mut bookmarks, found, &mut report);
+                report.profiles.push(name);
+            }
+            Err(e) if chosen => return Err(e),
+            Err(e) => log::warn!("Skipping {}: {}", name, e),
+        }
+    }
+
+    if report.imported > 0 {
+        save_bookmarks(&app, &bookmarks).map_err(|e| e.to_string())?;
+    }
+    log::info!(
+        "Imported {} bookmarks from {} profiles ({} already bookmarked)",
+        report.imported,
+        report.profiles.len(),
+        report.duplicates
+    );
+    Ok(report)
+}
@@ -122,3 +122,22 @@ fn test_bookmark_no_results() {
     let results = manager.search_bookmarks("nonexistent");
     assert_eq!(results.len(), 0);
 }
+
+#[test]
+fn test_bookmark_manager_deduplicates_pages() {
+    let mut manager = BookmarkManager::new();
+    let page = "8f3a1b2c4d5e6f708192a3b4c5d6e7f8";
+
+    let first = manager.add_bookmark(Bookmark::new(
+        "Team Wiki".to_string(),
+        format!("https://www.notion.so/acme/Team-Wiki-{}", page),
+    ));
+    let second = manager.add_bookmark(Bookmark::new(
+        "Wiki".to_string(),
+        format!("https://notion.so/{}?pvs=4", page),
+    ));
+
+    assert_eq!(first, second);
+    assert_eq!(manager.list_bookmarks().len(), 1);
+    assert_eq!(manager.get_bookmark(&first).unwrap().title, "Wiki");
+}
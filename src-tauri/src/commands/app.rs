@@ -3,6 +3,9 @@
 #[cfg(test)]
 mod tests;
 
+use crate::config::{self, UpdateChannel};
+use crate::updater::background::UpdateState;
+use crate::updater::release_notes::ReleaseNotes;
 use tauri::{AppHandle, Manager, Runtime};
 use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 use tauri_plugin_notification::NotificationExt;
@@ -38,9 +41,10 @@ pub async fn show_about<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
 pub async fn check_updates<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
     log::debug!("Manual update check requested");
     
-    match crate::updater::check(&app).await {
-        Ok(has_update) => {
-            if has_update {
+    match crate::updater::find_update(&app, false).await {
+        Ok(update) => {
+            let has_update = update.is_some();
+            if let Some(update) = update {
                 // Show notification
                 let _ = app.notification().builder()
                     .title("Update Available")
@@ -48,26 +52,32 @@ pub async fn check_updates<R: Runtime>(app: AppHandle<R>) -> Result<bool, String
                     .show();
                 
                 // Show dialog
-                let should_install = app.dialog()
-                    .message("A new version of Notive is available. Would you like to install it now?")
-                    .title("Update Available")
+                let notes = update
+                    .notes
+                    .as_ref()
+                    .map(crate::updater::release_notes::format_for_dialog)
+                    .filter(|text| !text.is_empty())
+                    .map(|text| format!("{}\n\n", text))
+                    .unwrap_or_default();
+                let should_download = app.dialog()
+                    .message(&format!(
+                        "{}Download Notive {} in the background and install it when you quit?",
+                        notes, update.version
+                    ))
+                    .title(&format!("Notive {} is available", update.version))
                     .kind(MessageDialogKind::Info)
                     .buttons(MessageDialogButtons::YesNo)
                     .show()
                     .await;
                 
-                if should_install {
-                    if let Err(e) = crate::updater::install(&app).await {
-                        log::error!("Failed to install update: {}", e);
-                        let _ = app.dialog()
-                            .message(&format!("Failed to install update: {}", e))
-                            .title("Update Error")
-                            .kind(MessageDialogKind::Error)
-                            .buttons(MessageDialogButtons::Ok)
-                            .show()
-                            .await;
-                        return Err(e);
-                    }
+                if should_download {
+                    // Download without blocking the caller; progress is reported via events
+                    let app_handle = app.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = crate::updater::background::download(&app_handle).await {
+                            log::error!("Failed to download update: {}", e);
+                        }
+                    });
                 }
             } else {
                 // Show no update available
@@ -94,3 +104,144 @@ pub async fn check_updates<R: Runtime>(app: AppHandle<R>) -> Result<bool, String
         }
     }
 }
+
+/// Gets the parsed release notes for a version.
+///
+/// Defaults to the running version, which gives the "What's new" notes after an update.
+#[tauri::command]
+pub fn get_release_notes<R: Runtime>(
+    app: AppHandle<R>,
+    version: Option<String>,
+) -> Result<Option<ReleaseNotes>, String> {
+    let version = version.unwrap_or_else(|| app.package_info().version.to_string());
+    Ok(crate::updater::stored_release_notes(&app, &version))
+}
+
+/// Gets the state of the background update.
+#[tauri::command]
+pub fn get_update_state<R: Runtime>(app: AppHandle<R>) -> Result<UpdateState, String> {
+    Ok(crate::updater::background::current_state(&app))
+}
+
+/// Cancels the running background update download.
+#[tauri::command]
+pub fn cancel_update_download() -> Result<bool, String> {
+    Ok(crate::updater::background::cancel())
+}
+
+/// Restarts the application to install the downloaded update.
+#[tauri::command]
+pub async fn restart_to_update<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    if crate::updater::background::pending_version(&app).is_none() {
+        return Err("No update is ready to install".to_string());
+    }
+    
+    // The update is installed by the exit handler before the restart. Async
+    // commands run off the main thread, where restarting goes through the exit
+    // event (which also releases the single-instance socket).
+    app.restart();
+}
+
+/// Switches the update channel and immediately checks it for updates.
+///
+/// When `allow_downgrade` is set, an older build from the new channel is
+/// offered as well, which is how users move from Nightly or Beta back to Stable.
+#[tauri::command]
+pub async fn switch_update_channel<R: Runtime>(
+    app: AppHandle<R>,
+    channel: UpdateChannel,
+    allow_downgrade: bool,
+) -> Result<bool, String> {
+    log::debug!("Switching update channel to {:?} (downgrade: {})", channel, allow_downgrade);
+    
+    let mut settings = config::load(&app).unwrap_or_default();
+    settings.update_channel = channel.clone();
+    config::save(&app, &settings).map_err(|e| e.to_string())?;
+    
+    // A build downloaded from the previous channel must not install on quit
+    crate::updater::background::discard_pending(&app);
+    
+    let has_update = crate::updater::check_with(&app, allow_downgrade).await?;
+    if !has_update {
+        log::info!("No update available on {:?} channel", channel);
+        return Ok(false);
+    }
+    
+    let should_download = app.dialog()
+        .message(&format!(
+            "A build from the {:?} channel is available. Download it in the background and install it when you quit?",
+            channel
+        ))
+        .title("Switch Update Channel")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::YesNo)
+        .show()
+        .await;
+    
+    if should_download {
+        // Download without blocking the caller; progress is reported via events
+        let app_handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::updater::background::download_with(&app_handle, allow_downgrade).await {
+                log::error!("Failed to download update: {}", e);
+            }
+        });
+    }
+    
+    Ok(true)
+}
+
+/// Installs an update bundle from a local file and restarts.
+///
+/// The signature defaults to `<bundle>.sig` and the version is taken from the
+/// bundle's file name unless given.
+#[tauri::command]
+pub async fn install_update_from_file<R: Runtime>(
+    app: AppHandle<R>,
+    bundle_path: String,
+    signature_path: Option<String>,
+    version: Option<String>,
+) -> Result<(), String> {
+    log::info!("Installing update from local file {}", bundle_path);
+    
+    let signature_path = signature_path.map(std::path::PathBuf::from);
+    let version = crate::updater::local::install_from_file(
+        &app,
+        std::path::Path::new(&bundle_path),
+        signature_path.as_deref(),
+        version,
+    )
+    .await?;
+    
+    log::info!("Update {} installed from local file, restarting...", version);
+    app.restart();
+}
+
+/// Restores the version and data that were replaced by the last update.
+#[tauri::command]
+pub async fn rollback_update<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    let rollback_dir = crate::updater::rollback::rollback_dir()
+        .ok_or("Could not determine data directory")?;
+    let snapshot = crate::updater::rollback::load_snapshot(&rollback_dir)
+        .ok_or("No previous version is available to roll back to")?;
+    
+    let confirmed = app.dialog()
+        .message(&format!(
+            "Roll back from Notive {} to {}? Your data will be restored to how it was before the update.",
+            snapshot.installed_version, snapshot.previous_version
+        ))
+        .title("Roll Back Update")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::YesNo)
+        .show()
+        .await;
+    
+    if !confirmed {
+        return Ok(());
+    }
+    
+    let store_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    crate::updater::rollback::rollback(&store_dir)?;
+    log::info!("Rollback complete, restarting...");
+    app.restart();
+}
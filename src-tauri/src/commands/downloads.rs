@@ -1,130 +1,631 @@
 //! Download management commands.
+//!
+//! Files are downloaded by Notive itself rather than the webview. Downloads
+//! wait in a queue, at most a configured number of them run at once, progress
+//! is reported through events, and every download is kept in a history that
+//! survives restarts. Downloads from Notion carry the webview's session
+//! cookies, so private files and exports work as they do in the browser.
 
 #[cfg(test)]
 mod tests;
 
-use tauri::{AppHandle, Manager, Runtime};
-use tauri_plugin_dialog::{DialogExt, FileDialogBuilder};
+pub mod manager;
+mod persistence;
+pub mod rules;
+
+use crate::handlers::downloads::{route_download, DownloadRoute};
+use crate::handlers::navigation::is_notion_url;
+use manager::{Download, DownloadStatus, Interrupt, ProgressThrottle, TransferControl, TransferOutcome};
+use persistence::{load_downloads, save_downloads};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_dialog::DialogExt;
 use tauri_plugin_notification::NotificationExt;
 
-/// Handles file downloads from the webview.
-/// 
-/// This command can show a native save dialog and trigger the download.
+/// Event carrying [`DownloadProgress`] payloads.
+pub const PROGRESS_EVENT: &str = "download-progress";
+
+/// Event carrying a [`Download`] whenever its status changes.
+pub const STATE_EVENT: &str = "download-state";
+
+/// Finished downloads kept in the history.
+const MAX_FINISHED_DOWNLOADS: usize = 200;
+
+// Download history, loaded from the store on first use
+static DOWNLOADS: Mutex<Option<Vec<Download>>> = Mutex::new(None);
+
+// Controls of the running transfers, by download ID
+static TRANSFERS: Mutex<Option<HashMap<String, Arc<TransferControl>>>> = Mutex::new(None);
+
+/// Download progress sent to the frontend.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DownloadProgress {
+    pub id: String,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub percent: Option<u8>,
+}
+
+fn with_downloads<R: Runtime, T>(app: &AppHandle<R>, f: impl FnOnce(&mut Vec<Download>) -> T) -> Option<T> {
+    let mut downloads = DOWNLOADS.lock().ok()?;
+    let downloads = downloads.get_or_insert_with(|| {
+        let mut loaded = load_downloads(app).unwrap_or_else(|e| {
+            log::warn!("Failed to load downloads: {}", e);
+            Vec::new()
+        });
+        manager::on_startup(&mut loaded);
+        loaded
+    });
+    Some(f(downloads))
+}
+
+fn save<R: Runtime>(app: &AppHandle<R>) {
+    let Some(downloads) = with_downloads(app, |downloads| downloads.clone()) else {
+        return;
+    };
+    if let Err(e) = save_downloads(app, &downloads) {
+        log::warn!("Failed to save downloads: {}", e);
+    }
+}
+
+/// Changes a download, then saves the history and notifies the frontend.
+fn update<R: Runtime>(app: &AppHandle<R>, id: &str, f: impl FnOnce(&mut Download)) -> Option<Download> {
+    let updated = with_downloads(app, |downloads| {
+        let download = downloads.iter_mut().find(|d| d.id == id)?;
+        f(download);
+        let updated = download.clone();
+        manager::trim_history(downloads, MAX_FINISHED_DOWNLOADS);
+        Some(updated)
+    })
+    .flatten()?;
+
+    save(app);
+    let _ = app.emit(STATE_EVENT, &updated);
+    Some(updated)
+}
+
+fn transfer_control(id: &str) -> Option<Arc<TransferControl>> {
+    TRANSFERS.lock().ok()?.as_ref()?.get(id).cloned()
+}
+
+/// Drops the control of a transfer that stopped.
+fn forget_transfer(id: &str) {
+    if let Ok(mut transfers) = TRANSFERS.lock() {
+        if let Some(transfers) = transfers.as_mut() {
+            transfers.remove(id);
+        }
+    }
+}
+
+/// Gets the Notion session cookies for a download URL.
+fn session_cookies<R: Runtime>(app: &AppHandle<R>, url: &str) -> Vec<(String, String)> {
+    if !is_notion_url(url) {
+        return Vec::new();
+    }
+    let (Some(window), Ok(parsed)) = (app.get_webview_window("main"), url.parse::<url::Url>()) else {
+        return Vec::new();
+    };
+
+    match window.cookies_for_url(parsed) {
+        Ok(cookies) => cookies
+            .iter()
+            .map(|cookie| (cookie.name().to_string(), cookie.value().to_string()))
+            .collect(),
+        Err(e) => {
+            log::warn!("Failed to read session cookies: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Opens a folder in the file manager.
+fn open_folder<R: Runtime>(app: &AppHandle<R>, folder: &Path) {
+    use tauri_plugin_shell::ShellExt;
+    if let Err(e) = app.shell().open(folder.to_string_lossy(), None) {
+        log::warn!("Failed to open {}: {}", folder.display(), e);
+    }
+}
+
+/// Notifies that a download finished, offering to open its folder.
+fn notify_completed<R: Runtime>(app: &AppHandle<R>, download: &Download) {
+    if !crate::config::load(app).unwrap_or_default().notifications_enabled {
+        return;
+    }
+    let folder = download.path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let body = format!("{} was saved to {}", download.file_name, folder.display());
+
+    #[cfg(target_os = "linux")]
+    {
+        // The notification plugin has no desktop actions, so talk to the
+        // notification server directly and wait for the click off-thread
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let shown = notify_rust::Notification::new()
+                .appname("Notive")
+                .summary("Download Complete")
+                .body(&body)
+                .action("default", "")
+                .action("open-folder", "Open Folder")
+                .show();
+            match shown {
+                Ok(handle) => handle.wait_for_action(|action| {
+                    if action == "default" || action == "open-folder" {
+                        open_folder(&app, &folder);
+                    }
+                }),
+                Err(e) => log::warn!("Failed to show download notification: {}", e),
+            }
+        });
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = app.notification().builder()
+            .title("Download Complete")
+            .body(&body)
+            .show();
+    }
+}
+
+/// Runs the first matching download rule on a finished download, records
+/// what it did and then notifies. Rules touch the disk, so they run off the
+/// calling thread.
+fn on_completed<R: Runtime>(app: &AppHandle<R>, download: Download) {
+    let rules = rules::load_rules(app).unwrap_or_else(|e| {
+        log::warn!("Failed to load download rules: {}", e);
+        Vec::new()
+    });
+    let Some(rule) = rules::find_rule(&rules, &download).cloned() else {
+        notify_completed(app, &download);
+        return;
+    };
+
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        use tauri_plugin_shell::ShellExt;
+        log::info!("Applying download rule \"{}\" to {}", rule.name, download.path.display());
+
+        let base = download_dir(&app).unwrap_or_else(|_| {
+            download.path.parent().map(Path::to_path_buf).unwrap_or_default()
+        });
+        let open = |path: &Path| {
+            app.shell()
+                .open(path.to_string_lossy(), None)
+                .map_err(|e| e.to_string())
+        };
+        let (path, steps) = rules::apply_rule(&rule, &download, &base, dirs::home_dir().as_deref(), &open);
+        for step in steps.iter().filter(|step| !step.success) {
+            log::warn!("Download rule \"{}\" failed to {}: {}", step.rule, step.action, step.detail);
+        }
+
+        let processed = update(&app, &download.id, |d| {
+            if let Some(name) = path.file_name() {
+                d.file_name = name.to_string_lossy().into_owned();
+            }
+            d.path = path;
+            d.processing.extend(steps);
+        });
+        notify_completed(&app, processed.as_ref().unwrap_or(&download));
+    });
+}
+
+/// Starts queued downloads while fewer than the configured limit are running.
+fn pump<R: Runtime>(app: &AppHandle<R>) {
+    let limit = crate::config::load(app).unwrap_or_default().max_concurrent_downloads;
+    let started = with_downloads(app, |downloads| {
+        let ids = manager::next_to_start(downloads, limit);
+        downloads
+            .iter_mut()
+            .filter(|d| ids.contains(&d.id))
+            .map(|d| {
+                d.status = DownloadStatus::Downloading;
+                d.error = None;
+                d.clone()
+            })
+            .collect::<Vec<_>>()
+    })
+    .unwrap_or_default();
+    if started.is_empty() {
+        return;
+    }
+    save(app);
+
+    for download in started {
+        let control = Arc::new(TransferControl::default());
+        if let Ok(mut transfers) = TRANSFERS.lock() {
+            transfers
+                .get_or_insert_with(HashMap::new)
+                .insert(download.id.clone(), control.clone());
+        }
+        let _ = app.emit(STATE_EVENT, &download);
+        tauri::async_runtime::spawn(run(app.clone(), download, control));
+    }
+}
+
+/// Runs a single download to completion, pause, cancellation or failure.
+async fn run<R: Runtime>(app: AppHandle<R>, download: Download, control: Arc<TransferControl>) {
+    log::info!("Downloading {} to {}", download.url, download.path.display());
+
+    let mut headers = Vec::new();
+    if let Some(cookies) = manager::cookie_header(&session_cookies(&app, &download.url)) {
+        headers.push(("Cookie".to_string(), cookies));
+    }
+
+    let mut throttle = ProgressThrottle::default();
+    let result = manager::transfer(
+        &reqwest::Client::new(),
+        &download.url,
+        &download.path,
+        &headers,
+        &control,
+        |downloaded, total| {
+            with_downloads(&app, |downloads| {
+                if let Some(d) = downloads.iter_mut().find(|d| d.id == download.id) {
+                    d.downloaded = downloaded;
+                    d.total = total;
+                }
+            });
+            if throttle.should_report(downloaded, total) {
+                let progress = DownloadProgress {
+                    id: download.id.clone(),
+                    downloaded,
+                    total,
+                    percent: manager::percent(downloaded, total),
+                };
+                let _ = app.emit(PROGRESS_EVENT, &progress);
+            }
+        },
+    )
+    .await;
+
+    // A paused transfer is forgotten along with its status change, so a
+    // resume either reaches its control or finds the download paused
+    if !matches!(result, Ok(TransferOutcome::Interrupted(Interrupt::Pause))) {
+        forget_transfer(&download.id);
+    }
+
+    let finished_at = Some(chrono::Utc::now().to_rfc3339());
+    match result {
+        Ok(TransferOutcome::Completed { size, mime_type }) => {
+            log::info!("Download complete: {}", download.path.display());
+            let completed = update(&app, &download.id, |d| {
+                d.status = DownloadStatus::Completed;
+                d.downloaded = size;
+                d.total = Some(size);
+                d.mime_type = mime_type;
+                d.finished_at = finished_at;
+            });
+            if let Some(completed) = completed {
+                on_completed(&app, completed);
+            }
+        }
+        Ok(TransferOutcome::Interrupted(Interrupt::Pause)) => {
+            log::debug!("Download paused: {}", download.id);
+            let paused = update(&app, &download.id, |d| {
+                forget_transfer(&d.id);
+                d.status = if control.take_resume() {
+                    DownloadStatus::Queued
+                } else {
+                    DownloadStatus::Paused
+                };
+            });
+            if paused.is_none() {
+                forget_transfer(&download.id);
+            }
+        }
+        Ok(TransferOutcome::Interrupted(Interrupt::Cancel)) => {
+            log::debug!("Download cancelled: {}", download.id);
+            let _ = std::fs::remove_file(download.part_path());
+            update(&app, &download.id, |d| {
+                d.status = DownloadStatus::Cancelled;
+                d.finished_at = finished_at;
+            });
+        }
+        Err(e) => {
+            log::warn!("Download failed: {}: {}", download.url, e);
+            update(&app, &download.id, |d| {
+                d.status = DownloadStatus::Failed;
+                d.error = Some(e);
+                d.finished_at = finished_at;
+            });
+        }
+    }
+
+    pump(&app);
+}
+
+/// Resolves the download folder from the `download_folder` setting.
+///
+/// The setting may be a path, a `file://` URL, or start with `~/`; without a
+/// usable setting this is the system downloads folder, or else `home`.
+pub fn resolve_download_dir(
+    setting: Option<&str>,
+    home: Option<PathBuf>,
+    system: Option<PathBuf>,
+) -> Option<PathBuf> {
+    let configured = setting.map(str::trim).filter(|s| !s.is_empty()).and_then(|setting| {
+        let path = if setting == "~" {
+            home.clone()?
+        } else if let Some(rest) = setting.strip_prefix("~/") {
+            home.clone()?.join(rest)
+        } else if setting.starts_with("file:") {
+            url::Url::parse(setting).ok()?.to_file_path().ok()?
+        } else {
+            PathBuf::from(setting)
+        };
+        path.is_absolute().then_some(path)
+    });
+
+    configured.or(system).or(home)
+}
+
+/// Gets the folder downloads are saved to.
+pub fn download_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    let settings = crate::config::load(app).unwrap_or_default();
+    resolve_download_dir(
+        settings.download_folder.as_deref(),
+        dirs::home_dir(),
+        crate::utils::paths::downloads_dir(),
+    )
+    .ok_or_else(|| "Could not determine downloads directory".to_string())
+}
+
+/// Gets a free path for `file_name` in the download folder.
+pub fn default_path<R: Runtime>(app: &AppHandle<R>, file_name: &str) -> Result<PathBuf, String> {
+    let dir = download_dir(app)?;
+    let taken: Vec<PathBuf> = with_downloads(app, |downloads| {
+        downloads
+            .iter()
+            .filter(|d| !d.status.is_finished())
+            .map(|d| d.path.clone())
+            .collect()
+    })
+    .unwrap_or_default();
+    Ok(manager::unique_path(&dir, &manager::sanitize_file_name(file_name), &taken))
+}
+
+/// Adds a download to the queue. Returns its ID.
+pub fn enqueue<R: Runtime>(
+    app: &AppHandle<R>,
+    url: String,
+    path: PathBuf,
+    source_page: Option<String>,
+) -> Result<String, String> {
+    let parsed = url::Url::parse(&url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Only HTTP downloads are supported: {}", url));
+    }
+
+    let download = Download::new(url, path, source_page);
+    let id = download.id.clone();
+    with_downloads(app, |downloads| downloads.push(download.clone()));
+    save(app);
+    let _ = app.emit(STATE_EVENT, &download);
+
+    pump(app);
+    Ok(id)
+}
+
+/// Records a download that the webview performs itself. Returns its ID.
+pub fn track_external<R: Runtime>(
+    app: &AppHandle<R>,
+    url: String,
+    path: PathBuf,
+    source_page: Option<String>,
+) -> String {
+    let mut download = Download::new(url, path, source_page);
+    download.status = DownloadStatus::Downloading;
+    let id = download.id.clone();
+    with_downloads(app, |downloads| downloads.push(download.clone()));
+    save(app);
+    let _ = app.emit(STATE_EVENT, &download);
+    id
+}
+
+/// Records how a download performed by the webview ended.
+pub fn finish_external<R: Runtime>(app: &AppHandle<R>, id: &str, result: Result<(), String>) {
+    let finished_at = Some(chrono::Utc::now().to_rfc3339());
+    let finished = update(app, id, |d| {
+        match &result {
+            Ok(()) => {
+                let size = std::fs::metadata(&d.path).map(|m| m.len()).unwrap_or(d.downloaded);
+                d.status = DownloadStatus::Completed;
+                d.downloaded = size;
+                d.total = Some(size);
+            }
+            Err(e) => {
+                d.status = DownloadStatus::Failed;
+                d.error = Some(e.clone());
+            }
+        }
+        d.finished_at = finished_at;
+    });
+
+    match finished {
+        Some(download) if download.status == DownloadStatus::Completed => {
+            log::info!("Webview download complete: {}", download.path.display());
+            on_completed(app, download);
+        }
+        Some(download) => log::warn!("Webview download failed: {}: {:?}", download.url, download.error),
+        None => {}
+    }
+    pump(app);
+}
+
+/// Starts the downloads left in the queue when Notive last quit.
+pub fn resume_queue<R: Runtime>(app: &AppHandle<R>) {
+    pump(app);
+}
+
+/// Downloads a file into the downloads directory.
+///
+/// Returns the ID of the queued download.
 #[tauri::command]
 pub async fn handle_download<R: Runtime>(
     app: AppHandle<R>,
     url: String,
     filename: Option<String>,
-) -> Result<(), String> {
+) -> Result<String, String> {
     log::debug!("Download requested: {} -> {:?}", url, filename);
-    
-    let file_name = filename.unwrap_or_else(|| {
-        url.split('/')
-            .last()
-            .unwrap_or("file")
-            .split('?')
-            .next()
-            .unwrap_or("file")
-            .to_string()
-    });
-    
+
+    let file_name = filename.unwrap_or_else(|| manager::file_name_from_url(&url));
+    let path = default_path(&app, &file_name)?;
+    let id = enqueue(&app, url, path, None)?;
+
     // Show notification that download started
     let _ = app.notification().builder()
         .title("Download Started")
         .body(&format!("Downloading: {}", file_name))
         .show();
-    
-    // Optionally show a save dialog (for future enhancement)
-    // For now, we'll use the webview's native download handling
-    // which respects the user's browser download preferences
-    
-    // Open the URL in the webview, which will trigger the native download
-    if let Some(window) = app.get_webview_window("main") {
-        // Use a more reliable method to trigger download
-        let script = format!(
-            r#"
-            (function() {{
-                const link = document.createElement('a');
-                link.href = '{}';
-                link.download = '{}';
-                link.target = '_blank';
-                document.body.appendChild(link);
-                link.click();
-                document.body.removeChild(link);
-            }})();
-            "#,
-            url.replace('\'', "\\'"),
-            file_name.replace('\'', "\\'")
-        );
-        window.eval(&script).map_err(|e| e.to_string())?;
-    }
-    
-    Ok(())
+
+    Ok(id)
 }
 
-/// Shows a native file save dialog and triggers download.
-/// This provides more control over where files are saved.
+/// Shows a native file save dialog and downloads the file to the chosen path.
+///
+/// Returns the ID of the queued download, or `None` if the dialog was cancelled.
 #[tauri::command]
 pub async fn download_with_dialog<R: Runtime>(
     app: AppHandle<R>,
     url: String,
     suggested_filename: Option<String>,
-) -> Result<(), String> {
+) -> Result<Option<String>, String> {
     log::debug!("Download with dialog requested: {} -> {:?}", url, suggested_filename);
-    
-    let default_name = suggested_filename.unwrap_or_else(|| {
-        url.split('/')
-            .last()
-            .unwrap_or("file")
-            .split('?')
-            .next()
-            .unwrap_or("file")
-            .to_string()
-    });
-    
+
+    let default_name = suggested_filename.unwrap_or_else(|| manager::file_name_from_url(&url));
+
     // Show save dialog
     let file_path = app.dialog()
         .file()
         .set_file_name(&default_name)
         .save()
         .await;
-    
+
     match file_path {
         Some(path) => {
             log::info!("User selected save location: {:?}", path);
-            
+            let id = enqueue(&app, url, path.clone(), None)?;
+
             // Show notification
             let _ = app.notification().builder()
                 .title("Download Started")
                 .body(&format!("Downloading to: {}", path.display()))
                 .show();
-            
-            // For now, we'll still use the webview download
-            // In a full implementation, we'd download the file directly using HTTP
-            // and save it to the selected path
-            if let Some(window) = app.get_webview_window("main") {
-                let script = format!(
-                    r#"
-                    (function() {{
-                        const link = document.createElement('a');
-                        link.href = '{}';
-                        link.download = '{}';
-                        document.body.appendChild(link);
-                        link.click();
-                        document.body.removeChild(link);
-                    }})();
-                    "#,
-                    url.replace('\'', "\\'"),
-                    default_name.replace('\'', "\\'")
-                );
-                window.eval(&script).map_err(|e| e.to_string())?;
-            }
-            
-            Ok(())
+
+            Ok(Some(id))
         }
         None => {
             log::debug!("User cancelled download dialog");
-            Ok(())
+            Ok(None)
         }
     }
 }
+
+/// Lists downloads, most recent first.
+#[tauri::command]
+pub fn list_downloads<R: Runtime>(app: AppHandle<R>) -> Result<Vec<Download>, String> {
+    let mut downloads = with_downloads(&app, |downloads| downloads.clone()).unwrap_or_default();
+    downloads.reverse();
+    Ok(downloads)
+}
+
+/// Pauses a running or queued download.
+#[tauri::command]
+pub fn pause_download<R: Runtime>(app: AppHandle<R>, download_id: String) -> Result<(), String> {
+    if let Some(control) = transfer_control(&download_id) {
+        control.interrupt(Interrupt::Pause);
+        return Ok(());
+    }
+    update(&app, &download_id, |d| {
+        if d.status == DownloadStatus::Queued {
+            d.status = DownloadStatus::Paused;
+        }
+    })
+    .ok_or_else(|| format!("Download {} not found", download_id))?;
+    Ok(())
+}
+
+/// Resumes a paused or failed download from where it stopped.
+#[tauri::command]
+pub fn resume_download<R: Runtime>(app: AppHandle<R>, download_id: String) -> Result<(), String> {
+    let url = with_downloads(&app, |downloads| {
+        downloads.iter().find(|d| d.id == download_id).map(|d| d.url.clone())
+    })
+    .flatten()
+    .ok_or_else(|| format!("Download {} not found", download_id))?;
+    // `blob:` and `data:` downloads only exist inside the page that started them
+    if route_download(&url) == DownloadRoute::WebView {
+        return Err(format!("Download {} was saved by the page and cannot be resumed", download_id));
+    }
+
+    let download = update(&app, &download_id, |d| match d.status {
+        DownloadStatus::Paused | DownloadStatus::Failed => {
+            d.status = DownloadStatus::Queued;
+            d.error = None;
+            d.finished_at = None;
+        }
+        // A paused transfer stays downloading until it stopped
+        DownloadStatus::Downloading => {
+            if let Some(control) = transfer_control(&d.id) {
+                control.resume();
+            }
+        }
+        _ => {}
+    })
+    .ok_or_else(|| format!("Download {} not found", download_id))?;
+
+    if download.status != DownloadStatus::Queued && download.status != DownloadStatus::Downloading {
+        return Err(format!("Download {} cannot be resumed", download_id));
+    }
+    pump(&app);
+    Ok(())
+}
+
+/// Cancels a download and deletes its partial file.
+#[tauri::command]
+pub fn cancel_download<R: Runtime>(app: AppHandle<R>, download_id: String) -> Result<(), String> {
+    if let Some(control) = transfer_control(&download_id) {
+        control.interrupt(Interrupt::Cancel);
+        return Ok(());
+    }
+    let download = update(&app, &download_id, |d| {
+        if !d.status.is_finished() {
+            d.status = DownloadStatus::Cancelled;
+            d.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+    })
+    .ok_or_else(|| format!("Download {} not found", download_id))?;
+    let _ = std::fs::remove_file(download.part_path());
+    Ok(())
+}
+
+/// Removes a download from the history. The downloaded file is kept.
+#[tauri::command]
+pub fn remove_download<R: Runtime>(app: AppHandle<R>, download_id: String) -> Result<(), String> {
+    if let Some(control) = transfer_control(&download_id) {
+        control.interrupt(Interrupt::Cancel);
+    }
+    let removed = with_downloads(&app, |downloads| {
+        let index = downloads.iter().position(|d| d.id == download_id)?;
+        Some(downloads.remove(index))
+    })
+    .flatten()
+    .ok_or_else(|| format!("Download {} not found", download_id))?;
+
+    if removed.status != DownloadStatus::Completed {
+        let _ = std::fs::remove_file(removed.part_path());
+    }
+    save(&app);
+    Ok(())
+}
+
+/// Removes all finished downloads from the history.
+#[tauri::command]
+pub fn clear_download_history<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    with_downloads(&app, |downloads| downloads.retain(|d| !d.status.is_finished()));
+    save(&app);
+    Ok(())
+}
@@ -0,0 +1,439 @@
+//! Download records and the HTTP transfer behind them.
+//!
+//! Files are streamed into a `.part` file next to their destination, which is
+//! renamed once complete. A paused or interrupted download keeps its partial
+//! file and continues from there with an HTTP `Range` request; servers that
+//! ignore the range simply send the whole file again.
+
+#[cfg(test)]
+mod tests;
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Notify;
+
+/// Bytes between progress reports when the total size is unknown.
+const UNKNOWN_SIZE_STEP: u64 = 512 * 1024;
+
+/// Suffix of partially downloaded files.
+const PART_SUFFIX: &str = ".part";
+
+/// File name used when neither the server nor the URL suggests one.
+const FALLBACK_FILE_NAME: &str = "download";
+
+/// Lifecycle of a download.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadStatus {
+    Queued,
+    Downloading,
+    Paused,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl DownloadStatus {
+    /// Checks whether the download has stopped for good.
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// A download and its progress, as kept in the download history.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Download {
+    pub id: String,
+    pub url: String,
+    /// Where the finished file is saved.
+    pub path: PathBuf,
+    pub file_name: String,
+    /// Page the download was started from, if known.
+    #[serde(default)]
+    pub source_page: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    pub status: DownloadStatus,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub created_at: String, // ISO 8601 format
+    #[serde(default)]
+    pub finished_at: Option<String>,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// What download rules did to the finished file.
+    #[serde(default)]
+    pub processing: Vec<super::rules::ProcessingStep>,
+}
+
+impl Download {
+    pub fn new(url: String, path: PathBuf, source_page: Option<String>) -> Self {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| FALLBACK_FILE_NAME.to_string());
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            url,
+            path,
+            file_name,
+            source_page,
+            mime_type: None,
+            status: DownloadStatus::Queued,
+            downloaded: 0,
+            total: None,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+            error: None,
+            processing: Vec::new(),
+        }
+    }
+
+    /// Gets the partial file the download is written to.
+    pub fn part_path(&self) -> PathBuf {
+        part_path(&self.path)
+    }
+}
+
+/// Normalizes downloads loaded at startup.
+///
+/// Transfers do not survive a restart, so downloads that were running are
+/// paused and can be resumed from their partial file. Downloads the webview
+/// performed itself (from `blob:` or `data:` URLs) cannot be fetched again
+/// and are marked failed.
+pub fn on_startup(downloads: &mut [Download]) {
+    for download in downloads {
+        if download.status != DownloadStatus::Downloading {
+            continue;
+        }
+        if download.url.starts_with("http:") || download.url.starts_with("https:") {
+            download.status = DownloadStatus::Paused;
+        } else {
+            download.status = DownloadStatus::Failed;
+            download.error = Some("Interrupted when Notive quit".to_string());
+        }
+    }
+}
+
+/// Picks the queued downloads to start, oldest first, without exceeding `limit`
+/// running downloads.
+pub fn next_to_start(downloads: &[Download], limit: usize) -> Vec<String> {
+    let running = downloads
+        .iter()
+        .filter(|d| d.status == DownloadStatus::Downloading)
+        .count();
+
+    downloads
+        .iter()
+        .filter(|d| d.status == DownloadStatus::Queued)
+        .take(limit.max(1).saturating_sub(running))
+        .map(|d| d.id.clone())
+        .collect()
+}
+
+/// Drops the oldest finished downloads beyond `max_finished`.
+pub fn trim_history(downloads: &mut Vec<Download>, max_finished: usize) {
+    let finished = downloads.iter().filter(|d| d.status.is_finished()).count();
+    let mut excess = finished.saturating_sub(max_finished);
+    downloads.retain(|d| {
+        if excess > 0 && d.status.is_finished() {
+            excess -= 1;
+            false
+        } else {
+            true
+        }
+    });
+}
+
+/// Gets the partial file for a destination path.
+pub fn part_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(PART_SUFFIX);
+    path.with_file_name(name)
+}
+
+/// Makes a server- or URL-provided file name safe to save.
+pub fn sanitize_file_name(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let name: String = name
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .trim()
+        .trim_start_matches('.')
+        .to_string();
+
+    if name.is_empty() {
+        FALLBACK_FILE_NAME.to_string()
+    } else {
+        name
+    }
+}
+
+/// Gets a file name from the last path segment of a URL.
+pub fn file_name_from_url(url: &str) -> String {
+    let segment = url::Url::parse(url)
+        .ok()
+        .and_then(|parsed| {
+            parsed
+                .path_segments()
+                .and_then(|mut segments| segments.next_back().map(str::to_string))
+        })
+        .unwrap_or_default();
+    sanitize_file_name(&crate::handlers::title::percent_decode(&segment))
+}
+
+/// Gets a path in `dir` for `file_name` that no file or download uses yet,
+/// numbering it like `report (1).pdf` if needed.
+pub fn unique_path(dir: &Path, file_name: &str, taken: &[PathBuf]) -> PathBuf {
+    let is_free = |path: &Path| !path.exists() && !part_path(path).exists() && !taken.iter().any(|t| t == path);
+
+    let candidate = dir.join(file_name);
+    if is_free(&candidate) {
+        return candidate;
+    }
+
+    let (stem, extension) = match file_name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (file_name, String::new()),
+    };
+    (1..)
+        .map(|n| dir.join(format!("{} ({}){}", stem, n, extension)))
+        .find(|path| is_free(path))
+        .unwrap_or(candidate)
+}
+
+/// Builds a `Cookie` header from name/value pairs.
+pub fn cookie_header(cookies: &[(String, String)]) -> Option<String> {
+    if cookies.is_empty() {
+        return None;
+    }
+    Some(
+        cookies
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; "),
+    )
+}
+
+/// Why a running transfer was stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interrupt {
+    /// Keep the partial file to resume later.
+    Pause,
+    /// Discard the partial file.
+    Cancel,
+}
+
+/// Lets a running transfer be paused or cancelled from elsewhere.
+#[derive(Debug, Default)]
+pub struct TransferControl {
+    interrupt: Mutex<Option<Interrupt>>,
+    /// Resume asked for while the transfer was still stopping for a pause.
+    resume: AtomicBool,
+    notify: Notify,
+}
+
+impl TransferControl {
+    /// Asks the transfer to stop at the next opportunity.
+    pub fn interrupt(&self, interrupt: Interrupt) {
+        if let Ok(mut current) = self.interrupt.lock() {
+            *current = Some(interrupt);
+            self.resume.store(false, Ordering::SeqCst);
+        }
+        self.notify.notify_one();
+    }
+
+    /// Asks a transfer that is pausing to be queued again once it stopped.
+    /// Returns false if no pause was requested.
+    pub fn resume(&self) -> bool {
+        let Ok(current) = self.interrupt.lock() else {
+            return false;
+        };
+        let pausing = *current == Some(Interrupt::Pause);
+        if pausing {
+            self.resume.store(true, Ordering::SeqCst);
+        }
+        pausing
+    }
+
+    /// Checks whether a resume was asked for since the last pause, clearing it.
+    pub fn take_resume(&self) -> bool {
+        self.resume.swap(false, Ordering::SeqCst)
+    }
+
+    fn requested(&self) -> Option<Interrupt> {
+        self.interrupt.lock().ok().and_then(|current| *current)
+    }
+}
+
+/// How a transfer ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferOutcome {
+    Completed {
+        size: u64,
+        mime_type: Option<String>,
+    },
+    Interrupted(Interrupt),
+}
+
+/// Throttles progress reports to one per percent, or one per
+/// [`UNKNOWN_SIZE_STEP`] bytes when the size is unknown.
+#[derive(Debug, Default)]
+pub struct ProgressThrottle {
+    last_percent: Option<u8>,
+    last_reported: u64,
+}
+
+impl ProgressThrottle {
+    /// Checks whether progress at `downloaded` bytes is worth reporting.
+    pub fn should_report(&mut self, downloaded: u64, total: Option<u64>) -> bool {
+        let percent = percent(downloaded, total);
+        let report = match percent {
+            Some(p) => self.last_percent != Some(p),
+            None => downloaded.saturating_sub(self.last_reported) >= UNKNOWN_SIZE_STEP,
+        };
+        if report {
+            self.last_percent = percent;
+            self.last_reported = downloaded;
+        }
+        report
+    }
+}
+
+/// Gets how far a download is, if its size is known.
+pub fn percent(downloaded: u64, total: Option<u64>) -> Option<u8> {
+    total
+        .filter(|t| *t > 0)
+        .map(|t| (downloaded.min(t) * 100 / t) as u8)
+}
+
+/// Gets the total size from a `Content-Range: bytes start-end/total` header.
+fn content_range_total(header: &str) -> Option<u64> {
+    header.rsplit_once('/').and_then(|(_, total)| total.trim().parse().ok())
+}
+
+/// Gets the start offset from a `Content-Range: bytes start-end/total` header.
+fn content_range_start(header: &str) -> Option<u64> {
+    header
+        .trim()
+        .strip_prefix("bytes ")
+        .and_then(|range| range.split_once('-'))
+        .and_then(|(start, _)| start.trim().parse().ok())
+}
+
+/// Downloads `url` to `path`, resuming from its partial file if there is one.
+///
+/// `on_progress` receives the bytes downloaded so far and the total size, if
+/// known, after every chunk.
+pub async fn transfer(
+    client: &reqwest::Client,
+    url: &str,
+    path: &Path,
+    headers: &[(String, String)],
+    control: &TransferControl,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<TransferOutcome, String> {
+    let part = part_path(path);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let existing = tokio::fs::metadata(&part).await.map(|m| m.len()).unwrap_or(0);
+    let mut request = client.get(url);
+    for (name, value) in headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    if existing > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", existing));
+    }
+
+    let header_value = |response: &reqwest::Response, name: reqwest::header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+
+    let mut response = request.send().await.map_err(|e| format!("Download failed: {}", e))?;
+    let status = response.status();
+    let mime_type = header_value(&response, reqwest::header::CONTENT_TYPE)
+        .map(|t| t.split(';').next().unwrap_or_default().trim().to_string());
+
+    // The partial file already holds everything
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && existing > 0 {
+        let total = header_value(&response, reqwest::header::CONTENT_RANGE).and_then(|r| content_range_total(&r));
+        if total == Some(existing) {
+            tokio::fs::rename(&part, path)
+                .await
+                .map_err(|e| format!("Failed to save {}: {}", path.display(), e))?;
+            on_progress(existing, total);
+            return Ok(TransferOutcome::Completed { size: existing, mime_type });
+        }
+    }
+    if !status.is_success() {
+        return Err(format!("Download failed: HTTP {}", status));
+    }
+
+    // Append only if the server continues exactly where the partial file ends
+    let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT
+        && header_value(&response, reqwest::header::CONTENT_RANGE).and_then(|r| content_range_start(&r))
+            == Some(existing);
+    let (mut downloaded, total) = if resumed {
+        let total = header_value(&response, reqwest::header::CONTENT_RANGE).and_then(|r| content_range_total(&r));
+        (existing, total)
+    } else {
+        (0, response.content_length())
+    };
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&part)
+        .await
+        .map_err(|e| format!("Failed to open {}: {}", part.display(), e))?;
+    on_progress(downloaded, total);
+
+    loop {
+        if let Some(interrupt) = control.requested() {
+            file.flush().await.map_err(|e| e.to_string())?;
+            return Ok(TransferOutcome::Interrupted(interrupt));
+        }
+
+        let chunk = tokio::select! {
+            chunk = response.chunk() => chunk.map_err(|e| format!("Download failed: {}", e))?,
+            _ = control.notify.notified() => continue,
+        };
+        let Some(chunk) = chunk else {
+            break;
+        };
+
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| format!("Failed to write {}: {}", part.display(), e))?;
+        downloaded += chunk.len() as u64;
+        on_progress(downloaded, total);
+    }
+
+    file.flush().await.map_err(|e| e.to_string())?;
+    drop(file);
+    if let Some(total) = total {
+        if downloaded != total {
+            return Err(format!("Download incomplete: got {} of {} bytes", downloaded, total));
+        }
+    }
+    tokio::fs::rename(&part, path)
+        .await
+        .map_err(|e| format!("Failed to save {}: {}", path.display(), e))?;
+
+    Ok(TransferOutcome::Completed { size: downloaded, mime_type })
+}
@@ -0,0 +1,329 @@
+//! Tests for download records and transfers.
+
+use super::*;
+use crate::utils::test_server::{Response, TestServer};
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// Body served by the test server.
+fn body() -> Vec<u8> {
+    (0..64 * 1024).map(|i| (i % 251) as u8).collect()
+}
+
+/// Starts serving [`body`] at every path but `/missing`; `honor_range`
+/// controls `Range` support, and `chunk_delay` slows the body down so
+/// transfers can be interrupted.
+async fn file_server(honor_range: bool, chunk_delay: Duration) -> TestServer {
+    TestServer::start(move |request| {
+        if request.path() == "/missing" {
+            return Response::new("404 Not Found");
+        }
+
+        let body = body();
+        let start = request
+            .header("range")
+            .and_then(|range| range.strip_prefix("bytes=")?.trim_end_matches('-').parse::<usize>().ok())
+            .filter(|_| honor_range);
+        let response = match start {
+            Some(start) => Response::new("206 Partial Content")
+                .header("Content-Type", "application/zip")
+                .header("Content-Range", &format!("bytes {}-{}/{}", start, body.len() - 1, body.len()))
+                .body(body[start..].to_vec()),
+            None => Response::new("200 OK")
+                .header("Content-Type", "application/zip; charset=binary")
+                .body(body),
+        };
+        response.chunk_delay(chunk_delay)
+    })
+    .await
+}
+
+fn download(status: DownloadStatus) -> Download {
+    let mut download = Download::new(
+        "https://file.notion.so/export.zip".to_string(),
+        PathBuf::from("/tmp/export.zip"),
+        None,
+    );
+    download.status = status;
+    download
+}
+
+#[test]
+fn test_new_download_takes_file_name_from_path() {
+    let download = Download::new(
+        "https://example.com/a".to_string(),
+        PathBuf::from("/home/user/Downloads/Export.zip"),
+        Some("https://www.notion.so/Team-Wiki".to_string()),
+    );
+    assert_eq!(download.file_name, "Export.zip");
+    assert_eq!(download.status, DownloadStatus::Queued);
+    assert_eq!(download.part_path(), PathBuf::from("/home/user/Downloads/Export.zip.part"));
+}
+
+#[test]
+fn test_on_startup_pauses_running_downloads() {
+    let mut downloads = vec![
+        download(DownloadStatus::Downloading),
+        download(DownloadStatus::Queued),
+        download(DownloadStatus::Completed),
+    ];
+    let mut blob = download(DownloadStatus::Downloading);
+    blob.url = "blob:https://www.notion.so/1b2c".to_string();
+    downloads.push(blob);
+
+    on_startup(&mut downloads);
+    assert_eq!(downloads[0].status, DownloadStatus::Paused);
+    assert_eq!(downloads[1].status, DownloadStatus::Queued);
+    assert_eq!(downloads[2].status, DownloadStatus::Completed);
+    assert_eq!(downloads[3].status, DownloadStatus::Failed);
+    assert!(downloads[3].error.is_some());
+}
+
+#[test]
+fn test_next_to_start_respects_limit() {
+    let downloads = vec![
+        download(DownloadStatus::Downloading),
+        download(DownloadStatus::Queued),
+        download(DownloadStatus::Paused),
+        download(DownloadStatus::Queued),
+        download(DownloadStatus::Queued),
+    ];
+
+    assert_eq!(next_to_start(&downloads, 3), vec![downloads[1].id.clone(), downloads[3].id.clone()]);
+    assert!(next_to_start(&downloads, 1).is_empty());
+    // A limit of zero still lets one download run
+    assert!(next_to_start(&downloads[1..], 0).len() == 1);
+}
+
+#[test]
+fn test_trim_history_keeps_unfinished_downloads() {
+    let mut downloads = vec![
+        download(DownloadStatus::Completed),
+        download(DownloadStatus::Paused),
+        download(DownloadStatus::Failed),
+        download(DownloadStatus::Cancelled),
+    ];
+    let kept = [downloads[1].id.clone(), downloads[3].id.clone()];
+
+    trim_history(&mut downloads, 1);
+    let ids: Vec<_> = downloads.iter().map(|d| d.id.clone()).collect();
+    assert_eq!(ids, kept);
+}
+
+#[test]
+fn test_sanitize_file_name() {
+    assert_eq!(sanitize_file_name("report.pdf"), "report.pdf");
+    assert_eq!(sanitize_file_name("../../etc/passwd"), "passwd");
+    assert_eq!(sanitize_file_name("C:\\temp\\notes.md"), "notes.md");
+    assert_eq!(sanitize_file_name(".bashrc"), "bashrc");
+    assert_eq!(sanitize_file_name("  "), "download");
+    assert_eq!(sanitize_file_name(".."), "download");
+}
+
+#[test]
+fn test_file_name_from_url() {
+    assert_eq!(file_name_from_url("https://example.com/files/Export%20Data.zip?x=1"), "Export Data.zip");
+    assert_eq!(file_name_from_url("https://example.com/"), "download");
+    assert_eq!(file_name_from_url("not a url"), "download");
+}
+
+#[test]
+fn test_unique_path_numbers_taken_names() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("report.pdf"), b"x").unwrap();
+    std::fs::write(dir.path().join("report (1).pdf.part"), b"x").unwrap();
+    let taken = vec![dir.path().join("report (2).pdf")];
+
+    assert_eq!(unique_path(dir.path(), "notes.md", &taken), dir.path().join("notes.md"));
+    assert_eq!(unique_path(dir.path(), "report.pdf", &taken), dir.path().join("report (3).pdf"));
+
+    std::fs::write(dir.path().join("README"), b"x").unwrap();
+    assert_eq!(unique_path(dir.path(), "README", &[]), dir.path().join("README (1)"));
+}
+
+#[test]
+fn test_cookie_header() {
+    assert_eq!(cookie_header(&[]), None);
+    let cookies = vec![
+        ("token_v2".to_string(), "abc".to_string()),
+        ("notion_user_id".to_string(), "42".to_string()),
+    ];
+    assert_eq!(cookie_header(&cookies), Some("token_v2=abc; notion_user_id=42".to_string()));
+}
+
+#[test]
+fn test_progress_throttle() {
+    let mut throttle = ProgressThrottle::default();
+    assert!(throttle.should_report(0, Some(1000)));
+    assert!(!throttle.should_report(5, Some(1000)));
+    assert!(throttle.should_report(10, Some(1000)));
+    assert!(throttle.should_report(1000, Some(1000)));
+
+    let mut throttle = ProgressThrottle::default();
+    assert!(!throttle.should_report(1024, None));
+    assert!(throttle.should_report(UNKNOWN_SIZE_STEP, None));
+    assert!(!throttle.should_report(UNKNOWN_SIZE_STEP + 1, None));
+}
+
+#[test]
+fn test_transfer_control_resume_while_pausing() {
+    let control = TransferControl::default();
+    assert!(!control.resume());
+    assert!(!control.take_resume());
+
+    control.interrupt(Interrupt::Pause);
+    assert!(control.resume());
+    assert!(control.take_resume());
+    assert!(!control.take_resume());
+
+    // Pausing again drops the earlier resume
+    assert!(control.resume());
+    control.interrupt(Interrupt::Pause);
+    assert!(!control.take_resume());
+
+    control.interrupt(Interrupt::Cancel);
+    assert!(!control.resume());
+}
+
+#[test]
+fn test_content_range_parsing() {
+    assert_eq!(content_range_start("bytes 100-199/200"), Some(100));
+    assert_eq!(content_range_total("bytes 100-199/200"), Some(200));
+    assert_eq!(content_range_total("bytes */200"), Some(200));
+    assert_eq!(content_range_total("bytes 0-99/*"), None);
+}
+
+#[tokio::test]
+async fn test_transfer_downloads_file() {
+    let server = file_server(true, Duration::ZERO).await;
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("nested").join("export.zip");
+    let control = TransferControl::default();
+    let mut reports = Vec::new();
+
+    let outcome = transfer(
+        &reqwest::Client::new(),
+        &server.url("export.zip"),
+        &path,
+        &[("Cookie".to_string(), "token_v2=secret".to_string())],
+        &control,
+        |downloaded, total| reports.push((downloaded, total)),
+    )
+    .await
+    .unwrap();
+
+    let expected = body();
+    assert_eq!(
+        outcome,
+        TransferOutcome::Completed {
+            size: expected.len() as u64,
+            mime_type: Some("application/zip".to_string()),
+        }
+    );
+    assert_eq!(std::fs::read(&path).unwrap(), expected);
+    assert!(!part_path(&path).exists());
+    assert_eq!(reports.first(), Some(&(0, Some(expected.len() as u64))));
+    assert_eq!(reports.last(), Some(&(expected.len() as u64, Some(expected.len() as u64))));
+
+    let requests = server.requests();
+    assert!(requests[0].head.contains("token_v2=secret"));
+    assert_eq!(requests[0].header("range"), None);
+}
+
+#[tokio::test]
+async fn test_transfer_resumes_partial_file() {
+    let server = file_server(true, Duration::ZERO).await;
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("export.zip");
+    let expected = body();
+    std::fs::write(part_path(&path), &expected[..10_000]).unwrap();
+
+    let mut first_report = None;
+    let outcome = transfer(
+        &reqwest::Client::new(),
+        &server.url("export.zip"),
+        &path,
+        &[],
+        &TransferControl::default(),
+        |downloaded, _| {
+            first_report.get_or_insert(downloaded);
+        },
+    )
+    .await
+    .unwrap();
+
+    assert!(matches!(outcome, TransferOutcome::Completed { size, .. } if size == expected.len() as u64));
+    assert_eq!(std::fs::read(&path).unwrap(), expected);
+    assert_eq!(first_report, Some(10_000));
+    assert_eq!(server.requests()[0].header("range").as_deref(), Some("bytes=10000-"));
+}
+
+#[tokio::test]
+async fn test_transfer_restarts_when_range_is_ignored() {
+    let server = file_server(false, Duration::ZERO).await;
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("export.zip");
+    std::fs::write(part_path(&path), b"stale partial data").unwrap();
+
+    transfer(
+        &reqwest::Client::new(),
+        &server.url("export.zip"),
+        &path,
+        &[],
+        &TransferControl::default(),
+        |_, _| {},
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), body());
+}
+
+#[tokio::test]
+async fn test_transfer_pause_and_resume() {
+    let server = file_server(true, Duration::from_millis(20)).await;
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("export.zip");
+    let client = reqwest::Client::new();
+    let control = TransferControl::default();
+
+    let outcome = transfer(&client, &server.url("export.zip"), &path, &[], &control, |downloaded, _| {
+        if downloaded >= 8192 {
+            control.interrupt(Interrupt::Pause);
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(outcome, TransferOutcome::Interrupted(Interrupt::Pause));
+    assert!(!path.exists());
+    let partial = std::fs::metadata(part_path(&path)).unwrap().len();
+    assert!(partial >= 8192 && partial < body().len() as u64);
+
+    transfer(&client, &server.url("export.zip"), &path, &[], &TransferControl::default(), |_, _| {})
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), body());
+    assert_eq!(server.requests()[1].header("range"), Some(format!("bytes={}-", partial)));
+}
+
+#[tokio::test]
+async fn test_transfer_reports_http_errors() {
+    let server = file_server(true, Duration::ZERO).await;
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("missing.zip");
+
+    let error = transfer(
+        &reqwest::Client::new(),
+        &server.url("missing"),
+        &path,
+        &[],
+        &TransferControl::default(),
+        |_, _| {},
+    )
+    .await
+    .unwrap_err();
+
+    assert!(error.contains("404"), "{}", error);
+    assert!(!path.exists());
+}
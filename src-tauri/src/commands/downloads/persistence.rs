@@ -0,0 +1,41 @@
+//! Download history persistence.
+
+use super::manager::Download;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_store::{with_store, StoreCollection};
+
+const DOWNLOADS_STORE_PATH: &str = "downloads.json";
+
+/// Loads the download history from persistent storage.
+pub fn load_downloads<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<Download>, Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+    
+    with_store(app, stores, DOWNLOADS_STORE_PATH, |store| {
+        if let Some(value) = store.get("downloads") {
+            let downloads: Vec<Download> = serde_json::from_value(value.clone())
+                .map_err(|e| tauri_plugin_store::Error::Deserialize(e.to_string()))?;
+            Ok(downloads)
+        } else {
+            Ok(vec![])
+        }
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
+
+/// Saves the download history to persistent storage.
+pub fn save_downloads<R: Runtime>(
+    app: &AppHandle<R>,
+    downloads: &[Download],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+    
+    with_store(app, stores, DOWNLOADS_STORE_PATH, |store| {
+        let data = serde_json::to_value(downloads)
+            .map_err(|e| tauri_plugin_store::Error::Serialize(e.to_string()))?;
+        store.insert("downloads".to_string(), data)?;
+        store.save()?;
+        log::debug!("Downloads saved to store: {} entries", downloads.len());
+        Ok(())
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
@@ -0,0 +1,559 @@
+//! Post-processing rules for finished downloads.
+//!
+//! A rule matches downloads by file extension, MIME type and the page they
+//! were started from; every criterion a rule sets must match. The first
+//! enabled rule that matches runs its actions in order:
+//!
+//! - `move` into a folder, which may contain `{extension}`, `{year}`,
+//!   `{month}` and `{host}` placeholders
+//! - `unzip` an archive next to it; Notion exports get a readable folder name,
+//!   nested export parts are extracted too, and the page IDs Notion appends
+//!   to every file name are removed (links between pages are updated to match)
+//! - `verify_checksum` against a SHA-256 from the rule or a `.sha256` file
+//! - `open` with the default application
+//!
+//! A failing action stops the rule. Each action is recorded on the download,
+//! so its history shows what was done to the file.
+
+#[cfg(test)]
+mod tests;
+
+use super::manager::{unique_path, Download};
+use crate::handlers::rules::Matcher;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+const SETTINGS_STORE_PATH: &str = "settings.json";
+const RULES_KEY: &str = "download_rules";
+
+/// Files whose links to other exported pages are updated when IDs are removed.
+const LINKING_EXTENSIONS: &[&str] = &["md", "html", "csv"];
+
+/// Length of a Notion page ID without hyphens.
+const PAGE_ID_LENGTH: usize = 32;
+
+/// A rule applied to finished downloads.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DownloadRule {
+    pub name: String,
+    /// File extensions such as `zip` or `tar.gz`; any if empty.
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// MIME types such as `application/pdf` or `image/*`; any if empty.
+    #[serde(default)]
+    pub mime_types: Vec<String>,
+    /// Navigation rule pattern for the page the download came from.
+    #[serde(default)]
+    pub source: Option<String>,
+    pub actions: Vec<RuleAction>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Something done to a finished download.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleAction {
+    Move {
+        folder: String,
+    },
+    Unzip {
+        #[serde(default)]
+        delete_archive: bool,
+        #[serde(default = "default_true")]
+        strip_page_ids: bool,
+    },
+    VerifyChecksum {
+        #[serde(default)]
+        sha256: Option<String>,
+    },
+    Open,
+}
+
+impl RuleAction {
+    /// Name of the action in processing records.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Move { .. } => "move",
+            Self::Unzip { .. } => "unzip",
+            Self::VerifyChecksum { .. } => "verify_checksum",
+            Self::Open => "open",
+        }
+    }
+}
+
+/// Record of one action run on a download.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProcessingStep {
+    pub rule: String,
+    pub action: String,
+    pub success: bool,
+    pub detail: String,
+    /// Where the file or extracted folder is after the action.
+    pub path: PathBuf,
+    pub at: String, // ISO 8601 format
+}
+
+/// Checks whether a file name has one of the extensions.
+fn extension_matches(file_name: &str, extensions: &[String]) -> bool {
+    let file_name = file_name.to_lowercase();
+    extensions.iter().any(|extension| {
+        let extension = extension.trim().trim_start_matches('.').to_lowercase();
+        !extension.is_empty() && file_name.ends_with(&format!(".{}", extension))
+    })
+}
+
+/// Checks whether a MIME type matches one of the patterns.
+fn mime_matches(mime_type: Option<&str>, patterns: &[String]) -> bool {
+    let Some(mime_type) = mime_type.map(str::to_lowercase) else {
+        return false;
+    };
+    patterns.iter().any(|pattern| {
+        let pattern = pattern.trim().to_lowercase();
+        match pattern.strip_suffix("/*") {
+            Some(kind) => mime_type.split('/').next() == Some(kind),
+            None => mime_type == pattern,
+        }
+    })
+}
+
+/// Checks whether a rule applies to a download.
+pub fn rule_matches(rule: &DownloadRule, download: &Download) -> bool {
+    if !rule.enabled {
+        return false;
+    }
+    if !rule.extensions.is_empty() && !extension_matches(&download.file_name, &rule.extensions) {
+        return false;
+    }
+    if !rule.mime_types.is_empty() && !mime_matches(download.mime_type.as_deref(), &rule.mime_types) {
+        return false;
+    }
+    match &rule.source {
+        None => true,
+        Some(pattern) => {
+            let page = download.source_page.as_deref().and_then(|page| url::Url::parse(page).ok());
+            match (Matcher::parse(pattern), page) {
+                (Ok(matcher), Some(page)) => matcher.matches(&page),
+                _ => false,
+            }
+        }
+    }
+}
+
+/// Finds the rule that applies to a download: the first enabled match.
+pub fn find_rule<'a>(rules: &'a [DownloadRule], download: &Download) -> Option<&'a DownloadRule> {
+    rules.iter().find(|rule| rule_matches(rule, download))
+}
+
+/// Checks that every rule can run.
+pub fn validate_rules(rules: &[DownloadRule]) -> Result<(), String> {
+    for rule in rules {
+        if rule.actions.is_empty() {
+            return Err(format!("Download rule \"{}\" has no actions", rule.name));
+        }
+        if let Some(source) = &rule.source {
+            Matcher::parse(source)?;
+        }
+        for action in &rule.actions {
+            match action {
+                RuleAction::Move { folder } if folder.trim().is_empty() => {
+                    return Err(format!("Download rule \"{}\" moves to an empty folder", rule.name));
+                }
+                RuleAction::VerifyChecksum { sha256: Some(sha256) } if parse_sha256(sha256).is_none() => {
+                    return Err(format!("Download rule \"{}\" has an invalid SHA-256 checksum", rule.name));
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses a hex SHA-256 checksum, possibly followed by a file name.
+fn parse_sha256(text: &str) -> Option<String> {
+    let checksum = text.split_whitespace().next()?.to_ascii_lowercase();
+    (checksum.len() == 64 && checksum.chars().all(|c| c.is_ascii_hexdigit())).then_some(checksum)
+}
+
+/// Computes the SHA-256 checksum of a file.
+pub fn sha256_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Fills the placeholders of a move folder and resolves it against `base`.
+pub fn resolve_folder(folder: &str, download: &Download, base: &Path, home: Option<&Path>) -> PathBuf {
+    let now = chrono::Local::now();
+    let extension = download
+        .file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_lowercase())
+        .unwrap_or_default();
+    let host = download
+        .source_page
+        .as_deref()
+        .and_then(|page| url::Url::parse(page).ok())
+        .or_else(|| url::Url::parse(&download.url).ok())
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default();
+
+    let folder = folder
+        .trim()
+        .replace("{extension}", &extension)
+        .replace("{year}", &now.format("%Y").to_string())
+        .replace("{month}", &now.format("%m").to_string())
+        .replace("{host}", &host);
+
+    match (folder.strip_prefix("~/"), home) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => base.join(folder),
+    }
+}
+
+/// Moves a file or folder, copying files across file systems.
+fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if from.is_dir() {
+        return Err(format!("Failed to move {} to {}", from.display(), to.display()));
+    }
+    fs::copy(from, to).map_err(|e| format!("Failed to move {}: {}", from.display(), e))?;
+    fs::remove_file(from).map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+}
+
+/// Checks whether a file name looks like a Notion export, e.g. `Export-5c7e9a1b-….zip`.
+pub fn is_notion_export(file_name: &str) -> bool {
+    let lower = file_name.to_lowercase();
+    lower
+        .strip_prefix("export-")
+        .filter(|_| lower.ends_with(".zip"))
+        .map(|rest| rest.chars().take(8).filter(char::is_ascii_hexdigit).count() == 8)
+        .unwrap_or(false)
+}
+
+/// Removes the page ID Notion appends to exported names, like
+/// `Meeting Notes 5c7e….md` or `Tasks 5c7e…_all.csv`.
+///
+/// Returns the new name and the removed ID, if there was one.
+pub fn strip_export_id(name: &str) -> (String, Option<String>) {
+    let bytes = name.as_bytes();
+    let mut search = 0;
+    while let Some(offset) = name[search..].find(' ') {
+        let start = search + offset;
+        let id_end = start + 1 + PAGE_ID_LENGTH;
+        let is_id = id_end <= bytes.len() && bytes[start + 1..id_end].iter().all(u8::is_ascii_hexdigit);
+        let at_boundary = matches!(bytes.get(id_end), None | Some(b'.' | b'_'));
+        if is_id && at_boundary && start > 0 {
+            let id = name[start + 1..id_end].to_string();
+            return (format!("{}{}", &name[..start], &name[id_end..]), Some(id));
+        }
+        search = start + 1;
+    }
+    (name.to_string(), None)
+}
+
+/// Removes the given page IDs from links in exported text, where they follow
+/// a space or an encoded space (`%20`).
+pub fn strip_ids_in_text(text: &str, ids: &HashSet<String>) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    loop {
+        let space = rest.find(' ');
+        let encoded = rest.find("%20");
+        let (index, separator_len) = match (space, encoded) {
+            (Some(s), Some(e)) if e < s => (e, 3),
+            (Some(s), _) => (s, 1),
+            (None, Some(e)) => (e, 3),
+            (None, None) => break,
+        };
+
+        let candidate = rest.get(index + separator_len..index + separator_len + PAGE_ID_LENGTH);
+        match candidate {
+            Some(id) if ids.contains(&id.to_ascii_lowercase()) => {
+                out.push_str(&rest[..index]);
+                rest = &rest[index + separator_len + PAGE_ID_LENGTH..];
+            }
+            _ => {
+                out.push_str(&rest[..index + separator_len]);
+                rest = &rest[index + separator_len..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Extracts a zip archive into `dest`, skipping entries that would escape it.
+///
+/// Returns the number of files extracted.
+pub fn extract_zip(archive: &Path, dest: &Path) -> Result<usize, String> {
+    let file = fs::File::open(archive).map_err(|e| format!("Failed to open {}: {}", archive.display(), e))?;
+    let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("Invalid archive {}: {}", archive.display(), e))?;
+    let mut extracted = 0;
+
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index).map_err(|e| format!("Invalid archive entry: {}", e))?;
+        let Some(relative) = entry.enclosed_name() else {
+            log::warn!("Skipping unsafe archive entry: {}", entry.name());
+            continue;
+        };
+        let target = dest.join(relative);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&target).map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let mut out = fs::File::create(&target).map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+        std::io::copy(&mut entry, &mut out).map_err(|e| format!("Failed to extract {}: {}", target.display(), e))?;
+        extracted += 1;
+    }
+    Ok(extracted)
+}
+
+/// Removes exported page IDs from the names below `dir`, deepest first, and
+/// updates the links between pages. Returns the number of names changed.
+pub fn strip_export_ids(dir: &Path) -> Result<usize, String> {
+    fn rename_below(dir: &Path, stripped: &mut HashSet<String>, kept: &mut HashSet<String>) -> Result<usize, String> {
+        let mut renamed = 0;
+        let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .collect();
+        entries.sort();
+
+        for path in entries {
+            if path.is_dir() {
+                renamed += rename_below(&path, stripped, kept)?;
+            }
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            let (new_name, Some(id)) = strip_export_id(&name) else {
+                continue;
+            };
+            let target = path.with_file_name(&new_name);
+            // Two pages with the same title keep their IDs apart
+            if target.exists() || fs::rename(&path, &target).is_err() {
+                kept.insert(id.to_ascii_lowercase());
+                continue;
+            }
+            stripped.insert(id.to_ascii_lowercase());
+            renamed += 1;
+        }
+        Ok(renamed)
+    }
+
+    fn rewrite_links(dir: &Path, ids: &HashSet<String>) -> Result<(), String> {
+        for entry in fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if path.is_dir() {
+                rewrite_links(&path, ids)?;
+                continue;
+            }
+            let links = path
+                .extension()
+                .map(|e| LINKING_EXTENSIONS.contains(&e.to_string_lossy().to_lowercase().as_str()))
+                .unwrap_or(false);
+            if !links {
+                continue;
+            }
+            let Ok(text) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let rewritten = strip_ids_in_text(&text, ids);
+            if rewritten != text {
+                fs::write(&path, rewritten).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            }
+        }
+        Ok(())
+    }
+
+    let mut stripped = HashSet::new();
+    let mut kept = HashSet::new();
+    let renamed = rename_below(dir, &mut stripped, &mut kept)?;
+    let ids: HashSet<String> = stripped.difference(&kept).cloned().collect();
+    if !ids.is_empty() {
+        rewrite_links(dir, &ids)?;
+    }
+    Ok(renamed)
+}
+
+/// Extracts an archive next to it, returning the folder and a description.
+fn unzip(archive: &Path, delete_archive: bool, strip_page_ids: bool) -> Result<(PathBuf, String), String> {
+    let parent = archive.parent().unwrap_or(Path::new("."));
+    let file_name = archive.file_name().unwrap_or_default().to_string_lossy().into_owned();
+    let notion_export = is_notion_export(&file_name);
+    let folder_name = if notion_export {
+        format!("Notion Export {}", chrono::Local::now().format("%Y-%m-%d"))
+    } else {
+        file_name
+            .rsplit_once('.')
+            .map(|(stem, _)| stem.to_string())
+            .filter(|stem| !stem.is_empty())
+            .unwrap_or_else(|| file_name.clone())
+    };
+    let dest = unique_path(parent, &folder_name, &[]);
+    fs::create_dir_all(&dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+
+    let mut extracted = extract_zip(archive, &dest)?;
+
+    // Large workspace exports come as an archive of archives
+    if notion_export {
+        let parts: Vec<PathBuf> = fs::read_dir(&dest)
+            .map_err(|e| format!("Failed to read {}: {}", dest.display(), e))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file() && is_notion_export(&path.file_name().unwrap_or_default().to_string_lossy()))
+            .collect();
+        for part in parts {
+            // The part itself was counted as an extracted file
+            extracted = extracted - 1 + extract_zip(&part, &dest)?;
+            fs::remove_file(&part).map_err(|e| format!("Failed to remove {}: {}", part.display(), e))?;
+        }
+    }
+
+    let mut detail = format!("Extracted {} files to {}", extracted, dest.display());
+    if notion_export && strip_page_ids {
+        let renamed = strip_export_ids(&dest)?;
+        detail.push_str(&format!(", removed page IDs from {} names", renamed));
+    }
+    if delete_archive {
+        fs::remove_file(archive).map_err(|e| format!("Failed to remove {}: {}", archive.display(), e))?;
+        detail.push_str(", deleted the archive");
+    }
+    Ok((dest, detail))
+}
+
+/// Verifies a file against an expected SHA-256 checksum, from the rule or a
+/// `<file>.sha256` file next to it. Fails if neither provides one.
+fn verify_checksum(path: &Path, expected: Option<&str>) -> Result<String, String> {
+    let actual = sha256_file(path)?;
+    let mut sidecar = path.as_os_str().to_os_string();
+    sidecar.push(".sha256");
+    let expected = match expected {
+        Some(expected) => parse_sha256(expected),
+        None => fs::read_to_string(PathBuf::from(sidecar)).ok().and_then(|text| parse_sha256(&text)),
+    };
+
+    match expected {
+        Some(expected) if expected == actual => Ok(format!("SHA-256 {} verified", actual)),
+        Some(expected) => Err(format!("SHA-256 mismatch: expected {}, got {}", expected, actual)),
+        None => Err(format!("No expected checksum for {}", path.display())),
+    }
+}
+
+/// Runs a rule's actions on a finished download.
+///
+/// `download_dir` resolves relative move folders, and `open` opens a path
+/// with the default application. Returns where the file ended up and a
+/// record of every action.
+pub fn apply_rule(
+    rule: &DownloadRule,
+    download: &Download,
+    download_dir: &Path,
+    home: Option<&Path>,
+    open: &dyn Fn(&Path) -> Result<(), String>,
+) -> (PathBuf, Vec<ProcessingStep>) {
+    let mut path = download.path.clone();
+    let mut steps = Vec::new();
+
+    for action in &rule.actions {
+        let result = match action {
+            RuleAction::Move { folder } => {
+                let folder = resolve_folder(folder, download, download_dir, home);
+                let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                let target = unique_path(&folder, &name, &[]);
+                move_path(&path, &target).map(|()| {
+                    path = target;
+                    format!("Moved to {}", path.display())
+                })
+            }
+            RuleAction::Unzip { delete_archive, strip_page_ids } => {
+                unzip(&path, *delete_archive, *strip_page_ids).map(|(dest, detail)| {
+                    path = dest;
+                    detail
+                })
+            }
+            RuleAction::VerifyChecksum { sha256 } => verify_checksum(&path, sha256.as_deref()),
+            RuleAction::Open => open(&path).map(|()| format!("Opened {}", path.display())),
+        };
+
+        let success = result.is_ok();
+        steps.push(ProcessingStep {
+            rule: rule.name.clone(),
+            action: action.name().to_string(),
+            success,
+            detail: result.unwrap_or_else(|e| e),
+            path: path.clone(),
+            at: chrono::Utc::now().to_rfc3339(),
+        });
+        if !success {
+            break;
+        }
+    }
+
+    (path, steps)
+}
+
+/// Loads the download rules from the settings store.
+pub fn load_rules<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<DownloadRule>, String> {
+    let stores = app.state::<tauri_plugin_store::StoreCollection<R>>();
+    tauri_plugin_store::with_store(app, stores, SETTINGS_STORE_PATH, |store| {
+        if let Some(value) = store.get(RULES_KEY) {
+            serde_json::from_value(value.clone())
+                .map_err(|e| tauri_plugin_store::Error::Deserialize(e.to_string()))
+        } else {
+            Ok(Vec::new())
+        }
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Gets the download rules.
+#[tauri::command]
+pub fn get_download_rules<R: Runtime>(app: AppHandle<R>) -> Result<Vec<DownloadRule>, String> {
+    load_rules(&app)
+}
+
+/// Replaces the download rules.
+#[tauri::command]
+pub fn set_download_rules<R: Runtime>(
+    app: AppHandle<R>,
+    rules: Vec<DownloadRule>,
+) -> Result<(), String> {
+    validate_rules(&rules)?;
+
+    let stores = app.state::<tauri_plugin_store::StoreCollection<R>>();
+    tauri_plugin_store::with_store(app, stores, SETTINGS_STORE_PATH, |store| {
+        let value = serde_json::to_value(&rules)
+            .map_err(|e| tauri_plugin_store::Error::Serialize(e.to_string()))?;
+        store.insert(RULES_KEY.to_string(), value)?;
+        store.save()?;
+        Ok(())
+    })
+    .map_err(|e| e.to_string())
+}
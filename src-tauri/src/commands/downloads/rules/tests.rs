@@ -0,0 +1,361 @@
+//! Tests for download post-processing rules.
+
+use super::*;
+use std::io::Write;
+use tempfile::TempDir;
+
+const PAGE_ID: &str = "5c7e9a1b2c3d4e5f60718293a4b5c6d7";
+const OTHER_ID: &str = "0123456789abcdef0123456789abcdef";
+
+fn completed(path: PathBuf) -> Download {
+    let mut download = Download::new(
+        "https://file.notion.so/export.zip".to_string(),
+        path,
+        Some("https://www.notion.so/workspace/Page-5c7e9a1b".to_string()),
+    );
+    download.status = super::super::manager::DownloadStatus::Completed;
+    download
+}
+
+fn rule(actions: Vec<RuleAction>) -> DownloadRule {
+    DownloadRule {
+        name: "Test".to_string(),
+        extensions: Vec::new(),
+        mime_types: Vec::new(),
+        source: None,
+        actions,
+        enabled: true,
+    }
+}
+
+/// Writes a zip archive with the given files.
+fn write_zip(path: &Path, files: &[(&str, &[u8])]) {
+    let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+    for (name, contents) in files {
+        zip.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(contents).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+fn no_open(_: &Path) -> Result<(), String> {
+    panic!("open should not be called");
+}
+
+#[test]
+fn test_rule_deserializes_with_defaults() {
+    let rule: DownloadRule = serde_json::from_value(serde_json::json!({
+        "name": "Exports",
+        "extensions": ["zip"],
+        "actions": [{ "type": "unzip" }, { "type": "move", "folder": "Notion" }]
+    }))
+    .unwrap();
+
+    assert!(rule.enabled);
+    assert_eq!(rule.source, None);
+    assert_eq!(
+        rule.actions,
+        vec![
+            RuleAction::Unzip { delete_archive: false, strip_page_ids: true },
+            RuleAction::Move { folder: "Notion".to_string() },
+        ]
+    );
+}
+
+#[test]
+fn test_rule_matches_extension_mime_and_source() {
+    let mut download = completed(PathBuf::from("/tmp/Report.PDF"));
+    download.file_name = "Report.PDF".to_string();
+    download.mime_type = Some("application/pdf".to_string());
+
+    let mut by_extension = rule(vec![RuleAction::Open]);
+    by_extension.extensions = vec![".pdf".to_string()];
+    assert!(rule_matches(&by_extension, &download));
+    by_extension.extensions = vec!["zip".to_string()];
+    assert!(!rule_matches(&by_extension, &download));
+
+    let mut by_mime = rule(vec![RuleAction::Open]);
+    by_mime.mime_types = vec!["application/*".to_string()];
+    assert!(rule_matches(&by_mime, &download));
+    by_mime.mime_types = vec!["image/*".to_string()];
+    assert!(!rule_matches(&by_mime, &download));
+
+    let mut by_source = rule(vec![RuleAction::Open]);
+    by_source.source = Some("*.notion.so".to_string());
+    assert!(rule_matches(&by_source, &download));
+    by_source.source = Some("github.com".to_string());
+    assert!(!rule_matches(&by_source, &download));
+
+    download.source_page = None;
+    by_source.source = Some("*.notion.so".to_string());
+    assert!(!rule_matches(&by_source, &download));
+}
+
+#[test]
+fn test_rule_requires_every_criterion() {
+    let mut download = completed(PathBuf::from("/tmp/archive.tar.gz"));
+    download.file_name = "archive.tar.gz".to_string();
+    download.mime_type = None;
+
+    let mut both = rule(vec![RuleAction::Open]);
+    both.extensions = vec!["tar.gz".to_string()];
+    assert!(rule_matches(&both, &download));
+
+    both.mime_types = vec!["application/gzip".to_string()];
+    assert!(!rule_matches(&both, &download));
+}
+
+#[test]
+fn test_find_rule_skips_disabled_and_takes_first() {
+    let download = completed(PathBuf::from("/tmp/export.zip"));
+    let mut disabled = rule(vec![RuleAction::Open]);
+    disabled.name = "Disabled".to_string();
+    disabled.enabled = false;
+    let mut first = rule(vec![RuleAction::Open]);
+    first.name = "First".to_string();
+    let mut second = rule(vec![RuleAction::Open]);
+    second.name = "Second".to_string();
+
+    let rules = vec![disabled, first, second];
+    assert_eq!(find_rule(&rules, &download).unwrap().name, "First");
+    assert!(find_rule(&[], &download).is_none());
+}
+
+#[test]
+fn test_validate_rules() {
+    assert!(validate_rules(&[rule(vec![RuleAction::Open])]).is_ok());
+    assert!(validate_rules(&[rule(Vec::new())]).is_err());
+    assert!(validate_rules(&[rule(vec![RuleAction::Move { folder: " ".to_string() }])]).is_err());
+    assert!(validate_rules(&[rule(vec![RuleAction::VerifyChecksum {
+        sha256: Some("abc".to_string())
+    }])])
+    .is_err());
+
+    let mut bad_source = rule(vec![RuleAction::Open]);
+    bad_source.source = Some(String::new());
+    assert!(validate_rules(&[bad_source]).is_err());
+}
+
+#[test]
+fn test_is_notion_export() {
+    assert!(is_notion_export("Export-5c7e9a1b-2c3d-4e5f-6071-8293a4b5c6d7.zip"));
+    assert!(is_notion_export("Export-5c7e9a1b-2c3d-4e5f-6071-8293a4b5c6d7-Part-1.zip"));
+    assert!(!is_notion_export("Export-report.zip"));
+    assert!(!is_notion_export("Export-5c7e9a1b.pdf"));
+    assert!(!is_notion_export("photos.zip"));
+}
+
+#[test]
+fn test_strip_export_id() {
+    assert_eq!(
+        strip_export_id(&format!("Meeting Notes {}.md", PAGE_ID)),
+        ("Meeting Notes.md".to_string(), Some(PAGE_ID.to_string()))
+    );
+    assert_eq!(
+        strip_export_id(&format!("Tasks {}_all.csv", PAGE_ID)),
+        ("Tasks_all.csv".to_string(), Some(PAGE_ID.to_string()))
+    );
+    assert_eq!(
+        strip_export_id(&format!("Projects {}", PAGE_ID)),
+        ("Projects".to_string(), Some(PAGE_ID.to_string()))
+    );
+    assert_eq!(strip_export_id("Plain name.md"), ("Plain name.md".to_string(), None));
+    // An ID alone is the whole name, not a suffix
+    assert_eq!(strip_export_id(&format!(" {}.md", PAGE_ID)).1, None);
+}
+
+#[test]
+fn test_strip_ids_in_text_only_removes_known_ids() {
+    let ids: HashSet<String> = [PAGE_ID.to_string()].into();
+    let text = format!(
+        "[Notes](Meeting%20Notes%20{}.md) and [Other](Other%20{}.md), Notes {}",
+        PAGE_ID, OTHER_ID, PAGE_ID
+    );
+    assert_eq!(
+        strip_ids_in_text(&text, &ids),
+        format!("[Notes](Meeting%20Notes.md) and [Other](Other%20{}.md), Notes", OTHER_ID)
+    );
+}
+
+#[test]
+fn test_move_fills_placeholders_and_avoids_collisions() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("report.pdf");
+    fs::write(&file, b"pdf").unwrap();
+    fs::create_dir_all(dir.path().join("Sorted/pdf")).unwrap();
+    fs::write(dir.path().join("Sorted/pdf/report.pdf"), b"older").unwrap();
+
+    let mut download = completed(file.clone());
+    download.file_name = "report.pdf".to_string();
+    let rule = rule(vec![RuleAction::Move { folder: "Sorted/{extension}".to_string() }]);
+    let (path, steps) = apply_rule(&rule, &download, dir.path(), None, &no_open);
+
+    assert_eq!(path, dir.path().join("Sorted/pdf/report (1).pdf"));
+    assert_eq!(fs::read(&path).unwrap(), b"pdf");
+    assert!(!file.exists());
+    assert_eq!(steps.len(), 1);
+    assert!(steps[0].success);
+    assert_eq!(steps[0].action, "move");
+    assert_eq!(steps[0].path, path);
+}
+
+#[test]
+fn test_resolve_folder_uses_home() {
+    let download = completed(PathBuf::from("/tmp/a.zip"));
+    let home = PathBuf::from("/home/user");
+    assert_eq!(
+        resolve_folder("~/Archives/{host}", &download, Path::new("/downloads"), Some(&home)),
+        PathBuf::from("/home/user/Archives/www.notion.so")
+    );
+    assert_eq!(
+        resolve_folder("/srv/files", &download, Path::new("/downloads"), Some(&home)),
+        PathBuf::from("/srv/files")
+    );
+}
+
+#[test]
+fn test_unzip_notion_export_strips_ids_and_rewrites_links() {
+    let dir = TempDir::new().unwrap();
+    let part = dir.path().join("part.zip");
+    write_zip(
+        &part,
+        &[(
+            &format!("Projects {}/Roadmap {}.md", PAGE_ID, OTHER_ID),
+            b"# Roadmap",
+        )],
+    );
+    let archive = dir.path().join("Export-5c7e9a1b-2c3d-4e5f-6071-8293a4b5c6d7.zip");
+    let index = format!("[Roadmap](Projects%20{}/Roadmap%20{}.md)", PAGE_ID, OTHER_ID);
+    write_zip(
+        &archive,
+        &[
+            (&format!("Projects {}.md", PAGE_ID), index.as_bytes()),
+            ("Export-5c7e9a1b-2c3d-4e5f-6071-8293a4b5c6d7-Part-1.zip", &fs::read(&part).unwrap()),
+        ],
+    );
+
+    let rule = rule(vec![RuleAction::Unzip { delete_archive: true, strip_page_ids: true }]);
+    let (path, steps) = apply_rule(&rule, &completed(archive.clone()), dir.path(), None, &no_open);
+
+    assert!(steps[0].success, "{}", steps[0].detail);
+    assert!(path.file_name().unwrap().to_string_lossy().starts_with("Notion Export "));
+    assert!(!archive.exists());
+    assert_eq!(fs::read_to_string(path.join("Projects.md")).unwrap(), "[Roadmap](Projects/Roadmap.md)");
+    assert_eq!(fs::read_to_string(path.join("Projects/Roadmap.md")).unwrap(), "# Roadmap");
+    assert!(!path.join("Export-5c7e9a1b-2c3d-4e5f-6071-8293a4b5c6d7-Part-1.zip").exists());
+}
+
+#[test]
+fn test_strip_export_ids_keeps_ids_that_would_collide() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join(format!("Notes {}.md", PAGE_ID)), "a").unwrap();
+    fs::write(dir.path().join(format!("Notes {}.md", OTHER_ID)), "b").unwrap();
+    fs::write(
+        dir.path().join("Index.md"),
+        format!("Notes%20{}.md Notes%20{}.md", PAGE_ID, OTHER_ID),
+    )
+    .unwrap();
+
+    assert_eq!(strip_export_ids(dir.path()).unwrap(), 1);
+    let index = fs::read_to_string(dir.path().join("Index.md")).unwrap();
+    // Entries are renamed in sorted order, so the first ID wins the plain name
+    assert_eq!(index, format!("Notes%20{}.md Notes.md", PAGE_ID));
+    assert_eq!(fs::read_to_string(dir.path().join("Notes.md")).unwrap(), "b");
+    assert!(dir.path().join(format!("Notes {}.md", PAGE_ID)).exists());
+}
+
+#[test]
+fn test_unzip_plain_archive_keeps_names_and_skips_unsafe_entries() {
+    let dir = TempDir::new().unwrap();
+    let archive = dir.path().join("photos.zip");
+    write_zip(
+        &archive,
+        &[
+            (&format!("Album {}.txt", PAGE_ID), b"kept"),
+            ("../escape.txt", b"nope"),
+        ],
+    );
+
+    let rule = rule(vec![RuleAction::Unzip { delete_archive: false, strip_page_ids: true }]);
+    let (path, steps) = apply_rule(&rule, &completed(archive.clone()), dir.path(), None, &no_open);
+
+    assert!(steps[0].success);
+    assert_eq!(path, dir.path().join("photos"));
+    assert!(path.join(format!("Album {}.txt", PAGE_ID)).exists());
+    assert!(!dir.path().join("escape.txt").exists());
+    assert!(archive.exists());
+}
+
+#[test]
+fn test_verify_checksum() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("data.bin");
+    fs::write(&file, b"abc").unwrap();
+    let sha256 = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    assert_eq!(sha256_file(&file).unwrap(), sha256);
+
+    let matching = rule(vec![RuleAction::VerifyChecksum { sha256: Some(sha256.to_uppercase()) }]);
+    let (_, steps) = apply_rule(&matching, &completed(file.clone()), dir.path(), None, &no_open);
+    assert!(steps[0].success);
+
+    let mismatched = rule(vec![
+        RuleAction::VerifyChecksum { sha256: Some("0".repeat(64)) },
+        RuleAction::Open,
+    ]);
+    let (path, steps) = apply_rule(&mismatched, &completed(file.clone()), dir.path(), None, &no_open);
+    assert_eq!(steps.len(), 1, "a failed action stops the rule");
+    assert!(!steps[0].success);
+    assert!(steps[0].detail.contains("mismatch"));
+    assert_eq!(path, file);
+}
+
+#[test]
+fn test_verify_checksum_reads_sidecar() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("data.bin");
+    fs::write(&file, b"abc").unwrap();
+    let rule = rule(vec![RuleAction::VerifyChecksum { sha256: None }]);
+
+    let sha256 = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    fs::write(dir.path().join("data.bin.sha256"), format!("{}  data.bin\n", sha256)).unwrap();
+    let (_, steps) = apply_rule(&rule, &completed(file.clone()), dir.path(), None, &no_open);
+    assert!(steps[0].success);
+
+    fs::write(dir.path().join("data.bin.sha256"), format!("{}  data.bin\n", "1".repeat(64))).unwrap();
+    let (_, steps) = apply_rule(&rule, &completed(file), dir.path(), None, &no_open);
+    assert!(!steps[0].success);
+}
+
+#[test]
+fn test_verify_checksum_without_expected_checksum() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("data.bin");
+    fs::write(&file, b"abc").unwrap();
+    let rule = rule(vec![RuleAction::VerifyChecksum { sha256: None }, RuleAction::Open]);
+
+    let (path, steps) = apply_rule(&rule, &completed(file.clone()), dir.path(), None, &no_open);
+    assert_eq!(steps.len(), 1, "nothing to verify against stops the rule");
+    assert!(!steps[0].success);
+    assert!(steps[0].detail.starts_with("No expected checksum for"));
+    assert_eq!(path, file);
+}
+
+#[test]
+fn test_open_receives_final_path() {
+    let dir = TempDir::new().unwrap();
+    let file = dir.path().join("notes.txt");
+    fs::write(&file, b"notes").unwrap();
+    let opened = std::cell::RefCell::new(None);
+    let open = |path: &Path| {
+        *opened.borrow_mut() = Some(path.to_path_buf());
+        Ok(())
+    };
+
+    let rule = rule(vec![RuleAction::Move { folder: "Text".to_string() }, RuleAction::Open]);
+    let (path, steps) = apply_rule(&rule, &completed(file), dir.path(), None, &open);
+
+    assert_eq!(steps.len(), 2);
+    assert!(steps.iter().all(|step| step.success));
+    assert_eq!(opened.into_inner(), Some(dir.path().join("Text/notes.txt")));
+    assert_eq!(path, dir.path().join("Text/notes.txt"));
+}
@@ -32,3 +32,50 @@ fn test_filename_fallback() {
     
     assert!(!filename.is_empty());
 }
+
+#[test]
+fn test_webview_download_file_names() {
+    use super::manager::file_name_from_url;
+
+    assert_eq!(file_name_from_url("https://example.com/file.pdf"), "file.pdf");
+    assert_eq!(file_name_from_url("https://example.com/file.pdf?download=true"), "file.pdf");
+    assert_eq!(file_name_from_url("https://example.com/"), "download");
+}
+
+#[test]
+fn test_resolve_download_dir_prefers_setting() {
+    use super::resolve_download_dir;
+    use std::path::PathBuf;
+
+    let home = Some(PathBuf::from("/home/user"));
+    let system = Some(PathBuf::from("/home/user/Downloads"));
+
+    assert_eq!(
+        resolve_download_dir(Some("/data/notion"), home.clone(), system.clone()),
+        Some(PathBuf::from("/data/notion"))
+    );
+    assert_eq!(
+        resolve_download_dir(Some("~/Exports"), home.clone(), system.clone()),
+        Some(PathBuf::from("/home/user/Exports"))
+    );
+    assert_eq!(
+        resolve_download_dir(Some("file:///srv/files"), home.clone(), system.clone()),
+        Some(PathBuf::from("/srv/files"))
+    );
+}
+
+#[test]
+fn test_resolve_download_dir_falls_back() {
+    use super::resolve_download_dir;
+    use std::path::PathBuf;
+
+    let home = Some(PathBuf::from("/home/user"));
+    let system = Some(PathBuf::from("/home/user/Downloads"));
+
+    assert_eq!(resolve_download_dir(None, home.clone(), system.clone()), system);
+    assert_eq!(resolve_download_dir(Some("  "), home.clone(), system.clone()), system);
+    // Relative paths are ambiguous, so they are ignored
+    assert_eq!(resolve_download_dir(Some("Exports"), home.clone(), system.clone()), system);
+    assert_eq!(resolve_download_dir(Some("~/Exports"), None, system.clone()), system);
+    assert_eq!(resolve_download_dir(None, home.clone(), None), home);
+}
@@ -6,6 +6,7 @@
 pub mod downloads;
 pub mod sync;
 pub mod notifications;
+pub mod pdf;
 pub mod settings;
 pub mod window;
 
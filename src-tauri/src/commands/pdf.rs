@@ -0,0 +1,341 @@
+//! Export to PDF.
+//!
+//! Renders the current page of a window into a PDF with WebKit's print
+//! operation, sending it straight to GTK's "Print to File" printer so no
+//! print dialog is shown. Headers and footers are not something WebKit
+//! prints on its own; they are added to the page as fixed, print-only
+//! elements for the duration of the export.
+
+#[cfg(test)]
+mod tests;
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_dialog::DialogExt;
+
+/// How long an export may take before it is given up on.
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Time for the page to lay out the header and footer before printing.
+const DECORATION_DELAY: Duration = Duration::from_millis(150);
+
+/// ID prefix of the elements added for headers and footers.
+const DECORATION_ID: &str = "notive-pdf";
+
+/// Paper sizes offered for exports.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaperSize {
+    #[default]
+    A4,
+    A3,
+    A5,
+    Letter,
+    Legal,
+}
+
+impl PaperSize {
+    /// PWG name GTK uses for the paper.
+    pub fn gtk_name(self) -> &'static str {
+        match self {
+            Self::A4 => "iso_a4",
+            Self::A3 => "iso_a3",
+            Self::A5 => "iso_a5",
+            Self::Letter => "na_letter",
+            Self::Legal => "na_legal",
+        }
+    }
+
+    /// Width and height in portrait orientation, in millimetres.
+    pub fn size_mm(self) -> (f64, f64) {
+        match self {
+            Self::A4 => (210.0, 297.0),
+            Self::A3 => (297.0, 420.0),
+            Self::A5 => (148.0, 210.0),
+            Self::Letter => (215.9, 279.4),
+            Self::Legal => (215.9, 355.6),
+        }
+    }
+}
+
+/// Page margins in millimetres. Margins that are left out keep their default.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Margins {
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+    pub left: f64,
+}
+
+impl Default for Margins {
+    fn default() -> Self {
+        Self {
+            top: 15.0,
+            right: 15.0,
+            bottom: 15.0,
+            left: 15.0,
+        }
+    }
+}
+
+/// Options for a PDF export.
+///
+/// Header and footer text may contain `{title}`, `{url}` and `{date}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PdfExportOptions {
+    pub paper_size: PaperSize,
+    pub landscape: bool,
+    pub margins: Margins,
+    pub header: Option<String>,
+    pub footer: Option<String>,
+}
+
+impl PdfExportOptions {
+    /// Checks that the margins leave room for the page content.
+    pub fn validate(&self) -> Result<(), String> {
+        let Margins { top, right, bottom, left } = self.margins;
+        if [top, right, bottom, left].iter().any(|m| !m.is_finite() || *m < 0.0) {
+            return Err("Margins must be zero or more millimetres".to_string());
+        }
+
+        let (width, height) = self.paper_size.size_mm();
+        let (width, height) = if self.landscape { (height, width) } else { (width, height) };
+        if left + right >= width || top + bottom >= height {
+            return Err("Margins leave no room on the page".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Fills the placeholders of a header or footer.
+pub fn fill_placeholders(template: &str, title: &str, url: &str, date: &str) -> String {
+    template
+        .replace("{title}", title)
+        .replace("{url}", url)
+        .replace("{date}", date)
+}
+
+/// Makes sure an export path ends in `.pdf`.
+pub fn pdf_path(path: &Path) -> PathBuf {
+    let is_pdf = path
+        .extension()
+        .map(|e| e.eq_ignore_ascii_case("pdf"))
+        .unwrap_or(false);
+    if is_pdf {
+        path.to_path_buf()
+    } else {
+        let mut path = path.as_os_str().to_os_string();
+        path.push(".pdf");
+        PathBuf::from(path)
+    }
+}
+
+/// Suggests a file name for exporting a page with this title.
+pub fn default_file_name(title: &str) -> String {
+    let title = title.trim();
+    let title = if title.is_empty() { "Notion page" } else { title };
+    format!("{}.pdf", super::downloads::manager::sanitize_file_name(title))
+}
+
+/// Builds the script that adds a print-only header and footer to the page.
+pub fn decoration_script(header: Option<&str>, footer: Option<&str>) -> String {
+    // serde_json quotes the text as a valid JavaScript string literal
+    let quote = |text: Option<&str>| serde_json::to_string(&text).unwrap_or_else(|_| "null".to_string());
+    format!(
+        r##"(function() {{
+  var id = "{id}";
+  var header = {header}, footer = {footer};
+  var style = document.createElement("style");
+  style.id = id + "-style";
+  style.textContent =
+    "#" + id + "-header, #" + id + "-footer {{ display: none; }}" +
+    "@media print {{" +
+    "  #" + id + "-header, #" + id + "-footer {{ display: block; position: fixed; left: 0; right: 0;" +
+    "    font: 9pt sans-serif; color: #555; text-align: center; white-space: pre-wrap; }}" +
+    "  #" + id + "-header {{ top: 0; }}" +
+    "  #" + id + "-footer {{ bottom: 0; }}" +
+    "  body {{ padding-top: " + (header ? "2em" : "0") + " !important;" +
+    "    padding-bottom: " + (footer ? "2em" : "0") + " !important; }}" +
+    "}}";
+  document.head.appendChild(style);
+  [["header", header], ["footer", footer]].forEach(function(part) {{
+    if (part[1] === null) return;
+    var el = document.createElement("div");
+    el.id = id + "-" + part[0];
+    el.textContent = part[1];
+    document.body.appendChild(el);
+  }});
+}})();"##,
+        id = DECORATION_ID,
+        header = quote(header),
+        footer = quote(footer),
+    )
+}
+
+/// Builds the script that removes what [`decoration_script`] added.
+pub fn removal_script() -> String {
+    format!(
+        r#"["style", "header", "footer"].forEach(function(part) {{
+  var el = document.getElementById("{}-" + part);
+  if (el) el.remove();
+}});"#,
+        DECORATION_ID
+    )
+}
+
+/// Prints the window's page to `path` and waits for WebKit to finish.
+#[cfg(target_os = "linux")]
+async fn print_to_file<R: Runtime>(
+    window: &tauri::WebviewWindow<R>,
+    path: &Path,
+    options: &PdfExportOptions,
+) -> Result<(), String> {
+    use std::sync::Mutex;
+
+    let uri = url::Url::from_file_path(path)
+        .map_err(|_| format!("Invalid export path: {}", path.display()))?
+        .to_string();
+    let options = options.clone();
+    let (sender, receiver) = tokio::sync::oneshot::channel::<Result<(), String>>();
+    let sender = std::sync::Arc::new(Mutex::new(Some(sender)));
+
+    window
+        .with_webview(move |webview| {
+            use webkit2gtk::PrintOperationExt;
+
+            let finish = move |result: Result<(), String>| {
+                if let Some(sender) = sender.lock().ok().and_then(|mut s| s.take()) {
+                    let _ = sender.send(result);
+                }
+            };
+            let Some(webview) = webview.as_ref().and_then(|w| w.downcast_ref::<webkit2gtk::WebView>()) else {
+                finish(Err("The window has no WebKit view".to_string()));
+                return;
+            };
+
+            let orientation = if options.landscape {
+                gtk::PageOrientation::Landscape
+            } else {
+                gtk::PageOrientation::Portrait
+            };
+            let paper = gtk::PaperSize::new(Some(options.paper_size.gtk_name()));
+
+            let settings = gtk::PrintSettings::new();
+            settings.set_printer("Print to File");
+            settings.set("output-file-format", Some("pdf"));
+            settings.set("output-uri", Some(uri.as_str()));
+            settings.set_paper_size(&paper);
+            settings.set_orientation(orientation);
+
+            let page_setup = gtk::PageSetup::new();
+            page_setup.set_paper_size(&paper);
+            page_setup.set_orientation(orientation);
+            page_setup.set_top_margin(options.margins.top, gtk::Unit::Mm);
+            page_setup.set_right_margin(options.margins.right, gtk::Unit::Mm);
+            page_setup.set_bottom_margin(options.margins.bottom, gtk::Unit::Mm);
+            page_setup.set_left_margin(options.margins.left, gtk::Unit::Mm);
+
+            let operation = webkit2gtk::PrintOperation::new(webview);
+            operation.set_print_settings(&settings);
+            operation.set_page_setup(&page_setup);
+
+            let finished = finish.clone();
+            operation.connect_finished(move |_| finished(Ok(())));
+            operation.connect_failed(move |_, error| finish(Err(error.to_string())));
+            operation.print();
+        })
+        .map_err(|e| e.to_string())?;
+
+    match tokio::time::timeout(EXPORT_TIMEOUT, receiver).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(_)) => Err("The print operation was dropped".to_string()),
+        Err(_) => Err("Timed out exporting to PDF".to_string()),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn print_to_file<R: Runtime>(
+    _window: &tauri::WebviewWindow<R>,
+    _path: &Path,
+    _options: &PdfExportOptions,
+) -> Result<(), String> {
+    Err("Export to PDF needs WebKitGTK".to_string())
+}
+
+/// Exports the current page of a window to a PDF.
+///
+/// Exports the main window unless `label` names another, and asks where to
+/// save unless `path` is given. Returns the saved path, or `None` if the
+/// save dialog was cancelled.
+#[tauri::command]
+pub async fn export_to_pdf<R: Runtime>(
+    app: AppHandle<R>,
+    label: Option<String>,
+    path: Option<String>,
+    options: Option<PdfExportOptions>,
+) -> Result<Option<String>, String> {
+    let options = options.unwrap_or_default();
+    options.validate()?;
+
+    let label = label.unwrap_or_else(|| "main".to_string());
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("Window not found: {}", label))?;
+    let title = window.title().unwrap_or_default();
+    let page_url = window.url().map(|u| u.to_string()).unwrap_or_default();
+
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let chosen = app
+                .dialog()
+                .file()
+                .add_filter("PDF", &["pdf"])
+                .set_file_name(&default_file_name(&title))
+                .save()
+                .await;
+            match chosen {
+                Some(path) => path,
+                None => {
+                    log::debug!("User cancelled PDF export");
+                    return Ok(None);
+                }
+            }
+        }
+    };
+    let path = pdf_path(&path);
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    log::info!("Exporting {} to {}", page_url, path.display());
+
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let fill = |text: &Option<String>| {
+        text.as_deref()
+            .filter(|t| !t.trim().is_empty())
+            .map(|t| fill_placeholders(t, &title, &page_url, &date))
+    };
+    let (header, footer) = (fill(&options.header), fill(&options.footer));
+    let decorated = header.is_some() || footer.is_some();
+    if decorated {
+        window
+            .eval(&decoration_script(header.as_deref(), footer.as_deref()))
+            .map_err(|e| e.to_string())?;
+        tokio::time::sleep(DECORATION_DELAY).await;
+    }
+
+    let result = print_to_file(&window, &path, &options).await;
+
+    if decorated {
+        let _ = window.eval(&removal_script());
+    }
+    result?;
+
+    log::info!("Exported PDF: {}", path.display());
+    Ok(Some(path.to_string_lossy().into_owned()))
+}
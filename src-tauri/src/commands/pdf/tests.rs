@@ -0,0 +1,96 @@
+//! Tests for PDF export options.
+
+use super::*;
+
+#[test]
+fn test_options_deserialize_with_defaults() {
+    let options: PdfExportOptions = serde_json::from_value(serde_json::json!({
+        "paper_size": "letter",
+        "footer": "{title}"
+    }))
+    .unwrap();
+
+    assert_eq!(options.paper_size, PaperSize::Letter);
+    assert!(!options.landscape);
+    assert_eq!(options.margins, Margins::default());
+    assert_eq!(options.header, None);
+    assert_eq!(options.footer.as_deref(), Some("{title}"));
+}
+
+#[test]
+fn test_partial_margins_keep_defaults() {
+    let options: PdfExportOptions = serde_json::from_value(serde_json::json!({
+        "margins": { "top": 10 }
+    }))
+    .unwrap();
+
+    assert_eq!(
+        options.margins,
+        Margins {
+            top: 10.0,
+            ..Margins::default()
+        }
+    );
+}
+
+#[test]
+fn test_paper_names() {
+    assert_eq!(PaperSize::A4.gtk_name(), "iso_a4");
+    assert_eq!(PaperSize::Letter.gtk_name(), "na_letter");
+    assert_eq!(PaperSize::default(), PaperSize::A4);
+}
+
+#[test]
+fn test_validate_margins() {
+    assert!(PdfExportOptions::default().validate().is_ok());
+
+    let mut options = PdfExportOptions::default();
+    options.margins.left = -1.0;
+    assert!(options.validate().is_err());
+
+    options.margins.left = f64::NAN;
+    assert!(options.validate().is_err());
+
+    // 250mm of side margins fit on landscape A4 but not portrait
+    let mut options = PdfExportOptions::default();
+    options.margins.left = 125.0;
+    options.margins.right = 125.0;
+    assert!(options.validate().is_err());
+    options.landscape = true;
+    assert!(options.validate().is_ok());
+}
+
+#[test]
+fn test_fill_placeholders() {
+    assert_eq!(
+        fill_placeholders("{title} - {date}", "Standup", "https://www.notion.so/Standup", "2026-10-18"),
+        "Standup - 2026-10-18"
+    );
+    assert_eq!(
+        fill_placeholders("{url}", "Standup", "https://www.notion.so/Standup", "2026-10-18"),
+        "https://www.notion.so/Standup"
+    );
+    assert_eq!(fill_placeholders("Confidential", "a", "b", "c"), "Confidential");
+}
+
+#[test]
+fn test_pdf_path_adds_extension() {
+    assert_eq!(pdf_path(Path::new("/tmp/notes")), PathBuf::from("/tmp/notes.pdf"));
+    assert_eq!(pdf_path(Path::new("/tmp/notes.PDF")), PathBuf::from("/tmp/notes.PDF"));
+    assert_eq!(pdf_path(Path::new("/tmp/notes.md")), PathBuf::from("/tmp/notes.md.pdf"));
+}
+
+#[test]
+fn test_default_file_name() {
+    assert_eq!(default_file_name("Meeting Notes"), "Meeting Notes.pdf");
+    assert_eq!(default_file_name("  "), "Notion page.pdf");
+    assert!(!default_file_name("Q3/Q4 plan").contains('/'));
+}
+
+#[test]
+fn test_decoration_script_quotes_text() {
+    let script = decoration_script(Some("He said \"hi\"</script>"), None);
+    assert!(script.contains(r#"var header = "He said \"hi\"</script>", footer = null;"#));
+    assert!(script.contains("notive-pdf"));
+    assert!(removal_script().contains("notive-pdf"));
+}
@@ -98,6 +98,17 @@ async fn apply_settings<R: Runtime>(
         apply_theme(app, &new_settings.theme)?;
     }
     
+    // Re-check for updates when the update channel changes
+    if new_settings.update_channel != old_settings.update_channel {
+        log::info!("Update channel changed to {:?}", new_settings.update_channel);
+        let app_handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = crate::updater::check(&app_handle).await {
+                log::warn!("Update check after channel change failed: {}", e);
+            }
+        });
+    }
+    
     Ok(())
 }
 
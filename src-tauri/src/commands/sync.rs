@@ -3,14 +3,31 @@
 #[cfg(test)]
 mod tests;
 
+use crate::backup::{self, crypto, Category, RestoreMode};
 use crate::config;
+use serde::Serialize;
 use tauri::{AppHandle, Manager, Runtime};
 use tauri_plugin_dialog::{DialogExt, FileDialogBuilder};
 use tauri_plugin_fs::FsExt;
 
-/// Exports settings to a JSON file.
+/// Runs encryption or decryption off the async runtime: the Argon2id key
+/// derivation is deliberately slow.
+async fn run_crypto<T, F>(f: F) -> Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Exports settings to a JSON file, encrypted if a passphrase is given.
 #[tauri::command]
-pub async fn export_settings<R: Runtime>(app: AppHandle<R>) -> Result<String, String> {
+pub async fn export_settings<R: Runtime>(
+    app: AppHandle<R>,
+    passphrase: Option<String>,
+) -> Result<String, String> {
     log::debug!("Exporting settings...");
     
     // Load current settings
@@ -19,13 +36,21 @@ pub async fn export_settings<R: Runtime>(app: AppHandle<R>) -> Result<String, St
     // Serialize to JSON
     let json = serde_json::to_string_pretty(&settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let (bytes, file_name, filter) = match passphrase {
+        Some(passphrase) => (
+            run_crypto(move || crypto::encrypt(json.as_bytes(), &passphrase)).await?,
+            "notive-settings.enc",
+            ("Encrypted settings", "enc"),
+        ),
+        None => (json.into_bytes(), "notive-settings.json", ("JSON", "json")),
+    };
     
     // Show save dialog
     let file_path = app
         .dialog()
         .file()
-        .set_file_name("notive-settings.json")
-        .add_filter("JSON", &["json"])
+        .set_file_name(file_name)
+        .add_filter(filter.0, &[filter.1])
         .save()
         .await;
     
@@ -33,7 +58,7 @@ pub async fn export_settings<R: Runtime>(app: AppHandle<R>) -> Result<String, St
         Some(path) => {
             // Write to file
             app.fs()
-                .write_file(&path, json.as_bytes())
+                .write_file(&path, &bytes)
                 .await
                 .map_err(|e| format!("Failed to write file: {}", e))?;
             
@@ -44,16 +69,20 @@ pub async fn export_settings<R: Runtime>(app: AppHandle<R>) -> Result<String, St
     }
 }
 
-/// Imports settings from a JSON file.
+/// Imports settings from a JSON file, decrypting it with the passphrase
+/// if it was exported encrypted.
 #[tauri::command]
-pub async fn import_settings<R: Runtime>(app: AppHandle<R>) -> Result<String, String> {
+pub async fn import_settings<R: Runtime>(
+    app: AppHandle<R>,
+    passphrase: Option<String>,
+) -> Result<String, String> {
     log::debug!("Importing settings...");
     
     // Show open dialog
     let file_path = app
         .dialog()
         .file()
-        .add_filter("JSON", &["json"])
+        .add_filter("Settings", &["json", "enc"])
         .pick_file()
         .await;
     
@@ -62,12 +91,13 @@ pub async fn import_settings<R: Runtime>(app: AppHandle<R>) -> Result<String, St
             // Read file
             let contents = app
                 .fs()
-                .read_text_file(&path)
+                .read_file(&path)
                 .await
                 .map_err(|e| format!("Failed to read file: {}", e))?;
+            let contents = run_crypto(move || crypto::open(contents, passphrase.as_deref())).await?;
             
             // Deserialize settings
-            let settings: config::UserSettings = serde_json::from_str(&contents)
+            let settings: config::UserSettings = serde_json::from_slice(&contents)
                 .map_err(|e| format!("Invalid settings file: {}", e))?;
             
             // Save settings
@@ -81,19 +111,41 @@ pub async fn import_settings<R: Runtime>(app: AppHandle<R>) -> Result<String, St
 }
 
 /// Gets settings as JSON string (for sync).
+///
+/// With a passphrase the JSON is encrypted and returned as armored text.
 #[tauri::command]
-pub fn get_settings_json<R: Runtime>(app: AppHandle<R>) -> Result<String, String> {
+pub async fn get_settings_json<R: Runtime>(
+    app: AppHandle<R>,
+    passphrase: Option<String>,
+) -> Result<String, String> {
     let settings = config::load(&app).map_err(|e| e.to_string())?;
-    serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())
+    let json = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+    match passphrase {
+        Some(passphrase) => {
+            let encrypted = run_crypto(move || crypto::encrypt(json.as_bytes(), &passphrase)).await?;
+            Ok(crypto::armor(&encrypted))
+        }
+        None => Ok(json),
+    }
 }
 
 /// Restores settings from JSON string (for sync).
+///
+/// Accepts the armored text from [`get_settings_json`] along with its passphrase.
 #[tauri::command]
 pub async fn restore_settings_json<R: Runtime>(
     app: AppHandle<R>,
     json: String,
+    passphrase: Option<String>,
 ) -> Result<(), String> {
-    let settings: config::UserSettings = serde_json::from_str(&json)
+    let json = match crypto::dearmor(&json) {
+        Some(encrypted) => {
+            let encrypted = encrypted?;
+            run_crypto(move || crypto::open(encrypted, passphrase.as_deref())).await?
+        }
+        None => json.into_bytes(),
+    };
+    let settings: config::UserSettings = serde_json::from_slice(&json)
         .map_err(|e| format!("Invalid settings JSON: {}", e))?;
     
     config::save(&app, &settings).map_err(|e| e.to_string())?;
@@ -101,3 +153,155 @@ pub async fn restore_settings_json<R: Runtime>(
     log::info!("Settings restored from JSON");
     Ok(())
 }
+
+/// A backup file and what it holds, shown before restoring it.
+///
+/// The manifest of an encrypted backup is only known once the passphrase is given.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupSummary {
+    pub path: String,
+    pub encrypted: bool,
+    pub manifest: Option<backup::Manifest>,
+}
+
+/// Reads backup contents, decrypting them if needed.
+async fn read_backup_bytes(bytes: Vec<u8>, passphrase: Option<String>) -> Result<backup::Backup, String> {
+    run_crypto(move || backup::read_backup(&crypto::open(bytes, passphrase.as_deref())?)).await
+}
+
+/// Reads a backup file, decrypting it if needed.
+async fn read_backup_file(path: &str, passphrase: Option<String>) -> Result<backup::Backup, String> {
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    read_backup_bytes(bytes, passphrase).await
+}
+
+/// Exports every store into a backup archive, encrypted if a passphrase is given.
+///
+/// Keyring secrets are only included when `include_secrets` is set.
+#[tauri::command]
+pub async fn export_backup<R: Runtime>(
+    app: AppHandle<R>,
+    include_secrets: Option<bool>,
+    passphrase: Option<String>,
+) -> Result<String, String> {
+    log::debug!("Exporting backup...");
+
+    let stores = backup::persistence::load_stores(&app).map_err(|e| e.to_string())?;
+    let secrets = if include_secrets.unwrap_or(false) {
+        Some(backup::persistence::load_secrets()?)
+    } else {
+        None
+    };
+    let mut bytes = backup::write_backup(&stores, secrets.as_deref(), env!("CARGO_PKG_VERSION"))?;
+    let mut extension = "zip";
+    if let Some(passphrase) = passphrase {
+        bytes = run_crypto(move || crypto::encrypt(&bytes, &passphrase)).await?;
+        extension = "enc";
+    }
+
+    let file_path = app
+        .dialog()
+        .file()
+        .set_file_name(&format!(
+            "notive-backup-{}.{}",
+            chrono::Local::now().format("%Y-%m-%d"),
+            extension
+        ))
+        .add_filter("Notive backup", &[extension])
+        .save()
+        .await;
+
+    match file_path {
+        Some(path) => {
+            app.fs()
+                .write_file(&path, &bytes)
+                .await
+                .map_err(|e| format!("Failed to write file: {}", e))?;
+
+            log::info!("Backup exported to: {:?}", path);
+            Ok(format!("Backup exported to: {}", path.display()))
+        }
+        None => Err("Export cancelled".to_string()),
+    }
+}
+
+/// Reads a backup archive's manifest, asking for the file unless `path` is given.
+///
+/// Returns `None` if the file dialog was cancelled.
+#[tauri::command]
+pub async fn inspect_backup<R: Runtime>(
+    app: AppHandle<R>,
+    path: Option<String>,
+    passphrase: Option<String>,
+) -> Result<Option<BackupSummary>, String> {
+    let path = match path {
+        Some(path) => path,
+        None => {
+            let picked = app
+                .dialog()
+                .file()
+                .add_filter("Notive backup", &["zip", "enc"])
+                .pick_file()
+                .await;
+            match picked {
+                Some(path) => path.display().to_string(),
+                None => return Ok(None),
+            }
+        }
+    };
+
+    let bytes = std::fs::read(&path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let encrypted = crypto::is_encrypted(&bytes);
+    if encrypted && passphrase.is_none() {
+        return Ok(Some(BackupSummary {
+            path,
+            encrypted,
+            manifest: None,
+        }));
+    }
+    let backup = read_backup_bytes(bytes, passphrase).await?;
+    Ok(Some(BackupSummary {
+        path,
+        encrypted,
+        manifest: Some(backup.manifest),
+    }))
+}
+
+/// Restores categories from a backup archive.
+///
+/// Restores every category in the backup unless `categories` narrows it
+/// down, merging by default. Secrets are only restored when
+/// `restore_secrets` is set and the backup has them.
+#[tauri::command]
+pub async fn import_backup<R: Runtime>(
+    app: AppHandle<R>,
+    path: String,
+    categories: Option<Vec<Category>>,
+    mode: Option<RestoreMode>,
+    restore_secrets: Option<bool>,
+    passphrase: Option<String>,
+) -> Result<String, String> {
+    log::debug!("Importing backup from {}...", path);
+
+    let backup = read_backup_file(&path, passphrase).await?;
+    let categories = categories.unwrap_or_else(|| backup.manifest.categories.clone());
+    let mode = mode.unwrap_or_default();
+
+    let mut stores = backup::persistence::load_stores(&app).map_err(|e| e.to_string())?;
+    let changed = backup::restore_stores(&mut stores, &backup.stores, &categories, mode);
+    backup::persistence::save_stores(&app, &stores, &changed).map_err(|e| e.to_string())?;
+
+    let mut secrets = 0;
+    if restore_secrets.unwrap_or(false) && backup.manifest.includes_secrets {
+        secrets = backup::persistence::save_secrets(&backup.secrets)?;
+    }
+
+    log::info!(
+        "Backup restored from {} ({:?}, {} stores changed, {} secrets)",
+        path,
+        mode,
+        changed.len(),
+        secrets
+    );
+    Ok("Backup restored successfully. Please restart the application for all changes to take effect.".to_string())
+}
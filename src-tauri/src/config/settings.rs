@@ -27,10 +27,18 @@ pub struct UserSettings {
     // Updates
     pub auto_update: bool,
     pub update_channel: UpdateChannel,
+    /// Local directory or `file://` URL mirroring the release manifests.
+    pub update_mirror: Option<String>,
 
     // Autostart
     pub autostart_enabled: bool,
 
+    // Downloads
+    /// Downloads that may run at the same time; more wait in the queue.
+    pub max_concurrent_downloads: usize,
+    /// Folder downloads are saved to; the system downloads folder if unset.
+    pub download_folder: Option<String>,
+
     // Performance
     pub hardware_acceleration: bool,
     pub spellcheck: bool,
@@ -51,7 +59,10 @@ fn default() -> Self {
             shortcuts: ShortcutSettings::default(),
             auto_update: true,
             update_channel: UpdateChannel::Stable,
+            update_mirror: None,
             autostart_enabled: false,
+            max_concurrent_downloads: 3,
+            download_folder: None,
             hardware_acceleration: true,
             spellcheck: true,
         }
@@ -64,6 +75,7 @@ fn default() -> Self {
 pub struct ShortcutSettings {
     pub toggle_window: String,
     pub quick_capture: String,
+    pub toggle_mini_window: String,
     pub reload: String,
     pub zoom_in: String,
     pub zoom_out: String,
@@ -75,6 +87,7 @@ fn default() -> Self {
         Self {
             toggle_window: "CommandOrControl+Shift+N".to_string(),
             quick_capture: "CommandOrControl+Shift+C".to_string(),
+            toggle_mini_window: "CommandOrControl+Shift+M".to_string(),
             reload: "CommandOrControl+R".to_string(),
             zoom_in: "CommandOrControl+=".to_string(),
             zoom_out: "CommandOrControl+-".to_string(),
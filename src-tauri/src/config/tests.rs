@@ -15,7 +15,10 @@ fn test_user_settings_default() {
     assert!(settings.notifications_enabled);
     assert!(settings.auto_update);
     assert_eq!(settings.update_channel, UpdateChannel::Stable);
+    assert!(settings.update_mirror.is_none());
     assert!(!settings.autostart_enabled);
+    assert_eq!(settings.max_concurrent_downloads, 3);
+    assert!(settings.download_folder.is_none());
     assert!(settings.hardware_acceleration);
     assert!(settings.spellcheck);
 }
@@ -26,6 +29,7 @@ fn test_shortcut_settings_default() {
     
     assert_eq!(shortcuts.toggle_window, "CommandOrControl+Shift+N");
     assert_eq!(shortcuts.quick_capture, "CommandOrControl+Shift+C");
+    assert_eq!(shortcuts.toggle_mini_window, "CommandOrControl+Shift+M");
     assert_eq!(shortcuts.reload, "CommandOrControl+R");
     assert_eq!(shortcuts.zoom_in, "CommandOrControl+=");
     assert_eq!(shortcuts.zoom_out, "CommandOrControl+-");
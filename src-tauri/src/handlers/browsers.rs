@@ -0,0 +1,468 @@
+//! Browser selection for external links.
+//!
+//! Browser rules use the same patterns as navigation rules and pick the
+//! browser for links that open externally: a desktop entry from the XDG
+//! application directories, optionally with a browser profile. Links no rule
+//! matches, and rules without a browser, go to the system default browser.
+
+#[cfg(test)]
+mod tests;
+
+use super::rules::Matcher;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_shell::ShellExt;
+
+const SETTINGS_STORE_PATH: &str = "settings.json";
+const RULES_KEY: &str = "browser_rules";
+
+/// Data directories searched when `$XDG_DATA_DIRS` is not set.
+const DEFAULT_DATA_DIRS: &str = "/usr/local/share:/usr/share";
+
+/// MIME types of desktop entries that can open web links.
+const WEB_MIME_TYPES: &[&str] = &["x-scheme-handler/http", "x-scheme-handler/https"];
+
+/// Executable names of browsers taking `-P <profile>`.
+const FIREFOX_FAMILY: &[&str] = &["firefox", "librewolf", "waterfox", "floorp"];
+
+/// Executable names of browsers taking `--profile-directory=<profile>`.
+const CHROMIUM_FAMILY: &[&str] = &["chrome", "chromium", "brave", "vivaldi", "edge", "opera"];
+
+/// Desktop entries from the last scan of the application directories.
+static ENTRIES: Mutex<Option<Vec<DesktopEntry>>> = Mutex::new(None);
+
+/// A rule choosing the browser for matching links.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BrowserRule {
+    pub pattern: String,
+    /// Desktop entry ID such as `firefox.desktop`; `None` uses the default browser.
+    #[serde(default)]
+    pub browser: Option<String>,
+    /// Browser profile to open the link in.
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// An application found in the XDG application directories.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DesktopEntry {
+    /// Desktop file ID, e.g. `firefox.desktop`.
+    pub id: String,
+    pub name: String,
+    pub exec: String,
+    pub path: PathBuf,
+    /// Whether the entry handles `http`/`https` links.
+    pub is_browser: bool,
+}
+
+/// Parses the `[Desktop Entry]` group of a desktop file.
+///
+/// Returns `None` for entries that are not launchable applications.
+pub fn parse_desktop_entry(id: &str, path: &Path, contents: &str) -> Option<DesktopEntry> {
+    let mut in_entry = false;
+    let mut name = None;
+    let mut exec = None;
+    let mut is_application = false;
+    let mut hidden = false;
+    let mut is_browser = false;
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        if !in_entry {
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "Name" => name = Some(value.to_string()),
+            "Exec" => exec = Some(value.to_string()),
+            "Type" => is_application = value == "Application",
+            "Hidden" => hidden = value == "true",
+            "MimeType" => {
+                is_browser = value
+                    .split(';')
+                    .any(|mime| WEB_MIME_TYPES.contains(&mime.trim()));
+            }
+            _ => {}
+        }
+    }
+
+    if !is_application || hidden {
+        return None;
+    }
+
+    Some(DesktopEntry {
+        id: id.to_string(),
+        name: name.unwrap_or_else(|| id.trim_end_matches(".desktop").to_string()),
+        exec: exec?,
+        path: path.to_path_buf(),
+        is_browser,
+    })
+}
+
+/// Gets the XDG application directories, most important first.
+pub fn application_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = dirs::data_dir().into_iter().collect();
+
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_DATA_DIRS.to_string());
+    dirs.extend(
+        data_dirs
+            .split(':')
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from),
+    );
+
+    dirs.into_iter().map(|dir| dir.join("applications")).collect()
+}
+
+/// Finds the desktop entries in the given application directories.
+///
+/// An ID found in an earlier directory hides the same ID in later ones, so a
+/// user's `Hidden=true` override removes a system entry.
+pub fn discover_entries(dirs: &[PathBuf]) -> Vec<DesktopEntry> {
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+
+    for dir in dirs {
+        let mut files = Vec::new();
+        collect_desktop_files(dir, dir, &mut files);
+        files.sort();
+
+        for (id, path) in files {
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            let Ok(contents) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            if let Some(entry) = parse_desktop_entry(&id, &path, &contents) {
+                entries.push(entry);
+            }
+        }
+    }
+
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
+    entries
+}
+
+/// Collects `.desktop` files below `dir` with their desktop file IDs.
+///
+/// Files in subdirectories get IDs joined with `-`, e.g. `kde4/konqueror.desktop`
+/// becomes `kde4-konqueror.desktop`.
+fn collect_desktop_files(root: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) {
+    let Ok(read_dir) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_desktop_files(root, &path, files);
+        } else if path.extension().is_some_and(|ext| ext == "desktop") {
+            if let Ok(relative) = path.strip_prefix(root) {
+                let id = relative.to_string_lossy().replace('/', "-");
+                files.push((id, path));
+            }
+        }
+    }
+}
+
+/// Finds a desktop entry by ID, with or without the `.desktop` suffix.
+pub fn find_entry<'a>(entries: &'a [DesktopEntry], id: &str) -> Option<&'a DesktopEntry> {
+    let id = id.trim();
+    entries
+        .iter()
+        .find(|entry| entry.id == id || entry.id.trim_end_matches(".desktop") == id)
+}
+
+/// Splits an `Exec` value into arguments, honouring double quotes.
+pub fn split_exec(exec: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quoted = false;
+    let mut chars = exec.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            '\\' if quoted => match chars.next() {
+                Some(escaped) => current.push(escaped),
+                None => return Err(format!("Invalid Exec line: {}", exec)),
+            },
+            c if c.is_whitespace() && !quoted => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+
+    if quoted {
+        return Err(format!("Unterminated quote in Exec line: {}", exec));
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+/// Gets the arguments that select a browser profile.
+fn profile_args(entry: &DesktopEntry, args: &[String], profile: &str) -> Result<Vec<String>, String> {
+    // Match on every argument so that `flatpak run ... org.mozilla.firefox` is recognised
+    let names: Vec<String> = args
+        .iter()
+        .map(|arg| arg.rsplit('/').next().unwrap_or(arg).to_lowercase())
+        .collect();
+    let is_family = |family: &[&str]| {
+        names
+            .iter()
+            .any(|name| family.iter().any(|browser| name.contains(browser)))
+    };
+
+    if is_family(FIREFOX_FAMILY) {
+        Ok(vec!["-P".to_string(), profile.to_string()])
+    } else if is_family(CHROMIUM_FAMILY) {
+        Ok(vec![format!("--profile-directory={}", profile)])
+    } else {
+        Err(format!("Browser profiles are not supported for {}", entry.name))
+    }
+}
+
+/// Builds the command line that opens `url` with a desktop entry.
+///
+/// Expands the URL and file field codes to the URL, drops the icon and
+/// deprecated codes, and places profile arguments before the URL.
+pub fn build_command(entry: &DesktopEntry, url: &str, profile: Option<&str>) -> Result<Vec<String>, String> {
+    let args = split_exec(&entry.exec)?;
+    if args.is_empty() {
+        return Err(format!("{} has an empty Exec line", entry.id));
+    }
+
+    let mut extra = match profile.map(str::trim).filter(|p| !p.is_empty()) {
+        Some(profile) => profile_args(entry, &args, profile)?,
+        None => Vec::new(),
+    };
+    let mut command = Vec::with_capacity(args.len() + extra.len() + 1);
+    let mut url_added = false;
+
+    for arg in args {
+        match arg.as_str() {
+            // Flatpak file forwarding markers
+            "@@" | "@@u" => continue,
+            "%i" | "%d" | "%D" | "%n" | "%N" | "%v" | "%m" => continue,
+            _ => {}
+        }
+
+        let takes_url = ["%u", "%U", "%f", "%F"].iter().any(|code| arg.contains(code));
+        if takes_url && url_added {
+            continue;
+        }
+        if takes_url {
+            command.append(&mut extra);
+            url_added = true;
+        }
+
+        command.push(expand_field_codes(&arg, entry, url));
+    }
+
+    if !url_added {
+        command.append(&mut extra);
+        command.push(url.to_string());
+    }
+    Ok(command)
+}
+
+fn expand_field_codes(arg: &str, entry: &DesktopEntry, url: &str) -> String {
+    let mut expanded = String::with_capacity(arg.len());
+    let mut chars = arg.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('u' | 'U' | 'f' | 'F') => expanded.push_str(url),
+            Some('c') => expanded.push_str(&entry.name),
+            Some('k') => expanded.push_str(&entry.path.to_string_lossy()),
+            Some('%') => expanded.push('%'),
+            _ => {}
+        }
+    }
+    expanded
+}
+
+/// Picks the most specific enabled rule matching a URL.
+pub fn choose_rule<'a>(url: &str, rules: &'a [BrowserRule]) -> Option<&'a BrowserRule> {
+    let parsed = url::Url::parse(url).ok()?;
+
+    rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .filter_map(|rule| Matcher::parse(&rule.pattern).ok().map(|matcher| (rule, matcher)))
+        .filter(|(_, matcher)| matcher.matches(&parsed))
+        // max_by_key keeps the last maximum, so reverse to prefer earlier rules on ties
+        .rev()
+        .max_by_key(|(_, matcher)| matcher.specificity())
+        .map(|(rule, _)| rule)
+}
+
+/// Checks that every rule pattern parses and profiles name a browser.
+pub fn validate_rules(rules: &[BrowserRule]) -> Result<(), String> {
+    rules.iter().try_for_each(|rule| {
+        Matcher::parse(&rule.pattern)?;
+        if rule.profile.is_some() && rule.browser.is_none() {
+            return Err(format!(
+                "Browser rule {} sets a profile but no browser",
+                rule.pattern
+            ));
+        }
+        Ok(())
+    })
+}
+
+/// Loads the browser rules from the settings store.
+pub fn load_rules<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<BrowserRule>, String> {
+    let stores = app.state::<tauri_plugin_store::StoreCollection<R>>();
+    tauri_plugin_store::with_store(app, stores, SETTINGS_STORE_PATH, |store| {
+        if let Some(value) = store.get(RULES_KEY) {
+            serde_json::from_value(value.clone())
+                .map_err(|e| tauri_plugin_store::Error::Deserialize(e.to_string()))
+        } else {
+            Ok(Vec::new())
+        }
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Scans the application directories and caches the entries found.
+fn scan_entries() -> Vec<DesktopEntry> {
+    let entries = discover_entries(&application_dirs());
+    if let Ok(mut cached) = ENTRIES.lock() {
+        *cached = Some(entries.clone());
+    }
+    entries
+}
+
+/// Gets a desktop entry from the cache, scanning again if it is not there.
+fn entry_for(browser: &str) -> Option<DesktopEntry> {
+    let cached = ENTRIES
+        .lock()
+        .ok()
+        .and_then(|cached| cached.as_deref().and_then(|entries| find_entry(entries, browser).cloned()));
+    cached.or_else(|| find_entry(&scan_entries(), browser).cloned())
+}
+
+/// Launches a desktop entry with a URL.
+fn launch(browser: &str, profile: Option<&str>, url: &str) -> Result<(), String> {
+    let entry = entry_for(browser).ok_or_else(|| format!("Browser {} was not found", browser))?;
+    let command = build_command(&entry, url, profile)?;
+
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", entry.name, e))?;
+
+    // Reap the browser process once it exits
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+/// Opens a link outside Notive, in the browser chosen by the browser rules.
+///
+/// Falls back to the system default browser if the chosen one cannot start.
+pub fn open_external<R: Runtime>(app: &AppHandle<R>, url: &str) {
+    let rules = load_rules(app).unwrap_or_else(|e| {
+        log::warn!("Failed to load browser rules: {}", e);
+        Vec::new()
+    });
+
+    if let Some(rule) = choose_rule(url, &rules) {
+        if let Some(browser) = &rule.browser {
+            match launch(browser, rule.profile.as_deref(), url) {
+                Ok(()) => {
+                    log::debug!("Opened {} in {} (rule {})", url, browser, rule.pattern);
+                    return;
+                }
+                Err(e) => log::warn!("{}; using the default browser", e),
+            }
+        }
+    }
+
+    if let Err(e) = app.shell().open(url, None) {
+        log::warn!("Failed to open {} externally: {}", url, e);
+    }
+}
+
+/// Lists the installed applications that can open web links.
+///
+/// Scans again, so browsers installed since the last scan show up in the settings.
+#[tauri::command]
+pub fn list_browsers() -> Vec<DesktopEntry> {
+    scan_entries()
+        .into_iter()
+        .filter(|entry| entry.is_browser)
+        .collect()
+}
+
+/// Gets the browser rules.
+#[tauri::command]
+pub fn get_browser_rules<R: Runtime>(app: AppHandle<R>) -> Result<Vec<BrowserRule>, String> {
+    load_rules(&app)
+}
+
+/// Replaces the browser rules.
+#[tauri::command]
+pub fn set_browser_rules<R: Runtime>(
+    app: AppHandle<R>,
+    rules: Vec<BrowserRule>,
+) -> Result<(), String> {
+    validate_rules(&rules)?;
+
+    let stores = app.state::<tauri_plugin_store::StoreCollection<R>>();
+    tauri_plugin_store::with_store(app, stores, SETTINGS_STORE_PATH, |store| {
+        let value = serde_json::to_value(&rules)
+            .map_err(|e| tauri_plugin_store::Error::Serialize(e.to_string()))?;
+        store.insert(RULES_KEY.to_string(), value)?;
+        store.save()?;
+        Ok(())
+    })
+    .map_err(|e| e.to_string())
+}
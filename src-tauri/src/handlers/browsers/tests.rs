@@ -0,0 +1,228 @@
+//! Tests for browser selection.
+
+use super::*;
+use tempfile::TempDir;
+
+const FIREFOX: &str = "[Desktop Entry]
+Type=Application
+Name=Firefox
+Name[de]=Firefox Webbrowser
+Exec=firefox %u
+MimeType=text/html;x-scheme-handler/http;x-scheme-handler/https;
+
+[Desktop Action new-window]
+Name=New Window
+Exec=firefox --new-window %u
+";
+
+fn entry(name: &str, exec: &str) -> DesktopEntry {
+    DesktopEntry {
+        id: format!("{}.desktop", name.to_lowercase()),
+        name: name.to_string(),
+        exec: exec.to_string(),
+        path: PathBuf::from(format!("/usr/share/applications/{}.desktop", name.to_lowercase())),
+        is_browser: true,
+    }
+}
+
+fn rule(pattern: &str, browser: Option<&str>, profile: Option<&str>) -> BrowserRule {
+    BrowserRule {
+        pattern: pattern.to_string(),
+        browser: browser.map(str::to_string),
+        profile: profile.map(str::to_string),
+        enabled: true,
+    }
+}
+
+fn write(dir: &Path, name: &str, contents: &str) {
+    let path = dir.join(name);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, contents).unwrap();
+}
+
+#[test]
+fn test_parse_desktop_entry() {
+    let path = Path::new("/usr/share/applications/firefox.desktop");
+    let entry = parse_desktop_entry("firefox.desktop", path, FIREFOX).unwrap();
+
+    assert_eq!(entry.name, "Firefox");
+    // Actions do not override the main entry
+    assert_eq!(entry.exec, "firefox %u");
+    assert!(entry.is_browser);
+}
+
+#[test]
+fn test_parse_desktop_entry_skips_non_applications() {
+    let path = Path::new("/tmp/x.desktop");
+    assert!(parse_desktop_entry("x.desktop", path, "[Desktop Entry]\nType=Link\nName=X\nURL=https://x").is_none());
+    assert!(parse_desktop_entry(
+        "x.desktop",
+        path,
+        "[Desktop Entry]\nType=Application\nName=X\nExec=x\nHidden=true"
+    )
+    .is_none());
+    assert!(parse_desktop_entry("x.desktop", path, "[Desktop Entry]\nType=Application\nName=X").is_none());
+
+    let editor = parse_desktop_entry(
+        "x.desktop",
+        path,
+        "[Desktop Entry]\nType=Application\nName=X\nExec=x %F\nMimeType=text/plain;",
+    )
+    .unwrap();
+    assert!(!editor.is_browser);
+}
+
+#[test]
+fn test_discover_entries_precedence_and_subdirectories() {
+    let user = TempDir::new().unwrap();
+    let system = TempDir::new().unwrap();
+
+    write(system.path(), "firefox.desktop", FIREFOX);
+    write(
+        system.path(),
+        "chromium.desktop",
+        "[Desktop Entry]\nType=Application\nName=Chromium\nExec=chromium %U\nMimeType=x-scheme-handler/https;",
+    );
+    write(
+        system.path(),
+        "kde4/konqueror.desktop",
+        "[Desktop Entry]\nType=Application\nName=Konqueror\nExec=konqueror %u",
+    );
+    // The user hides the system Chromium and customises Firefox
+    write(user.path(), "chromium.desktop", "[Desktop Entry]\nType=Application\nHidden=true");
+    write(user.path(), "firefox.desktop", &FIREFOX.replace("firefox %u", "firefox --private-window %u"));
+
+    let entries = discover_entries(&[user.path().to_path_buf(), system.path().to_path_buf()]);
+    let ids: Vec<_> = entries.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, ["firefox.desktop", "kde4-konqueror.desktop"]);
+    assert_eq!(entries[0].exec, "firefox --private-window %u");
+    assert!(entries[0].path.starts_with(user.path()));
+}
+
+#[test]
+fn test_discover_entries_ignores_missing_dirs() {
+    assert!(discover_entries(&[PathBuf::from("/nonexistent/applications")]).is_empty());
+}
+
+#[test]
+fn test_find_entry_with_or_without_suffix() {
+    let entries = vec![entry("Firefox", "firefox %u")];
+    assert!(find_entry(&entries, "firefox.desktop").is_some());
+    assert!(find_entry(&entries, "firefox").is_some());
+    assert!(find_entry(&entries, "chromium").is_none());
+}
+
+#[test]
+fn test_split_exec() {
+    assert_eq!(split_exec("firefox  %u").unwrap(), ["firefox", "%u"]);
+    assert_eq!(
+        split_exec(r#""/opt/My Browser/browser" --flag="a \"b\"" %U"#).unwrap(),
+        ["/opt/My Browser/browser", "--flag=a \"b\"", "%U"]
+    );
+    assert_eq!(split_exec(r#"app """#).unwrap(), ["app", ""]);
+    assert!(split_exec(r#"app "unterminated"#).is_err());
+}
+
+#[test]
+fn test_build_command_expands_field_codes() {
+    let url = "https://github.com/notive";
+    let browser = entry("Browser", "browser --name=%c %i --open %u %%");
+    assert_eq!(
+        build_command(&browser, url, None).unwrap(),
+        ["browser", "--name=Browser", "--open", url, "%"]
+    );
+
+    // Without a field code the URL is appended
+    let browser = entry("Browser", "browser --new-tab");
+    assert_eq!(build_command(&browser, url, None).unwrap(), ["browser", "--new-tab", url]);
+}
+
+#[test]
+fn test_build_command_with_profiles() {
+    let url = "https://github.com/notive";
+
+    let firefox = entry("Firefox", "/usr/lib/firefox/firefox %u");
+    assert_eq!(
+        build_command(&firefox, url, Some("work")).unwrap(),
+        ["/usr/lib/firefox/firefox", "-P", "work", url]
+    );
+
+    let chrome = entry("Google Chrome", "/usr/bin/google-chrome-stable %U");
+    assert_eq!(
+        build_command(&chrome, url, Some("Profile 1")).unwrap(),
+        ["/usr/bin/google-chrome-stable", "--profile-directory=Profile 1", url]
+    );
+
+    let unknown = entry("Other", "other %u");
+    assert!(build_command(&unknown, url, Some("work")).is_err());
+    // A blank profile is the same as none
+    assert!(build_command(&unknown, url, Some(" ")).is_ok());
+}
+
+#[test]
+fn test_build_command_for_flatpak() {
+    let firefox = entry(
+        "Firefox",
+        "/usr/bin/flatpak run --branch=stable --arch=x86_64 --command=firefox --file-forwarding org.mozilla.firefox @@u %u @@",
+    );
+    assert_eq!(
+        build_command(&firefox, "https://example.com", Some("work")).unwrap(),
+        [
+            "/usr/bin/flatpak",
+            "run",
+            "--branch=stable",
+            "--arch=x86_64",
+            "--command=firefox",
+            "--file-forwarding",
+            "org.mozilla.firefox",
+            "-P",
+            "work",
+            "https://example.com",
+        ]
+    );
+}
+
+#[test]
+fn test_choose_rule_most_specific_wins() {
+    let rules = vec![
+        rule("*.github.com", Some("chromium"), None),
+        rule("github.com", Some("firefox"), Some("work")),
+        rule("github.com/notive", None, None),
+        rule("www.youtube.com", None, None),
+    ];
+
+    let chosen = choose_rule("https://github.com/other", &rules).unwrap();
+    assert_eq!(chosen.browser.as_deref(), Some("firefox"));
+    assert_eq!(chosen.profile.as_deref(), Some("work"));
+
+    assert_eq!(choose_rule("https://gist.github.com/x", &rules).unwrap().browser.as_deref(), Some("chromium"));
+    assert_eq!(choose_rule("https://github.com/notive/x", &rules).unwrap().browser, None);
+    assert_eq!(choose_rule("https://www.youtube.com/watch", &rules).unwrap().browser, None);
+    assert!(choose_rule("https://example.com", &rules).is_none());
+}
+
+#[test]
+fn test_choose_rule_ties_and_disabled_rules() {
+    let mut disabled = rule("example.com", Some("chromium"), None);
+    disabled.enabled = false;
+    let rules = vec![
+        disabled,
+        rule("example.com", Some("firefox"), None),
+        rule("EXAMPLE.com", Some("epiphany"), None),
+    ];
+
+    assert_eq!(choose_rule("https://example.com", &rules).unwrap().browser.as_deref(), Some("firefox"));
+}
+
+#[test]
+fn test_validate_rules() {
+    assert!(validate_rules(&[rule("github.com", Some("firefox"), Some("work"))]).is_ok());
+    assert!(validate_rules(&[rule("*.", Some("firefox"), None)]).is_err());
+    assert!(validate_rules(&[rule("github.com", None, Some("work"))]).is_err());
+}
+
+#[test]
+fn test_rule_deserialization_defaults() {
+    let parsed: BrowserRule = serde_json::from_str(r#"{"pattern":"youtube.com"}"#).unwrap();
+    assert_eq!(parsed, rule("youtube.com", None, None));
+}
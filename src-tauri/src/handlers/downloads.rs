@@ -0,0 +1,150 @@
+//! Downloads started by the webview.
+//!
+//! WebKit would otherwise save downloads such as Notion exports on its own,
+//! to a location of its choosing. Its `download-started` signal is hooked so
+//! HTTP downloads are handed to the Notive download manager instead, and the
+//! rest (`blob:` and `data:` URLs, which only exist inside the page) are saved
+//! by WebKit into the Notive download folder and tracked in the history.
+
+#[cfg(test)]
+mod tests;
+
+use tauri::{Manager, Runtime, WebviewWindow};
+
+/// Who performs a download started by the webview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DownloadRoute {
+    /// Fetched again by the download manager.
+    Manager,
+    /// Left to WebKit, saved into the download folder.
+    WebView,
+}
+
+/// Decides who performs a webview download of `url`.
+pub fn route_download(url: &str) -> DownloadRoute {
+    match url::Url::parse(url).map(|u| u.scheme().to_string()).as_deref() {
+        Ok("http" | "https") => DownloadRoute::Manager,
+        _ => DownloadRoute::WebView,
+    }
+}
+
+/// Picks the file name for a webview download: WebKit's suggestion, or else
+/// the last segment of the URL.
+pub fn download_file_name(suggested: &str, url: &str) -> String {
+    if suggested.trim().is_empty() {
+        crate::commands::downloads::manager::file_name_from_url(url)
+    } else {
+        crate::commands::downloads::manager::sanitize_file_name(suggested)
+    }
+}
+
+#[cfg(target_os = "linux")]
+thread_local! {
+    // Web contexts whose downloads are already hooked; windows may share one
+    static HOOKED_CONTEXTS: std::cell::RefCell<Vec<webkit2gtk::glib::WeakRef<webkit2gtk::WebContext>>> =
+        const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Hands a download to the download manager, or lets WebKit save it into the
+/// download folder. Returns true once the download is taken care of.
+#[cfg(target_os = "linux")]
+fn decide_destination<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    download: &webkit2gtk::Download,
+    url: &str,
+    suggested: &str,
+    source_page: Option<String>,
+) -> bool {
+    use crate::commands::downloads;
+    use webkit2gtk::DownloadExt;
+
+    let file_name = download_file_name(suggested, url);
+    let path = match downloads::default_path(app, &file_name) {
+        Ok(path) => path,
+        Err(e) => {
+            log::warn!("Failed to choose download location: {}", e);
+            return false;
+        }
+    };
+
+    if route_download(url) == DownloadRoute::Manager {
+        download.cancel();
+        if let Err(e) = downloads::enqueue(app, url.to_string(), path, source_page) {
+            log::warn!("Failed to queue download {}: {}", url, e);
+        }
+        return true;
+    }
+
+    let id = downloads::track_external(app, url.to_string(), path.clone(), source_page);
+    let Ok(destination) = url::Url::from_file_path(&path) else {
+        let error = format!("Invalid download location: {}", path.display());
+        downloads::finish_external(app, &id, Err(error));
+        return false;
+    };
+    download.set_destination(destination.as_str());
+
+    let finished_app = app.clone();
+    let finished_id = id.clone();
+    download.connect_finished(move |_| {
+        downloads::finish_external(&finished_app, &finished_id, Ok(()));
+    });
+    let failed_app = app.clone();
+    download.connect_failed(move |_, error| {
+        downloads::finish_external(&failed_app, &id, Err(error.to_string()));
+    });
+    true
+}
+
+/// Routes the downloads of a window's webview through Notive.
+pub fn watch_downloads<R: Runtime>(window: &WebviewWindow<R>) {
+    let app = window.app_handle().clone();
+    let result = window.with_webview(move |webview| {
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::glib::prelude::ObjectExt;
+            use webkit2gtk::{DownloadExt, URIRequestExt, WebContextExt, WebViewExt};
+
+            let Some(context) = webview
+                .as_ref()
+                .and_then(|w| w.downcast_ref::<webkit2gtk::WebView>())
+                .and_then(|w| w.context())
+            else {
+                return;
+            };
+            let hooked = HOOKED_CONTEXTS.with(|hooked| {
+                let mut hooked = hooked.borrow_mut();
+                hooked.retain(|weak| weak.upgrade().is_some());
+                if hooked.iter().any(|weak| weak.upgrade().as_ref() == Some(&context)) {
+                    return true;
+                }
+                hooked.push(context.downgrade());
+                false
+            });
+            if hooked {
+                return;
+            }
+
+            context.connect_download_started(move |_, download| {
+                let url = download
+                    .request()
+                    .and_then(|request| request.uri())
+                    .map(|uri| uri.to_string())
+                    .unwrap_or_default();
+                let source_page = download
+                    .web_view()
+                    .and_then(|webview| webview.uri())
+                    .map(|uri| uri.to_string());
+                log::debug!("Webview download started: {}", url);
+
+                let app = app.clone();
+                download.connect_decide_destination(move |download, suggested| {
+                    decide_destination(&app, download, &url, suggested, source_page.clone())
+                });
+            });
+        }
+    });
+
+    if let Err(e) = result {
+        log::warn!("Failed to watch downloads: {}", e);
+    }
+}
@@ -0,0 +1,23 @@
+//! Tests for webview downloads.
+
+use super::*;
+
+#[test]
+fn test_http_downloads_go_to_the_manager() {
+    assert_eq!(route_download("https://file.notion.so/f/export.zip"), DownloadRoute::Manager);
+    assert_eq!(route_download("http://example.com/report.pdf"), DownloadRoute::Manager);
+}
+
+#[test]
+fn test_page_local_downloads_stay_in_the_webview() {
+    assert_eq!(route_download("blob:https://www.notion.so/5f3c"), DownloadRoute::WebView);
+    assert_eq!(route_download("data:text/csv;base64,YSxi"), DownloadRoute::WebView);
+    assert_eq!(route_download(""), DownloadRoute::WebView);
+}
+
+#[test]
+fn test_download_file_name() {
+    assert_eq!(download_file_name("Export-5c7e.zip", "https://file.notion.so/f/x"), "Export-5c7e.zip");
+    assert_eq!(download_file_name("../Export.zip", "https://file.notion.so/f/x"), "Export.zip");
+    assert_eq!(download_file_name("", "https://file.notion.so/f/Table.csv"), "Table.csv");
+}
@@ -1,6 +1,10 @@
 //! Event handlers.
 
+pub mod browsers;
+pub mod downloads;
 pub mod navigation;
+pub mod rules;
+pub mod title;
 pub mod window;
 
 #[cfg(test)]
@@ -1,13 +1,15 @@
 //! Navigation and URL handlers.
 
+use super::rules::{self, NavigationAction};
 use crate::analytics;
 use crate::history;
-use tauri::{Manager, Runtime};
-use tauri_plugin_shell::ShellExt;
+use tauri::{Manager, Runtime, WindowBuilder, WindowUrl};
 use url::Url;
 
 /// Domains allowed to load within the WebView.
-const ALLOWED_HOSTS: &[&str] = &["notion.so", "www.notion.so", "notion-static.com"];
+///
+/// These are the built-in rules, evaluated after the user's navigation rules.
+pub(crate) const ALLOWED_HOSTS: &[&str] = &["notion.so", "www.notion.so", "notion-static.com"];
 
 /// OAuth provider domains that should open in external browser.
 const OAUTH_HOSTS: &[&str] = &[
@@ -17,6 +19,9 @@
     "github.com",
 ];
 
+/// Label prefix of popup windows opened by a `popup` rule.
+const POPUP_LABEL_PREFIX: &str = "popup-";
+
 /// Checks if a host matches a domain (exact match or subdomain).
 ///
 /// Examples:
@@ -29,20 +34,23 @@ pub fn host_matches_domain(host: &str, domain: &str) -> bool {
 }
 
 #[cfg(not(test))]
-fn host_matches_domain(host: &str, domain: &str) -> bool {
+pub(crate) fn host_matches_domain(host: &str, domain: &str) -> bool {
     host == domain || host.ends_with(&format!(".{}", domain))
 }
 
-/// Checks if a URL should be opened externally.
+/// Checks if a URL should be opened externally under the built-in rules.
 pub fn should_open_externally(url: &str) -> bool {
+    rules::evaluate(url, &[]).action != NavigationAction::Webview
+}
+
+/// Checks if a URL points at a Notion domain.
+pub fn is_notion_url(url: &str) -> bool {
     if let Ok(parsed) = Url::parse(url) {
         if let Some(host) = parsed.host_str() {
-            // Check if it's an allowed Notion domain
-            let is_notion = ALLOWED_HOSTS.iter().any(|h| host_matches_domain(host, h));
-            return !is_notion;
+            return ALLOWED_HOSTS.iter().any(|h| host_matches_domain(host, h));
         }
     }
-    true
+    false
 }
 
 /// Checks if a URL is an OAuth provider.
@@ -55,34 +63,62 @@ pub fn is_oauth_url(url: &str) -> bool {
     false
 }
 
-/// Handles navigation events from the webview.
+/// Adjusts a rule's action to the window the navigation happens in.
+///
+/// A popup window loads its page like any other webview, so a URL matching a
+/// `popup` rule stays in the popup instead of opening yet another one.
+pub fn action_in_window(action: NavigationAction, window_label: &str) -> NavigationAction {
+    if action == NavigationAction::Popup && window_label.starts_with(POPUP_LABEL_PREFIX) {
+        NavigationAction::Webview
+    } else {
+        action
+    }
+}
+
+/// Handles navigation events from the webview of the window `window_label`.
 /// Returns true to allow navigation, false to prevent it.
 pub fn handle_navigation<R: Runtime>(
     app: &tauri::AppHandle<R>,
+    window_label: &str,
     url: &str,
 ) -> bool {
-    // Check if URL should open externally
-    if should_open_externally(url) {
-        log::debug!("Opening URL externally: {}", url);
-        
-        // Open in external browser
-        let _ = app.shell().open(url, None);
-        
-        // Prevent navigation in webview
-        return false;
+    let user_rules = rules::load_rules(app).unwrap_or_else(|e| {
+        log::warn!("Failed to load navigation rules: {}", e);
+        Vec::new()
+    });
+    let decision = rules::evaluate(url, &user_rules);
+    
+    match action_in_window(decision.action, window_label) {
+        NavigationAction::Webview => {}
+        NavigationAction::External => {
+            log::debug!("Opening URL externally: {} ({})", url, decision.reason);
+            
+            // Open in the browser chosen by the browser rules
+            super::browsers::open_external(app, url);
+            
+            // Prevent navigation in webview
+            return false;
+        }
+        NavigationAction::Popup => {
+            log::debug!("Opening URL in popup: {} ({})", url, decision.reason);
+            if let Err(e) = open_popup(app, url) {
+                log::warn!("Failed to open popup window: {}", e);
+            }
+            return false;
+        }
+        NavigationAction::Block => {
+            log::info!("Blocked navigation to {} ({})", url, decision.reason);
+            return false;
+        }
     }
     
     // Record page visit for history (only for Notion pages)
-    if !should_open_externally(url) {
+    if is_notion_url(url) {
         let app_handle = app.clone();
         let url_clone = url.to_string();
         tauri::async_runtime::spawn(async move {
-            // Extract title from URL or use URL as title
-            let title = url_clone
-                .split('/')
-                .last()
-                .unwrap_or("Notion Page")
-                .to_string();
+            // Use the page's last known title, or one derived from the URL
+            let title = super::title::title_for(&url_clone);
             
             // Record in history
             let title_clone = title.clone();
@@ -102,3 +138,23 @@ pub fn handle_navigation<R: Runtime>(
     // Allow navigation within webview
     true
 }
+
+/// Opens a URL in a separate popup window.
+fn open_popup<R: Runtime>(app: &tauri::AppHandle<R>, url: &str) -> Result<(), String> {
+    let label = format!("{}{}", POPUP_LABEL_PREFIX, uuid::Uuid::new_v4());
+    let parsed = url.parse::<Url>().map_err(|e| format!("Invalid URL: {}", e))?;
+    let title = parsed.host_str().unwrap_or("Notive").to_string();
+    
+    let window = WindowBuilder::new(app, &label, WindowUrl::External(parsed))
+        .title(&format!("Notive - {}", title))
+        .inner_size(900.0, 700.0)
+        .min_inner_size(400.0, 300.0)
+        .resizable(true)
+        .center()
+        .build()
+        .map_err(|e| e.to_string())?;
+    
+    super::title::watch_titles(&window);
+    super::downloads::watch_downloads(&window);
+    Ok(())
+}
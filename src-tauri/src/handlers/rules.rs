@@ -0,0 +1,342 @@
+//! User-configurable navigation rules.
+//!
+//! A rule pairs a URL pattern with an action. Patterns come in three forms:
+//!
+//! - `example.com` matches exactly that host
+//! - `*.example.com` matches any subdomain of `example.com`, but not the domain itself
+//! - `example.com/tools` matches paths below `/tools` on the host, which may
+//!   itself be a wildcard (`*.example.com/tools`)
+//!
+//! # Evaluation order
+//!
+//! 1. URLs that cannot be parsed, or have no host, open externally.
+//! 2. Enabled user rules, most specific first: path-prefix rules (longest
+//!    prefix first), then exact hosts, then wildcard subdomains (longest
+//!    domain first). Rules of equal specificity keep their list order.
+//! 3. Built-in rules: Notion hosts stay in the webview.
+//! 4. Everything else opens externally.
+
+#[cfg(test)]
+mod tests;
+
+use super::navigation::{host_matches_domain, ALLOWED_HOSTS};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime};
+use url::Url;
+
+const SETTINGS_STORE_PATH: &str = "settings.json";
+const RULES_KEY: &str = "navigation_rules";
+
+/// Where a navigation ends up.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NavigationAction {
+    /// Load in the current webview.
+    Webview,
+    /// Open in the system browser.
+    External,
+    /// Open in a separate Notive window.
+    Popup,
+    /// Do not open at all.
+    Block,
+}
+
+/// A user-defined navigation rule.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NavigationRule {
+    pub pattern: String,
+    pub action: NavigationAction,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// How a pattern matches hosts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostMatcher {
+    Exact(String),
+    Subdomain(String),
+}
+
+/// A parsed rule pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matcher {
+    pub host: HostMatcher,
+    pub path_prefix: Option<String>,
+}
+
+impl Matcher {
+    /// Parses a rule pattern such as `*.example.com/tools`.
+    pub fn parse(pattern: &str) -> Result<Self, String> {
+        let pattern = pattern.trim();
+        let without_scheme = pattern
+            .split_once("://")
+            .map(|(_, rest)| rest)
+            .unwrap_or(pattern);
+
+        let (host, path) = match without_scheme.find('/') {
+            Some(index) => (&without_scheme[..index], Some(&without_scheme[index..])),
+            None => (without_scheme, None),
+        };
+        let host = host.to_ascii_lowercase();
+
+        let host = match host.strip_prefix("*.") {
+            Some(domain) => HostMatcher::Subdomain(validate_host(domain, pattern)?),
+            None => HostMatcher::Exact(validate_host(&host, pattern)?),
+        };
+
+        let path_prefix = path
+            .map(|p| p.split(['?', '#']).next().unwrap_or(p))
+            .filter(|p| *p != "/")
+            .map(str::to_string);
+
+        Ok(Self { host, path_prefix })
+    }
+
+    /// Checks whether the matcher applies to a parsed URL.
+    pub fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.to_ascii_lowercase();
+
+        let host_matches = match &self.host {
+            HostMatcher::Exact(expected) => host == *expected,
+            HostMatcher::Subdomain(domain) => host != *domain && host_matches_domain(&host, domain),
+        };
+        if !host_matches {
+            return false;
+        }
+
+        match &self.path_prefix {
+            None => true,
+            Some(prefix) => path_has_prefix(url.path(), prefix),
+        }
+    }
+
+    /// Human-readable kind, used in explanations.
+    pub fn kind(&self) -> &'static str {
+        match (&self.host, &self.path_prefix) {
+            (_, Some(_)) => "path prefix",
+            (HostMatcher::Exact(_), None) => "exact host",
+            (HostMatcher::Subdomain(_), None) => "wildcard subdomain",
+        }
+    }
+
+    /// Sort key where greater means more specific.
+    pub(crate) fn specificity(&self) -> (usize, u8, usize) {
+        let (exact, host_len) = match &self.host {
+            HostMatcher::Exact(host) => (1, host.len()),
+            HostMatcher::Subdomain(domain) => (0, domain.len()),
+        };
+        let path_len = self.path_prefix.as_ref().map(|p| p.len()).unwrap_or(0);
+        (path_len, exact, host_len)
+    }
+}
+
+fn validate_host(host: &str, pattern: &str) -> Result<String, String> {
+    let valid = !host.is_empty()
+        && !host.starts_with('.')
+        && !host.ends_with('.')
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.');
+    if valid {
+        Ok(host.to_string())
+    } else {
+        Err(format!("Invalid navigation rule pattern: {}", pattern))
+    }
+}
+
+/// Checks a path against a prefix on segment boundaries.
+///
+/// `/tools` matches `/tools` and `/tools/x`, but not `/toolshed`.
+fn path_has_prefix(path: &str, prefix: &str) -> bool {
+    if prefix.ends_with('/') {
+        return path.starts_with(prefix) || path == prefix.trim_end_matches('/');
+    }
+    match path.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('/'),
+        None => false,
+    }
+}
+
+/// Where the deciding rule came from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSource {
+    User,
+    Builtin,
+    Default,
+}
+
+/// A rule considered while deciding a navigation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TraceStep {
+    pub source: RuleSource,
+    pub pattern: String,
+    pub kind: String,
+    pub action: NavigationAction,
+    pub matched: bool,
+}
+
+/// The outcome of evaluating a URL, with the rules that were considered.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NavigationDecision {
+    pub url: String,
+    pub action: NavigationAction,
+    pub source: RuleSource,
+    /// Pattern of the deciding rule, if any.
+    pub rule: Option<String>,
+    pub reason: String,
+    pub trace: Vec<TraceStep>,
+}
+
+/// Decides where a URL should go.
+pub fn evaluate(url: &str, rules: &[NavigationRule]) -> NavigationDecision {
+    let mut trace = Vec::new();
+    let decision = |action, source, rule: Option<String>, reason: String, trace| NavigationDecision {
+        url: url.to_string(),
+        action,
+        source,
+        rule,
+        reason,
+        trace,
+    };
+
+    let parsed = match Url::parse(url) {
+        Ok(parsed) if parsed.host_str().is_some() => parsed,
+        _ => {
+            return decision(
+                NavigationAction::External,
+                RuleSource::Default,
+                None,
+                "URL has no host, so it is handed to the system".to_string(),
+                trace,
+            )
+        }
+    };
+
+    // User rules, most specific first; the sort is stable so ties keep list order
+    let mut candidates: Vec<(&NavigationRule, Matcher)> = rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .filter_map(|rule| match Matcher::parse(&rule.pattern) {
+            Ok(matcher) => Some((rule, matcher)),
+            Err(e) => {
+                log::warn!("Skipping navigation rule: {}", e);
+                None
+            }
+        })
+        .collect();
+    candidates.sort_by_key(|(_, matcher)| std::cmp::Reverse(matcher.specificity()));
+
+    for (rule, matcher) in &candidates {
+        let matched = matcher.matches(&parsed);
+        trace.push(TraceStep {
+            source: RuleSource::User,
+            pattern: rule.pattern.clone(),
+            kind: matcher.kind().to_string(),
+            action: rule.action,
+            matched,
+        });
+        if matched {
+            return decision(
+                rule.action,
+                RuleSource::User,
+                Some(rule.pattern.clone()),
+                format!("Matched {} rule \"{}\"", matcher.kind(), rule.pattern),
+                trace,
+            );
+        }
+    }
+
+    // Built-in Notion hosts
+    let host = parsed.host_str().unwrap_or_default();
+    for domain in ALLOWED_HOSTS {
+        let matched = host_matches_domain(host, domain);
+        trace.push(TraceStep {
+            source: RuleSource::Builtin,
+            pattern: domain.to_string(),
+            kind: "domain".to_string(),
+            action: NavigationAction::Webview,
+            matched,
+        });
+        if matched {
+            return decision(
+                NavigationAction::Webview,
+                RuleSource::Builtin,
+                Some(domain.to_string()),
+                format!("{} is a Notion domain", host),
+                trace,
+            );
+        }
+    }
+
+    decision(
+        NavigationAction::External,
+        RuleSource::Default,
+        None,
+        "No rule matched, so the link opens in the system browser".to_string(),
+        trace,
+    )
+}
+
+/// Checks that every rule pattern parses.
+pub fn validate_rules(rules: &[NavigationRule]) -> Result<(), String> {
+    rules
+        .iter()
+        .try_for_each(|rule| Matcher::parse(&rule.pattern).map(|_| ()))
+}
+
+/// Loads the navigation rules from the settings store.
+pub fn load_rules<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<NavigationRule>, String> {
+    let stores = app.state::<tauri_plugin_store::StoreCollection<R>>();
+    tauri_plugin_store::with_store(app, stores, SETTINGS_STORE_PATH, |store| {
+        if let Some(value) = store.get(RULES_KEY) {
+            serde_json::from_value(value.clone())
+                .map_err(|e| tauri_plugin_store::Error::Deserialize(e.to_string()))
+        } else {
+            Ok(Vec::new())
+        }
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Gets the user navigation rules.
+#[tauri::command]
+pub fn get_navigation_rules<R: Runtime>(app: AppHandle<R>) -> Result<Vec<NavigationRule>, String> {
+    load_rules(&app)
+}
+
+/// Replaces the user navigation rules.
+#[tauri::command]
+pub fn set_navigation_rules<R: Runtime>(
+    app: AppHandle<R>,
+    rules: Vec<NavigationRule>,
+) -> Result<(), String> {
+    validate_rules(&rules)?;
+
+    let stores = app.state::<tauri_plugin_store::StoreCollection<R>>();
+    tauri_plugin_store::with_store(app, stores, SETTINGS_STORE_PATH, |store| {
+        let value = serde_json::to_value(&rules)
+            .map_err(|e| tauri_plugin_store::Error::Serialize(e.to_string()))?;
+        store.insert(RULES_KEY.to_string(), value)?;
+        store.save()?;
+        Ok(())
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Explains where a URL would go and which rules were considered.
+#[tauri::command]
+pub fn explain_navigation<R: Runtime>(
+    app: AppHandle<R>,
+    url: String,
+) -> Result<NavigationDecision, String> {
+    let rules = load_rules(&app)?;
+    Ok(evaluate(&url, &rules))
+}
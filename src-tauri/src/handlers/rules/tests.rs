@@ -0,0 +1,210 @@
+//! Tests for navigation rules.
+
+use super::*;
+
+fn rule(pattern: &str, action: NavigationAction) -> NavigationRule {
+    NavigationRule {
+        pattern: pattern.to_string(),
+        action,
+        enabled: true,
+    }
+}
+
+fn url(value: &str) -> Url {
+    Url::parse(value).unwrap()
+}
+
+#[test]
+fn test_parse_patterns() {
+    assert_eq!(
+        Matcher::parse("sso.example.com").unwrap(),
+        Matcher {
+            host: HostMatcher::Exact("sso.example.com".to_string()),
+            path_prefix: None,
+        }
+    );
+    assert_eq!(
+        Matcher::parse("*.Example.com").unwrap(),
+        Matcher {
+            host: HostMatcher::Subdomain("example.com".to_string()),
+            path_prefix: None,
+        }
+    );
+    assert_eq!(
+        Matcher::parse("https://tools.example.com/embed?x=1").unwrap(),
+        Matcher {
+            host: HostMatcher::Exact("tools.example.com".to_string()),
+            path_prefix: Some("/embed".to_string()),
+        }
+    );
+    assert_eq!(Matcher::parse("example.com/").unwrap().path_prefix, None);
+}
+
+#[test]
+fn test_parse_rejects_invalid_patterns() {
+    assert!(Matcher::parse("").is_err());
+    assert!(Matcher::parse("*.").is_err());
+    assert!(Matcher::parse("foo.*.com").is_err());
+    assert!(Matcher::parse("exa mple.com").is_err());
+    assert!(Matcher::parse("/only/a/path").is_err());
+    assert!(Matcher::parse("example.com:8080").is_err());
+}
+
+#[test]
+fn test_exact_matcher() {
+    let matcher = Matcher::parse("sso.example.com").unwrap();
+    assert!(matcher.matches(&url("https://sso.example.com/login")));
+    assert!(matcher.matches(&url("https://SSO.example.com")));
+    assert!(!matcher.matches(&url("https://example.com")));
+    assert!(!matcher.matches(&url("https://a.sso.example.com")));
+}
+
+#[test]
+fn test_wildcard_matcher_excludes_bare_domain() {
+    let matcher = Matcher::parse("*.example.com").unwrap();
+    assert!(matcher.matches(&url("https://a.example.com")));
+    assert!(matcher.matches(&url("https://a.b.example.com")));
+    assert!(!matcher.matches(&url("https://example.com")));
+    assert!(!matcher.matches(&url("https://evil-example.com")));
+}
+
+#[test]
+fn test_path_prefix_matches_on_segment_boundaries() {
+    let matcher = Matcher::parse("example.com/tools").unwrap();
+    assert!(matcher.matches(&url("https://example.com/tools")));
+    assert!(matcher.matches(&url("https://example.com/tools/board?id=1")));
+    assert!(!matcher.matches(&url("https://example.com/toolshed")));
+    assert!(!matcher.matches(&url("https://example.com/")));
+
+    let matcher = Matcher::parse("*.example.com/tools/").unwrap();
+    assert!(matcher.matches(&url("https://a.example.com/tools")));
+    assert!(matcher.matches(&url("https://a.example.com/tools/x")));
+    assert!(!matcher.matches(&url("https://example.com/tools/x")));
+}
+
+#[test]
+fn test_builtin_rules_without_user_rules() {
+    let decision = evaluate("https://www.notion.so/page", &[]);
+    assert_eq!(decision.action, NavigationAction::Webview);
+    assert_eq!(decision.source, RuleSource::Builtin);
+
+    let decision = evaluate("https://example.com", &[]);
+    assert_eq!(decision.action, NavigationAction::External);
+    assert_eq!(decision.source, RuleSource::Default);
+    assert_eq!(decision.rule, None);
+}
+
+#[test]
+fn test_unparseable_urls_open_externally() {
+    for value in ["not-a-url", "", "mailto:someone@example.com"] {
+        let decision = evaluate(value, &[rule("example.com", NavigationAction::Block)]);
+        assert_eq!(decision.action, NavigationAction::External);
+        assert!(decision.trace.is_empty());
+    }
+}
+
+#[test]
+fn test_user_rules_take_precedence_over_builtins() {
+    let rules = vec![rule("www.notion.so/admin", NavigationAction::Block)];
+
+    let decision = evaluate("https://www.notion.so/admin/members", &rules);
+    assert_eq!(decision.action, NavigationAction::Block);
+    assert_eq!(decision.source, RuleSource::User);
+
+    let decision = evaluate("https://www.notion.so/page", &rules);
+    assert_eq!(decision.action, NavigationAction::Webview);
+    assert_eq!(decision.source, RuleSource::Builtin);
+}
+
+#[test]
+fn test_most_specific_rule_wins_regardless_of_order() {
+    let rules = vec![
+        rule("*.example.com", NavigationAction::External),
+        rule("sso.example.com", NavigationAction::Webview),
+        rule("sso.example.com/admin", NavigationAction::Block),
+        rule("sso.example.com/admin/audit", NavigationAction::Popup),
+    ];
+
+    assert_eq!(evaluate("https://other.example.com", &rules).action, NavigationAction::External);
+    assert_eq!(evaluate("https://sso.example.com/login", &rules).action, NavigationAction::Webview);
+    assert_eq!(evaluate("https://sso.example.com/admin", &rules).action, NavigationAction::Block);
+    assert_eq!(
+        evaluate("https://sso.example.com/admin/audit/1", &rules).action,
+        NavigationAction::Popup
+    );
+}
+
+#[test]
+fn test_longer_wildcard_beats_shorter_wildcard() {
+    let rules = vec![
+        rule("*.example.com", NavigationAction::External),
+        rule("*.internal.example.com", NavigationAction::Webview),
+    ];
+
+    assert_eq!(
+        evaluate("https://wiki.internal.example.com", &rules).action,
+        NavigationAction::Webview
+    );
+}
+
+#[test]
+fn test_equal_specificity_keeps_list_order() {
+    let rules = vec![
+        rule("example.com/a", NavigationAction::Popup),
+        rule("example.com/b", NavigationAction::Block),
+        rule("EXAMPLE.com/a", NavigationAction::Block),
+    ];
+
+    let decision = evaluate("https://example.com/a", &rules);
+    assert_eq!(decision.action, NavigationAction::Popup);
+    assert_eq!(decision.rule.as_deref(), Some("example.com/a"));
+}
+
+#[test]
+fn test_disabled_and_invalid_rules_are_skipped() {
+    let mut disabled = rule("example.com", NavigationAction::Webview);
+    disabled.enabled = false;
+    let rules = vec![disabled, rule("bad pattern", NavigationAction::Webview)];
+
+    let decision = evaluate("https://example.com", &rules);
+    assert_eq!(decision.action, NavigationAction::External);
+    assert!(decision.trace.iter().all(|step| step.source != RuleSource::User));
+}
+
+#[test]
+fn test_trace_lists_rules_in_evaluation_order() {
+    let rules = vec![
+        rule("*.example.com", NavigationAction::Popup),
+        rule("example.com/x", NavigationAction::Block),
+    ];
+
+    let decision = evaluate("https://www.notion.so", &rules);
+    let patterns: Vec<_> = decision.trace.iter().map(|s| s.pattern.as_str()).collect();
+    assert_eq!(
+        patterns,
+        ["example.com/x", "*.example.com", "notion.so"]
+    );
+    assert_eq!(decision.trace[0].kind, "path prefix");
+    assert_eq!(decision.trace[1].kind, "wildcard subdomain");
+    assert!(!decision.trace[0].matched);
+    assert!(decision.trace[2].matched);
+    assert_eq!(decision.rule.as_deref(), Some("notion.so"));
+}
+
+#[test]
+fn test_validate_rules() {
+    assert!(validate_rules(&[rule("example.com", NavigationAction::Popup)]).is_ok());
+    let err = validate_rules(&[rule("*.", NavigationAction::Popup)]).unwrap_err();
+    assert!(err.contains("*."));
+}
+
+#[test]
+fn test_rule_serialization() {
+    let json = serde_json::to_value(rule("*.example.com", NavigationAction::Popup)).unwrap();
+    assert_eq!(json["action"], "popup");
+
+    let parsed: NavigationRule =
+        serde_json::from_str(r#"{"pattern":"example.com","action":"block"}"#).unwrap();
+    assert!(parsed.enabled);
+    assert_eq!(parsed.action, NavigationAction::Block);
+}
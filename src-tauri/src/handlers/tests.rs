@@ -57,3 +57,23 @@ fn test_should_open_externally_invalid_url() {
     assert!(should_open_externally("not-a-url"));
     assert!(should_open_externally(""));
 }
+
+#[test]
+fn test_is_notion_url() {
+    assert!(is_notion_url("https://www.notion.so/page"));
+    assert!(is_notion_url("https://file.notion-static.com/x.png"));
+    assert!(!is_notion_url("https://example.com"));
+    assert!(!is_notion_url("not-a-url"));
+}
+
+#[test]
+fn test_popup_rules_load_in_place_inside_popups() {
+    use super::rules::NavigationAction;
+
+    assert_eq!(action_in_window(NavigationAction::Popup, "main"), NavigationAction::Popup);
+    assert_eq!(action_in_window(NavigationAction::Popup, "workspace-1234"), NavigationAction::Popup);
+    // Otherwise the popup's own first load would open another popup
+    assert_eq!(action_in_window(NavigationAction::Popup, "popup-1234"), NavigationAction::Webview);
+    assert_eq!(action_in_window(NavigationAction::External, "popup-1234"), NavigationAction::External);
+    assert_eq!(action_in_window(NavigationAction::Block, "popup-1234"), NavigationAction::Block);
+}
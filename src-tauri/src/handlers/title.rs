@@ -0,0 +1,190 @@
+//! Page titles for history and analytics.
+//!
+//! Real titles come from the webview's document title, which WebKit reports
+//! through its `title` property notification once a page has loaded. Until
+//! then (or if the page never sets one) a title is derived from the URL slug.
+
+#[cfg(test)]
+mod tests;
+
+use crate::notion_url::canonical_key;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow};
+use url::Url;
+
+/// Title used when neither the document nor the URL has a usable one.
+const FALLBACK_TITLE: &str = "Untitled";
+
+/// Suffixes Notion appends to document titles.
+const TITLE_SUFFIXES: &[&str] = &[" | Notion", " - Notion", " – Notion"];
+
+/// Titles kept in memory before the cache is reset.
+const MAX_CACHED_TITLES: usize = 500;
+
+// Last document title seen per page
+static TITLE_CACHE: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+/// Cleans a document title, returning `None` while it is not meaningful.
+///
+/// Notion shows just "Notion" while a page loads, and may prefix the title
+/// with an unread count such as "(3) ".
+pub fn clean_document_title(title: &str) -> Option<String> {
+    let mut title = title.trim();
+
+    if let Some(rest) = title.strip_prefix('(') {
+        if let Some((count, after)) = rest.split_once(") ") {
+            if !count.is_empty() && count.chars().all(|c| c.is_ascii_digit()) {
+                title = after.trim_start();
+            }
+        }
+    }
+
+    for suffix in TITLE_SUFFIXES {
+        if let Some(stripped) = title.strip_suffix(suffix) {
+            title = stripped.trim_end();
+        }
+    }
+
+    if title.is_empty() || title.eq_ignore_ascii_case("notion") {
+        None
+    } else {
+        Some(title.to_string())
+    }
+}
+
+/// Builds a readable title from the last path segment of a URL.
+///
+/// `Team-Wiki-8f3a...` becomes `Team Wiki`; a bare page ID or an empty path
+/// falls back to a generic title.
+pub fn slug_title(url: &str) -> String {
+    let segment = Url::parse(url)
+        .ok()
+        .and_then(|parsed| {
+            parsed
+                .path_segments()
+                .and_then(|segments| segments.rev().find(|s| !s.is_empty()).map(str::to_string))
+        })
+        .unwrap_or_default();
+
+    let segment = percent_decode(&segment);
+    let without_id = strip_page_id(&segment);
+    let title = without_id.replace('-', " ");
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if title.is_empty() {
+        FALLBACK_TITLE.to_string()
+    } else {
+        title
+    }
+}
+
+/// Removes a trailing 32-character hex page ID from a slug.
+fn strip_page_id(segment: &str) -> &str {
+    let is_id = |s: &str| s.len() == 32 && s.chars().all(|c| c.is_ascii_hexdigit());
+
+    if is_id(segment) {
+        return "";
+    }
+    match segment.rsplit_once('-') {
+        Some((slug, id)) if is_id(id) => slug,
+        _ => segment,
+    }
+}
+
+/// Decodes percent-encoded bytes, e.g. in URL path segments.
+pub(crate) fn percent_decode(input: &str) -> String {
+    let hex = |b: u8| (b as char).to_digit(16).map(|d| d as u8);
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let (Some(high), Some(low)) = (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                out.push(high << 4 | low);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Remembers the document title of a page.
+pub fn remember_title(url: &str, title: &str) {
+    if let Ok(mut cache) = TITLE_CACHE.lock() {
+        let cache = cache.get_or_insert_with(HashMap::new);
+        if cache.len() >= MAX_CACHED_TITLES {
+            cache.clear();
+        }
+        cache.insert(canonical_key(url), title.to_string());
+    }
+}
+
+/// Gets the best known title for a page: its last document title, or the slug.
+pub fn title_for(url: &str) -> String {
+    TITLE_CACHE
+        .lock()
+        .ok()
+        .and_then(|cache| cache.as_ref().and_then(|c| c.get(&canonical_key(url)).cloned()))
+        .unwrap_or_else(|| slug_title(url))
+}
+
+/// Records a document title reported by the webview of window `label`.
+///
+/// Updates the history entry and page views of the page, so entries recorded
+/// with the slug fallback get their real title.
+pub fn on_title_changed<R: Runtime>(app: &AppHandle<R>, label: &str, url: &str, title: &str) {
+    let Some(title) = clean_document_title(title) else {
+        return;
+    };
+    if !super::navigation::is_notion_url(url) {
+        return;
+    }
+
+    remember_title(url, &title);
+
+    // Everything below loads and saves stores, which must not hold up the
+    // main thread the webview reports titles on
+    let app = app.clone();
+    let label = label.to_string();
+    let url = url.to_string();
+    tauri::async_runtime::spawn(async move {
+        crate::popouts::on_page_changed(&app, &label, &url, &title);
+        crate::mini::on_page_changed(&app, &label, &url, &title);
+        crate::workspaces::on_page_changed(&app, &label, &url);
+        if let Err(e) = crate::history::update_page_title(&app, &url, &title) {
+            log::warn!("Failed to update history title: {}", e);
+        }
+        if let Err(e) = crate::analytics::update_page_title(&app, &url, &title) {
+            log::warn!("Failed to update page view title: {}", e);
+        }
+    });
+}
+
+/// Watches a window's document title and feeds it into [`on_title_changed`].
+pub fn watch_titles<R: Runtime>(window: &WebviewWindow<R>) {
+    let app = window.app_handle().clone();
+    let label = window.label().to_string();
+    let result = window.with_webview(move |webview| {
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::WebViewExt;
+            if let Some(webview) = webview.as_ref().and_then(|w| w.downcast_ref::<webkit2gtk::WebView>()) {
+                webview.connect_title_notify(move |webview| {
+                    if let (Some(uri), Some(title)) = (webview.uri(), webview.title()) {
+                        on_title_changed(&app, &label, &uri, &title);
+                    }
+                });
+            }
+        }
+    });
+
+    if let Err(e) = result {
+        log::warn!("Failed to watch page titles: {}", e);
+    }
+}
@@ -0,0 +1,59 @@
+//! Tests for page titles.
+
+use super::*;
+
+const PAGE_ID: &str = "8f3a1b2c4d5e6f708192a3b4c5d6e7f8";
+
+#[test]
+fn test_slug_title_strips_page_id() {
+    assert_eq!(
+        slug_title(&format!("https://www.notion.so/acme/Team-Wiki-{}", PAGE_ID)),
+        "Team Wiki"
+    );
+    assert_eq!(
+        slug_title(&format!("https://www.notion.so/Q3--Roadmap-{}?pvs=4", PAGE_ID)),
+        "Q3 Roadmap"
+    );
+}
+
+#[test]
+fn test_slug_title_decodes_percent_escapes() {
+    assert_eq!(
+        slug_title(&format!("https://www.notion.so/Caf%C3%A9-Notes-{}", PAGE_ID)),
+        "Café Notes"
+    );
+    assert_eq!(slug_title("https://www.notion.so/100%25-Done"), "100% Done");
+    assert_eq!(slug_title("https://www.notion.so/Broken-%ZZ"), "Broken %ZZ");
+}
+
+#[test]
+fn test_slug_title_fallback() {
+    assert_eq!(slug_title(&format!("https://www.notion.so/{}", PAGE_ID)), FALLBACK_TITLE);
+    assert_eq!(slug_title("https://www.notion.so/"), FALLBACK_TITLE);
+    assert_eq!(slug_title("not a url"), FALLBACK_TITLE);
+}
+
+#[test]
+fn test_slug_title_keeps_short_hex_words() {
+    assert_eq!(slug_title("https://www.notion.so/Cafe-Bad"), "Cafe Bad");
+}
+
+#[test]
+fn test_clean_document_title() {
+    assert_eq!(clean_document_title("Team Wiki").as_deref(), Some("Team Wiki"));
+    assert_eq!(clean_document_title("  Team Wiki | Notion ").as_deref(), Some("Team Wiki"));
+    assert_eq!(clean_document_title("(3) Inbox").as_deref(), Some("Inbox"));
+    assert_eq!(clean_document_title("(Draft) Plan").as_deref(), Some("(Draft) Plan"));
+    assert_eq!(clean_document_title("Notion"), None);
+    assert_eq!(clean_document_title("   "), None);
+}
+
+#[test]
+fn test_title_for_prefers_remembered_title() {
+    let url = format!("https://www.notion.so/Remembered-{}", PAGE_ID);
+    assert_eq!(title_for(&url), "Remembered");
+
+    remember_title(&url, "Remembered Page Title");
+    assert_eq!(title_for(&format!("{}?pvs=4", url)), "Remembered Page Title");
+    assert_eq!(title_for(&format!("https://notion.so/{}", PAGE_ID)), "Remembered Page Title");
+}
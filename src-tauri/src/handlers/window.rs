@@ -3,14 +3,41 @@
 #[cfg(test)]
 mod tests;
 
+use crate::mini;
+use crate::monitor;
+use crate::popouts;
 use crate::privacy;
 use crate::tray;
+use crate::workspaces;
 use tauri::{Runtime, Window, WindowEvent};
 
 /// Handles window events.
 pub fn handle_window_event<R: Runtime>(window: &Window<R>, event: &WindowEvent) {
     match event {
         WindowEvent::CloseRequested { api, .. } => {
+            // A closed pop-out is gone for good, rather than hidden to the tray
+            if popouts::id_from_label(window.label()).is_some() {
+                popouts::on_closed(window.app_handle(), window.label());
+                return;
+            }
+            
+            // Closing a workspace window closes it, keeping its page and placement
+            if workspaces::id_from_label(window.label()).is_some() {
+                monitor::remember_window(window, true);
+                workspaces::on_closed(window.app_handle(), window.label());
+                return;
+            }
+            
+            // Closing the mini window closes it, keeping its page and placement
+            if window.label() == mini::LABEL {
+                monitor::remember_window(window, true);
+                mini::on_closed(window.app_handle());
+                return;
+            }
+            
+            // Save where the window was before it closes or hides
+            monitor::remember_window(window, true);
+            
             // Check if close-to-tray is enabled
             let app = window.app_handle();
             if crate::config::get_close_to_tray(&app) {
@@ -31,6 +58,9 @@ pub fn handle_window_event<R: Runtime>(window: &Window<R>, event: &WindowEvent)
                 }
             }
         }
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) => {
+            monitor::remember_window(window, false);
+        }
         WindowEvent::Focused(focused) => {
             log::trace!("Window focused: {}", focused);
             if *focused {
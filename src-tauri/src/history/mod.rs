@@ -5,6 +5,7 @@
 
 mod persistence;
 
+use crate::notion_url::same_page;
 use persistence::{load_history, save_history};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
@@ -47,11 +48,12 @@ pub fn new(max_entries: usize) -> Self {
     }
 
     pub fn add_entry(&mut self, mut entry: HistoryEntry) {
-        // Check if URL already exists
-        if let Some(existing) = self.history.iter_mut().find(|e| e.url == entry.url) {
+        // Check if the page already exists, possibly under another URL
+        if let Some(existing) = self.history.iter_mut().find(|e| same_page(&e.url, &entry.url)) {
             existing.visit_count += 1;
             existing.visited_at = chrono::Utc::now().to_rfc3339();
             existing.title = entry.title; // Update title in case it changed
+            existing.url = entry.url;
         } else {
             // Add new entry
             if self.history.len() >= self.max_entries {
@@ -89,11 +91,12 @@ pub async fn record_page_visit<R: Runtime>(
     
     let mut history = load_history(&app).unwrap_or_default();
     
-    // Check if URL already exists
-    if let Some(existing) = history.iter_mut().find(|e| e.url == url) {
+    // Check if the page already exists, possibly under another URL
+    if let Some(existing) = history.iter_mut().find(|e| same_page(&e.url, &url)) {
         existing.visit_count += 1;
         existing.visited_at = chrono::Utc::now().to_rfc3339();
         existing.title = title; // Update title in case it changed
+        existing.url = url;
     } else {
         // Add new entry
         let entry = HistoryEntry::new(title, url);
@@ -113,6 +116,32 @@ pub async fn record_page_visit<R: Runtime>(
     Ok(())
 }
 
+/// Sets the title of the history entry for `url`.
+///
+/// Returns true if an entry was updated.
+pub fn apply_page_title(history: &mut [HistoryEntry], url: &str, title: &str) -> bool {
+    match history
+        .iter_mut()
+        .find(|e| same_page(&e.url, url))
+    {
+        Some(entry) if entry.title != title => {
+            entry.title = title.to_string();
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Updates the stored title of a visited page once its real title is known.
+pub fn update_page_title<R: Runtime>(app: &AppHandle<R>, url: &str, title: &str) -> Result<(), String> {
+    let mut history = load_history(app).unwrap_or_default();
+    if apply_page_title(&mut history, url, title) {
+        save_history(app, &history).map_err(|e| e.to_string())?;
+        log::debug!("History title updated: {} -> {}", url, title);
+    }
+    Ok(())
+}
+
 /// Gets recent pages.
 #[tauri::command]
 pub fn get_recent_pages<R: Runtime>(
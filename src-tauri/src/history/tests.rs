@@ -1,6 +1,6 @@
 //! Tests for history management.
 
-use super::{HistoryEntry, HistoryManager};
+use super::{apply_page_title, HistoryEntry, HistoryManager};
 
 #[test]
 fn test_history_entry_creation() {
@@ -116,3 +116,37 @@ fn test_history_manager_remove_nonexistent() {
     assert!(removed.is_none());
     assert_eq!(manager.get_recent(10).len(), 1);
 }
+
+#[test]
+fn test_apply_page_title() {
+    let mut history = vec![
+        HistoryEntry::new("Team Wiki".to_string(), "https://www.notion.so/Team-Wiki-abc".to_string()),
+        HistoryEntry::new("Other".to_string(), "https://www.notion.so/Other".to_string()),
+    ];
+
+    assert!(apply_page_title(&mut history, "https://www.notion.so/Team-Wiki-abc?pvs=4", "Team Wiki 📚"));
+    assert_eq!(history[0].title, "Team Wiki 📚");
+    assert_eq!(history[1].title, "Other");
+
+    // Same title again is not a change
+    assert!(!apply_page_title(&mut history, "https://www.notion.so/Team-Wiki-abc", "Team Wiki 📚"));
+    assert!(!apply_page_title(&mut history, "https://www.notion.so/Missing", "Missing"));
+}
+
+#[test]
+fn test_history_manager_merges_url_variants() {
+    let mut manager = HistoryManager::new(100);
+    let page = "8f3a1b2c4d5e6f708192a3b4c5d6e7f8";
+
+    for url in [
+        format!("https://www.notion.so/acme/Team-Wiki-{}", page),
+        format!("https://notion.so/{}?pvs=4", page),
+        format!("https://www.notion.so/Team-Wiki-{}#0123456789abcdef0123456789abcdef", page),
+    ] {
+        manager.add_entry(HistoryEntry::new("Team Wiki".to_string(), url));
+    }
+
+    let recent = manager.get_recent(10);
+    assert_eq!(recent.len(), 1);
+    assert_eq!(recent[0].visit_count, 3);
+}
@@ -0,0 +1,292 @@
+//! Single-instance enforcement and launch argument forwarding.
+//!
+//! The first instance listens on a Unix domain socket in the runtime
+//! directory. Later launches connect to it, send their parsed arguments as
+//! one JSON line, and exit. The running instance then raises its window and
+//! navigates to the forwarded URL.
+
+#[cfg(test)]
+mod tests;
+
+use crate::utils::paths;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+use url::Url;
+
+/// Socket file name inside the runtime directory.
+const SOCKET_NAME: &str = "instance.sock";
+
+/// How long a second launch waits for the running instance to answer.
+const FORWARD_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Reply sent once forwarded arguments were received.
+const ACK: &str = "ok";
+
+// Socket bound by this process, removed again on exit
+static BOUND_SOCKET: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// Arguments a launch can pass to Notive.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LaunchArgs {
+    /// Start hidden in the tray (`--minimized`).
+    pub minimized: bool,
+    /// Page to open, from a positional URL argument.
+    pub url: Option<String>,
+    /// Local update bundle to install (`--install-update <path>`).
+    pub install_update: Option<PathBuf>,
+}
+
+impl LaunchArgs {
+    /// Parses command-line arguments, excluding the program name.
+    ///
+    /// Relative paths are resolved against the current directory so that
+    /// they still work after being forwarded to the running instance.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut launch = Self::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--minimized" => launch.minimized = true,
+                "--install-update" => {
+                    let path = args
+                        .next()
+                        .ok_or("--install-update requires the path of an update bundle")?;
+                    launch.install_update = Some(absolute_path(Path::new(path)));
+                }
+                flag if flag.starts_with('-') => {
+                    log::debug!("Ignoring unknown argument: {}", flag);
+                }
+                // Launchers may pass file paths or other values along
+                value => match normalize_url(value) {
+                    Some(url) => launch.url = Some(url),
+                    None => log::warn!("Ignoring argument that is not a URL: {}", value),
+                },
+            }
+        }
+
+        Ok(launch)
+    }
+}
+
+fn absolute_path(path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    std::env::current_dir()
+        .map(|dir| dir.join(path))
+        .unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Converts a command-line URL into a page URL.
+///
+/// Accepts `http(s)` URLs and `notion://` links, which the Notion apps use
+/// for "Open in app" (`notion://www.notion.so/Page-abc123`, or without the
+/// host).
+pub fn normalize_url(arg: &str) -> Option<String> {
+    let url = Url::parse(arg.trim()).ok()?;
+
+    match url.scheme() {
+        "http" | "https" => url.host_str().map(|_| url.to_string()),
+        "notion" => {
+            let host = url.host_str().unwrap_or_default();
+            let mut page = if crate::handlers::navigation::is_notion_url(&format!("https://{}", host)) {
+                Url::parse(&format!("https://{}", host)).ok()?
+            } else {
+                // notion://Page-abc123 carries the first path segment as its host
+                Url::parse(&format!("https://www.notion.so/{}", host)).ok()?
+            };
+
+            let path = format!(
+                "{}/{}",
+                page.path().trim_end_matches('/'),
+                url.path().trim_start_matches('/')
+            );
+            page.set_path(path.trim_end_matches('/'));
+            page.set_query(url.query());
+            page.set_fragment(url.fragment());
+            Some(page.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Gets the path of the instance socket.
+pub fn socket_path() -> Option<PathBuf> {
+    paths::app_runtime_dir().map(|dir| dir.join(SOCKET_NAME))
+}
+
+/// Result of trying to become the running instance.
+#[derive(Debug)]
+pub enum Instance {
+    /// No other instance is running; this one now owns the socket.
+    Primary(UnixListener),
+    /// Another instance is running and received the arguments.
+    Forwarded,
+}
+
+/// Becomes the primary instance, or forwards `args` to the running one.
+pub fn acquire(socket: &Path, args: &LaunchArgs) -> std::io::Result<Instance> {
+    if let Some(dir) = socket.parent() {
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+    }
+
+    // A second attempt covers another instance binding between our two steps
+    for _ in 0..2 {
+        match forward(socket, args) {
+            Ok(()) => return Ok(Instance::Forwarded),
+            Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => {
+                // Nobody is listening; a leftover socket file is from a crashed instance
+                if e.kind() == ErrorKind::ConnectionRefused {
+                    log::debug!("Removing stale instance socket {:?}", socket);
+                    let _ = std::fs::remove_file(socket);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+
+        match UnixListener::bind(socket) {
+            Ok(listener) => {
+                if let Ok(mut bound) = BOUND_SOCKET.lock() {
+                    *bound = Some(socket.to_path_buf());
+                }
+                return Ok(Instance::Primary(listener));
+            }
+            Err(e) if e.kind() == ErrorKind::AddrInUse => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(std::io::Error::new(
+        ErrorKind::AddrInUse,
+        "Instance socket is in use but not answering",
+    ))
+}
+
+/// Sends arguments to the running instance and waits for its reply.
+fn forward(socket: &Path, args: &LaunchArgs) -> std::io::Result<()> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+    stream.set_write_timeout(Some(FORWARD_TIMEOUT))?;
+
+    let mut message = serde_json::to_string(args)?;
+    message.push('\n');
+    stream.write_all(message.as_bytes())?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream).read_line(&mut reply)?;
+    if reply.trim() != ACK {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "Running instance did not acknowledge the launch",
+        ));
+    }
+    Ok(())
+}
+
+/// Reads forwarded arguments from a connection and acknowledges them.
+pub fn receive(stream: &mut UnixStream) -> std::io::Result<LaunchArgs> {
+    stream.set_read_timeout(Some(FORWARD_TIMEOUT))?;
+
+    let mut line = String::new();
+    BufReader::new(&*stream).read_line(&mut line)?;
+    let args = serde_json::from_str(&line)?;
+
+    stream.write_all(format!("{}\n", ACK).as_bytes())?;
+    Ok(args)
+}
+
+/// Handles launches forwarded to this instance in a background thread.
+pub fn listen<R: Runtime>(app: AppHandle<R>, listener: UnixListener) {
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    log::warn!("Failed to accept forwarded launch: {}", e);
+                    continue;
+                }
+            };
+
+            match receive(&mut stream) {
+                Ok(args) => {
+                    log::info!("Received forwarded launch: {:?}", args);
+                    apply(&app, &args, true);
+                }
+                Err(e) => log::warn!("Failed to read forwarded launch: {}", e),
+            }
+        }
+    });
+}
+
+/// Removes the instance socket on exit.
+///
+/// Only a process that bound the socket removes it; removing it from a
+/// launch that was forwarded would orphan the running instance.
+pub fn release() {
+    let socket = BOUND_SOCKET.lock().ok().and_then(|mut bound| bound.take());
+    if let Some(socket) = socket {
+        let _ = std::fs::remove_file(socket);
+    }
+}
+
+/// Applies launch arguments to the running app.
+///
+/// At startup `--minimized` hides the main window. A forwarded launch raises
+/// the window unless it asked to stay minimized.
+pub fn apply<R: Runtime>(app: &AppHandle<R>, args: &LaunchArgs, forwarded: bool) {
+    if let Some(window) = app.get_webview_window("main") {
+        if args.minimized {
+            if !forwarded {
+                let _ = window.hide();
+                log::info!("Started minimized to tray");
+            }
+        } else if forwarded {
+            let _ = window.show();
+            let _ = window.unminimize();
+            let _ = window.set_focus();
+        }
+
+        // Navigation rules decide whether the page loads here or elsewhere
+        if let Some(url) = &args.url {
+            let script = format!(
+                "window.location.href = {};",
+                serde_json::to_string(url).unwrap_or_default()
+            );
+            if let Err(e) = window.eval(&script) {
+                log::warn!("Failed to open {}: {}", url, e);
+            }
+        }
+    }
+
+    if let Some(bundle) = args.install_update.clone() {
+        let handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            install_update(&handle, &bundle).await;
+        });
+    }
+}
+
+/// Installs a local update bundle passed with `--install-update`.
+async fn install_update<R: Runtime>(app: &AppHandle<R>, bundle: &Path) {
+    let bundle_path = bundle.to_string_lossy().to_string();
+    if let Err(e) = crate::commands::app::install_update_from_file(app.clone(), bundle_path, None, None).await {
+        log::error!("Failed to install update from {:?}: {}", bundle, e);
+        let _ = app.dialog()
+            .message(&e)
+            .title("Update Failed")
+            .kind(MessageDialogKind::Error)
+            .show()
+            .await;
+    }
+}
@@ -0,0 +1,140 @@
+//! Tests for single-instance handling.
+
+use super::*;
+use tempfile::TempDir;
+
+fn args(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn test_parse_empty_args() {
+    assert_eq!(LaunchArgs::parse(&[]).unwrap(), LaunchArgs::default());
+}
+
+#[test]
+fn test_parse_url_and_minimized() {
+    let launch = LaunchArgs::parse(&args(&["--minimized", "https://www.notion.so/Team-Wiki-abc123"])).unwrap();
+    assert!(launch.minimized);
+    assert_eq!(launch.url.as_deref(), Some("https://www.notion.so/Team-Wiki-abc123"));
+    assert_eq!(launch.install_update, None);
+}
+
+#[test]
+fn test_parse_install_update_resolves_relative_path() {
+    let launch = LaunchArgs::parse(&args(&["--install-update", "notive.AppImage"])).unwrap();
+    let path = launch.install_update.unwrap();
+    assert!(path.is_absolute());
+    assert!(path.ends_with("notive.AppImage"));
+
+    let launch = LaunchArgs::parse(&args(&["--install-update", "/tmp/notive.AppImage"])).unwrap();
+    assert_eq!(launch.install_update, Some(PathBuf::from("/tmp/notive.AppImage")));
+}
+
+#[test]
+fn test_parse_errors() {
+    assert!(LaunchArgs::parse(&args(&["--install-update"])).is_err());
+}
+
+#[test]
+fn test_parse_ignores_arguments_that_are_not_urls() {
+    let launch = LaunchArgs::parse(&args(&["/home/user/notes.txt", "https://www.notion.so/Team-Wiki-abc123"])).unwrap();
+    assert_eq!(launch.url.as_deref(), Some("https://www.notion.so/Team-Wiki-abc123"));
+
+    assert_eq!(LaunchArgs::parse(&args(&["not a url"])).unwrap(), LaunchArgs::default());
+}
+
+#[test]
+fn test_parse_ignores_unknown_flags() {
+    let launch = LaunchArgs::parse(&args(&["--some-flag", "--minimized"])).unwrap();
+    assert!(launch.minimized);
+}
+
+#[test]
+fn test_normalize_http_urls() {
+    assert_eq!(
+        normalize_url("https://www.notion.so/Team-Wiki-abc123?pvs=4").as_deref(),
+        Some("https://www.notion.so/Team-Wiki-abc123?pvs=4")
+    );
+    assert_eq!(normalize_url("file:///etc/passwd"), None);
+    assert_eq!(normalize_url("Team-Wiki-abc123"), None);
+}
+
+#[test]
+fn test_normalize_notion_scheme() {
+    assert_eq!(
+        normalize_url("notion://www.notion.so/acme/Team-Wiki-abc123").as_deref(),
+        Some("https://www.notion.so/acme/Team-Wiki-abc123")
+    );
+    assert_eq!(
+        normalize_url("notion://Team-Wiki-abc123").as_deref(),
+        Some("https://www.notion.so/Team-Wiki-abc123")
+    );
+    assert_eq!(
+        normalize_url("notion://acme/Team-Wiki-abc123?p=def#block").as_deref(),
+        Some("https://www.notion.so/acme/Team-Wiki-abc123?p=def#block")
+    );
+}
+
+#[test]
+fn test_first_launch_becomes_primary() {
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join("run").join(SOCKET_NAME);
+
+    let instance = acquire(&socket, &LaunchArgs::default()).unwrap();
+    assert!(matches!(instance, Instance::Primary(_)));
+    assert!(socket.exists());
+}
+
+#[test]
+fn test_second_launch_forwards_args() {
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join(SOCKET_NAME);
+
+    let Instance::Primary(listener) = acquire(&socket, &LaunchArgs::default()).unwrap() else {
+        panic!("first launch should be primary");
+    };
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        receive(&mut stream).unwrap()
+    });
+
+    let forwarded = LaunchArgs {
+        minimized: false,
+        url: Some("https://www.notion.so/Team-Wiki-abc123".to_string()),
+        install_update: None,
+    };
+    let instance = acquire(&socket, &forwarded).unwrap();
+
+    assert!(matches!(instance, Instance::Forwarded));
+    assert_eq!(server.join().unwrap(), forwarded);
+}
+
+#[test]
+fn test_stale_socket_is_replaced() {
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join(SOCKET_NAME);
+
+    // A crashed instance leaves its socket file behind
+    drop(UnixListener::bind(&socket).unwrap());
+    assert!(socket.exists());
+
+    let instance = acquire(&socket, &LaunchArgs::default()).unwrap();
+    assert!(matches!(instance, Instance::Primary(_)));
+}
+
+#[test]
+fn test_unresponsive_instance_is_an_error() {
+    let dir = TempDir::new().unwrap();
+    let socket = dir.path().join(SOCKET_NAME);
+
+    // Accepts the connection but closes it without acknowledging
+    let listener = UnixListener::bind(&socket).unwrap();
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        drop(stream);
+    });
+
+    assert!(acquire(&socket, &LaunchArgs::default()).is_err());
+    server.join().unwrap();
+}
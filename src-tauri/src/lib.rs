@@ -5,9 +5,11 @@
 //! keyboard shortcuts, and auto-updates.
 
 mod app;
+mod backup;
 mod commands;
 mod config;
 mod handlers;
+mod instance;
 mod notifications;
 mod shortcuts;
 mod tray;
@@ -20,10 +22,15 @@
 mod accounts;
 mod bookmarks;
 mod history;
+mod mini;
+mod monitor;
+mod notion_url;
+mod popouts;
 mod privacy;
 mod quickcapture;
 mod security;
 mod sessions;
+mod sync;
 mod templates;
 mod analytics;
 
@@ -37,8 +44,33 @@
 #[cfg(test)]
 pub use history;
 
+use std::path::PathBuf;
 use tauri::Manager;
 
+pub use instance::LaunchArgs;
+
+/// Builds the Tauri context from `tauri.conf.json`.
+fn context() -> tauri::Context<tauri::Wry> {
+    tauri::generate_context!()
+}
+
+/// Gets the store directory before the Tauri app has started.
+fn store_dir(context: &tauri::Context<tauri::Wry>) -> Result<PathBuf, String> {
+    utils::paths::store_dir(&context.config().identifier)
+        .ok_or_else(|| "Could not determine store directory".to_string())
+}
+
+/// Restores the version and data that were replaced by the last update.
+///
+/// Used by the `--rollback` command-line flag, before any window is created.
+pub fn rollback_update() -> Result<String, String> {
+    let snapshot = updater::rollback::rollback(&store_dir(&context())?)?;
+    Ok(format!(
+        "Rolled back from {} to {}",
+        snapshot.installed_version, snapshot.previous_version
+    ))
+}
+
 /// Runs the Notive application.
 ///
 /// This function initializes all plugins, sets up the application state,
@@ -46,10 +78,44 @@
 ///
 /// # Arguments
 ///
-/// * `start_minimized` - If true, the window will be hidden to tray on startup.
-pub fn run(start_minimized: bool) {
+/// * `args` - Parsed command-line arguments. If another instance is already
+///   running, they are forwarded to it and this process exits.
+pub fn run(args: LaunchArgs) {
     env_logger::init();
 
+    // Hand the launch over to a running instance, if there is one
+    let mut listener = match instance::socket_path().map(|socket| instance::acquire(&socket, &args)) {
+        Some(Ok(instance::Instance::Forwarded)) => {
+            log::info!("Notive is already running, forwarded launch arguments");
+            return;
+        }
+        Some(Ok(instance::Instance::Primary(listener))) => Some(listener),
+        Some(Err(e)) => {
+            log::warn!("Single-instance check failed, starting anyway: {}", e);
+            None
+        }
+        None => None,
+    };
+
+    // Roll back an update that keeps failing to start
+    let context = context();
+    let restored = store_dir(&context)
+        .ok()
+        .and_then(|store_dir| updater::check_startup_health(env!("CARGO_PKG_VERSION"), &store_dir));
+    if let Some(binary) = restored {
+        log::info!("Relaunching previous version: {:?}", binary);
+        // Let the relaunched binary take over the instance socket
+        listener = None;
+        instance::release();
+        match std::process::Command::new(&binary)
+            .args(std::env::args().skip(1))
+            .spawn()
+        {
+            Ok(_) => std::process::exit(0),
+            Err(e) => log::error!("Failed to relaunch previous version: {}", e),
+        }
+    }
+
     tauri::Builder::default()
         // Plugins
         .plugin(tauri_plugin_store::Builder::new().build())
@@ -78,12 +144,26 @@ pub fn run(start_minimized: bool) {
 
             // Global shortcuts will be registered in app::init with settings
 
-            // Hide window to tray if --minimized flag was passed
-            if start_minimized {
-                if let Some(window) = app.get_webview_window("main") {
-                    let _ = window.hide();
-                    log::info!("Started minimized to tray");
-                }
+            // Apply --minimized, a page URL, or --install-update
+            instance::apply(app.handle(), &args, false);
+
+            // Reopen the pop-out, mini and workspace windows from last time
+            popouts::restore(app.handle(), !args.minimized);
+            mini::restore(app.handle(), !args.minimized);
+            workspaces::restore(app.handle(), !args.minimized);
+
+            // Remove browser data of workspaces deleted while their window was open
+            workspaces::cleanup(app.handle());
+
+            // Continue queued downloads; interrupted ones wait to be resumed
+            commands::downloads::resume_queue(app.handle());
+
+            // Pick up changes made on other devices, now and then periodically
+            sync::start_background(app.handle());
+
+            // Accept launches forwarded by later instances
+            if let Some(listener) = listener {
+                instance::listen(app.handle().clone(), listener);
             }
 
             // Setup auto-updater (background check)
@@ -92,6 +172,9 @@ pub fn run(start_minimized: bool) {
                 updater::setup(&handle).await;
             });
 
+            // Confirm a freshly installed update once it has run for a while
+            tauri::async_runtime::spawn(updater::confirm_startup_after_delay());
+
             log::info!("Notive started successfully");
             Ok(())
         })
@@ -107,13 +190,47 @@ pub fn run(start_minimized: bool) {
             commands::notifications::show_notification,
             commands::downloads::handle_download,
             commands::downloads::download_with_dialog,
+            commands::downloads::list_downloads,
+            commands::downloads::pause_download,
+            commands::downloads::resume_download,
+            commands::downloads::cancel_download,
+            commands::downloads::remove_download,
+            commands::downloads::clear_download_history,
+            commands::downloads::rules::get_download_rules,
+            commands::downloads::rules::set_download_rules,
+            commands::pdf::export_to_pdf,
             commands::app::show_about,
             commands::app::check_updates,
+            commands::app::switch_update_channel,
+            commands::app::rollback_update,
+            commands::app::get_release_notes,
+            commands::app::get_update_state,
+            commands::app::cancel_update_download,
+            commands::app::restart_to_update,
+            commands::app::install_update_from_file,
             commands::settings::open_settings_window,
+            handlers::rules::get_navigation_rules,
+            handlers::rules::set_navigation_rules,
+            handlers::rules::explain_navigation,
+            handlers::browsers::list_browsers,
+            handlers::browsers::get_browser_rules,
+            handlers::browsers::set_browser_rules,
+            popouts::pop_out,
+            popouts::list_popouts,
+            popouts::focus_popout,
+            popouts::close_popout,
+            mini::open_mini_window,
+            mini::close_mini_window,
+            mini::toggle_mini_window,
+            mini::get_mini_window_settings,
+            mini::set_mini_window_options,
             workspaces::create_workspace,
             workspaces::list_workspaces,
             workspaces::switch_workspace,
             workspaces::close_workspace,
+            workspaces::delete_workspace,
+            workspaces::update_workspace,
+            workspaces::reorder_workspaces,
             tabs::open_tab,
             tabs::close_tab,
             tabs::switch_tab,
@@ -133,6 +250,8 @@ pub fn run(start_minimized: bool) {
             bookmarks::list_bookmarks,
             bookmarks::search_bookmarks,
             bookmarks::get_bookmark,
+            bookmarks::list_browser_profiles,
+            bookmarks::import_browser_bookmarks,
             history::record_page_visit,
             history::get_recent_pages,
             history::clear_history,
@@ -141,6 +260,15 @@ pub fn run(start_minimized: bool) {
             commands::sync::import_settings,
             commands::sync::get_settings_json,
             commands::sync::restore_settings_json,
+            commands::sync::export_backup,
+            commands::sync::inspect_backup,
+            commands::sync::import_backup,
+            sync::get_sync_status,
+            sync::set_sync_folder,
+            sync::set_webdav_sync,
+            sync::set_sync_interval,
+            sync::sync_now,
+            sync::resolve_sync_conflict,
             search::global_search,
             search::get_search_history,
             search::clear_search_history,
@@ -215,9 +343,20 @@ pub fn run(start_minimized: bool) {
         .on_window_event(handlers::window::handle_window_event)
         // Navigation events
         .on_navigation(|window, url| {
-            handlers::handle_navigation(window.app_handle(), &url.to_string())
+            handlers::handle_navigation(window.app_handle(), window.label(), &url.to_string())
         })
         // Run
-        .run(tauri::generate_context!())
-        .expect("error while running notive");
+        .build(context)
+        .expect("error while building notive")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                instance::release();
+
+                // Quitting normally shows that a freshly installed update works
+                updater::confirm_startup();
+
+                // Install a downloaded update on quit or restart
+                updater::background::install_pending(app);
+            }
+        });
 }
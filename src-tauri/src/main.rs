@@ -11,12 +11,20 @@ fn print_version() {
 fn print_help() {
     println!("{} - A high-performance Notion desktop wrapper for Linux", APP_NAME);
     println!();
-    println!("Usage: notive [OPTIONS]");
+    println!("Usage: notive [OPTIONS] [URL]");
+    println!();
+    println!("Arguments:");
+    println!("  [URL]         Notion page to open (https:// or notion:// link)");
+    println!();
+    println!("If Notive is already running, the URL and options are passed to it.");
     println!();
     println!("Options:");
     println!("  --version     Show version information");
     println!("  --help        Show this help message");
     println!("  --minimized   Start minimized to tray");
+    println!("  --rollback    Restore the version replaced by the last update");
+    println!("  --install-update <path>");
+    println!("                Install a signed update bundle (signature read from <path>.sig)");
     println!();
     println!("Keyboard Shortcuts:");
     println!("  Ctrl+Shift+N  Toggle window visibility");
@@ -43,8 +51,27 @@ fn main() {
         return;
     }
     
-    // Check for --minimized flag
-    let start_minimized = args.iter().any(|arg| arg == "--minimized");
+    // Handle --rollback
+    if args.iter().any(|arg| arg == "--rollback") {
+        match notive_lib::rollback_update() {
+            Ok(message) => println!("{}", message),
+            Err(e) => {
+                eprintln!("Rollback failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    
+    // Parse --minimized, --install-update <path> and a page URL
+    let launch_args = match notive_lib::LaunchArgs::parse(args.get(1..).unwrap_or_default()) {
+        Ok(launch_args) => launch_args,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Run 'notive --help' for usage.");
+            std::process::exit(1);
+        }
+    };
     
-    notive_lib::run(start_minimized);
+    notive_lib::run(launch_args);
 }
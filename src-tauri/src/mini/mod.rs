@@ -0,0 +1,310 @@
+//! Always-on-top mini window.
+//!
+//! The mini window is a small window that stays above all others and shows a
+//! single page, such as a checklist or timer. Its page, opacity, zoom,
+//! border and click-through state are saved in their own store; where it sits
+//! on screen is kept by the window placement module like any other window.
+
+#[cfg(test)]
+mod tests;
+
+mod persistence;
+
+use crate::handlers::navigation::is_notion_url;
+use persistence::{load_mini_settings, save_mini_settings};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow, WindowBuilder, WindowUrl};
+
+/// Label of the mini window.
+pub const LABEL: &str = "mini";
+
+/// Lowest opacity, so the window never becomes invisible.
+pub const MIN_OPACITY: f64 = 0.2;
+/// Zoom range of the mini window.
+pub const MIN_ZOOM: f64 = 0.5;
+pub const MAX_ZOOM: f64 = 2.0;
+
+/// Saved state of the mini window.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MiniWindowSettings {
+    /// Page shown in the mini window.
+    pub url: Option<String>,
+    /// Whether the mini window was open when Notive last quit.
+    pub open: bool,
+    /// Hides the title bar and window border.
+    pub borderless: bool,
+    pub opacity: f64,
+    pub zoom: f64,
+    /// Lets clicks pass through to the windows below.
+    pub click_through: bool,
+}
+
+impl Default for MiniWindowSettings {
+    fn default() -> Self {
+        Self {
+            url: None,
+            open: false,
+            borderless: false,
+            opacity: 1.0,
+            zoom: 0.8,
+            click_through: false,
+        }
+    }
+}
+
+/// Changes to the mini window's appearance. Fields left out stay as they are.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MiniWindowOptions {
+    pub borderless: Option<bool>,
+    pub opacity: Option<f64>,
+    pub zoom: Option<f64>,
+    pub click_through: Option<bool>,
+}
+
+/// Clamps an opacity into the supported range.
+pub fn clamp_opacity(opacity: f64) -> f64 {
+    if opacity.is_nan() {
+        return 1.0;
+    }
+    opacity.clamp(MIN_OPACITY, 1.0)
+}
+
+/// Clamps a zoom level into the supported range.
+pub fn clamp_zoom(zoom: f64) -> f64 {
+    if zoom.is_nan() {
+        return 1.0;
+    }
+    zoom.clamp(MIN_ZOOM, MAX_ZOOM)
+}
+
+impl MiniWindowSettings {
+    /// Applies changed options, keeping opacity and zoom in range.
+    pub fn apply(&mut self, options: &MiniWindowOptions) {
+        if let Some(borderless) = options.borderless {
+            self.borderless = borderless;
+        }
+        if let Some(opacity) = options.opacity {
+            self.opacity = opacity;
+        }
+        if let Some(zoom) = options.zoom {
+            self.zoom = zoom;
+        }
+        if let Some(click_through) = options.click_through {
+            self.click_through = click_through;
+        }
+        self.opacity = clamp_opacity(self.opacity);
+        self.zoom = clamp_zoom(self.zoom);
+    }
+}
+
+fn load<R: Runtime>(app: &AppHandle<R>) -> MiniWindowSettings {
+    let mut settings = load_mini_settings(app).unwrap_or_else(|e| {
+        log::warn!("Failed to load mini window settings: {}", e);
+        MiniWindowSettings::default()
+    });
+    settings.apply(&MiniWindowOptions::default());
+    settings
+}
+
+fn save<R: Runtime>(app: &AppHandle<R>, settings: &MiniWindowSettings) -> Result<(), String> {
+    save_mini_settings(app, settings).map_err(|e| e.to_string())
+}
+
+/// Applies opacity, zoom, border and click-through to the open mini window.
+fn apply_to_window<R: Runtime>(window: &WebviewWindow<R>, settings: &MiniWindowSettings) {
+    let _ = window.set_decorations(!settings.borderless);
+    let _ = window.set_always_on_top(true);
+    if let Err(e) = window.set_ignore_cursor_events(settings.click_through) {
+        log::warn!("Failed to set mini window click-through: {}", e);
+    }
+
+    let zoom = settings.zoom;
+    let result = window.with_webview(move |webview| {
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::WebViewExt;
+            if let Some(webview) = webview.as_ref().and_then(|w| w.downcast_ref::<webkit2gtk::WebView>()) {
+                webview.set_zoom_level(zoom);
+            }
+        }
+    });
+    if let Err(e) = result {
+        log::warn!("Failed to set mini window zoom: {}", e);
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let target = window.clone();
+        let opacity = settings.opacity;
+        let _ = window.run_on_main_thread(move || {
+            use gtk::prelude::WidgetExt;
+            match target.gtk_window() {
+                Ok(gtk_window) => gtk_window.set_opacity(opacity),
+                Err(e) => log::warn!("Failed to set mini window opacity: {}", e),
+            }
+        });
+    }
+}
+
+/// Creates the mini window for a page.
+fn open_window<R: Runtime>(
+    app: &AppHandle<R>,
+    page_url: &str,
+    settings: &MiniWindowSettings,
+    visible: bool,
+) -> Result<WebviewWindow<R>, String> {
+    let url = page_url
+        .parse::<url::Url>()
+        .map_err(|e| format!("Invalid URL: {}", e))?;
+
+    let window = WindowBuilder::new(app, LABEL, WindowUrl::External(url))
+        .title(&format!("Notive Mini - {}", crate::handlers::title::title_for(page_url)))
+        .inner_size(360.0, 480.0)
+        .min_inner_size(200.0, 150.0)
+        .resizable(true)
+        .always_on_top(true)
+        .decorations(!settings.borderless)
+        .skip_taskbar(true)
+        .visible(visible)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    crate::handlers::title::watch_titles(&window);
+    crate::handlers::downloads::watch_downloads(&window);
+    crate::monitor::restore_window(&window);
+    apply_to_window(&window, settings);
+    Ok(window)
+}
+
+/// Reopens the mini window if it was open when Notive last quit.
+pub fn restore<R: Runtime>(app: &AppHandle<R>, visible: bool) {
+    let settings = load(app);
+    let Some(url) = settings.url.as_deref().filter(|_| settings.open) else {
+        return;
+    };
+    if let Err(e) = open_window(app, url, &settings, visible) {
+        log::warn!("Failed to restore mini window: {}", e);
+    }
+}
+
+/// Keeps the mini window's page in step with where it navigated.
+pub fn on_page_changed<R: Runtime>(app: &AppHandle<R>, label: &str, url: &str, title: &str) {
+    if label != LABEL {
+        return;
+    }
+
+    let mut settings = load(app);
+    if settings.url.as_deref() != Some(url) {
+        settings.url = Some(url.to_string());
+        if let Err(e) = save(app, &settings) {
+            log::warn!("Failed to save mini window settings: {}", e);
+        }
+    }
+    if let Some(window) = app.get_webview_window(LABEL) {
+        let _ = window.set_title(&format!("Notive Mini - {}", title));
+    }
+}
+
+/// Records that the user closed the mini window, keeping its page and placement.
+pub fn on_closed<R: Runtime>(app: &AppHandle<R>) {
+    let mut settings = load(app);
+    if settings.open {
+        settings.open = false;
+        if let Err(e) = save(app, &settings) {
+            log::warn!("Failed to save mini window settings: {}", e);
+        }
+    }
+    crate::tray::refresh_menu(app);
+    log::debug!("Mini window closed");
+}
+
+/// Opens the mini window, or brings it to the front.
+///
+/// Without a URL, the mini window shows the page it showed last, or else the
+/// page shown in the main window.
+#[tauri::command]
+pub async fn open_mini_window<R: Runtime>(app: AppHandle<R>, url: Option<String>) -> Result<(), String> {
+    let mut settings = load(&app);
+    let url = match url.or_else(|| settings.url.clone()) {
+        Some(url) => url,
+        None => app
+            .get_webview_window("main")
+            .ok_or("Main window not found")?
+            .url()
+            .map_err(|e| e.to_string())?
+            .to_string(),
+    };
+    if !is_notion_url(&url) {
+        return Err(format!("Only Notion pages can be shown in the mini window: {}", url));
+    }
+
+    let window = match app.get_webview_window(LABEL) {
+        Some(window) => {
+            if settings.url.as_deref() != Some(url.as_str()) {
+                let parsed = url.parse::<url::Url>().map_err(|e| format!("Invalid URL: {}", e))?;
+                window.navigate(parsed).map_err(|e| e.to_string())?;
+            }
+            window
+        }
+        None => open_window(&app, &url, &settings, true)?,
+    };
+    let _ = window.show();
+    let _ = window.unminimize();
+    let _ = window.set_focus();
+
+    settings.url = Some(url);
+    settings.open = true;
+    save(&app, &settings)?;
+    crate::tray::refresh_menu(&app);
+    Ok(())
+}
+
+/// Closes the mini window.
+#[tauri::command]
+pub fn close_mini_window<R: Runtime>(app: AppHandle<R>) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        crate::monitor::remember_window(window.as_ref().window(), true);
+        window.destroy().map_err(|e| e.to_string())?;
+    }
+    on_closed(&app);
+    Ok(())
+}
+
+/// Opens the mini window if it is closed, and closes it otherwise.
+///
+/// Returns whether the mini window is open afterwards.
+#[tauri::command]
+pub async fn toggle_mini_window<R: Runtime>(app: AppHandle<R>) -> Result<bool, String> {
+    if app.get_webview_window(LABEL).is_some() {
+        close_mini_window(app)?;
+        Ok(false)
+    } else {
+        open_mini_window(app, None).await?;
+        Ok(true)
+    }
+}
+
+/// Gets the mini window settings.
+#[tauri::command]
+pub fn get_mini_window_settings<R: Runtime>(app: AppHandle<R>) -> Result<MiniWindowSettings, String> {
+    Ok(load(&app))
+}
+
+/// Changes the mini window's opacity, zoom, border or click-through.
+#[tauri::command]
+pub fn set_mini_window_options<R: Runtime>(
+    app: AppHandle<R>,
+    options: MiniWindowOptions,
+) -> Result<MiniWindowSettings, String> {
+    let mut settings = load(&app);
+    settings.apply(&options);
+    save(&app, &settings)?;
+
+    if let Some(window) = app.get_webview_window(LABEL) {
+        apply_to_window(&window, &settings);
+    }
+    crate::tray::refresh_menu(&app);
+    Ok(settings)
+}
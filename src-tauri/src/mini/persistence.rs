@@ -0,0 +1,43 @@
+//! Mini window persistence.
+
+use super::MiniWindowSettings;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_store::{with_store, StoreCollection};
+
+const MINI_STORE_PATH: &str = "mini.json";
+
+/// Loads the mini window settings from persistent storage.
+pub fn load_mini_settings<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<MiniWindowSettings, Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+    
+    with_store(app, stores, MINI_STORE_PATH, |store| {
+        if let Some(value) = store.get("mini") {
+            let settings: MiniWindowSettings = serde_json::from_value(value.clone())
+                .map_err(|e| tauri_plugin_store::Error::Deserialize(e.to_string()))?;
+            Ok(settings)
+        } else {
+            Ok(MiniWindowSettings::default())
+        }
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
+
+/// Saves the mini window settings to persistent storage.
+pub fn save_mini_settings<R: Runtime>(
+    app: &AppHandle<R>,
+    settings: &MiniWindowSettings,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+    
+    with_store(app, stores, MINI_STORE_PATH, |store| {
+        let data = serde_json::to_value(settings)
+            .map_err(|e| tauri_plugin_store::Error::Serialize(e.to_string()))?;
+        store.insert("mini".to_string(), data)?;
+        store.save()?;
+        log::debug!("Mini window settings saved to store");
+        Ok(())
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
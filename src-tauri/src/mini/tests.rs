@@ -0,0 +1,83 @@
+//! Tests for the mini window.
+
+use super::*;
+
+#[test]
+fn test_default_settings() {
+    let settings = MiniWindowSettings::default();
+    assert_eq!(settings.url, None);
+    assert!(!settings.open);
+    assert!(!settings.borderless);
+    assert_eq!(settings.opacity, 1.0);
+    assert!(!settings.click_through);
+}
+
+#[test]
+fn test_clamp_opacity() {
+    assert_eq!(clamp_opacity(0.5), 0.5);
+    assert_eq!(clamp_opacity(0.0), MIN_OPACITY);
+    assert_eq!(clamp_opacity(-1.0), MIN_OPACITY);
+    assert_eq!(clamp_opacity(1.5), 1.0);
+    assert_eq!(clamp_opacity(f64::NAN), 1.0);
+}
+
+#[test]
+fn test_clamp_zoom() {
+    assert_eq!(clamp_zoom(1.25), 1.25);
+    assert_eq!(clamp_zoom(0.1), MIN_ZOOM);
+    assert_eq!(clamp_zoom(10.0), MAX_ZOOM);
+    assert_eq!(clamp_zoom(f64::NAN), 1.0);
+}
+
+#[test]
+fn test_apply_changes_only_given_options() {
+    let mut settings = MiniWindowSettings {
+        url: Some("https://www.notion.so/Checklist".to_string()),
+        open: true,
+        ..Default::default()
+    };
+
+    settings.apply(&MiniWindowOptions {
+        opacity: Some(0.6),
+        click_through: Some(true),
+        ..Default::default()
+    });
+
+    assert_eq!(settings.opacity, 0.6);
+    assert!(settings.click_through);
+    assert!(!settings.borderless);
+    assert_eq!(settings.zoom, MiniWindowSettings::default().zoom);
+    assert_eq!(settings.url.as_deref(), Some("https://www.notion.so/Checklist"));
+    assert!(settings.open);
+}
+
+#[test]
+fn test_apply_clamps_values() {
+    let mut settings = MiniWindowSettings::default();
+    settings.apply(&MiniWindowOptions {
+        opacity: Some(0.0),
+        zoom: Some(5.0),
+        borderless: Some(true),
+        ..Default::default()
+    });
+
+    assert_eq!(settings.opacity, MIN_OPACITY);
+    assert_eq!(settings.zoom, MAX_ZOOM);
+    assert!(settings.borderless);
+}
+
+#[test]
+fn test_settings_deserialize_with_missing_fields() {
+    let settings: MiniWindowSettings =
+        serde_json::from_str(r#"{"url": "https://www.notion.so/Timer", "opacity": 0.8}"#).unwrap();
+
+    assert_eq!(settings.url.as_deref(), Some("https://www.notion.so/Timer"));
+    assert_eq!(settings.opacity, 0.8);
+    assert_eq!(settings.zoom, MiniWindowSettings::default().zoom);
+    assert!(!settings.open);
+}
+
+#[test]
+fn test_mini_window_placement_is_persistent() {
+    assert!(crate::monitor::is_persistent(LABEL));
+}
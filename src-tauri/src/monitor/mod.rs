@@ -1,10 +1,34 @@
 //! Multi-monitor support and window placement.
+//!
+//! The placement of every window (position, size, maximized and fullscreen
+//! state, and monitor) is remembered as it moves and resizes, saved shortly
+//! after, and restored when the window is created again. Positions and sizes
+//! are in physical pixels.
 
 #[cfg(test)]
 mod tests;
 
+mod persistence;
+
+use persistence::{load_placements, save_placements};
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Manager, Runtime, Window};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, Runtime, WebviewWindow, Window};
+
+/// How long to wait after the last move or resize before saving.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// Label prefix of one-off popup windows, whose placement is not kept.
+const POPUP_LABEL_PREFIX: &str = "popup-";
+
+// Placements of all windows, loaded from the store on first use
+static PLACEMENTS: Mutex<Option<HashMap<String, WindowPlacement>>> = Mutex::new(None);
+
+// Bumped on every change, so only the last scheduled save writes
+static SAVE_GENERATION: AtomicU64 = AtomicU64::new(0);
 
 /// Monitor information.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,28 +42,246 @@ pub struct MonitorInfo {
 }
 
 /// Window placement preferences.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct WindowPlacement {
     pub window_label: String,
     pub monitor_id: Option<String>,
     pub position: Option<(i32, i32)>,
     pub size: Option<(u32, u32)>,
     pub maximized: bool,
+    #[serde(default)]
+    pub fullscreen: bool,
+}
+
+/// Gets a stable identifier for a monitor.
+fn monitor_id(monitor: &Monitor) -> String {
+    monitor.name().cloned().unwrap_or_else(|| {
+        let position = monitor.position();
+        let size = monitor.size();
+        format!("{}x{}+{}+{}", size.width, size.height, position.x, position.y)
+    })
+}
+
+fn monitor_info(monitor: &Monitor, primary: Option<&str>) -> MonitorInfo {
+    let id = monitor_id(monitor);
+    MonitorInfo {
+        name: monitor.name().cloned().unwrap_or_else(|| id.clone()),
+        is_primary: primary == Some(id.as_str()),
+        position: (monitor.position().x, monitor.position().y),
+        size: (monitor.size().width, monitor.size().height),
+        scale_factor: monitor.scale_factor(),
+        id,
+    }
+}
+
+/// Lists the connected monitors.
+fn list_monitors<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<MonitorInfo>, String> {
+    let primary = app
+        .primary_monitor()
+        .map_err(|e| e.to_string())?
+        .map(|monitor| monitor_id(&monitor));
+    let monitors = app.available_monitors().map_err(|e| e.to_string())?;
+
+    Ok(monitors
+        .iter()
+        .map(|monitor| monitor_info(monitor, primary.as_deref()))
+        .collect())
+}
+
+fn overlap(monitor: &MonitorInfo, position: (i32, i32), size: (u32, u32)) -> i64 {
+    let (mx, my) = (monitor.position.0 as i64, monitor.position.1 as i64);
+    let (mw, mh) = (monitor.size.0 as i64, monitor.size.1 as i64);
+    let (x, y) = (position.0 as i64, position.1 as i64);
+    let (w, h) = (size.0 as i64, size.1 as i64);
+
+    let width = (x + w).min(mx + mw) - x.max(mx);
+    let height = (y + h).min(my + mh) - y.max(my);
+    if width > 0 && height > 0 {
+        width * height
+    } else {
+        0
+    }
+}
+
+/// Fits a saved placement onto the connected monitors.
+///
+/// A placement on a monitor that is still connected, and still overlaps it,
+/// is kept as is. Otherwise it moves to its saved monitor, the monitor it
+/// overlaps most, or the primary monitor, and is shrunk and moved to fit.
+pub fn clamp_placement(placement: &WindowPlacement, monitors: &[MonitorInfo]) -> WindowPlacement {
+    let size = placement.size.unwrap_or((0, 0));
+    let saved = placement
+        .monitor_id
+        .as_ref()
+        .and_then(|id| monitors.iter().find(|m| &m.id == id));
+
+    if let (Some(monitor), Some(position)) = (saved, placement.position) {
+        if overlap(monitor, position, size) > 0 {
+            return placement.clone();
+        }
+    }
+
+    let target = saved
+        .or_else(|| {
+            let position = placement.position?;
+            monitors
+                .iter()
+                .map(|m| (m, overlap(m, position, size)))
+                .filter(|(_, area)| *area > 0)
+                .max_by_key(|(_, area)| *area)
+                .map(|(m, _)| m)
+        })
+        .or_else(|| monitors.iter().find(|m| m.is_primary))
+        .or_else(|| monitors.first());
+    let Some(target) = target else {
+        return placement.clone();
+    };
+
+    let mut clamped = placement.clone();
+    clamped.monitor_id = Some(target.id.clone());
+
+    let (mw, mh) = target.size;
+    let size = placement.size.map(|(w, h)| (w.min(mw), h.min(mh)));
+    clamped.size = size;
+
+    if let Some((x, y)) = placement.position {
+        let (w, h) = size.unwrap_or((0, 0));
+        let (mx, my) = target.position;
+        let max_x = mx + (mw - w) as i32;
+        let max_y = my + (mh - h) as i32;
+        clamped.position = Some((x.clamp(mx, max_x), y.clamp(my, max_y)));
+    }
+
+    clamped
+}
+
+/// Combines a newly captured placement with the previous one.
+///
+/// While maximized or fullscreen, the previous position and size are kept so
+/// the window returns to them when it is restored.
+pub fn merge_placement(previous: Option<&WindowPlacement>, mut current: WindowPlacement) -> WindowPlacement {
+    if current.maximized || current.fullscreen {
+        if let Some(previous) = previous {
+            current.position = previous.position.or(current.position);
+            current.size = previous.size.or(current.size);
+        }
+    }
+    current
+}
+
+/// Checks whether a window's placement should be remembered.
+pub fn is_persistent(label: &str) -> bool {
+    !label.starts_with(POPUP_LABEL_PREFIX)
+}
+
+fn capture<R: Runtime>(window: &Window<R>) -> WindowPlacement {
+    WindowPlacement {
+        window_label: window.label().to_string(),
+        monitor_id: window.current_monitor().ok().flatten().map(|m| monitor_id(&m)),
+        position: window.outer_position().ok().map(|p| (p.x, p.y)),
+        size: window.inner_size().ok().map(|s| (s.width, s.height)),
+        maximized: window.is_maximized().unwrap_or(false),
+        fullscreen: window.is_fullscreen().unwrap_or(false),
+    }
+}
+
+fn with_placements<R: Runtime, T>(
+    app: &AppHandle<R>,
+    f: impl FnOnce(&mut HashMap<String, WindowPlacement>) -> T,
+) -> Option<T> {
+    let mut placements = PLACEMENTS.lock().ok()?;
+    let placements = placements.get_or_insert_with(|| {
+        load_placements(app).unwrap_or_else(|e| {
+            log::warn!("Failed to load window placements: {}", e);
+            HashMap::new()
+        })
+    });
+    Some(f(placements))
+}
+
+fn save_now<R: Runtime>(app: &AppHandle<R>) {
+    let Some(placements) = with_placements(app, |placements| placements.clone()) else {
+        return;
+    };
+    if let Err(e) = save_placements(app, &placements) {
+        log::warn!("Failed to save window placements: {}", e);
+    }
+}
+
+/// Records the current placement of a window.
+///
+/// Moves and resizes arrive in bursts, so the save waits until they stop;
+/// `immediately` saves right away, e.g. when the window closes.
+pub fn remember_window<R: Runtime>(window: &Window<R>, immediately: bool) {
+    if !is_persistent(window.label()) || window.is_minimized().unwrap_or(false) {
+        return;
+    }
+
+    let app = window.app_handle().clone();
+    let current = capture(window);
+    with_placements(&app, |placements| {
+        let merged = merge_placement(placements.get(&current.window_label), current);
+        placements.insert(merged.window_label.clone(), merged);
+    });
+
+    let generation = SAVE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    if immediately {
+        save_now(&app);
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DELAY).await;
+        if SAVE_GENERATION.load(Ordering::SeqCst) == generation {
+            save_now(&app);
+        }
+    });
+}
+
+/// Forgets the placement of a window that will not be created again.
+pub fn forget_window<R: Runtime>(app: &AppHandle<R>, label: &str) {
+    if with_placements(app, |placements| placements.remove(label)).flatten().is_some() {
+        save_now(app);
+    }
+}
+
+/// Gets the saved placement of a window.
+pub fn saved_placement<R: Runtime>(app: &AppHandle<R>, label: &str) -> Option<WindowPlacement> {
+    with_placements(app, |placements| placements.get(label).cloned()).flatten()
+}
+
+/// Applies a window's saved placement, fitted onto the connected monitors.
+pub fn restore_window<R: Runtime>(window: &WebviewWindow<R>) {
+    let app = window.app_handle();
+    let Some(placement) = saved_placement(app, window.label()) else {
+        return;
+    };
+
+    let monitors = list_monitors(app).unwrap_or_else(|e| {
+        log::warn!("Failed to list monitors: {}", e);
+        Vec::new()
+    });
+    let placement = clamp_placement(&placement, &monitors);
+
+    if let Some((width, height)) = placement.size {
+        let _ = window.set_size(PhysicalSize::new(width, height));
+    }
+    if let Some((x, y)) = placement.position {
+        let _ = window.set_position(PhysicalPosition::new(x, y));
+    }
+    if placement.maximized {
+        let _ = window.maximize();
+    }
+    if placement.fullscreen {
+        let _ = window.set_fullscreen(true);
+    }
+
+    log::debug!("Window placement restored for: {}", placement.window_label);
 }
 
 /// Gets all available monitors.
 #[tauri::command]
-pub async fn get_monitors<R: Runtime>(_app: AppHandle<R>) -> Result<Vec<MonitorInfo>, String> {
-    // In a full implementation, we'd query system for monitors
-    // For now, return a placeholder
-    Ok(vec![MonitorInfo {
-        id: "primary".to_string(),
-        name: "Primary Monitor".to_string(),
-        position: (0, 0),
-        size: (1920, 1080),
-        scale_factor: 1.0,
-        is_primary: true,
-    }])
+pub async fn get_monitors<R: Runtime>(app: AppHandle<R>) -> Result<Vec<MonitorInfo>, String> {
+    list_monitors(&app)
 }
 
 /// Gets the primary monitor.
@@ -52,7 +294,7 @@ pub async fn get_primary_monitor<R: Runtime>(app: AppHandle<R>) -> Result<Monito
         .ok_or_else(|| "No primary monitor found".to_string())
 }
 
-/// Saves window placement.
+/// Moves and resizes a window, then saves its placement.
 #[tauri::command]
 pub async fn save_window_placement<R: Runtime>(
     app: AppHandle<R>,
@@ -61,17 +303,23 @@ pub async fn save_window_placement<R: Runtime>(
     position: Option<(i32, i32)>,
     size: Option<(u32, u32)>,
 ) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window(&window_label) {
-        if let Some(pos) = position {
-            window.set_position(tauri::LogicalPosition::new(pos.0 as f64, pos.1 as f64))
-                .map_err(|e| e.to_string())?;
-        }
-        if let Some(sz) = size {
-            window.set_size(tauri::LogicalSize::new(sz.0 as f64, sz.1 as f64))
-                .map_err(|e| e.to_string())?;
-        }
+    let window = app
+        .get_webview_window(&window_label)
+        .ok_or_else(|| format!("Window {} not found", window_label))?;
+
+    if let Some(monitor_id) = monitor_id {
+        move_window_to_monitor(app.clone(), window_label.clone(), monitor_id).await?;
+    }
+    if let Some(pos) = position {
+        window.set_position(PhysicalPosition::new(pos.0, pos.1))
+            .map_err(|e| e.to_string())?;
+    }
+    if let Some(sz) = size {
+        window.set_size(PhysicalSize::new(sz.0, sz.1))
+            .map_err(|e| e.to_string())?;
     }
-    
+
+    remember_window(&window.as_ref().window(), true);
     log::debug!("Window placement saved for: {}", window_label);
     Ok(())
 }
@@ -79,12 +327,13 @@ pub async fn save_window_placement<R: Runtime>(
 /// Restores window placement.
 #[tauri::command]
 pub async fn restore_window_placement<R: Runtime>(
-    _app: AppHandle<R>,
+    app: AppHandle<R>,
     window_label: String,
 ) -> Result<(), String> {
-    let _ = window_label;
-    // In a full implementation, we'd load saved placement
-    log::debug!("Window placement restored for: {}", window_label);
+    let window = app
+        .get_webview_window(&window_label)
+        .ok_or_else(|| format!("Window {} not found", window_label))?;
+    restore_window(&window);
     Ok(())
 }
 
@@ -100,14 +349,14 @@ pub async fn move_window_to_monitor<R: Runtime>(
         .into_iter()
         .find(|m| m.id == monitor_id)
         .ok_or_else(|| format!("Monitor {} not found", monitor_id))?;
-    
+
     if let Some(window) = app.get_webview_window(&window_label) {
-        window.set_position(tauri::LogicalPosition::new(
-            monitor.position.0 as f64,
-            monitor.position.1 as f64,
+        window.set_position(PhysicalPosition::new(
+            monitor.position.0,
+            monitor.position.1,
         ))
         .map_err(|e| e.to_string())?;
     }
-    
+
     Ok(())
 }
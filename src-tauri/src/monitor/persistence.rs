@@ -0,0 +1,44 @@
+//! Window placement persistence to the store.
+
+use crate::monitor::WindowPlacement;
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_store::{with_store, StoreCollection};
+
+const WINDOWS_STORE_PATH: &str = "windows.json";
+
+/// Saves window placements, keyed by window label.
+pub fn save_placements<R: Runtime>(
+    app: &AppHandle<R>,
+    placements: &HashMap<String, WindowPlacement>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+    
+    with_store(app, stores, WINDOWS_STORE_PATH, |store| {
+        let data = serde_json::to_value(placements)
+            .map_err(|e| tauri_plugin_store::Error::Serialize(e.to_string()))?;
+        store.insert("placements".to_string(), data)?;
+        store.save()?;
+        log::debug!("Window placements saved to store: {} windows", placements.len());
+        Ok(())
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
+
+/// Loads window placements, keyed by window label.
+pub fn load_placements<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<HashMap<String, WindowPlacement>, Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+    
+    with_store(app, stores, WINDOWS_STORE_PATH, |store| {
+        if let Some(data) = store.get("placements") {
+            match serde_json::from_value::<HashMap<String, WindowPlacement>>(data.clone()) {
+                Ok(placements) => return Ok(placements),
+                Err(e) => log::warn!("Failed to deserialize window placements: {}", e),
+            }
+        }
+        Ok(HashMap::new())
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
@@ -1,6 +1,6 @@
 //! Tests for multi-monitor support.
 
-use super::{MonitorInfo, WindowPlacement};
+use super::{clamp_placement, is_persistent, merge_placement, MonitorInfo, WindowPlacement};
 
 #[test]
 fn test_monitor_info_structure() {
@@ -26,8 +26,127 @@ fn test_window_placement_structure() {
         position: Some((100, 100)),
         size: Some((800, 600)),
         maximized: false,
+        fullscreen: false,
     };
     
     assert_eq!(placement.window_label, "main");
     assert_eq!(placement.position, Some((100, 100)));
 }
+
+fn monitor(id: &str, position: (i32, i32), size: (u32, u32), is_primary: bool) -> MonitorInfo {
+    MonitorInfo {
+        id: id.to_string(),
+        name: id.to_string(),
+        position,
+        size,
+        scale_factor: 1.0,
+        is_primary,
+    }
+}
+
+fn placement(monitor_id: &str, position: (i32, i32), size: (u32, u32)) -> WindowPlacement {
+    WindowPlacement {
+        window_label: "main".to_string(),
+        monitor_id: Some(monitor_id.to_string()),
+        position: Some(position),
+        size: Some(size),
+        maximized: false,
+        fullscreen: false,
+    }
+}
+
+#[test]
+fn test_clamp_keeps_placement_on_connected_monitor() {
+    let monitors = vec![
+        monitor("eDP-1", (0, 0), (1920, 1080), true),
+        monitor("HDMI-1", (1920, 0), (2560, 1440), false),
+    ];
+
+    let saved = placement("HDMI-1", (2000, 100), (1200, 800));
+    assert_eq!(clamp_placement(&saved, &monitors), saved);
+
+    // Partly off-screen is fine as long as it is still on its monitor
+    let saved = placement("HDMI-1", (4000, 100), (1200, 800));
+    assert_eq!(clamp_placement(&saved, &monitors), saved);
+}
+
+#[test]
+fn test_clamp_moves_window_off_disconnected_monitor() {
+    let monitors = vec![monitor("eDP-1", (0, 0), (1920, 1080), true)];
+
+    let clamped = clamp_placement(&placement("HDMI-1", (2000, 100), (1200, 800)), &monitors);
+    assert_eq!(clamped.monitor_id.as_deref(), Some("eDP-1"));
+    assert_eq!(clamped.position, Some((720, 100)));
+    assert_eq!(clamped.size, Some((1200, 800)));
+}
+
+#[test]
+fn test_clamp_shrinks_window_larger_than_monitor() {
+    let monitors = vec![monitor("eDP-1", (0, 0), (1366, 768), true)];
+
+    let clamped = clamp_placement(&placement("HDMI-1", (-500, -50), (2560, 1440)), &monitors);
+    assert_eq!(clamped.size, Some((1366, 768)));
+    assert_eq!(clamped.position, Some((0, 0)));
+}
+
+#[test]
+fn test_clamp_prefers_monitor_with_most_overlap() {
+    let monitors = vec![
+        monitor("left", (0, 0), (1920, 1080), true),
+        monitor("right", (1920, 0), (1920, 1080), false),
+    ];
+
+    // Saved on a monitor that is gone, straddling the two that remain
+    let clamped = clamp_placement(&placement("gone", (1800, 100), (800, 600)), &monitors);
+    assert_eq!(clamped.monitor_id.as_deref(), Some("right"));
+    assert_eq!(clamped.position, Some((1920, 100)));
+}
+
+#[test]
+fn test_clamp_moves_window_back_onto_rearranged_monitor() {
+    // The monitor still exists but now sits left of the primary one
+    let monitors = vec![
+        monitor("eDP-1", (0, 0), (1920, 1080), true),
+        monitor("HDMI-1", (-2560, 0), (2560, 1440), false),
+    ];
+
+    let clamped = clamp_placement(&placement("HDMI-1", (2000, 100), (1200, 800)), &monitors);
+    assert_eq!(clamped.monitor_id.as_deref(), Some("HDMI-1"));
+    assert_eq!(clamped.position, Some((-1200, 100)));
+}
+
+#[test]
+fn test_clamp_without_monitors_keeps_placement() {
+    let saved = placement("HDMI-1", (2000, 100), (1200, 800));
+    assert_eq!(clamp_placement(&saved, &[]), saved);
+}
+
+#[test]
+fn test_merge_keeps_normal_geometry_while_maximized() {
+    let previous = placement("eDP-1", (100, 100), (1200, 800));
+    let mut maximized = placement("eDP-1", (0, 0), (1920, 1080));
+    maximized.maximized = true;
+
+    let merged = merge_placement(Some(&previous), maximized);
+    assert!(merged.maximized);
+    assert_eq!(merged.position, Some((100, 100)));
+    assert_eq!(merged.size, Some((1200, 800)));
+
+    let moved = placement("eDP-1", (300, 200), (1000, 700));
+    assert_eq!(merge_placement(Some(&previous), moved.clone()), moved);
+}
+
+#[test]
+fn test_placement_deserializes_without_fullscreen() {
+    let json = r#"{"window_label":"main","monitor_id":null,"position":[10,20],"size":[800,600],"maximized":true}"#;
+    let parsed: WindowPlacement = serde_json::from_str(json).unwrap();
+    assert!(parsed.maximized);
+    assert!(!parsed.fullscreen);
+}
+
+#[test]
+fn test_popups_are_not_persistent() {
+    assert!(is_persistent("main"));
+    assert!(is_persistent("workspace-1234"));
+    assert!(!is_persistent("popup-1234"));
+}
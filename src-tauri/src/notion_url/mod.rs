@@ -0,0 +1,171 @@
+//! Parsing of Notion page URLs.
+//!
+//! The same page can be reached through many URLs:
+//!
+//! - `https://www.notion.so/acme/Team-Wiki-8f3a...` (workspace and title slug)
+//! - `https://notion.so/8f3a...?pvs=4` (bare page ID with a tracking flag)
+//! - `https://www.notion.so/Team-Wiki-8f3a...#1b2c...` (anchor to a block)
+//! - `https://acme.notion.site/Team-Wiki-8f3a...` (published page)
+//!
+//! [`NotionUrl::parse`] extracts the parts of such URLs, and [`canonical_key`]
+//! reduces them to a key that is equal for all URLs of one page.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::BTreeMap;
+use url::Url;
+
+/// Domain of the Notion app.
+const APP_DOMAIN: &str = "notion.so";
+
+/// Domain of published Notion sites (`<workspace>.notion.site`).
+const SITE_DOMAIN: &str = "notion.site";
+
+/// Query parameter holding a database view ID.
+const VIEW_PARAM: &str = "v";
+
+/// Query parameter holding the ID of a page opened in peek mode.
+const PEEK_PARAM: &str = "p";
+
+/// Prefix of canonical keys for URLs with a page ID.
+const PAGE_KEY_PREFIX: &str = "notion:";
+
+/// The parts of a Notion page URL.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NotionUrl {
+    /// Workspace slug, from the first path segment or a `notion.site` subdomain.
+    pub workspace: Option<String>,
+    /// Page ID as a lowercase hyphenated UUID.
+    pub page_id: Option<String>,
+    /// Block ID from the URL fragment, as a lowercase hyphenated UUID.
+    pub block_id: Option<String>,
+    /// Database view ID from the `v` parameter.
+    pub view_id: Option<String>,
+    /// Page opened in peek mode over a database, from the `p` parameter.
+    pub peek_id: Option<String>,
+    /// Remaining query parameters, such as `pvs` or `pm`.
+    pub flags: BTreeMap<String, String>,
+}
+
+impl NotionUrl {
+    /// Parses a Notion URL, returning `None` for URLs on other hosts.
+    pub fn parse(url: &str) -> Option<Self> {
+        let parsed = Url::parse(url.trim()).ok()?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return None;
+        }
+        let host = parsed.host_str()?.to_ascii_lowercase();
+
+        let site_workspace = if host == APP_DOMAIN || host == format!("www.{}", APP_DOMAIN) {
+            None
+        } else {
+            Some(host.strip_suffix(&format!(".{}", SITE_DOMAIN))?.to_string())
+        };
+
+        let segments: Vec<&str> = parsed
+            .path_segments()
+            .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+
+        let page_id = segments.last().and_then(|segment| page_id_from_slug(segment));
+        let workspace = site_workspace.or_else(|| match (segments.as_slice(), &page_id) {
+            // acme/Team-Wiki-<id>
+            ([workspace, _, ..], Some(_)) => Some(workspace.to_ascii_lowercase()),
+            _ => None,
+        });
+
+        let mut notion_url = Self {
+            workspace,
+            page_id,
+            block_id: parsed.fragment().and_then(normalize_id),
+            ..Self::default()
+        };
+
+        for (key, value) in parsed.query_pairs() {
+            match key.as_ref() {
+                VIEW_PARAM => notion_url.view_id = normalize_id(&value),
+                PEEK_PARAM => notion_url.peek_id = normalize_id(&value),
+                _ => {
+                    notion_url.flags.insert(key.into_owned(), value.into_owned());
+                }
+            }
+        }
+
+        Some(notion_url)
+    }
+
+    /// Gets the key identifying the page, if the URL names one.
+    ///
+    /// Workspace, title slug, block anchor, view and flags are not part of
+    /// the key: they change how a page is shown, not which page it is.
+    pub fn page_key(&self) -> Option<String> {
+        self.page_id
+            .as_ref()
+            .map(|id| format!("{}{}", PAGE_KEY_PREFIX, id))
+    }
+}
+
+/// Normalizes a 32-digit page, block or view ID to a hyphenated UUID.
+///
+/// Accepts the compact form used in URLs and the hyphenated form, in any case.
+pub fn normalize_id(id: &str) -> Option<String> {
+    let id = id.trim();
+    let compact = match id.len() {
+        32 => id.to_string(),
+        // Hyphens must be where a UUID has them
+        36 if [8, 13, 18, 23].iter().all(|&i| id.as_bytes()[i] == b'-') => id.replace('-', ""),
+        _ => return None,
+    };
+    if compact.len() != 32 || !compact.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let compact = compact.to_ascii_lowercase();
+    Some(format!(
+        "{}-{}-{}-{}-{}",
+        &compact[0..8],
+        &compact[8..12],
+        &compact[12..16],
+        &compact[16..20],
+        &compact[20..32]
+    ))
+}
+
+/// Extracts the page ID from a path segment such as `Team-Wiki-<id>`.
+fn page_id_from_slug(segment: &str) -> Option<String> {
+    if let Some(id) = normalize_id(segment) {
+        return Some(id);
+    }
+    let (_, id) = segment.rsplit_once('-')?;
+    if id.len() == 32 {
+        normalize_id(id)
+    } else {
+        None
+    }
+}
+
+/// Gets the key used to deduplicate pages across bookmarks, history,
+/// analytics and templates.
+///
+/// Notion URLs with a page ID map to `notion:<uuid>`. Other URLs fall back to
+/// their lowercase host and path, without query, fragment or trailing slash.
+pub fn canonical_key(url: &str) -> String {
+    if let Some(key) = NotionUrl::parse(url).and_then(|notion_url| notion_url.page_key()) {
+        return key;
+    }
+
+    match Url::parse(url.trim()) {
+        Ok(parsed) => format!(
+            "{}{}",
+            parsed.host_str().unwrap_or_default().to_ascii_lowercase(),
+            parsed.path().trim_end_matches('/')
+        ),
+        Err(_) => url.trim().to_string(),
+    }
+}
+
+/// Checks whether two URLs point at the same page.
+pub fn same_page(a: &str, b: &str) -> bool {
+    canonical_key(a) == canonical_key(b)
+}
@@ -0,0 +1,158 @@
+//! Tests for Notion URL parsing.
+
+use super::*;
+
+const PAGE: &str = "8f3a1b2c4d5e6f708192a3b4c5d6e7f8";
+const PAGE_UUID: &str = "8f3a1b2c-4d5e-6f70-8192-a3b4c5d6e7f8";
+const BLOCK: &str = "0123456789abcdef0123456789abcdef";
+const BLOCK_UUID: &str = "01234567-89ab-cdef-0123-456789abcdef";
+const VIEW: &str = "fedcba9876543210fedcba9876543210";
+const VIEW_UUID: &str = "fedcba98-7654-3210-fedc-ba9876543210";
+
+fn parse(url: &str) -> NotionUrl {
+    NotionUrl::parse(url).unwrap_or_else(|| panic!("not a Notion URL: {}", url))
+}
+
+#[test]
+fn test_normalize_id() {
+    assert_eq!(normalize_id(PAGE).as_deref(), Some(PAGE_UUID));
+    assert_eq!(normalize_id(PAGE_UUID).as_deref(), Some(PAGE_UUID));
+    assert_eq!(normalize_id(&PAGE.to_uppercase()).as_deref(), Some(PAGE_UUID));
+    assert_eq!(normalize_id(&format!(" {} ", PAGE)).as_deref(), Some(PAGE_UUID));
+}
+
+#[test]
+fn test_normalize_id_rejects_non_ids() {
+    assert_eq!(normalize_id(""), None);
+    assert_eq!(normalize_id(&PAGE[..31]), None);
+    assert_eq!(normalize_id(&format!("{}0", PAGE)), None);
+    assert_eq!(normalize_id(&PAGE.replace('8', "g")), None);
+    // Hyphens in the wrong places
+    assert_eq!(normalize_id("8f3a1b2c4-d5e-6f70-8192-a3b4c5d6e7f8"), None);
+    assert_eq!(normalize_id("8f3a-1b2c4d5e6f708192a3b4c5d6e7f8"), None);
+}
+
+#[test]
+fn test_parse_workspace_and_title_slug() {
+    let url = parse(&format!("https://www.notion.so/acme/Team-Wiki-{}", PAGE));
+    assert_eq!(url.workspace.as_deref(), Some("acme"));
+    assert_eq!(url.page_id.as_deref(), Some(PAGE_UUID));
+    assert_eq!(url.block_id, None);
+    assert_eq!(url.view_id, None);
+    assert!(url.flags.is_empty());
+}
+
+#[test]
+fn test_parse_bare_page_id() {
+    let url = parse(&format!("https://notion.so/{}", PAGE));
+    assert_eq!(url.workspace, None);
+    assert_eq!(url.page_id.as_deref(), Some(PAGE_UUID));
+
+    let url = parse(&format!("https://www.notion.so/acme/{}/", PAGE));
+    assert_eq!(url.workspace.as_deref(), Some("acme"));
+    assert_eq!(url.page_id.as_deref(), Some(PAGE_UUID));
+}
+
+#[test]
+fn test_parse_hyphenated_page_id() {
+    let url = parse(&format!("https://www.notion.so/{}", PAGE_UUID));
+    assert_eq!(url.page_id.as_deref(), Some(PAGE_UUID));
+}
+
+#[test]
+fn test_parse_block_anchor() {
+    let url = parse(&format!("https://www.notion.so/Team-Wiki-{}#{}", PAGE, BLOCK));
+    assert_eq!(url.page_id.as_deref(), Some(PAGE_UUID));
+    assert_eq!(url.block_id.as_deref(), Some(BLOCK_UUID));
+
+    // Fragments that are not block IDs are ignored
+    let url = parse(&format!("https://www.notion.so/Team-Wiki-{}#comments", PAGE));
+    assert_eq!(url.block_id, None);
+}
+
+#[test]
+fn test_parse_view_peek_and_flags() {
+    let url = parse(&format!(
+        "https://www.notion.so/acme/{}?v={}&p={}&pm=s&pvs=4",
+        PAGE, VIEW, BLOCK
+    ));
+    assert_eq!(url.page_id.as_deref(), Some(PAGE_UUID));
+    assert_eq!(url.view_id.as_deref(), Some(VIEW_UUID));
+    assert_eq!(url.peek_id.as_deref(), Some(BLOCK_UUID));
+    assert_eq!(url.flags.len(), 2);
+    assert_eq!(url.flags["pm"], "s");
+    assert_eq!(url.flags["pvs"], "4");
+}
+
+#[test]
+fn test_parse_published_site() {
+    let url = parse(&format!("https://Acme.notion.site/Team-Wiki-{}", PAGE));
+    assert_eq!(url.workspace.as_deref(), Some("acme"));
+    assert_eq!(url.page_id.as_deref(), Some(PAGE_UUID));
+}
+
+#[test]
+fn test_parse_pages_without_id() {
+    let url = parse("https://www.notion.so/login");
+    assert_eq!(url.page_id, None);
+    assert_eq!(url.workspace, None);
+    assert_eq!(url.page_key(), None);
+
+    let url = parse("https://www.notion.so/acme/Team-Wiki");
+    assert_eq!(url.page_id, None);
+    assert_eq!(url.workspace, None);
+
+    // A title word that merely looks like hex is not an ID
+    let url = parse("https://www.notion.so/Cafe-Decade");
+    assert_eq!(url.page_id, None);
+}
+
+#[test]
+fn test_parse_rejects_other_hosts() {
+    assert_eq!(NotionUrl::parse(&format!("https://example.com/{}", PAGE)), None);
+    assert_eq!(NotionUrl::parse(&format!("https://evil-notion.so/{}", PAGE)), None);
+    assert_eq!(NotionUrl::parse(&format!("https://notion.site/{}", PAGE)), None);
+    assert_eq!(NotionUrl::parse(&format!("file:///notion.so/{}", PAGE)), None);
+    assert_eq!(NotionUrl::parse("not a url"), None);
+}
+
+#[test]
+fn test_canonical_key_merges_url_variants() {
+    let variants = [
+        format!("https://www.notion.so/acme/Team-Wiki-{}", PAGE),
+        format!("https://notion.so/{}?pvs=4", PAGE),
+        format!("https://www.notion.so/Team-Wiki-{}#{}", PAGE, BLOCK),
+        format!("https://www.notion.so/Renamed-Page-{}", PAGE),
+        format!("https://www.notion.so/{}?v={}", PAGE_UUID, VIEW),
+        format!("https://acme.notion.site/Team-Wiki-{}", PAGE),
+    ];
+
+    for variant in &variants {
+        assert_eq!(canonical_key(variant), format!("notion:{}", PAGE_UUID), "{}", variant);
+    }
+}
+
+#[test]
+fn test_canonical_key_keeps_pages_apart() {
+    assert_ne!(
+        canonical_key(&format!("https://www.notion.so/{}", PAGE)),
+        canonical_key(&format!("https://www.notion.so/{}", BLOCK))
+    );
+}
+
+#[test]
+fn test_canonical_key_fallback() {
+    assert_eq!(canonical_key("https://www.notion.so/login?next=/x"), "www.notion.so/login");
+    assert_eq!(canonical_key("https://Example.com/docs/#intro"), "example.com/docs");
+    assert_eq!(canonical_key(" not a url "), "not a url");
+}
+
+#[test]
+fn test_same_page() {
+    assert!(same_page(
+        &format!("https://www.notion.so/acme/Team-Wiki-{}", PAGE),
+        &format!("https://notion.so/{}?pvs=4#{}", PAGE, BLOCK)
+    ));
+    assert!(same_page("https://example.com/a/", "https://example.com/a?x=1"));
+    assert!(!same_page("https://example.com/a", "https://example.com/b"));
+}
@@ -0,0 +1,222 @@
+//! Pop-out windows showing a single page.
+//!
+//! A pop-out is a lightweight standalone window for one Notion page, sharing
+//! the main window's session. Open pop-outs are remembered and reopened at
+//! startup, where the window placement module puts them back in place.
+
+#[cfg(test)]
+mod tests;
+
+mod persistence;
+
+use crate::handlers::navigation::is_notion_url;
+use crate::notion_url::same_page;
+use persistence::{load_popouts, save_popouts};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow, WindowBuilder, WindowUrl};
+
+/// Label prefix of pop-out windows, followed by the pop-out ID.
+pub const LABEL_PREFIX: &str = "popout-";
+
+/// A page popped out into its own window.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PopOut {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub created_at: String, // ISO 8601 format
+}
+
+impl PopOut {
+    pub fn new(url: String) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            title: crate::handlers::title::title_for(&url),
+            url,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    /// Gets the label of the pop-out's window.
+    pub fn label(&self) -> String {
+        format!("{}{}", LABEL_PREFIX, self.id)
+    }
+}
+
+/// Gets the pop-out ID from a window label, if it is a pop-out window.
+pub fn id_from_label(label: &str) -> Option<&str> {
+    label.strip_prefix(LABEL_PREFIX)
+}
+
+/// Finds the pop-out showing the same page as `url`.
+pub fn find_by_page<'a>(popouts: &'a [PopOut], url: &str) -> Option<&'a PopOut> {
+    popouts.iter().find(|p| same_page(&p.url, url))
+}
+
+/// Records the page a pop-out navigated to. Returns true if it changed.
+pub fn apply_page_change(popouts: &mut [PopOut], id: &str, url: &str, title: &str) -> bool {
+    match popouts.iter_mut().find(|p| p.id == id) {
+        Some(popout) if popout.url != url || popout.title != title => {
+            popout.url = url.to_string();
+            popout.title = title.to_string();
+            true
+        }
+        _ => false,
+    }
+}
+
+fn window_title(page_title: &str) -> String {
+    format!("Notive - {}", page_title)
+}
+
+fn focus<R: Runtime>(window: &WebviewWindow<R>) {
+    let _ = window.show();
+    let _ = window.unminimize();
+    let _ = window.set_focus();
+}
+
+/// Creates the window of a pop-out.
+fn open_window<R: Runtime>(
+    app: &AppHandle<R>,
+    popout: &PopOut,
+    visible: bool,
+) -> Result<WebviewWindow<R>, String> {
+    let url = popout
+        .url
+        .parse::<url::Url>()
+        .map_err(|e| format!("Invalid URL: {}", e))?;
+
+    let window = WindowBuilder::new(app, &popout.label(), WindowUrl::External(url))
+        .title(&window_title(&popout.title))
+        .inner_size(900.0, 700.0)
+        .min_inner_size(400.0, 300.0)
+        .resizable(true)
+        .center()
+        .visible(visible)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    crate::handlers::title::watch_titles(&window);
+    crate::handlers::downloads::watch_downloads(&window);
+    crate::monitor::restore_window(&window);
+    Ok(window)
+}
+
+/// Reopens the pop-outs that were open when Notive last quit.
+pub fn restore<R: Runtime>(app: &AppHandle<R>, visible: bool) {
+    let popouts = load_popouts(app).unwrap_or_else(|e| {
+        log::warn!("Failed to load pop-out windows: {}", e);
+        Vec::new()
+    });
+
+    for popout in &popouts {
+        if let Err(e) = open_window(app, popout, visible) {
+            log::warn!("Failed to restore pop-out {}: {}", popout.url, e);
+        }
+    }
+    if !popouts.is_empty() {
+        log::info!("Restored {} pop-out windows", popouts.len());
+    }
+}
+
+/// Keeps a pop-out's URL and title in step with the page it shows.
+pub fn on_page_changed<R: Runtime>(app: &AppHandle<R>, label: &str, url: &str, title: &str) {
+    let Some(id) = id_from_label(label) else {
+        return;
+    };
+
+    let mut popouts = load_popouts(app).unwrap_or_default();
+    if !apply_page_change(&mut popouts, id, url, title) {
+        return;
+    }
+    if let Err(e) = save_popouts(app, &popouts) {
+        log::warn!("Failed to save pop-out windows: {}", e);
+    }
+    if let Some(window) = app.get_webview_window(label) {
+        let _ = window.set_title(&window_title(title));
+    }
+    crate::tray::refresh_menu(app);
+}
+
+/// Forgets a pop-out whose window the user closed.
+pub fn on_closed<R: Runtime>(app: &AppHandle<R>, label: &str) {
+    let Some(id) = id_from_label(label) else {
+        return;
+    };
+
+    let mut popouts = load_popouts(app).unwrap_or_default();
+    popouts.retain(|p| p.id != id);
+    if let Err(e) = save_popouts(app, &popouts) {
+        log::warn!("Failed to save pop-out windows: {}", e);
+    }
+    crate::monitor::forget_window(app, label);
+    crate::tray::refresh_menu(app);
+    log::debug!("Pop-out closed: {}", id);
+}
+
+/// Pops a page out into its own window.
+///
+/// Without a URL, the page shown in the main window is popped out. A page
+/// that already has a pop-out brings that window to the front instead.
+#[tauri::command]
+pub async fn pop_out<R: Runtime>(app: AppHandle<R>, url: Option<String>) -> Result<String, String> {
+    let url = match url {
+        Some(url) => url,
+        None => app
+            .get_webview_window("main")
+            .ok_or("Main window not found")?
+            .url()
+            .map_err(|e| e.to_string())?
+            .to_string(),
+    };
+    if !is_notion_url(&url) {
+        return Err(format!("Only Notion pages can be popped out: {}", url));
+    }
+
+    let mut popouts = load_popouts(&app).unwrap_or_default();
+    if let Some(existing) = find_by_page(&popouts, &url) {
+        if let Some(window) = app.get_webview_window(&existing.label()) {
+            focus(&window);
+            return Ok(existing.id.clone());
+        }
+    }
+
+    let popout = PopOut::new(url);
+    let window = open_window(&app, &popout, true)?;
+    focus(&window);
+
+    popouts.retain(|p| !same_page(&p.url, &popout.url));
+    popouts.push(popout.clone());
+    save_popouts(&app, &popouts).map_err(|e| e.to_string())?;
+    crate::tray::refresh_menu(&app);
+
+    log::info!("Popped out: {} ({})", popout.url, popout.id);
+    Ok(popout.id)
+}
+
+/// Lists the open pop-out windows.
+#[tauri::command]
+pub fn list_popouts<R: Runtime>(app: AppHandle<R>) -> Result<Vec<PopOut>, String> {
+    load_popouts(&app).map_err(|e| e.to_string())
+}
+
+/// Brings a pop-out window to the front.
+#[tauri::command]
+pub fn focus_popout<R: Runtime>(app: AppHandle<R>, popout_id: String) -> Result<(), String> {
+    let window = app
+        .get_webview_window(&format!("{}{}", LABEL_PREFIX, popout_id))
+        .ok_or_else(|| format!("Pop-out {} not found", popout_id))?;
+    focus(&window);
+    Ok(())
+}
+
+/// Closes a pop-out window.
+#[tauri::command]
+pub fn close_popout<R: Runtime>(app: AppHandle<R>, popout_id: String) -> Result<(), String> {
+    let label = format!("{}{}", LABEL_PREFIX, popout_id);
+    if let Some(window) = app.get_webview_window(&label) {
+        window.destroy().map_err(|e| e.to_string())?;
+    }
+    on_closed(&app, &label);
+    Ok(())
+}
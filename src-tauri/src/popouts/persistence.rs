@@ -0,0 +1,41 @@
+//! Pop-out window persistence.
+
+use super::PopOut;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_store::{with_store, StoreCollection};
+
+const POPOUTS_STORE_PATH: &str = "popouts.json";
+
+/// Loads the open pop-out windows from persistent storage.
+pub fn load_popouts<R: Runtime>(app: &AppHandle<R>) -> Result<Vec<PopOut>, Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+    
+    with_store(app, stores, POPOUTS_STORE_PATH, |store| {
+        if let Some(value) = store.get("popouts") {
+            let popouts: Vec<PopOut> = serde_json::from_value(value.clone())
+                .map_err(|e| tauri_plugin_store::Error::Deserialize(e.to_string()))?;
+            Ok(popouts)
+        } else {
+            Ok(vec![])
+        }
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
+
+/// Saves the open pop-out windows to persistent storage.
+pub fn save_popouts<R: Runtime>(
+    app: &AppHandle<R>,
+    popouts: &[PopOut],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+    
+    with_store(app, stores, POPOUTS_STORE_PATH, |store| {
+        let data = serde_json::to_value(popouts)
+            .map_err(|e| tauri_plugin_store::Error::Serialize(e.to_string()))?;
+        store.insert("popouts".to_string(), data)?;
+        store.save()?;
+        log::debug!("Pop-outs saved to store: {} windows", popouts.len());
+        Ok(())
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
@@ -0,0 +1,54 @@
+//! Tests for pop-out windows.
+
+use super::*;
+
+const PAGE: &str = "5c7e9a1b3d5f70829a4b6c8d0e2f4a6b";
+
+#[test]
+fn test_popout_creation() {
+    let popout = PopOut::new(format!("https://www.notion.so/acme/Meeting-Notes-{}", PAGE));
+    assert!(!popout.id.is_empty());
+    assert_eq!(popout.title, "Meeting Notes");
+    assert!(!popout.created_at.is_empty());
+}
+
+#[test]
+fn test_label_round_trip() {
+    let popout = PopOut::new("https://www.notion.so/Spec".to_string());
+    assert_eq!(id_from_label(&popout.label()), Some(popout.id.as_str()));
+    assert_eq!(id_from_label("main"), None);
+    assert_eq!(id_from_label("popup-1234"), None);
+}
+
+#[test]
+fn test_find_by_page_matches_url_variants() {
+    let popouts = vec![
+        PopOut::new(format!("https://www.notion.so/acme/Spec-{}", PAGE)),
+        PopOut::new("https://www.notion.so/Board".to_string()),
+    ];
+
+    let found = find_by_page(&popouts, &format!("https://notion.so/{}?pvs=4", PAGE));
+    assert_eq!(found.map(|p| p.id.as_str()), Some(popouts[0].id.as_str()));
+    assert!(find_by_page(&popouts, "https://www.notion.so/Other").is_none());
+}
+
+#[test]
+fn test_apply_page_change() {
+    let mut popouts = vec![PopOut::new(format!("https://www.notion.so/Spec-{}", PAGE))];
+    let id = popouts[0].id.clone();
+
+    assert!(apply_page_change(&mut popouts, &id, "https://www.notion.so/Board", "Task Board"));
+    assert_eq!(popouts[0].url, "https://www.notion.so/Board");
+    assert_eq!(popouts[0].title, "Task Board");
+
+    assert!(!apply_page_change(&mut popouts, &id, "https://www.notion.so/Board", "Task Board"));
+    assert!(!apply_page_change(&mut popouts, "missing", "https://www.notion.so/x", "X"));
+}
+
+#[test]
+fn test_popout_serialization() {
+    let popout = PopOut::new("https://www.notion.so/Spec".to_string());
+    let json = serde_json::to_string(&popout).unwrap();
+    let parsed: PopOut = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, popout);
+}
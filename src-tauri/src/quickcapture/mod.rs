@@ -25,6 +25,7 @@ pub fn new(name: String, url: String, description: Option<String>) -> Self {
 
 /// Quick capture configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct QuickCaptureConfig {
     pub default_template: Option<String>,
     pub templates: Vec<CaptureTemplate>,
@@ -24,9 +24,10 @@ pub enum SearchSource {
     History,
     Workspace,
     Tab,
+    PopOut,
 }
 
-/// Searches across all sources (bookmarks, history, workspaces, tabs).
+/// Searches across all sources (bookmarks, history, workspaces, pop-outs).
 #[tauri::command]
 pub async fn global_search<R: Runtime>(
     app: AppHandle<R>,
@@ -97,6 +98,26 @@ pub async fn global_search<R: Runtime>(
         Err(e) => log::warn!("Failed to load workspaces for search: {}", e),
     }
     
+    // Search pop-out windows
+    match crate::popouts::list_popouts(app.clone()) {
+        Ok(popouts) => {
+            for popout in popouts {
+                let relevance = calculate_relevance(&popout.title, &popout.url, &query_lower);
+                if relevance > 0.0 {
+                    results.push(SearchResult {
+                        id: popout.id.clone(),
+                        title: popout.title.clone(),
+                        url: popout.url.clone(),
+                        snippet: None,
+                        source: SearchSource::PopOut,
+                        relevance,
+                    });
+                }
+            }
+        }
+        Err(e) => log::warn!("Failed to load pop-outs for search: {}", e),
+    }
+    
     // Sort by relevance (highest first)
     results.sort_by(|a, b| b.relevance.partial_cmp(&a.relevance).unwrap_or(std::cmp::Ordering::Equal));
     
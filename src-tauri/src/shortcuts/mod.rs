@@ -48,6 +48,17 @@ pub fn register_with_settings<R: Runtime>(
         log::warn!("Failed to register quick_capture shortcut: {}", e);
     }
 
+    // Register mini window shortcut
+    if let Err(e) = register_shortcut(
+        app,
+        &shortcuts.toggle_mini_window,
+        move |app| {
+            toggle_mini_window(app);
+        },
+    ) {
+        log::warn!("Failed to register toggle_mini_window shortcut: {}", e);
+    }
+
     Ok(())
 }
 
@@ -105,6 +116,16 @@ fn quick_capture<R: Runtime>(app: &tauri::AppHandle<R>) {
     });
 }
 
+/// Opens or closes the always-on-top mini window.
+fn toggle_mini_window<R: Runtime>(app: &tauri::AppHandle<R>) {
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::mini::toggle_mini_window(handle).await {
+            log::warn!("Failed to toggle mini window: {}", e);
+        }
+    });
+}
+
 /// Updates shortcuts based on user settings.
 pub fn update<R: Runtime>(
     app: &tauri::AppHandle<R>,
@@ -0,0 +1,98 @@
+//! Change files in a synced folder.
+//!
+//! Every device owns `notive-sync/<device ID>.json` inside the chosen
+//! folder. Files are replaced atomically so a folder sync never picks up a
+//! half-written file, and the conflict copies some sync tools make (e.g.
+//! `<ID>.sync-conflict-….json`) are ignored: the device that owns the file
+//! rewrites it on its next sync anyway.
+
+#[cfg(test)]
+mod tests;
+
+use super::{DeviceChanges, FORMAT_VERSION};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory inside the synced folder holding the change files.
+pub const SYNC_DIR: &str = "notive-sync";
+
+/// Checks whether a file in the sync directory is a change file rather than
+/// a temporary file or a conflict copy.
+pub fn is_change_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+        && path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| !stem.is_empty() && !stem.contains('.'))
+}
+
+/// Reads and writes change files in a folder.
+#[derive(Debug, Clone)]
+pub struct FolderBackend {
+    dir: PathBuf,
+}
+
+impl FolderBackend {
+    pub fn new(folder: PathBuf) -> Self {
+        Self {
+            dir: folder.join(SYNC_DIR),
+        }
+    }
+
+    /// Gets the path of a device's change file.
+    pub fn path_for(&self, device_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", device_id))
+    }
+
+    /// Reads the change files of every device. Unreadable files and files
+    /// from newer versions of Notive are skipped with a warning.
+    pub fn read_devices(&self) -> Result<Vec<DeviceChanges>, String> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read {}: {}", self.dir.display(), e)),
+        };
+
+        let mut devices = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if !is_change_file(&path) {
+                continue;
+            }
+            let changes: DeviceChanges = match fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()))
+            {
+                Ok(changes) => changes,
+                Err(e) => {
+                    log::warn!("Skipping unreadable sync file {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if changes.version > FORMAT_VERSION {
+                log::warn!(
+                    "Skipping sync file {} from a newer version of Notive",
+                    path.display()
+                );
+                continue;
+            }
+            devices.push(changes);
+        }
+        devices.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        Ok(devices)
+    }
+
+    /// Writes this device's change file.
+    pub fn write(&self, changes: &DeviceChanges) -> Result<(), String> {
+        fs::create_dir_all(&self.dir).map_err(|e| format!("Failed to create {}: {}", self.dir.display(), e))?;
+        let bytes = serde_json::to_vec_pretty(changes).map_err(|e| format!("Failed to serialize sync file: {}", e))?;
+
+        // Hidden and without a .json extension, so folder syncs and other
+        // devices leave it alone until it is renamed into place
+        let temp = self.dir.join(format!(".{}.tmp", changes.device_id));
+        let path = self.path_for(&changes.device_id);
+        fs::write(&temp, bytes)
+            .and_then(|()| fs::rename(&temp, &path))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
@@ -0,0 +1,69 @@
+//! Tests for change files in a synced folder.
+
+use super::*;
+use crate::sync::merge::Records;
+
+fn changes(device_id: &str) -> DeviceChanges {
+    DeviceChanges {
+        version: FORMAT_VERSION,
+        device_id: device_id.to_string(),
+        device_name: format!("{} laptop", device_id),
+        updated_at: 1_700_000_000_000,
+        records: Records::new(),
+    }
+}
+
+#[test]
+fn test_missing_folder_has_no_devices() {
+    let dir = tempfile::tempdir().unwrap();
+    let backend = FolderBackend::new(dir.path().to_path_buf());
+    assert!(backend.read_devices().unwrap().is_empty());
+}
+
+#[test]
+fn test_write_and_read_devices() {
+    let dir = tempfile::tempdir().unwrap();
+    let backend = FolderBackend::new(dir.path().to_path_buf());
+    backend.write(&changes("b")).unwrap();
+    backend.write(&changes("a")).unwrap();
+
+    let mut updated = changes("a");
+    updated.updated_at += 1;
+    backend.write(&updated).unwrap();
+
+    assert_eq!(backend.read_devices().unwrap(), vec![updated, changes("b")]);
+    assert!(dir.path().join(SYNC_DIR).join("a.json").exists());
+    assert!(!dir.path().join(SYNC_DIR).join(".a.tmp").exists());
+}
+
+#[test]
+fn test_ignores_conflict_copies_and_other_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let backend = FolderBackend::new(dir.path().to_path_buf());
+    backend.write(&changes("a")).unwrap();
+
+    let sync_dir = dir.path().join(SYNC_DIR);
+    let copy = serde_json::to_vec(&changes("a")).unwrap();
+    fs::write(sync_dir.join("a.sync-conflict-20240101-120000-ABCDEF.json"), &copy).unwrap();
+    fs::write(sync_dir.join(".b.tmp"), &copy).unwrap();
+    fs::write(sync_dir.join("notes.txt"), b"hello").unwrap();
+
+    let devices = backend.read_devices().unwrap();
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].device_id, "a");
+}
+
+#[test]
+fn test_skips_unreadable_and_newer_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let backend = FolderBackend::new(dir.path().to_path_buf());
+    backend.write(&changes("a")).unwrap();
+
+    let mut newer = changes("b");
+    newer.version = FORMAT_VERSION + 1;
+    backend.write(&newer).unwrap();
+    fs::write(backend.path_for("c"), b"{ not json").unwrap();
+
+    let devices = backend.read_devices().unwrap();
+    assert_eq!(devices, vec![changes("a")]);
+}
@@ -0,0 +1,380 @@
+//! Three-way merge of synced records.
+//!
+//! Every synced record carries the time it was changed, the device that
+//! changed it, and the time and value of the version it replaced. When two
+//! devices hold different versions of a record:
+//!
+//! - if one was made on top of the other, the later one is taken
+//! - otherwise they were changed at the same time, and are merged against
+//!   the version both replaced: objects field by field, with a field
+//!   changed differently on both sides being a conflict
+//!
+//! A conflict takes the side changed most recently, so every device settles
+//! on the same value, and is reported so the user can pick the other one.
+
+#[cfg(test)]
+mod tests;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Current values of the synced records: collection, then record ID.
+pub type Collections = BTreeMap<String, BTreeMap<String, Value>>;
+
+/// Timestamp of records this device had before its first sync.
+pub const JOINED: i64 = 0;
+
+/// Synced records with their timestamps: collection, then record ID.
+pub type Records = BTreeMap<String, BTreeMap<String, Record>>;
+
+/// A synced record. A record without a value has been deleted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Record {
+    #[serde(default)]
+    pub value: Option<Value>,
+    /// When the record last changed, in milliseconds since the Unix epoch.
+    pub modified: i64,
+    /// Device that made the change.
+    pub device: String,
+    /// When the version this one replaced was changed.
+    #[serde(default)]
+    pub parent: i64,
+    /// The value this one replaced, the common ancestor when merging
+    /// changes made at the same time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_value: Option<Value>,
+}
+
+impl Record {
+    /// Orders records by when they changed, breaking ties by device so
+    /// every device makes the same choice.
+    fn is_newer_than(&self, other: &Record) -> bool {
+        (self.modified, &self.device) > (other.modified, &other.device)
+    }
+
+    /// Checks whether this version was made on top of `other`.
+    fn supersedes(&self, other: &Record) -> bool {
+        self.parent >= other.modified && self.modified > other.modified
+    }
+}
+
+/// Which side of a conflict a merge kept.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Local,
+    Remote,
+}
+
+/// A record changed differently on two devices.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Conflict {
+    pub id: String,
+    pub collection: String,
+    pub record: String,
+    /// Fields changed differently on both sides; empty for whole values.
+    pub fields: Vec<String>,
+    pub local: Option<Value>,
+    pub remote: Option<Value>,
+    pub remote_device: String,
+    /// The side the merged record took for the conflicting fields.
+    pub kept: Side,
+    pub detected_at: i64,
+}
+
+/// The result of merging this device with the others.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MergeOutcome {
+    /// The merged records: the new base and this device's change file.
+    pub records: Records,
+    pub conflicts: Vec<Conflict>,
+    /// Records taken from other devices.
+    pub pulled: usize,
+    /// Records changed on this device since the last sync.
+    pub pushed: usize,
+}
+
+/// Merges one value three ways. Fields changed differently on both sides
+/// are added to `conflicts` and take the remote side if `prefer_remote`.
+pub fn merge_values(
+    base: Option<&Value>,
+    local: Option<&Value>,
+    remote: Option<&Value>,
+    prefer_remote: bool,
+    path: &str,
+    conflicts: &mut Vec<String>,
+) -> Option<Value> {
+    if local == remote || remote == base {
+        return local.cloned();
+    }
+    if local == base {
+        return remote.cloned();
+    }
+
+    if let (Some(Value::Object(l)), Some(Value::Object(r))) = (local, remote) {
+        let b = base.and_then(Value::as_object);
+        let keys: BTreeSet<&String> = l.keys().chain(r.keys()).chain(b.into_iter().flat_map(Map::keys)).collect();
+        let mut merged = Map::new();
+        for key in keys {
+            let field = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+            let value = merge_values(
+                b.and_then(|b| b.get(key)),
+                l.get(key),
+                r.get(key),
+                prefer_remote,
+                &field,
+                conflicts,
+            );
+            if let Some(value) = value {
+                merged.insert(key.clone(), value);
+            }
+        }
+        return Some(Value::Object(merged));
+    }
+
+    conflicts.push(path.to_string());
+    if prefer_remote {
+        remote.cloned()
+    } else {
+        local.cloned()
+    }
+}
+
+/// Stamps the local values: records that differ from the base are changes
+/// made on this device at `now`.
+///
+/// On the first sync (no base yet) every local record is stamped
+/// [`JOINED`], so a device joining sync adopts the synced data rather than
+/// overwrite it. Collections missing locally altogether (their store was
+/// never written) are left as they are rather than deleted.
+pub fn stamp_local(local: &Collections, base: &Records, device: &str, now: i64) -> Records {
+    let joining = base.values().all(BTreeMap::is_empty);
+    let mut stamped = base.clone();
+
+    for (collection, values) in local {
+        let base_records = base.get(collection);
+        let ids: BTreeSet<&String> = values
+            .keys()
+            .chain(base_records.into_iter().flat_map(BTreeMap::keys))
+            .collect();
+
+        let mut records = BTreeMap::new();
+        for id in ids {
+            let value = values.get(id);
+            let base_record = base_records.and_then(|b| b.get(id));
+            let record = match (value, base_record) {
+                (value, Some(base_record)) if base_record.value.as_ref() == value => base_record.clone(),
+                (value, base_record) => Record {
+                    value: value.cloned(),
+                    modified: if joining { JOINED } else { now },
+                    device: device.to_string(),
+                    parent: base_record.map(|r| r.modified).unwrap_or(JOINED),
+                    parent_value: base_record.and_then(|r| r.value.clone()),
+                },
+            };
+            records.insert(id.clone(), record);
+        }
+        stamped.insert(collection.clone(), records);
+    }
+    stamped
+}
+
+/// Picks the most recent version of each record among other devices.
+pub fn newest_remote<'a>(remotes: impl IntoIterator<Item = &'a Records>) -> Records {
+    let mut newest = Records::new();
+    for records in remotes {
+        for (collection, records) in records {
+            let target = newest.entry(collection.clone()).or_default();
+            for (id, record) in records {
+                match target.get(id) {
+                    Some(existing) if !record.is_newer_than(existing) => {}
+                    _ => {
+                        target.insert(id.clone(), record.clone());
+                    }
+                }
+            }
+        }
+    }
+    newest
+}
+
+fn newer<'a>(a: &'a Record, b: &'a Record) -> &'a Record {
+    if b.is_newer_than(a) {
+        b
+    } else {
+        a
+    }
+}
+
+/// Merges two versions of a record changed at the same time.
+fn merge_concurrent(
+    local: &Record,
+    remote: &Record,
+    collection: &str,
+    id: &str,
+    device: &str,
+    now: i64,
+    conflicts: &mut Vec<Conflict>,
+) -> Record {
+    // The older of the two parents is a version both sides have seen
+    let ancestor = if local.parent <= remote.parent {
+        local.parent_value.as_ref()
+    } else {
+        remote.parent_value.as_ref()
+    };
+    let prefer_remote = remote.is_newer_than(local);
+    let mut fields = Vec::new();
+    let value = merge_values(
+        ancestor,
+        local.value.as_ref(),
+        remote.value.as_ref(),
+        prefer_remote,
+        "",
+        &mut fields,
+    );
+    if !fields.is_empty() {
+        conflicts.push(Conflict {
+            id: uuid::Uuid::new_v4().to_string(),
+            collection: collection.to_string(),
+            record: id.to_string(),
+            fields: fields.into_iter().filter(|f| !f.is_empty()).collect(),
+            local: local.value.clone(),
+            remote: remote.value.clone(),
+            remote_device: remote.device.clone(),
+            kept: if prefer_remote { Side::Remote } else { Side::Local },
+            detected_at: now,
+        });
+    }
+
+    if value == remote.value {
+        return remote.clone();
+    }
+    if value == local.value {
+        return local.clone();
+    }
+    // A new version made on top of both
+    let newest = newer(local, remote);
+    Record {
+        value,
+        modified: now.max(newest.modified + 1),
+        device: device.to_string(),
+        parent: newest.modified,
+        parent_value: newest.value.clone(),
+    }
+}
+
+/// Merges this device's stamped records with the newest remote ones.
+/// `base` holds the records this device synced before: it counts the records
+/// changed on this device and tells whether the device is joining sync.
+pub fn merge(local: &Records, base: &Records, remote: &Records, device: &str, now: i64) -> MergeOutcome {
+    let mut outcome = MergeOutcome::default();
+    let collections: BTreeSet<&String> = local.keys().chain(remote.keys()).collect();
+
+    for collection in collections {
+        let ids: BTreeSet<&String> = local
+            .get(collection)
+            .into_iter()
+            .flat_map(BTreeMap::keys)
+            .chain(remote.get(collection).into_iter().flat_map(BTreeMap::keys))
+            .collect();
+        let mut merged = BTreeMap::new();
+
+        for id in ids {
+            let local_record = local.get(collection).and_then(|r| r.get(id));
+            let remote_record = remote.get(collection).and_then(|r| r.get(id));
+            let base_record = base.get(collection).and_then(|r| r.get(id));
+            if local_record.is_some_and(|r| base_record != Some(r)) {
+                outcome.pushed += 1;
+            }
+
+            let record = match (local_record, remote_record) {
+                (Some(local), None) => local.clone(),
+                (None, Some(remote)) => remote.clone(),
+                (None, None) => continue,
+                (Some(local), Some(remote)) => {
+                    if local.value == remote.value {
+                        newer(local, remote).clone()
+                    } else if remote.supersedes(local) {
+                        remote.clone()
+                    } else if local.supersedes(remote) {
+                        local.clone()
+                    } else if local.modified == JOINED && base_record.is_none() {
+                        // This device is joining, its data gives way to the synced data
+                        remote.clone()
+                    } else if local.modified == JOINED || remote.modified == JOINED {
+                        // Data from before the first sync gives way to synced data
+                        newer(local, remote).clone()
+                    } else {
+                        merge_concurrent(local, remote, collection, id, device, now, &mut outcome.conflicts)
+                    }
+                }
+            };
+            if local_record.and_then(|r| r.value.as_ref()) != record.value.as_ref() {
+                outcome.pulled += 1;
+            }
+            merged.insert(id.clone(), record);
+        }
+        outcome.records.insert(collection.clone(), merged);
+    }
+    outcome
+}
+
+/// Gets the values of merged records, leaving out deleted ones.
+pub fn values(records: &Records) -> Collections {
+    records
+        .iter()
+        .map(|(collection, records)| {
+            let values = records
+                .iter()
+                .filter_map(|(id, record)| record.value.clone().map(|value| (id.clone(), value)))
+                .collect();
+            (collection.clone(), values)
+        })
+        .collect()
+}
+
+/// Gets the value at a dotted field path.
+fn field<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |value, key| value.get(key))
+}
+
+/// Sets or removes the value at a dotted field path.
+fn set_field(target: &mut Value, path: &str, value: Option<Value>) {
+    let (parents, key) = match path.rsplit_once('.') {
+        Some((parents, key)) => (Some(parents), key),
+        None => (None, path),
+    };
+    let mut object = target;
+    for parent in parents.into_iter().flat_map(|p| p.split('.')) {
+        if !object.get(parent).is_some_and(Value::is_object) {
+            object[parent] = Value::Object(Map::new());
+        }
+        object = &mut object[parent];
+    }
+    if let Some(object) = object.as_object_mut() {
+        match value {
+            Some(value) => object.insert(key.to_string(), value),
+            None => object.remove(key),
+        };
+    }
+}
+
+/// Settles a conflict on the given side, starting from the current value
+/// of the record. Only the conflicting fields are taken from that side, so
+/// changes merged from the other side are kept.
+pub fn resolve(current: Option<&Value>, conflict: &Conflict, keep: Side) -> Option<Value> {
+    let chosen = match keep {
+        Side::Local => conflict.local.as_ref(),
+        Side::Remote => conflict.remote.as_ref(),
+    };
+    let (Some(current), false) = (current, conflict.fields.is_empty()) else {
+        return chosen.cloned();
+    };
+    let chosen = chosen?;
+    let mut value = current.clone();
+    for path in &conflict.fields {
+        set_field(&mut value, path, field(chosen, path).cloned());
+    }
+    Some(value)
+}
@@ -0,0 +1,265 @@
+//! Tests for the three-way merge.
+
+use super::*;
+use serde_json::json;
+
+fn record(value: Value, modified: i64, device: &str) -> Record {
+    Record {
+        value: Some(value),
+        modified,
+        device: device.to_string(),
+        parent: 0,
+        parent_value: None,
+    }
+}
+
+/// A version of a record made on top of `of`.
+fn change(of: &Record, value: Option<Value>, modified: i64, device: &str) -> Record {
+    Record {
+        value,
+        modified,
+        device: device.to_string(),
+        parent: of.modified,
+        parent_value: of.value.clone(),
+    }
+}
+
+fn records(collection: &str, entries: Vec<(&str, Record)>) -> Records {
+    let mut records = Records::new();
+    records.insert(
+        collection.to_string(),
+        entries.into_iter().map(|(id, r)| (id.to_string(), r)).collect(),
+    );
+    records
+}
+
+fn collections(collection: &str, entries: Vec<(&str, Value)>) -> Collections {
+    let mut values = Collections::new();
+    values.insert(
+        collection.to_string(),
+        entries.into_iter().map(|(id, v)| (id.to_string(), v)).collect(),
+    );
+    values
+}
+
+#[test]
+fn test_merge_values_combines_fields_changed_on_each_side() {
+    let base = json!({ "title": "Wiki", "url": "https://notion.so/wiki", "tags": ["a"] });
+    let local = json!({ "title": "Team wiki", "url": "https://notion.so/wiki", "tags": ["a"] });
+    let remote = json!({ "title": "Wiki", "url": "https://notion.so/wiki", "tags": ["a", "b"] });
+    let mut conflicts = Vec::new();
+
+    let merged = merge_values(Some(&base), Some(&local), Some(&remote), true, "", &mut conflicts);
+
+    assert!(conflicts.is_empty());
+    assert_eq!(merged, Some(json!({ "title": "Team wiki", "url": "https://notion.so/wiki", "tags": ["a", "b"] })));
+}
+
+#[test]
+fn test_merge_values_reports_nested_conflicts() {
+    let base = json!({ "shortcuts": { "reload": "Ctrl+R", "zoom_in": "Ctrl+=" } });
+    let local = json!({ "shortcuts": { "reload": "F5", "zoom_in": "Ctrl+=" } });
+    let remote = json!({ "shortcuts": { "reload": "Ctrl+Shift+R", "zoom_in": "Ctrl+Plus" } });
+    let mut conflicts = Vec::new();
+
+    let merged = merge_values(Some(&base), Some(&local), Some(&remote), false, "", &mut conflicts);
+
+    assert_eq!(conflicts, vec!["shortcuts.reload".to_string()]);
+    assert_eq!(merged, Some(json!({ "shortcuts": { "reload": "F5", "zoom_in": "Ctrl+Plus" } })));
+}
+
+#[test]
+fn test_stamp_local_marks_only_changed_records() {
+    let base = records(
+        "bookmarks",
+        vec![
+            ("b1", record(json!({ "title": "Wiki" }), 100, "other")),
+            ("b2", record(json!({ "title": "Roadmap" }), 100, "other")),
+        ],
+    );
+    let local = collections(
+        "bookmarks",
+        vec![("b1", json!({ "title": "Wiki" })), ("b3", json!({ "title": "New" }))],
+    );
+
+    let stamped = stamp_local(&local, &base, "me", 500);
+    let bookmarks = &stamped["bookmarks"];
+
+    assert_eq!(bookmarks["b1"], base["bookmarks"]["b1"]);
+    assert_eq!(bookmarks["b2"], change(&base["bookmarks"]["b2"], None, 500, "me"), "removed locally, so deleted");
+    assert_eq!(bookmarks["b3"], record(json!({ "title": "New" }), 500, "me"));
+}
+
+#[test]
+fn test_stamp_local_keeps_collections_missing_locally() {
+    let base = records("settings", vec![("theme", record(json!("dark"), 100, "other"))]);
+    let stamped = stamp_local(&Collections::new(), &base, "me", 500);
+    assert_eq!(stamped, base);
+}
+
+#[test]
+fn test_newest_remote_picks_latest_change() {
+    let a = records("templates", vec![("t1", record(json!("old"), 100, "a"))]);
+    let b = records("templates", vec![("t1", record(json!("new"), 200, "b"))]);
+    let c = records("templates", vec![("t1", record(json!("tie"), 200, "c"))]);
+
+    let newest = newest_remote([&a, &b]);
+    assert_eq!(newest["templates"]["t1"].value, Some(json!("new")));
+
+    // Equal timestamps are settled by device so every device agrees
+    let newest = newest_remote([&c, &b]);
+    assert_eq!(newest["templates"]["t1"].value, Some(json!("tie")));
+}
+
+#[test]
+fn test_merge_takes_the_side_that_changed() {
+    let one = record(json!("one"), 100, "me");
+    let two = record(json!("two"), 100, "me");
+    let base = records("bookmarks", vec![("b1", one.clone()), ("b2", two.clone())]);
+    let local = records(
+        "bookmarks",
+        vec![
+            ("b1", change(&one, Some(json!("one (local)")), 300, "me")),
+            ("b2", two.clone()),
+        ],
+    );
+    let remote = records(
+        "bookmarks",
+        vec![
+            ("b1", one.clone()),
+            ("b2", change(&two, Some(json!("two (remote)")), 200, "other")),
+            ("b3", record(json!("three"), 200, "other")),
+        ],
+    );
+
+    let outcome = merge(&local, &base, &remote, "me", 400);
+    let merged = values(&outcome.records);
+
+    assert_eq!(merged["bookmarks"]["b1"], json!("one (local)"));
+    assert_eq!(merged["bookmarks"]["b2"], json!("two (remote)"));
+    assert_eq!(merged["bookmarks"]["b3"], json!("three"));
+    assert!(outcome.conflicts.is_empty());
+    assert_eq!((outcome.pulled, outcome.pushed), (2, 1));
+}
+
+#[test]
+fn test_merge_ignores_remote_copies_already_replaced() {
+    let stale = record(json!("stale"), 100, "slow");
+    let current = change(&stale, Some(json!("current")), 300, "other");
+    let base = records("bookmarks", vec![("b1", current)]);
+
+    let outcome = merge(&base, &base, &records("bookmarks", vec![("b1", stale)]), "me", 400);
+    assert_eq!(values(&outcome.records)["bookmarks"]["b1"], json!("current"));
+    assert_eq!(outcome.pulled, 0);
+}
+
+#[test]
+fn test_merge_conflict_keeps_newer_side_and_is_reported() {
+    let base = record(json!({ "title": "Wiki", "url": "a" }), 100, "me");
+    let local = change(&base, Some(json!({ "title": "Local", "url": "b" })), 300, "me");
+    let remote = change(&base, Some(json!({ "title": "Remote", "url": "a" })), 200, "other");
+
+    let outcome = merge(
+        &records("bookmarks", vec![("b1", local)]),
+        &records("bookmarks", vec![("b1", base)]),
+        &records("bookmarks", vec![("b1", remote)]),
+        "me",
+        400,
+    );
+
+    assert_eq!(values(&outcome.records)["bookmarks"]["b1"], json!({ "title": "Local", "url": "b" }));
+    assert_eq!(outcome.records["bookmarks"]["b1"].modified, 300);
+    assert_eq!(outcome.conflicts.len(), 1);
+    let conflict = &outcome.conflicts[0];
+    assert_eq!(conflict.record, "b1");
+    assert_eq!(conflict.fields, vec!["title".to_string()]);
+    assert_eq!(conflict.kept, Side::Local);
+    assert_eq!(conflict.remote_device, "other");
+}
+
+#[test]
+fn test_merge_of_concurrent_edits_is_a_new_version() {
+    let base = record(json!({ "title": "Wiki", "tags": [] }), 100, "me");
+    let local = change(&base, Some(json!({ "title": "Team wiki", "tags": [] })), 300, "me");
+    let remote = change(&base, Some(json!({ "title": "Wiki", "tags": ["work"] })), 350, "other");
+
+    let outcome = merge(
+        &records("bookmarks", vec![("b1", local)]),
+        &records("bookmarks", vec![("b1", base)]),
+        &records("bookmarks", vec![("b1", remote.clone())]),
+        "me",
+        400,
+    );
+
+    let merged = &outcome.records["bookmarks"]["b1"];
+    assert_eq!(merged.value, Some(json!({ "title": "Team wiki", "tags": ["work"] })));
+    assert_eq!((merged.modified, merged.device.as_str()), (400, "me"));
+    assert!(merged.supersedes(&remote));
+    assert!(outcome.conflicts.is_empty());
+    assert_eq!(outcome.pulled, 1);
+}
+
+#[test]
+fn test_merge_edit_against_delete_is_a_whole_record_conflict() {
+    let base = record(json!({ "name": "Inbox" }), 100, "me");
+    let local = change(&base, None, 200, "me");
+    let remote = change(&base, Some(json!({ "name": "Inbox 2" })), 300, "other");
+
+    let outcome = merge(
+        &records("templates", vec![("t1", local)]),
+        &records("templates", vec![("t1", base)]),
+        &records("templates", vec![("t1", remote)]),
+        "me",
+        400,
+    );
+
+    assert_eq!(values(&outcome.records)["templates"]["t1"], json!({ "name": "Inbox 2" }));
+    assert_eq!(outcome.conflicts.len(), 1);
+    assert!(outcome.conflicts[0].fields.is_empty());
+    assert_eq!(outcome.conflicts[0].kept, Side::Remote);
+}
+
+#[test]
+fn test_joining_devices_converge_without_conflicts() {
+    let mine = stamp_local(&collections("settings", vec![("theme", json!("light"))]), &Records::new(), "a", 500);
+    let theirs = stamp_local(&collections("settings", vec![("theme", json!("dark"))]), &Records::new(), "b", 500);
+
+    // Joining at the same time, each adopts the other's data once, then both settle
+    let on_a = merge(&mine, &Records::new(), &theirs, "a", 500);
+    let on_b = merge(&theirs, &Records::new(), &mine, "b", 500);
+    let again_a = merge(&on_a.records, &on_a.records, &on_b.records, "a", 600);
+    let again_b = merge(&on_b.records, &on_b.records, &on_a.records, "b", 600);
+
+    assert!(on_a.conflicts.is_empty() && on_b.conflicts.is_empty());
+    assert!(again_a.conflicts.is_empty() && again_b.conflicts.is_empty());
+    assert_eq!(values(&again_a.records), values(&again_b.records));
+
+    // A device joining later adopts what was changed since
+    let synced = records("settings", vec![("theme", record(json!("dark"), 300, "b"))]);
+    let outcome = merge(&mine, &Records::new(), &synced, "a", 500);
+    assert_eq!(values(&outcome.records)["settings"]["theme"], json!("dark"));
+}
+
+#[test]
+fn test_resolve_takes_conflicting_fields_from_chosen_side() {
+    let conflict = Conflict {
+        id: "c1".to_string(),
+        collection: "bookmarks".to_string(),
+        record: "b1".to_string(),
+        fields: vec!["meta.title".to_string()],
+        local: Some(json!({ "meta": { "title": "Local" }, "url": "a" })),
+        remote: Some(json!({ "meta": { "title": "Remote" }, "url": "b" })),
+        remote_device: "other".to_string(),
+        kept: Side::Remote,
+        detected_at: 0,
+    };
+    let current = json!({ "meta": { "title": "Remote" }, "url": "b" });
+
+    assert_eq!(
+        resolve(Some(&current), &conflict, Side::Local),
+        Some(json!({ "meta": { "title": "Local" }, "url": "b" }))
+    );
+
+    let whole = Conflict { fields: Vec::new(), local: None, ..conflict };
+    assert_eq!(resolve(Some(&current), &whole, Side::Local), None);
+}
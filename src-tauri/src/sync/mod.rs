@@ -0,0 +1,663 @@
+//! Syncing bookmarks, templates and settings between devices.
+//!
+//! Each device writes its view of the synced records to its own change file
+//! and reads the change files of the other devices. The files are kept in a
+//! folder the user already syncs with Syncthing, Nextcloud or the like (see
+//! [`folder`]), or on a WebDAV server (see [`webdav`]). Since no two devices
+//! write the same file, the file transport never has to resolve a conflict;
+//! records changed on several devices are reconciled by a three-way merge
+//! (see [`merge`]).
+//!
+//! Synced collections:
+//!
+//! | collection          | source                                       |
+//! |---------------------|----------------------------------------------|
+//! | `bookmarks`         | `bookmarks.json`, by bookmark ID             |
+//! | `templates`         | `templates.json`, by template ID             |
+//! | `capture_templates` | `quick_capture_config` templates, by ID      |
+//! | `settings`          | `settings`, by field (except device-specific) |
+
+#[cfg(test)]
+mod tests;
+
+pub mod folder;
+pub mod merge;
+pub mod persistence;
+pub mod webdav;
+
+use crate::backup::{Stores, SECRET_SERVICE};
+use crate::security::token_storage::{self, StoredToken};
+use folder::FolderBackend;
+use merge::{Collections, Conflict, Records, Side};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Version of the change file layout written by this build.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Event emitted after a sync changed local data, with the [`SyncReport`].
+pub const SYNCED_EVENT: &str = "sync-completed";
+
+/// Minutes between background syncs unless set otherwise.
+pub const DEFAULT_INTERVAL_MINUTES: u32 = 15;
+
+/// How often the background task checks whether a sync is due.
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Times a sync starts over when its change file was replaced meanwhile.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Settings that describe this machine rather than the user's preferences.
+pub const DEVICE_SETTINGS: &[&str] = &[
+    "autostart_enabled",
+    "hardware_acceleration",
+    "download_folder",
+    "update_mirror",
+];
+
+/// Where a collection is kept in the stores.
+struct Source {
+    collection: &'static str,
+    file: &'static str,
+    key: &'static str,
+    /// Field of the stored object holding the records; `None` if the
+    /// stored value is the list itself.
+    list: Option<&'static str>,
+    /// Whether the records are the fields of the stored object rather than
+    /// the items of a list.
+    fields: bool,
+}
+
+const SOURCES: &[Source] = &[
+    Source {
+        collection: "bookmarks",
+        file: "bookmarks.json",
+        key: "bookmarks",
+        list: None,
+        fields: false,
+    },
+    Source {
+        collection: "templates",
+        file: "templates.json",
+        key: "templates",
+        list: None,
+        fields: false,
+    },
+    Source {
+        collection: "capture_templates",
+        file: "settings.json",
+        key: "quick_capture_config",
+        list: Some("templates"),
+        fields: false,
+    },
+    Source {
+        collection: "settings",
+        file: "settings.json",
+        key: "settings",
+        list: None,
+        fields: true,
+    },
+];
+
+/// A device's view of the synced records, as written to its change file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeviceChanges {
+    pub version: u32,
+    pub device_id: String,
+    pub device_name: String,
+    /// When the file was written, in milliseconds since the Unix epoch.
+    pub updated_at: i64,
+    pub records: Records,
+}
+
+/// A WebDAV account used for sync. The password is kept in the keyring.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebDavAccount {
+    pub url: String,
+    pub username: String,
+}
+
+/// Sync state of this device.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SyncState {
+    pub device_id: String,
+    pub device_name: String,
+    /// The synced folder.
+    pub folder: Option<String>,
+    /// The WebDAV server, used instead of a folder when set.
+    pub webdav: Option<WebDavAccount>,
+    /// Minutes between background syncs; 0 syncs only at startup and on request.
+    pub interval_minutes: u32,
+    /// The records as of the last sync, the common ancestor of the merge.
+    pub base: Records,
+    /// Conflicts the user has not settled yet.
+    pub conflicts: Vec<Conflict>,
+    pub last_sync: Option<i64>,
+    pub last_report: Option<SyncReport>,
+    pub last_error: Option<String>,
+}
+
+impl Default for SyncState {
+    fn default() -> Self {
+        Self {
+            device_id: String::new(),
+            device_name: String::new(),
+            folder: None,
+            webdav: None,
+            interval_minutes: DEFAULT_INTERVAL_MINUTES,
+            base: Records::new(),
+            conflicts: Vec::new(),
+            last_sync: None,
+            last_report: None,
+            last_error: None,
+        }
+    }
+}
+
+impl SyncState {
+    pub fn is_enabled(&self) -> bool {
+        self.folder.is_some() || self.webdav.is_some()
+    }
+
+    /// Switches to another folder or server. Other places hold other
+    /// devices, so the sync starts over.
+    fn set_target(&mut self, folder: Option<String>, webdav: Option<WebDavAccount>) {
+        if self.folder != folder || self.webdav != webdav {
+            self.base = Records::new();
+            self.conflicts.clear();
+            self.last_report = None;
+        }
+        self.folder = folder;
+        self.webdav = webdav;
+        self.last_error = None;
+    }
+}
+
+/// Summary of a sync run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SyncReport {
+    pub pulled: usize,
+    pub pushed: usize,
+    /// Conflicts found in this run.
+    pub conflicts: usize,
+    /// Other devices seen.
+    pub devices: Vec<String>,
+    /// Store files changed by the sync.
+    pub changed: Vec<String>,
+    pub finished_at: i64,
+}
+
+/// Status of sync shown in the settings.
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStatus {
+    pub device_id: String,
+    pub device_name: String,
+    pub folder: Option<String>,
+    pub webdav: Option<WebDavAccount>,
+    pub interval_minutes: u32,
+    pub conflicts: Vec<Conflict>,
+    pub last_sync: Option<i64>,
+    pub last_report: Option<SyncReport>,
+    pub last_error: Option<String>,
+}
+
+impl From<&SyncState> for SyncStatus {
+    fn from(state: &SyncState) -> Self {
+        Self {
+            device_id: state.device_id.clone(),
+            device_name: state.device_name.clone(),
+            folder: state.folder.clone(),
+            webdav: state.webdav.clone(),
+            interval_minutes: state.interval_minutes,
+            conflicts: state.conflicts.clone(),
+            last_sync: state.last_sync,
+            last_report: state.last_report.clone(),
+            last_error: state.last_error.clone(),
+        }
+    }
+}
+
+/// Change files read from a backend.
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    pub devices: Vec<DeviceChanges>,
+    /// ETag of this device's own file, on backends that have them.
+    pub etag: Option<String>,
+}
+
+/// Where the change files are kept.
+#[derive(Debug, Clone)]
+pub enum Backend {
+    Folder(FolderBackend),
+    WebDav(webdav::WebDavBackend),
+}
+
+impl Backend {
+    pub async fn read(&self, device_id: &str) -> Result<Snapshot, String> {
+        match self {
+            Self::Folder(folder) => Ok(Snapshot {
+                devices: folder.read_devices()?,
+                etag: None,
+            }),
+            Self::WebDav(server) => server.read(device_id).await,
+        }
+    }
+
+    /// Writes this device's change file. Returns `false` if it changed
+    /// since the snapshot with the given ETag was read.
+    pub async fn write(&self, changes: &DeviceChanges, etag: Option<&str>) -> Result<bool, String> {
+        match self {
+            Self::Folder(folder) => folder.write(changes).map(|()| true),
+            Self::WebDav(server) => server.write(changes, etag).await,
+        }
+    }
+}
+
+fn record_id(value: &Value) -> Option<&str> {
+    value.get("id").and_then(Value::as_str)
+}
+
+/// Gets the stored value holding a collection's records.
+fn source_value<'a>(stores: &'a Stores, source: &Source) -> Option<&'a Value> {
+    let value = stores.get(source.file)?.get(source.key)?;
+    match source.list {
+        Some(field) => value.get(field),
+        None => Some(value),
+    }
+}
+
+/// Reads the synced collections from the stores. Collections whose store
+/// was never written are left out.
+pub fn extract(stores: &Stores) -> Collections {
+    let mut collections = Collections::new();
+    for source in SOURCES {
+        let Some(value) = source_value(stores, source) else {
+            continue;
+        };
+        let records: BTreeMap<String, Value> = if source.fields {
+            value
+                .as_object()
+                .into_iter()
+                .flatten()
+                .filter(|(key, _)| !DEVICE_SETTINGS.contains(&key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        } else {
+            value
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|item| record_id(item).map(|id| (id.to_string(), item.clone())))
+                .collect()
+        };
+        collections.insert(source.collection.to_string(), records);
+    }
+    collections
+}
+
+/// Updates a list in place: records keep their position, deleted ones are
+/// dropped and new ones are appended. Items without an ID are not synced
+/// and stay where they are.
+fn apply_list(current: &[Value], records: &BTreeMap<String, Value>) -> Vec<Value> {
+    let mut list: Vec<Value> = current
+        .iter()
+        .filter_map(|item| match record_id(item) {
+            Some(id) => records.get(id).cloned(),
+            None => Some(item.clone()),
+        })
+        .collect();
+    for (id, value) in records {
+        if !current.iter().any(|item| record_id(item) == Some(id)) {
+            list.push(value.clone());
+        }
+    }
+    list
+}
+
+/// Gets a value as an object, replacing it with an empty one if it is not.
+fn object_mut(value: &mut Value) -> &mut Map<String, Value> {
+    if !value.is_object() {
+        *value = Value::Object(Map::new());
+    }
+    match value {
+        Value::Object(object) => object,
+        _ => unreachable!(),
+    }
+}
+
+/// Writes merged collections back into the stores.
+///
+/// Returns the names of the store files that changed.
+pub fn apply(stores: &mut Stores, collections: &Collections) -> Vec<String> {
+    let mut changed = Vec::new();
+    for source in SOURCES {
+        let Some(records) = collections.get(source.collection) else {
+            continue;
+        };
+        if records.is_empty() && source_value(stores, source).is_none() {
+            continue;
+        }
+        let entries = stores.entry(source.file.to_string()).or_default();
+        let stored = entries.entry(source.key.to_string()).or_insert(Value::Null);
+        let before = stored.clone();
+
+        let target = match source.list {
+            Some(field) => object_mut(stored).entry(field).or_insert(Value::Null),
+            None => &mut *stored,
+        };
+        if source.fields {
+            let object = object_mut(target);
+            for (field, value) in records {
+                if !DEVICE_SETTINGS.contains(&field.as_str()) {
+                    object.insert(field.clone(), value.clone());
+                }
+            }
+        } else {
+            let current = target.as_array().cloned().unwrap_or_default();
+            *target = Value::Array(apply_list(&current, records));
+        }
+
+        if *stored != before && !changed.iter().any(|file| file == source.file) {
+            changed.push(source.file.to_string());
+        }
+    }
+    changed
+}
+
+/// Merges the stores with the other devices' change files.
+///
+/// Updates the stores and the sync state in place, and returns this
+/// device's new change file along with a report.
+pub fn sync_stores(
+    state: &mut SyncState,
+    stores: &mut Stores,
+    remotes: &[DeviceChanges],
+    now: i64,
+) -> (DeviceChanges, SyncReport) {
+    let others: Vec<&DeviceChanges> = remotes.iter().filter(|d| d.device_id != state.device_id).collect();
+    let local = merge::stamp_local(&extract(stores), &state.base, &state.device_id, now);
+    let remote = merge::newest_remote(others.iter().map(|d| &d.records));
+    let outcome = merge::merge(&local, &state.base, &remote, &state.device_id, now);
+
+    let changed = apply(stores, &merge::values(&outcome.records));
+    for conflict in &outcome.conflicts {
+        state
+            .conflicts
+            .retain(|c| c.collection != conflict.collection || c.record != conflict.record);
+        state.conflicts.push(conflict.clone());
+    }
+    state.base = outcome.records.clone();
+    state.last_sync = Some(now);
+    state.last_error = None;
+
+    let changes = DeviceChanges {
+        version: FORMAT_VERSION,
+        device_id: state.device_id.clone(),
+        device_name: state.device_name.clone(),
+        updated_at: now,
+        records: outcome.records,
+    };
+    let report = SyncReport {
+        pulled: outcome.pulled,
+        pushed: outcome.pushed,
+        conflicts: outcome.conflicts.len(),
+        devices: others.iter().map(|d| d.device_name.clone()).collect(),
+        changed,
+        finished_at: now,
+    };
+    (changes, report)
+}
+
+/// Syncs the stores through a backend, starting over if this device's
+/// change file was replaced while merging.
+pub async fn sync_with(
+    backend: &Backend,
+    state: &mut SyncState,
+    stores: &mut Stores,
+    now: i64,
+) -> Result<SyncReport, String> {
+    for _ in 0..MAX_ATTEMPTS {
+        let snapshot = backend.read(&state.device_id).await?;
+        let mut merged_state = state.clone();
+        let mut merged_stores = stores.clone();
+        let (changes, report) = sync_stores(&mut merged_state, &mut merged_stores, &snapshot.devices, now);
+        if backend.write(&changes, snapshot.etag.as_deref()).await? {
+            *state = merged_state;
+            *stores = merged_stores;
+            return Ok(report);
+        }
+        log::debug!("Sync file changed on the server while syncing; starting over");
+    }
+    Err("The sync file kept changing on the server; try again later".to_string())
+}
+
+/// Settles a conflict on the chosen side in the stores. The choice reaches
+/// the other devices with the next sync.
+///
+/// Returns the names of the store files that changed.
+pub fn resolve_conflict(state: &mut SyncState, stores: &mut Stores, id: &str, keep: Side) -> Result<Vec<String>, String> {
+    let index = state
+        .conflicts
+        .iter()
+        .position(|c| c.id == id)
+        .ok_or_else(|| format!("Sync conflict not found: {}", id))?;
+    let conflict = state.conflicts.remove(index);
+    if keep == conflict.kept {
+        return Ok(Vec::new());
+    }
+
+    let mut collections = extract(stores);
+    let records = collections.entry(conflict.collection.clone()).or_default();
+    match merge::resolve(records.get(&conflict.record), &conflict, keep) {
+        Some(value) => records.insert(conflict.record.clone(), value),
+        None => records.remove(&conflict.record),
+    };
+    Ok(apply(stores, &collections))
+}
+
+/// Names this device after its host name.
+fn device_name() -> String {
+    std::fs::read_to_string("/etc/hostname")
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .unwrap_or_else(|| "Notive device".to_string())
+}
+
+/// Serializes sync runs, so a manual sync and the startup sync cannot
+/// interleave their reads and writes.
+static SYNC_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// Loads the sync state, giving this device an identity on first use.
+fn load_state<R: Runtime>(app: &AppHandle<R>) -> Result<SyncState, String> {
+    let mut state = persistence::load(app).map_err(|e| e.to_string())?;
+    if state.device_id.is_empty() {
+        state.device_id = uuid::Uuid::new_v4().to_string();
+        state.device_name = device_name();
+        persistence::save(app, &state).map_err(|e| e.to_string())?;
+    }
+    Ok(state)
+}
+
+/// Opens the configured backend.
+fn backend(state: &SyncState) -> Result<Backend, String> {
+    if let Some(account) = &state.webdav {
+        let password = token_storage::get_token(SECRET_SERVICE, webdav::PASSWORD_ACCOUNT)?
+            .map(|token| token.token)
+            .ok_or_else(|| "The WebDAV password is missing; enter it again".to_string())?;
+        return webdav::WebDavBackend::new(&account.url, &account.username, &password).map(Backend::WebDav);
+    }
+    match &state.folder {
+        Some(folder) => Ok(Backend::Folder(FolderBackend::new(PathBuf::from(folder)))),
+        None => Err("Choose a sync folder or WebDAV server first".to_string()),
+    }
+}
+
+async fn sync_app<R: Runtime>(app: &AppHandle<R>, state: &mut SyncState) -> Result<SyncReport, String> {
+    let backend = backend(state)?;
+    let mut stores = crate::backup::persistence::load_stores(app).map_err(|e| e.to_string())?;
+    let report = sync_with(&backend, state, &mut stores, chrono::Utc::now().timestamp_millis()).await?;
+    crate::backup::persistence::save_stores(app, &stores, &report.changed).map_err(|e| e.to_string())?;
+    Ok(report)
+}
+
+/// Runs a sync with the configured folder or server.
+pub async fn run<R: Runtime>(app: &AppHandle<R>) -> Result<SyncReport, String> {
+    let _guard = SYNC_LOCK.lock().await;
+    let mut state = load_state(app)?;
+    let result = sync_app(app, &mut state).await;
+
+    match &result {
+        Ok(report) => {
+            log::info!(
+                "Synced with {} device(s): {} pulled, {} pushed, {} conflict(s)",
+                report.devices.len(),
+                report.pulled,
+                report.pushed,
+                report.conflicts
+            );
+            state.last_report = Some(report.clone());
+            let _ = app.emit(SYNCED_EVENT, report);
+        }
+        Err(e) => {
+            log::warn!("Sync failed: {}", e);
+            state.last_error = Some(e.clone());
+        }
+    }
+    persistence::save(app, &state).map_err(|e| e.to_string())?;
+    result
+}
+
+/// Syncs at startup and then every [`SyncState::interval_minutes`], while
+/// sync is set up.
+pub fn start_background<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last_run: Option<std::time::Instant> = None;
+        let mut interval = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let Ok(state) = load_state(&app) else {
+                continue;
+            };
+            let due = match last_run {
+                None => true,
+                Some(last_run) => {
+                    state.interval_minutes > 0
+                        && last_run.elapsed() >= std::time::Duration::from_secs(u64::from(state.interval_minutes) * 60)
+                }
+            };
+            if state.is_enabled() && due {
+                last_run = Some(std::time::Instant::now());
+                // Failures are kept in the status
+                let _ = run(&app).await;
+            }
+        }
+    });
+}
+
+/// Gets the sync status.
+#[tauri::command]
+pub async fn get_sync_status<R: Runtime>(app: AppHandle<R>) -> Result<SyncStatus, String> {
+    Ok(SyncStatus::from(&load_state(&app)?))
+}
+
+/// Syncs through a folder, or turns sync off with `None`, and syncs right away.
+#[tauri::command]
+pub async fn set_sync_folder<R: Runtime>(app: AppHandle<R>, folder: Option<String>) -> Result<SyncStatus, String> {
+    if let Some(folder) = &folder {
+        if !std::path::Path::new(folder).is_dir() {
+            return Err(format!("Not a folder: {}", folder));
+        }
+    }
+    {
+        let _guard = SYNC_LOCK.lock().await;
+        let mut state = load_state(&app)?;
+        if state.webdav.is_some() {
+            let _ = token_storage::delete_token(SECRET_SERVICE, webdav::PASSWORD_ACCOUNT);
+        }
+        state.set_target(folder.clone(), None);
+        persistence::save(&app, &state).map_err(|e| e.to_string())?;
+    }
+    if folder.is_some() {
+        // Failures are kept in the status
+        let _ = run(&app).await;
+    }
+    Ok(SyncStatus::from(&load_state(&app)?))
+}
+
+/// Syncs through a WebDAV server and syncs right away. Without a password
+/// the stored one is kept.
+#[tauri::command]
+pub async fn set_webdav_sync<R: Runtime>(
+    app: AppHandle<R>,
+    url: String,
+    username: String,
+    password: Option<String>,
+) -> Result<SyncStatus, String> {
+    let url = webdav::parse_url(&url)?.to_string();
+    {
+        let _guard = SYNC_LOCK.lock().await;
+        match password.filter(|p| !p.is_empty()) {
+            Some(password) => token_storage::store_token(
+                SECRET_SERVICE,
+                webdav::PASSWORD_ACCOUNT,
+                StoredToken {
+                    token: password,
+                    expires_at: None,
+                    refresh_token: None,
+                    created_at: chrono::Utc::now().timestamp() as u64,
+                },
+            )?,
+            None if token_storage::get_token(SECRET_SERVICE, webdav::PASSWORD_ACCOUNT)?.is_none() => {
+                return Err("Enter the WebDAV password".to_string());
+            }
+            None => {}
+        }
+        let mut state = load_state(&app)?;
+        state.set_target(None, Some(WebDavAccount { url, username }));
+        persistence::save(&app, &state).map_err(|e| e.to_string())?;
+    }
+    // Failures are kept in the status
+    let _ = run(&app).await;
+    Ok(SyncStatus::from(&load_state(&app)?))
+}
+
+/// Sets the minutes between background syncs; 0 turns them off.
+#[tauri::command]
+pub async fn set_sync_interval<R: Runtime>(app: AppHandle<R>, minutes: u32) -> Result<SyncStatus, String> {
+    let _guard = SYNC_LOCK.lock().await;
+    let mut state = load_state(&app)?;
+    state.interval_minutes = minutes;
+    persistence::save(&app, &state).map_err(|e| e.to_string())?;
+    Ok(SyncStatus::from(&state))
+}
+
+/// Syncs now and reports the result.
+#[tauri::command]
+pub async fn sync_now<R: Runtime>(app: AppHandle<R>) -> Result<SyncStatus, String> {
+    run(&app).await?;
+    Ok(SyncStatus::from(&load_state(&app)?))
+}
+
+/// Settles a sync conflict by keeping the given side.
+#[tauri::command]
+pub async fn resolve_sync_conflict<R: Runtime>(app: AppHandle<R>, id: String, keep: Side) -> Result<SyncStatus, String> {
+    {
+        let _guard = SYNC_LOCK.lock().await;
+        let mut state = load_state(&app)?;
+        let mut stores = crate::backup::persistence::load_stores(&app).map_err(|e| e.to_string())?;
+        let changed = resolve_conflict(&mut state, &mut stores, &id, keep)?;
+        crate::backup::persistence::save_stores(&app, &stores, &changed).map_err(|e| e.to_string())?;
+        persistence::save(&app, &state).map_err(|e| e.to_string())?;
+    }
+    if load_state(&app)?.is_enabled() {
+        let _ = run(&app).await;
+    }
+    Ok(SyncStatus::from(&load_state(&app)?))
+}
@@ -0,0 +1,36 @@
+//! Sync state persistence.
+
+use super::SyncState;
+use serde_json;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_store::{with_store, StoreCollection};
+
+const SYNC_STORE_PATH: &str = "sync.json";
+
+/// Loads the sync state from persistent storage.
+pub fn load<R: Runtime>(app: &AppHandle<R>) -> Result<SyncState, Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+
+    with_store(app, stores, SYNC_STORE_PATH, |store| {
+        if let Some(value) = store.get("sync_state") {
+            serde_json::from_value(value.clone()).map_err(|e| tauri_plugin_store::Error::Deserialize(e.to_string()))
+        } else {
+            Ok(SyncState::default())
+        }
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
+
+/// Saves the sync state to persistent storage.
+pub fn save<R: Runtime>(app: &AppHandle<R>, state: &SyncState) -> Result<(), Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+
+    with_store(app, stores, SYNC_STORE_PATH, |store| {
+        let data = serde_json::to_value(state).map_err(|e| tauri_plugin_store::Error::Serialize(e.to_string()))?;
+        store.insert("sync_state".to_string(), data)?;
+        store.save()?;
+        log::debug!("Sync state saved to store");
+        Ok(())
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
@@ -0,0 +1,244 @@
+//! Tests for syncing between devices.
+//!
+//! Each simulated device has its own stores and its own copy of the synced
+//! folder; `replicate` plays the part of Syncthing by copying change files
+//! between the folders.
+
+use super::folder::{FolderBackend, SYNC_DIR};
+use super::*;
+use serde_json::json;
+use tempfile::TempDir;
+
+struct Device {
+    state: SyncState,
+    stores: Stores,
+    folder: TempDir,
+}
+
+impl Device {
+    fn new(id: &str, stores: Value) -> Self {
+        Self {
+            state: SyncState {
+                device_id: id.to_string(),
+                device_name: format!("{} desktop", id),
+                ..SyncState::default()
+            },
+            stores: serde_json::from_value(stores).unwrap(),
+            folder: tempfile::tempdir().unwrap(),
+        }
+    }
+
+    fn sync(&mut self, now: i64) -> SyncReport {
+        let backend = FolderBackend::new(self.folder.path().to_path_buf());
+        let remotes = backend.read_devices().unwrap();
+        let (changes, report) = sync_stores(&mut self.state, &mut self.stores, &remotes, now);
+        backend.write(&changes).unwrap();
+        report
+    }
+
+    fn bookmarks(&self) -> &Value {
+        &self.stores["bookmarks.json"]["bookmarks"]
+    }
+
+    fn bookmarks_mut(&mut self) -> &mut Vec<Value> {
+        self.stores
+            .get_mut("bookmarks.json")
+            .and_then(|entries| entries.get_mut("bookmarks"))
+            .and_then(Value::as_array_mut)
+            .unwrap()
+    }
+
+    fn settings_mut(&mut self) -> &mut Map<String, Value> {
+        self.stores
+            .get_mut("settings.json")
+            .and_then(|entries| entries.get_mut("settings"))
+            .and_then(Value::as_object_mut)
+            .unwrap()
+    }
+}
+
+/// Copies each device's change file into the other device's folder.
+fn replicate(a: &Device, b: &Device) {
+    for (from, to) in [(a, b), (b, a)] {
+        let name = format!("{}.json", from.state.device_id);
+        let source = from.folder.path().join(SYNC_DIR).join(&name);
+        let target = to.folder.path().join(SYNC_DIR);
+        std::fs::create_dir_all(&target).unwrap();
+        if source.exists() {
+            std::fs::copy(source, target.join(name)).unwrap();
+        }
+    }
+}
+
+fn bookmark(id: &str, title: &str) -> Value {
+    json!({ "id": id, "title": title, "url": format!("https://www.notion.so/{}", id), "tags": [] })
+}
+
+/// Two devices that have synced the same bookmark once.
+fn paired() -> (Device, Device) {
+    let mut a = Device::new("device-a", json!({ "bookmarks.json": { "bookmarks": [bookmark("b1", "Wiki")] } }));
+    let mut b = Device::new("device-b", json!({}));
+    a.sync(1_000);
+    replicate(&a, &b);
+    b.sync(2_000);
+    replicate(&a, &b);
+    (a, b)
+}
+
+#[test]
+fn test_bookmarks_follow_between_devices() {
+    let (mut a, mut b) = paired();
+    assert_eq!(b.bookmarks(), &json!([bookmark("b1", "Wiki")]));
+
+    // Added on one device
+    b.bookmarks_mut().push(bookmark("b2", "Roadmap"));
+    let report = b.sync(3_000);
+    assert_eq!(report.pushed, 1);
+    replicate(&a, &b);
+    let report = a.sync(4_000);
+    assert_eq!(report.pulled, 1);
+    assert_eq!(report.devices, vec!["device-b desktop".to_string()]);
+    assert_eq!(report.changed, vec!["bookmarks.json".to_string()]);
+    assert_eq!(a.bookmarks(), &json!([bookmark("b1", "Wiki"), bookmark("b2", "Roadmap")]));
+
+    // Deleted on the other
+    a.bookmarks_mut().remove(0);
+    a.sync(5_000);
+    replicate(&a, &b);
+    b.sync(6_000);
+    assert_eq!(b.bookmarks(), &json!([bookmark("b2", "Roadmap")]));
+
+    // Nothing changed, nothing to do
+    replicate(&a, &b);
+    let report = a.sync(7_000);
+    assert_eq!((report.pulled, report.pushed, report.changed.len()), (0, 0, 0));
+}
+
+#[test]
+fn test_edits_to_different_fields_merge() {
+    let (mut a, mut b) = paired();
+
+    a.bookmarks_mut()[0]["title"] = json!("Team wiki");
+    b.bookmarks_mut()[0]["tags"] = json!(["work"]);
+    a.sync(3_000);
+    b.sync(3_500);
+    replicate(&a, &b);
+    a.sync(4_000);
+    b.sync(4_000);
+
+    let expected = json!({ "id": "b1", "title": "Team wiki", "url": "https://www.notion.so/b1", "tags": ["work"] });
+    assert_eq!(a.bookmarks()[0], expected);
+    assert_eq!(b.bookmarks()[0], expected);
+    assert!(a.state.conflicts.is_empty() && b.state.conflicts.is_empty());
+}
+
+#[test]
+fn test_conflicting_edits_are_surfaced_and_resolvable() {
+    let (mut a, mut b) = paired();
+
+    a.bookmarks_mut()[0]["title"] = json!("Wiki (A)");
+    b.bookmarks_mut()[0]["title"] = json!("Wiki (B)");
+    a.sync(3_000);
+    b.sync(3_500);
+    replicate(&a, &b);
+    let report = a.sync(4_000);
+    b.sync(4_000);
+
+    // Both settle on the more recent edit and report the conflict
+    assert_eq!(report.conflicts, 1);
+    assert_eq!(a.bookmarks()[0]["title"], json!("Wiki (B)"));
+    assert_eq!(b.bookmarks()[0]["title"], json!("Wiki (B)"));
+    let conflict = a.state.conflicts[0].clone();
+    assert_eq!((conflict.collection.as_str(), conflict.record.as_str()), ("bookmarks", "b1"));
+    assert_eq!(conflict.fields, vec!["title".to_string()]);
+    assert_eq!(conflict.kept, Side::Remote);
+
+    // Keeping the local edit sends it to the other device
+    let changed = resolve_conflict(&mut a.state, &mut a.stores, &conflict.id, Side::Local).unwrap();
+    assert_eq!(changed, vec!["bookmarks.json".to_string()]);
+    assert!(a.state.conflicts.is_empty());
+    a.sync(5_000);
+    replicate(&a, &b);
+    b.sync(6_000);
+    assert_eq!(b.bookmarks()[0]["title"], json!("Wiki (A)"));
+    assert!(resolve_conflict(&mut a.state, &mut a.stores, &conflict.id, Side::Local).is_err());
+}
+
+#[test]
+fn test_settings_sync_except_device_specific_ones() {
+    let mut a = Device::new(
+        "device-a",
+        json!({ "settings.json": { "settings": {
+            "zoom_level": 1.0,
+            "theme": "dark",
+            "download_folder": "/home/a/Downloads",
+            "update_mirror": "/mnt/a/notive-releases"
+        } } }),
+    );
+    let mut b = Device::new(
+        "device-b",
+        json!({ "settings.json": { "settings": {
+            "zoom_level": 1.0,
+            "theme": "light",
+            "download_folder": "/home/b/Downloads",
+            "update_mirror": null
+        } } }),
+    );
+    a.sync(1_000);
+    a.settings_mut().insert("zoom_level".to_string(), json!(1.25));
+    a.sync(2_000);
+    replicate(&a, &b);
+
+    // The joining device takes on the synced settings
+    b.sync(3_000);
+    let settings = &b.stores["settings.json"]["settings"];
+    assert_eq!(settings["zoom_level"], json!(1.25));
+    assert_eq!(settings["theme"], json!("dark"));
+    assert_eq!(settings["download_folder"], json!("/home/b/Downloads"));
+    assert_eq!(settings["update_mirror"], json!(null));
+    assert!(b.state.conflicts.is_empty());
+}
+
+#[test]
+fn test_capture_templates_keep_the_rest_of_the_config() {
+    let mut a = Device::new(
+        "device-a",
+        json!({ "settings.json": { "quick_capture_config": {
+            "templates": [{ "id": "t1", "name": "Inbox", "url": "https://www.notion.so/new" }],
+            "enable_tags": true
+        } } }),
+    );
+    let mut b = Device::new(
+        "device-b",
+        json!({ "settings.json": { "quick_capture_config": { "templates": [], "enable_tags": false } } }),
+    );
+    a.sync(1_000);
+    replicate(&a, &b);
+    b.sync(2_000);
+
+    let config = &b.stores["settings.json"]["quick_capture_config"];
+    assert_eq!(config["templates"][0]["name"], json!("Inbox"));
+    assert_eq!(config["enable_tags"], json!(false));
+}
+
+#[test]
+fn test_apply_keeps_order_and_unsynced_items() {
+    let mut stores: Stores = serde_json::from_value(json!({
+        "bookmarks.json": { "bookmarks": [bookmark("b2", "Two"), { "title": "No ID" }, bookmark("b1", "One")] }
+    }))
+    .unwrap();
+    let mut collections = extract(&stores);
+    assert_eq!(collections["bookmarks"].len(), 2);
+
+    let bookmarks = collections.get_mut("bookmarks").unwrap();
+    bookmarks.insert("b1".to_string(), bookmark("b1", "One (edited)"));
+    bookmarks.insert("b0".to_string(), bookmark("b0", "Zero"));
+    bookmarks.remove("b2");
+    apply(&mut stores, &collections);
+
+    assert_eq!(
+        stores["bookmarks.json"]["bookmarks"],
+        json!([{ "title": "No ID" }, bookmark("b1", "One (edited)"), bookmark("b0", "Zero")])
+    );
+    assert!(apply(&mut stores, &collections).is_empty());
+}
@@ -0,0 +1,251 @@
+//! Change files on a WebDAV server such as Nextcloud.
+//!
+//! The change files live in a `notive-sync/` collection under the
+//! configured URL, one per device, as in a synced folder. Writes carry the
+//! ETag read at the start of the sync (`If-Match`, or `If-None-Match: *`
+//! for a new file), so a file changed on the server in the meantime is
+//! never overwritten: the write is refused and the sync starts over.
+
+#[cfg(test)]
+mod tests;
+
+use super::folder::{is_change_file, SYNC_DIR};
+use super::{DeviceChanges, Snapshot, FORMAT_VERSION};
+use reqwest::{Method, StatusCode};
+use std::path::Path;
+use url::Url;
+
+/// Keyring account holding the WebDAV password.
+pub const PASSWORD_ACCOUNT: &str = "webdav_password";
+
+const PROPFIND_BODY: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<d:propfind xmlns:d="DAV:"><d:prop><d:getetag/></d:prop></d:propfind>"#;
+
+/// Reads and writes change files on a WebDAV server.
+#[derive(Debug, Clone)]
+pub struct WebDavBackend {
+    client: reqwest::Client,
+    collection: Url,
+    username: String,
+    password: String,
+}
+
+/// Checks a server URL, returning it with a trailing slash.
+pub fn parse_url(url: &str) -> Result<Url, String> {
+    let mut url = Url::parse(url.trim()).map_err(|e| format!("Invalid WebDAV URL: {}", e))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("The WebDAV URL must start with https:// or http://".to_string());
+    }
+    if !url.path().ends_with('/') {
+        let path = format!("{}/", url.path());
+        url.set_path(&path);
+    }
+    Ok(url)
+}
+
+/// Finds the contents of the elements with the given local name, whatever
+/// their namespace prefix. Nested elements of the same name are not
+/// expected in WebDAV responses.
+fn elements<'a>(xml: &'a str, name: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let tag = &rest[..tag_end];
+        let qualified = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+        let local = qualified.rsplit(':').next().unwrap_or("");
+        if tag.starts_with(['/', '?', '!']) || local != name {
+            continue;
+        }
+        if tag.ends_with('/') {
+            found.push("");
+            continue;
+        }
+        let body = &rest[tag_end + 1..];
+        let close = format!("</{}>", qualified);
+        let Some(end) = body.find(&close) else {
+            break;
+        };
+        found.push(&body[..end]);
+        rest = &body[end + close.len()..];
+    }
+    found
+}
+
+fn unescape(text: &str) -> String {
+    text.trim()
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+/// Reads the file names and ETags from a `PROPFIND` response, leaving out
+/// collections.
+pub fn parse_multistatus(xml: &str) -> Vec<(String, String)> {
+    elements(xml, "response")
+        .into_iter()
+        .filter_map(|response| {
+            let href = unescape(elements(response, "href").first()?);
+            if href.ends_with('/') {
+                return None;
+            }
+            let name = href.rsplit('/').next()?.to_string();
+            let etag = unescape(elements(response, "getetag").first().copied().unwrap_or(""));
+            if etag.is_empty() {
+                return None;
+            }
+            // Some servers leave out the quotes required in If-Match
+            let etag = if etag.starts_with('"') || etag.starts_with("W/") {
+                etag
+            } else {
+                format!("\"{}\"", etag)
+            };
+            Some((name, etag))
+        })
+        .collect()
+}
+
+impl WebDavBackend {
+    pub fn new(url: &str, username: &str, password: &str) -> Result<Self, String> {
+        let collection = parse_url(url)?
+            .join(&format!("{}/", SYNC_DIR))
+            .map_err(|e| format!("Invalid WebDAV URL: {}", e))?;
+        Ok(Self {
+            client: reqwest::Client::new(),
+            collection,
+            username: username.to_string(),
+            password: password.to_string(),
+        })
+    }
+
+    fn request(&self, method: Method, url: Url) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .basic_auth(&self.username, Some(&self.password))
+    }
+
+    fn file_url(&self, name: &str) -> Result<Url, String> {
+        self.collection.join(name).map_err(|e| format!("Invalid file name {}: {}", name, e))
+    }
+
+    /// Turns an unexpected response into an error message.
+    fn error(action: &str, status: StatusCode) -> String {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                "The WebDAV server refused the user name or password".to_string()
+            }
+            status => format!("Failed to {}: the WebDAV server answered {}", action, status),
+        }
+    }
+
+    /// Lists the change files with their ETags; `None` if the collection
+    /// does not exist yet.
+    async fn list(&self) -> Result<Option<Vec<(String, String)>>, String> {
+        let response = self
+            .request(Method::from_bytes(b"PROPFIND").expect("valid method"), self.collection.clone())
+            .header("Depth", "1")
+            .header("Content-Type", "application/xml; charset=utf-8")
+            .body(PROPFIND_BODY)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach the WebDAV server: {}", e))?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            StatusCode::MULTI_STATUS => {
+                let xml = response.text().await.map_err(|e| format!("Failed to list sync files: {}", e))?;
+                Ok(Some(parse_multistatus(&xml)))
+            }
+            status => Err(Self::error("list sync files", status)),
+        }
+    }
+
+    /// Reads the change files of every device, and the ETag of this
+    /// device's own file. Unreadable files and files from newer versions
+    /// of Notive are skipped with a warning.
+    pub async fn read(&self, device_id: &str) -> Result<Snapshot, String> {
+        let mut snapshot = Snapshot::default();
+        for (name, etag) in self.list().await?.unwrap_or_default() {
+            if !is_change_file(Path::new(&name)) {
+                continue;
+            }
+            if name == format!("{}.json", device_id) {
+                snapshot.etag = Some(etag);
+                continue;
+            }
+
+            let response = self
+                .request(Method::GET, self.file_url(&name)?)
+                .send()
+                .await
+                .map_err(|e| format!("Failed to download {}: {}", name, e))?;
+            if !response.status().is_success() {
+                // Deleted since it was listed
+                log::warn!("Skipping sync file {}: {}", name, response.status());
+                continue;
+            }
+            let bytes = response.bytes().await.map_err(|e| format!("Failed to download {}: {}", name, e))?;
+            match serde_json::from_slice::<DeviceChanges>(&bytes) {
+                Ok(changes) if changes.version > FORMAT_VERSION => {
+                    log::warn!("Skipping sync file {} from a newer version of Notive", name);
+                }
+                Ok(changes) => snapshot.devices.push(changes),
+                Err(e) => log::warn!("Skipping unreadable sync file {}: {}", name, e),
+            }
+        }
+        snapshot.devices.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        Ok(snapshot)
+    }
+
+    /// Writes this device's change file if it still has the given ETag
+    /// (or, without one, does not exist). Returns `false` if the file was
+    /// changed on the server since it was read.
+    pub async fn write(&self, changes: &DeviceChanges, etag: Option<&str>) -> Result<bool, String> {
+        let url = self.file_url(&format!("{}.json", changes.device_id))?;
+        let bytes = serde_json::to_vec_pretty(changes).map_err(|e| format!("Failed to serialize sync file: {}", e))?;
+
+        for attempt in 0..2 {
+            let request = self
+                .request(Method::PUT, url.clone())
+                .header("Content-Type", "application/json")
+                .body(bytes.clone());
+            let request = match etag {
+                Some(etag) => request.header("If-Match", etag),
+                None => request.header("If-None-Match", "*"),
+            };
+            let status = request
+                .send()
+                .await
+                .map_err(|e| format!("Failed to upload the sync file: {}", e))?
+                .status();
+
+            match status {
+                status if status.is_success() => return Ok(true),
+                StatusCode::PRECONDITION_FAILED => return Ok(false),
+                // The collection does not exist yet
+                StatusCode::CONFLICT | StatusCode::NOT_FOUND if attempt == 0 => self.create_collection().await?,
+                status => return Err(Self::error("upload the sync file", status)),
+            }
+        }
+        Err("Failed to create the sync folder on the WebDAV server".to_string())
+    }
+
+    async fn create_collection(&self) -> Result<(), String> {
+        let status = self
+            .request(Method::from_bytes(b"MKCOL").expect("valid method"), self.collection.clone())
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach the WebDAV server: {}", e))?
+            .status();
+        // 405 means it already exists
+        if status.is_success() || status == StatusCode::METHOD_NOT_ALLOWED {
+            Ok(())
+        } else {
+            Err(Self::error("create the sync folder", status))
+        }
+    }
+}
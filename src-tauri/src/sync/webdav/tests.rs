@@ -0,0 +1,262 @@
+//! Tests for the WebDAV backend, against an in-process WebDAV stand-in.
+
+use super::*;
+use crate::backup::Stores;
+use crate::sync::{sync_with, Backend, SyncState};
+use crate::utils::test_server::{Request, Response, TestServer};
+use base64::Engine;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+const USERNAME: &str = "alice";
+const PASSWORD: &str = "app-password";
+const COLLECTION: &str = "/dav/notive-sync/";
+
+/// File name to contents and version; the ETag is the quoted version.
+type Files = BTreeMap<String, (Vec<u8>, u64)>;
+
+/// Just enough of a WebDAV server for the sync: `PROPFIND`, `MKCOL`, `GET`
+/// and conditional `PUT` on a single collection, with Basic auth.
+struct DavServer {
+    http: TestServer,
+    collection: DavCollection,
+}
+
+/// Files of the collection served by a [`DavServer`].
+#[derive(Clone, Default)]
+struct DavCollection {
+    files: Arc<Mutex<Files>>,
+    exists: Arc<AtomicBool>,
+    version: Arc<AtomicU64>,
+    /// `PUT`s to answer as if another client had just replaced the file.
+    interfere: Arc<AtomicUsize>,
+}
+
+impl DavServer {
+    async fn start() -> Self {
+        let collection = DavCollection::default();
+        let handler = collection.clone();
+        let http = TestServer::start(move |request| handler.handle(request)).await;
+        Self { http, collection }
+    }
+
+    fn url(&self) -> String {
+        self.http.url("dav")
+    }
+
+    fn backend(&self) -> Backend {
+        Backend::WebDav(WebDavBackend::new(&self.url(), USERNAME, PASSWORD).unwrap())
+    }
+
+    fn requests(&self) -> Vec<String> {
+        self.http
+            .requests()
+            .iter()
+            .map(|request| format!("{} {}", request.method(), request.path()))
+            .collect()
+    }
+}
+
+impl DavCollection {
+    fn handle(&self, request: &Request) -> Response {
+        let credentials = base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", USERNAME, PASSWORD));
+        if request.header("authorization") != Some(format!("Basic {}", credentials)) {
+            return Response::new("401 Unauthorized");
+        }
+
+        let mut files = self.files.lock().unwrap();
+        let exists = self.exists.load(Ordering::SeqCst);
+        match (request.method(), request.path().strip_prefix(COLLECTION)) {
+            ("PROPFIND", Some("")) if !exists => Response::new("404 Not Found"),
+            ("PROPFIND", Some("")) => {
+                let mut xml = format!(
+                    r#"<?xml version="1.0"?><d:multistatus xmlns:d="DAV:"><d:response><d:href>{}</d:href><d:propstat><d:prop><d:getetag/></d:prop></d:propstat></d:response>"#,
+                    COLLECTION
+                );
+                for (name, (_, version)) in files.iter() {
+                    xml.push_str(&format!(
+                        "<d:response><d:href>{}{}</d:href><d:propstat><d:prop><d:getetag>&quot;{}&quot;</d:getetag></d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+                        COLLECTION, name, version
+                    ));
+                }
+                xml.push_str("</d:multistatus>");
+                Response::new("207 Multi-Status")
+                    .header("Content-Type", "application/xml")
+                    .body(xml.into_bytes())
+            }
+            ("MKCOL", Some("")) if exists => Response::new("405 Method Not Allowed"),
+            ("MKCOL", Some("")) => {
+                self.exists.store(true, Ordering::SeqCst);
+                Response::new("201 Created")
+            }
+            ("GET", Some(name)) => match files.get(name) {
+                Some((contents, version)) => Response::new("200 OK")
+                    .header("ETag", &format!("\"{}\"", version))
+                    .body(contents.clone()),
+                None => Response::new("404 Not Found"),
+            },
+            ("PUT", Some(_)) if !exists => Response::new("409 Conflict"),
+            ("PUT", Some(name)) => {
+                // Requests are handled one at a time while `files` is locked
+                let interfere = self.interfere.load(Ordering::SeqCst);
+                if interfere > 0 {
+                    self.interfere.store(interfere - 1, Ordering::SeqCst);
+                    let version = self.version.fetch_add(1, Ordering::SeqCst) + 1;
+                    let contents = files.get(name).map(|(c, _)| c.clone()).unwrap_or_default();
+                    files.insert(name.to_string(), (contents, version));
+                    return Response::new("412 Precondition Failed");
+                }
+                let current = files.get(name).map(|(_, version)| format!("\"{}\"", version));
+                let allowed = match (request.header("if-match"), request.header("if-none-match")) {
+                    (Some(etag), _) => current.as_deref() == Some(etag.as_str()),
+                    (None, Some(any)) if any == "*" => current.is_none(),
+                    _ => true,
+                };
+                if !allowed {
+                    return Response::new("412 Precondition Failed");
+                }
+                let version = self.version.fetch_add(1, Ordering::SeqCst) + 1;
+                files.insert(name.to_string(), (request.body.clone(), version));
+                Response::new("201 Created").header("ETag", &format!("\"{}\"", version))
+            }
+            _ => Response::new("405 Method Not Allowed"),
+        }
+    }
+}
+
+fn device(id: &str) -> SyncState {
+    SyncState {
+        device_id: id.to_string(),
+        device_name: id.to_string(),
+        ..SyncState::default()
+    }
+}
+
+fn stores(value: serde_json::Value) -> Stores {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn test_parse_url() {
+    assert_eq!(
+        parse_url(" https://cloud.example.com/remote.php/dav/files/alice ").unwrap().as_str(),
+        "https://cloud.example.com/remote.php/dav/files/alice/"
+    );
+    assert!(parse_url("ftp://cloud.example.com/").is_err());
+    assert!(parse_url("not a url").is_err());
+}
+
+#[test]
+fn test_parse_multistatus() {
+    let xml = r#"<?xml version="1.0"?>
+<d:multistatus xmlns:d="DAV:" xmlns:oc="http://owncloud.org/ns">
+  <d:response>
+    <d:href>/remote.php/dav/files/alice/notive-sync/</d:href>
+    <d:propstat><d:prop><d:getetag>&quot;65f0&quot;</d:getetag></d:prop></d:propstat>
+  </d:response>
+  <d:response>
+    <d:href>/remote.php/dav/files/alice/notive-sync/a.json</d:href>
+    <d:propstat><d:prop><d:getetag>&quot;65f1&quot;</d:getetag></d:prop></d:propstat>
+  </d:response>
+  <D:response xmlns:D="DAV:">
+    <D:href>/remote.php/dav/files/alice/notive-sync/b.json</D:href>
+    <D:propstat><D:prop><D:getetag>abc</D:getetag></D:prop></D:propstat>
+  </D:response>
+  <d:response>
+    <d:href>/remote.php/dav/files/alice/notive-sync/c.json</d:href>
+    <d:propstat><d:prop><d:getetag/></d:prop></d:propstat>
+  </d:response>
+</d:multistatus>"#;
+
+    assert_eq!(
+        parse_multistatus(xml),
+        vec![
+            ("a.json".to_string(), "\"65f1\"".to_string()),
+            ("b.json".to_string(), "\"abc\"".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_two_devices_sync_through_the_server() {
+    let server = DavServer::start().await;
+    let bookmark = json!({ "id": "b1", "title": "Wiki", "url": "https://www.notion.so/wiki" });
+
+    let mut a = device("device-a");
+    let mut a_stores = stores(json!({ "bookmarks.json": { "bookmarks": [bookmark.clone()] } }));
+    let report = sync_with(&server.backend(), &mut a, &mut a_stores, 1_000).await.unwrap();
+    assert_eq!(report.pushed, 1);
+    // The collection is created on first upload
+    assert!(server.requests().contains(&"MKCOL /dav/notive-sync/".to_string()));
+
+    let mut b = device("device-b");
+    let mut b_stores = Stores::new();
+    let report = sync_with(&server.backend(), &mut b, &mut b_stores, 2_000).await.unwrap();
+    assert_eq!(report.pulled, 1);
+    assert_eq!(report.devices, vec!["device-a".to_string()]);
+    assert_eq!(b_stores["bookmarks.json"]["bookmarks"], json!([bookmark]));
+
+    // The second upload of a device replaces its file by ETag
+    b_stores
+        .get_mut("bookmarks.json")
+        .and_then(|entries| entries.get_mut("bookmarks"))
+        .and_then(|list| list.as_array_mut())
+        .unwrap()[0]["title"] = json!("Team wiki");
+    sync_with(&server.backend(), &mut b, &mut b_stores, 3_000).await.unwrap();
+    sync_with(&server.backend(), &mut a, &mut a_stores, 4_000).await.unwrap();
+    assert_eq!(a_stores["bookmarks.json"]["bookmarks"][0]["title"], json!("Team wiki"));
+    assert_eq!(server.collection.files.lock().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_write_refuses_stale_etags() {
+    let server = DavServer::start().await;
+    let backend = WebDavBackend::new(&server.url(), USERNAME, PASSWORD).unwrap();
+    let mut changes = crate::sync::DeviceChanges {
+        version: FORMAT_VERSION,
+        device_id: "device-a".to_string(),
+        device_name: "device-a".to_string(),
+        updated_at: 1,
+        records: Default::default(),
+    };
+
+    assert!(backend.write(&changes, None).await.unwrap());
+    let etag = backend.read("device-a").await.unwrap().etag.unwrap();
+    // Created already, so a write expecting a new file is refused
+    assert!(!backend.write(&changes, None).await.unwrap());
+
+    changes.updated_at = 2;
+    assert!(backend.write(&changes, Some(&etag)).await.unwrap());
+    assert!(!backend.write(&changes, Some(&etag)).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_sync_starts_over_when_the_file_changes_meanwhile() {
+    let server = DavServer::start().await;
+    let mut state = device("device-a");
+    let mut stores = stores(json!({ "templates.json": { "templates": [{ "id": "t1", "name": "Meeting" }] } }));
+    sync_with(&server.backend(), &mut state, &mut stores, 1_000).await.unwrap();
+
+    let puts = || server.requests().iter().filter(|r| r.starts_with("PUT")).count();
+    let before = puts();
+
+    server.collection.interfere.store(1, Ordering::SeqCst);
+    sync_with(&server.backend(), &mut state, &mut stores, 2_000).await.unwrap();
+    assert_eq!(puts() - before, 2, "one refused, one retried");
+
+    // A file that keeps changing gives up instead of looping
+    server.collection.interfere.store(usize::MAX, Ordering::SeqCst);
+    let before = state.clone();
+    assert!(sync_with(&server.backend(), &mut state, &mut stores, 3_000).await.is_err());
+    assert_eq!(state, before);
+}
+
+#[tokio::test]
+async fn test_wrong_password_is_reported() {
+    let server = DavServer::start().await;
+    let backend = Backend::WebDav(WebDavBackend::new(&server.url(), USERNAME, "wrong").unwrap());
+    let err = sync_with(&backend, &mut device("device-a"), &mut Stores::new(), 1_000).await.unwrap_err();
+    assert!(err.contains("refused"), "{}", err);
+}
@@ -5,6 +5,7 @@
 
 mod persistence;
 
+use crate::notion_url::same_page;
 use persistence::{load_templates, save_templates};
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Manager, Runtime};
@@ -48,6 +49,11 @@ pub fn increment_usage(&mut self) {
     }
 }
 
+/// Finds the template that points at the same page as `url`.
+pub fn find_by_page<'a>(templates: &'a [PageTemplate], url: &str) -> Option<&'a PageTemplate> {
+    templates.iter().find(|t| same_page(&t.url, url))
+}
+
 /// Lists all available templates.
 #[tauri::command]
 pub fn list_templates<R: Runtime>(app: AppHandle<R>) -> Result<Vec<PageTemplate>, String> {
@@ -73,10 +79,13 @@ pub fn create_template<R: Runtime>(
     description: Option<String>,
     category: Option<String>,
 ) -> Result<String, String> {
-    let mut template = PageTemplate::new(name, url, description, category);
-    let template_id = template.id.clone();
-    
     let mut templates = load_templates(&app).unwrap_or_default();
+    if let Some(existing) = find_by_page(&templates, &url) {
+        return Err(format!("Template \"{}\" already uses this page", existing.name));
+    }
+    
+    let template = PageTemplate::new(name, url, description, category);
+    let template_id = template.id.clone();
     templates.push(template);
     save_templates(&app, &templates).map_err(|e| e.to_string())?;
     
@@ -95,6 +104,12 @@ pub fn update_template<R: Runtime>(
 ) -> Result<(), String> {
     let mut templates = load_templates(&app).map_err(|e| e.to_string())?;
     
+    if let Some(url) = &url {
+        if let Some(existing) = find_by_page(&templates, url).filter(|t| t.id != template_id) {
+            return Err(format!("Template \"{}\" already uses this page", existing.name));
+        }
+    }
+    
     if let Some(template) = templates.iter_mut().find(|t| t.id == template_id) {
         if let Some(name) = name {
             template.name = name;
@@ -1,6 +1,6 @@
 //! Tests for page templates.
 
-use super::PageTemplate;
+use super::{find_by_page, PageTemplate};
 
 #[test]
 fn test_template_creation() {
@@ -65,3 +65,21 @@ fn test_template_deserialization() {
     assert_eq!(template.name, "Test Template");
     assert_eq!(template.usage_count, 5);
 }
+
+#[test]
+fn test_find_by_page_matches_url_variants() {
+    let page = "8f3a1b2c4d5e6f708192a3b4c5d6e7f8";
+    let templates = vec![
+        PageTemplate::new(
+            "Meeting Notes".to_string(),
+            format!("https://www.notion.so/acme/Meeting-Notes-{}", page),
+            None,
+            None,
+        ),
+        PageTemplate::new("Other".to_string(), "https://example.com".to_string(), None, None),
+    ];
+
+    let found = find_by_page(&templates, &format!("https://notion.so/{}?pvs=4", page));
+    assert_eq!(found.map(|t| t.name.as_str()), Some("Meeting Notes"));
+    assert!(find_by_page(&templates, "https://www.notion.so/Unrelated").is_none());
+}
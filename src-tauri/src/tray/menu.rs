@@ -1,43 +1,114 @@
 //! Tray menu construction and handling.
 
 use tauri::{
-    menu::{Menu, MenuItem, PredefinedMenuItem},
+    menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     App, Manager, Runtime,
 };
 
-/// Builds and registers the tray menu.
-pub fn build<R: Runtime>(app: &App<R>) -> Result<(), Box<dyn std::error::Error>> {
+/// ID of the tray icon, used to replace its menu.
+pub const TRAY_ID: &str = "main";
+
+/// Prefix of menu item IDs that focus a pop-out window.
+const POPOUT_ITEM_PREFIX: &str = "popout:";
+
+/// Prefix of menu item IDs that switch to a workspace.
+const WORKSPACE_ITEM_PREFIX: &str = "workspace:";
+
+/// Builds the tray menu, including the workspaces and the open pop-out windows.
+pub fn build_menu<R: Runtime, M: Manager<R>>(manager: &M) -> tauri::Result<Menu<R>> {
     // Create menu items
-    let show = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
-    let hide = MenuItem::with_id(app, "hide", "Hide Window", true, None::<&str>)?;
-    let separator1 = PredefinedMenuItem::separator(app)?;
-    let settings = MenuItem::with_id(app, "settings", "Settings...", true, None::<&str>)?;
+    let show = MenuItem::with_id(manager, "show", "Show Window", true, None::<&str>)?;
+    let hide = MenuItem::with_id(manager, "hide", "Hide Window", true, None::<&str>)?;
+    let separator1 = PredefinedMenuItem::separator(manager)?;
+    let workspaces = workspaces_submenu(manager)?;
+    let pop_out = MenuItem::with_id(manager, "pop_out", "Pop Out Current Page", true, None::<&str>)?;
+    let popouts = popouts_submenu(manager)?;
+    let mini_settings = crate::mini::get_mini_window_settings(manager.app_handle().clone()).unwrap_or_default();
+    let mini_label = if mini_settings.open {
+        "Close Mini Window"
+    } else {
+        "Open Mini Window"
+    };
+    let mini = MenuItem::with_id(manager, "mini", mini_label, true, None::<&str>)?;
+    let mini_click_through = CheckMenuItem::with_id(
+        manager,
+        "mini_click_through",
+        "Mini Window Click-Through",
+        true,
+        mini_settings.click_through,
+        None::<&str>,
+    )?;
+    let separator2 = PredefinedMenuItem::separator(manager)?;
+    let settings = MenuItem::with_id(manager, "settings", "Settings...", true, None::<&str>)?;
     let check_updates =
-        MenuItem::with_id(app, "check_updates", "Check for Updates", true, None::<&str>)?;
-    let separator2 = PredefinedMenuItem::separator(app)?;
-    let about = MenuItem::with_id(app, "about", "About Notive", true, None::<&str>)?;
-    let separator3 = PredefinedMenuItem::separator(app)?;
-    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
+        MenuItem::with_id(manager, "check_updates", "Check for Updates", true, None::<&str>)?;
+    let separator3 = PredefinedMenuItem::separator(manager)?;
+    let about = MenuItem::with_id(manager, "about", "About Notive", true, None::<&str>)?;
+    let separator4 = PredefinedMenuItem::separator(manager)?;
+    let quit = MenuItem::with_id(manager, "quit", "Quit", true, None::<&str>)?;
 
     // Build menu
-    let menu = Menu::with_items(
-        app,
+    Menu::with_items(
+        manager,
         &[
             &show,
             &hide,
             &separator1,
+            &workspaces,
+            &pop_out,
+            &popouts,
+            &mini,
+            &mini_click_through,
+            &separator2,
             &settings,
             &check_updates,
-            &separator2,
-            &about,
             &separator3,
+            &about,
+            &separator4,
             &quit,
         ],
-    )?;
+    )
+}
+
+/// Builds the submenu listing the open pop-out windows.
+fn popouts_submenu<R: Runtime, M: Manager<R>>(manager: &M) -> tauri::Result<Submenu<R>> {
+    let popouts = crate::popouts::list_popouts(manager.app_handle().clone()).unwrap_or_default();
+
+    let items = popouts
+        .iter()
+        .map(|popout| {
+            let id = format!("{}{}", POPOUT_ITEM_PREFIX, popout.id);
+            MenuItem::with_id(manager, id, &popout.title, true, None::<&str>)
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let items: Vec<&dyn IsMenuItem<R>> = items.iter().map(|item| item as &dyn IsMenuItem<R>).collect();
+
+    Submenu::with_id_and_items(manager, "popouts", "Pop-out Windows", !items.is_empty(), &items)
+}
+
+/// Builds the submenu listing the workspaces in their saved order.
+fn workspaces_submenu<R: Runtime, M: Manager<R>>(manager: &M) -> tauri::Result<Submenu<R>> {
+    let workspaces = crate::workspaces::list_workspaces(manager.app_handle().clone()).unwrap_or_default();
+
+    let items = workspaces
+        .iter()
+        .map(|workspace| {
+            let id = format!("{}{}", WORKSPACE_ITEM_PREFIX, workspace.id);
+            MenuItem::with_id(manager, id, workspace.display_name(), true, None::<&str>)
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let items: Vec<&dyn IsMenuItem<R>> = items.iter().map(|item| item as &dyn IsMenuItem<R>).collect();
+
+    Submenu::with_id_and_items(manager, "workspaces", "Workspaces", !items.is_empty(), &items)
+}
+
+/// Builds and registers the tray menu.
+pub fn build<R: Runtime>(app: &App<R>) -> Result<(), Box<dyn std::error::Error>> {
+    let menu = build_menu(app)?;
 
     // Build tray icon
-    let _tray = TrayIconBuilder::new()
+    let _tray = TrayIconBuilder::with_id(TRAY_ID)
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&menu)
         .menu_on_left_click(false)
@@ -81,6 +152,35 @@ fn handle_menu_event<R: Runtime>(app: &tauri::AppHandle<R>, id: &str) {
                 let _ = window.hide();
             }
         }
+        "pop_out" => {
+            let app_handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::popouts::pop_out(app_handle, None).await {
+                    log::warn!("Failed to pop out page: {}", e);
+                }
+            });
+        }
+        "mini" => {
+            let app_handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = crate::mini::toggle_mini_window(app_handle).await {
+                    log::warn!("Failed to toggle mini window: {}", e);
+                }
+            });
+        }
+        "mini_click_through" => {
+            // Click-through windows ignore the mouse, so the tray is the way back
+            let click_through = crate::mini::get_mini_window_settings(app.clone())
+                .map(|settings| !settings.click_through)
+                .unwrap_or(false);
+            let options = crate::mini::MiniWindowOptions {
+                click_through: Some(click_through),
+                ..Default::default()
+            };
+            if let Err(e) = crate::mini::set_mini_window_options(app.clone(), options) {
+                log::warn!("Failed to set mini window click-through: {}", e);
+            }
+        }
         "settings" => {
             log::debug!("Settings requested");
             let app_handle = app.clone();
@@ -104,6 +204,20 @@ fn handle_menu_event<R: Runtime>(app: &tauri::AppHandle<R>, id: &str) {
                 let _ = crate::commands::app::show_about(app_handle).await;
             });
         }
-        _ => {}
+        id => {
+            if let Some(popout_id) = id.strip_prefix(POPOUT_ITEM_PREFIX) {
+                if let Err(e) = crate::popouts::focus_popout(app.clone(), popout_id.to_string()) {
+                    log::warn!("Failed to focus pop-out: {}", e);
+                }
+            } else if let Some(workspace_id) = id.strip_prefix(WORKSPACE_ITEM_PREFIX) {
+                let app_handle = app.clone();
+                let workspace_id = workspace_id.to_string();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = crate::workspaces::switch_workspace(app_handle, workspace_id).await {
+                        log::warn!("Failed to switch workspace: {}", e);
+                    }
+                });
+            }
+        }
     }
 }
@@ -17,6 +17,21 @@ pub fn setup<R: Runtime>(app: &App<R>) -> Result<(), Box<dyn std::error::Error>>
     Ok(())
 }
 
+/// Rebuilds the tray menu, e.g. after pop-out windows open or close.
+pub fn refresh_menu<R: Runtime>(app: &tauri::AppHandle<R>) {
+    let Some(tray) = app.tray_by_id(menu::TRAY_ID) else {
+        return;
+    };
+    match menu::build_menu(app) {
+        Ok(tray_menu) => {
+            if let Err(e) = tray.set_menu(Some(tray_menu)) {
+                log::warn!("Failed to update tray menu: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Failed to build tray menu: {}", e),
+    }
+}
+
 /// Increments the unread notification count and updates the tray badge.
 pub fn increment_unread_count<R: Runtime>(app: &tauri::AppHandle<R>) {
     let count = UNREAD_COUNT.fetch_add(1, Ordering::SeqCst) + 1;
@@ -0,0 +1,459 @@
+//! Background update downloads that install on the next quit.
+//!
+//! The update bundle is downloaded and verified while the app keeps running,
+//! cached on disk, and verified again before it is installed when the app
+//! exits or restarts. The state is persisted so a downloaded update survives
+//! restarts of the app.
+
+#[cfg(test)]
+mod tests;
+
+use super::local;
+use super::persistence::{load_update_state, save_update_state};
+use crate::config::UpdateChannel;
+use crate::utils::paths;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Runtime};
+use tauri_plugin_updater::Update;
+use tokio::sync::Notify;
+
+/// Event carrying [`UpdateProgress`] payloads.
+pub const PROGRESS_EVENT: &str = "update-progress";
+
+/// Event carrying [`UpdateState`] payloads.
+pub const STATE_EVENT: &str = "update-state";
+
+/// Bytes between progress events when the total size is unknown.
+const UNKNOWN_SIZE_STEP: u64 = 512 * 1024;
+
+// Cancellation handle of the running download, if any
+static DOWNLOAD_CANCEL: Mutex<Option<Arc<Notify>>> = Mutex::new(None);
+
+// Verified update waiting to be installed on exit
+static PENDING_UPDATE: Mutex<Option<Update>> = Mutex::new(None);
+
+/// Lifecycle of a background update.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum UpdateState {
+    #[default]
+    Idle,
+    Downloading {
+        version: String,
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    Ready {
+        version: String,
+        bundle_path: PathBuf,
+        /// Signature the bundle is checked against again before installing.
+        #[serde(default)]
+        signature: String,
+    },
+    Cancelled {
+        version: String,
+    },
+    Failed {
+        version: String,
+        error: String,
+    },
+}
+
+impl UpdateState {
+    /// Normalizes a state loaded at startup.
+    ///
+    /// Downloads do not survive a restart, and a ready update is only kept
+    /// while its cached bundle still exists and can be verified.
+    pub fn on_startup(self) -> Self {
+        match self {
+            Self::Downloading { .. } => Self::Idle,
+            Self::Ready { ref bundle_path, ref signature, .. }
+                if !bundle_path.is_file() || signature.is_empty() =>
+            {
+                Self::Idle
+            }
+            other => other,
+        }
+    }
+}
+
+/// Download progress sent to the frontend.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UpdateProgress {
+    pub version: String,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub percent: Option<u8>,
+}
+
+/// Turns download chunks into throttled progress updates.
+pub struct ProgressTracker {
+    version: String,
+    downloaded: u64,
+    last_percent: Option<u8>,
+    last_reported: u64,
+}
+
+impl ProgressTracker {
+    pub fn new(version: String) -> Self {
+        Self {
+            version,
+            downloaded: 0,
+            last_percent: None,
+            last_reported: 0,
+        }
+    }
+
+    /// Records a chunk, returning progress when it is worth reporting.
+    ///
+    /// With a known size this reports once per percent; otherwise once per
+    /// [`UNKNOWN_SIZE_STEP`] bytes.
+    pub fn advance(&mut self, chunk_len: usize, total: Option<u64>) -> Option<UpdateProgress> {
+        self.downloaded += chunk_len as u64;
+
+        let percent = total
+            .filter(|t| *t > 0)
+            .map(|t| (self.downloaded.min(t) * 100 / t) as u8);
+
+        let report = match percent {
+            Some(p) => self.last_percent != Some(p),
+            None => self.downloaded - self.last_reported >= UNKNOWN_SIZE_STEP,
+        };
+        if !report {
+            return None;
+        }
+
+        self.last_percent = percent;
+        self.last_reported = self.downloaded;
+        Some(UpdateProgress {
+            version: self.version.clone(),
+            downloaded: self.downloaded,
+            total,
+            percent,
+        })
+    }
+}
+
+/// Gets where the bundle for `version` is cached.
+pub fn bundle_path(cache_dir: &Path, version: &str) -> PathBuf {
+    cache_dir.join("updates").join(format!("notive-{}.update", version))
+}
+
+/// Persists the state and notifies the frontend.
+fn set_state<R: Runtime>(app: &AppHandle<R>, state: UpdateState) {
+    if let Err(e) = save_update_state(app, &state) {
+        log::warn!("Failed to save update state: {}", e);
+    }
+    let _ = app.emit(STATE_EVENT, &state);
+}
+
+/// Gets the current update state.
+pub fn current_state<R: Runtime>(app: &AppHandle<R>) -> UpdateState {
+    load_update_state(app).unwrap_or_default()
+}
+
+/// Restores a downloaded update at startup so it installs on the next quit.
+pub async fn resume<R: Runtime>(app: &AppHandle<R>) {
+    let state = current_state(app).on_startup();
+
+    if let UpdateState::Ready { version, bundle_path, .. } = &state {
+        // Make sure the cached update is still the one on offer
+        match offered_version(app, version).await {
+            Ok(Some(offered)) if offered == *version => {
+                log::info!("Update {} is ready and will be installed on quit", version);
+            }
+            Ok(_) => {
+                log::debug!("Discarding cached update {}", version);
+                let _ = std::fs::remove_file(bundle_path);
+                set_state(app, UpdateState::Idle);
+                return;
+            }
+            Err(e) => {
+                // Keep the bundle; it is still verified before it is installed
+                log::warn!("Could not re-check cached update {}: {}", version, e);
+            }
+        }
+    }
+
+    set_state(app, state);
+}
+
+/// Gets the version currently offered, arming the update handle if it is
+/// the cached `version`.
+async fn offered_version<R: Runtime>(app: &AppHandle<R>, version: &str) -> Result<Option<String>, String> {
+    let settings = crate::config::load(app).unwrap_or_default();
+    let current = &app.package_info().version;
+    if let Some(mirror) = settings.update_mirror {
+        let release = local::find_release(&mirror, &settings.update_channel, current, false)?;
+        return Ok(release.map(|release| release.version));
+    }
+
+    // The update handle is not serializable, so ask the server for it again
+    let updater = super::build_updater(app, &settings.update_channel, false)?;
+    let update = updater.check().await.map_err(|e| e.to_string())?;
+    Ok(update.map(|update| {
+        let offered = update.version.clone();
+        if offered == version {
+            arm(update);
+        }
+        offered
+    }))
+}
+
+/// Downloads the available update in the background.
+///
+/// The bundle is verified, cached, and installed on the next quit or restart.
+/// Returns the version that was downloaded.
+pub async fn download<R: Runtime>(app: &AppHandle<R>) -> Result<String, String> {
+    download_with(app, false).await
+}
+
+/// Downloads the available update in the background, optionally accepting
+/// an older version.
+pub async fn download_with<R: Runtime>(app: &AppHandle<R>, allow_downgrade: bool) -> Result<String, String> {
+    if let Some(version) = pending_version(app) {
+        return Ok(version);
+    }
+
+    let settings = crate::config::load(app).unwrap_or_default();
+    if let Some(mirror) = settings.update_mirror {
+        return copy_from_mirror(app, &mirror, &settings.update_channel, allow_downgrade);
+    }
+
+    let updater = super::build_updater(app, &settings.update_channel, allow_downgrade)?;
+    let update = updater
+        .check()
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "No update available".to_string())?;
+    let version = update.version.clone();
+
+    let cancel = Arc::new(Notify::new());
+    {
+        let mut current = DOWNLOAD_CANCEL.lock().map_err(|e| e.to_string())?;
+        if current.is_some() {
+            return Err("An update is already downloading".to_string());
+        }
+        *current = Some(cancel.clone());
+    }
+
+    log::info!("Downloading update {} in the background...", version);
+    set_state(
+        app,
+        UpdateState::Downloading {
+            version: version.clone(),
+            downloaded: 0,
+            total: None,
+        },
+    );
+
+    let mut tracker = ProgressTracker::new(version.clone());
+    let progress_app = app.clone();
+    let result = tokio::select! {
+        result = update.download(
+            move |chunk_len, total| {
+                if let Some(progress) = tracker.advance(chunk_len, total) {
+                    let _ = progress_app.emit(PROGRESS_EVENT, &progress);
+                }
+            },
+            || log::debug!("Update download finished, verifying..."),
+        ) => Some(result),
+        _ = cancel.notified() => None,
+    };
+
+    if let Ok(mut current) = DOWNLOAD_CANCEL.lock() {
+        *current = None;
+    }
+
+    let bytes = match result {
+        None => {
+            log::info!("Update download cancelled");
+            set_state(app, UpdateState::Cancelled { version });
+            return Err("Update download cancelled".to_string());
+        }
+        Some(Err(e)) => {
+            log::error!("Update download failed: {}", e);
+            set_state(
+                app,
+                UpdateState::Failed {
+                    version,
+                    error: e.to_string(),
+                },
+            );
+            return Err(e.to_string());
+        }
+        Some(Ok(bytes)) => bytes,
+    };
+
+    let cache_dir = paths::app_cache_dir().ok_or("Could not determine cache directory")?;
+    let bundle = bundle_path(&cache_dir, &version);
+    if let Some(parent) = bundle.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create update cache: {}", e))?;
+    }
+    std::fs::write(&bundle, &bytes).map_err(|e| format!("Failed to cache update: {}", e))?;
+
+    let signature = update.signature.clone();
+    arm(update);
+    set_state(
+        app,
+        UpdateState::Ready {
+            version: version.clone(),
+            bundle_path: bundle,
+            signature,
+        },
+    );
+
+    log::info!("Update {} downloaded, will install on quit", version);
+    Ok(version)
+}
+
+/// Copies the update offered by a mirror into the update cache.
+///
+/// Mirrors may be on removable media, so the bundle is cached like a
+/// download and installed on the next quit or restart.
+fn copy_from_mirror<R: Runtime>(
+    app: &AppHandle<R>,
+    mirror: &str,
+    channel: &UpdateChannel,
+    allow_downgrade: bool,
+) -> Result<String, String> {
+    let release = local::find_release(mirror, channel, &app.package_info().version, allow_downgrade)?
+        .ok_or_else(|| "No update available".to_string())?;
+    let bytes = std::fs::read(&release.bundle)
+        .map_err(|e| format!("Failed to read update bundle {}: {}", release.bundle.display(), e))?;
+    local::verify_bundle(&bytes, &release.signature, &local::configured_pubkey(app)?)?;
+
+    let cache_dir = paths::app_cache_dir().ok_or("Could not determine cache directory")?;
+    let bundle = bundle_path(&cache_dir, &release.version);
+    if let Some(parent) = bundle.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create update cache: {}", e))?;
+    }
+    std::fs::write(&bundle, &bytes).map_err(|e| format!("Failed to cache update: {}", e))?;
+
+    set_state(
+        app,
+        UpdateState::Ready {
+            version: release.version.clone(),
+            bundle_path: bundle,
+            signature: release.signature,
+        },
+    );
+
+    log::info!("Update {} copied from mirror, will install on quit", release.version);
+    Ok(release.version)
+}
+
+/// Keeps the update handle for installing on quit.
+fn arm(update: Update) {
+    if let Ok(mut pending) = PENDING_UPDATE.lock() {
+        *pending = Some(update);
+    }
+}
+
+/// Arms a cached bundle that could not be re-checked at startup.
+///
+/// Such a bundle installs without the update handle as well, but with it the
+/// updater plugin installs it like a freshly downloaded one.
+pub fn rearm<R: Runtime>(app: &AppHandle<R>, update: &Update) {
+    let armed = PENDING_UPDATE.lock().map(|pending| pending.is_some()).unwrap_or(true);
+    if armed {
+        return;
+    }
+    if let UpdateState::Ready { version, .. } = current_state(app) {
+        if version == update.version {
+            arm(update.clone());
+        }
+    }
+}
+
+/// Cancels the running background download.
+///
+/// Returns false if no download was running.
+pub fn cancel() -> bool {
+    match DOWNLOAD_CANCEL.lock() {
+        Ok(current) => match current.as_ref() {
+            Some(cancel) => {
+                cancel.notify_one();
+                true
+            }
+            None => false,
+        },
+        Err(_) => false,
+    }
+}
+
+/// Drops a downloaded update that has not been installed yet, e.g. one from
+/// the channel the user just switched away from.
+pub fn discard_pending<R: Runtime>(app: &AppHandle<R>) {
+    let UpdateState::Ready { version, bundle_path, .. } = current_state(app) else {
+        return;
+    };
+    if let Ok(mut pending) = PENDING_UPDATE.lock() {
+        *pending = None;
+    }
+    log::debug!("Discarding cached update {}", version);
+    let _ = std::fs::remove_file(bundle_path);
+    set_state(app, UpdateState::Idle);
+}
+
+/// Gets the version waiting to be installed on quit, if any.
+pub fn pending_version<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    match current_state(app) {
+        UpdateState::Ready { version, .. } => Some(version),
+        _ => None,
+    }
+}
+
+/// Installs the downloaded update. Called when the app exits.
+pub fn install_pending<R: Runtime>(app: &AppHandle<R>) {
+    let UpdateState::Ready { version, bundle_path, signature } = current_state(app) else {
+        return;
+    };
+    let update = PENDING_UPDATE.lock().ok().and_then(|mut pending| pending.take());
+
+    log::info!("Installing update {} on exit...", version);
+    let bytes = match std::fs::read(&bundle_path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::error!("Failed to read cached update: {}", e);
+            set_state(app, UpdateState::Idle);
+            return;
+        }
+    };
+
+    // The cached file may have been replaced since it was downloaded
+    let verified = local::configured_pubkey(app)
+        .and_then(|pubkey| local::verify_bundle(&bytes, &signature, &pubkey));
+    if let Err(e) = verified {
+        log::error!("Cached update {} failed verification: {}", version, e);
+        let _ = std::fs::remove_file(&bundle_path);
+        set_state(app, UpdateState::Failed { version, error: e });
+        return;
+    }
+
+    let current_version = app.package_info().version.to_string();
+    if let Err(e) = super::snapshot_before_install(app, &current_version, &version) {
+        log::warn!("Failed to create rollback snapshot: {}", e);
+    }
+
+    // Mirror updates, and downloads not re-checked since a restart, have no
+    // update handle and are installed from disk directly
+    let installed = match update {
+        Some(update) => update.install(bytes).map_err(|e| e.to_string()),
+        None => local::install_bundle(&bytes),
+    };
+
+    match installed {
+        Ok(()) => {
+            log::info!("Update {} installed", version);
+            let _ = std::fs::remove_file(&bundle_path);
+            set_state(app, UpdateState::Idle);
+        }
+        Err(e) => {
+            log::error!("Failed to install update: {}", e);
+            set_state(app, UpdateState::Failed { version, error: e });
+        }
+    }
+}
@@ -0,0 +1,104 @@
+//! Tests for background update downloads.
+
+use super::*;
+use tempfile::TempDir;
+
+#[test]
+fn test_progress_reports_each_percent_once() {
+    let mut tracker = ProgressTracker::new("1.1.0".to_string());
+    let total = Some(1000);
+    
+    let first = tracker.advance(10, total).unwrap();
+    assert_eq!(first.percent, Some(1));
+    assert_eq!(first.downloaded, 10);
+    
+    // Still 1%, nothing new to report
+    assert!(tracker.advance(5, total).is_none());
+    
+    let next = tracker.advance(985, total).unwrap();
+    assert_eq!(next.percent, Some(100));
+    assert_eq!(next.version, "1.1.0");
+}
+
+#[test]
+fn test_progress_with_unknown_size() {
+    let mut tracker = ProgressTracker::new("1.1.0".to_string());
+    
+    assert!(tracker.advance(1024, None).is_none());
+    
+    let progress = tracker.advance(UNKNOWN_SIZE_STEP as usize, None).unwrap();
+    assert_eq!(progress.percent, None);
+    assert_eq!(progress.downloaded, 1024 + UNKNOWN_SIZE_STEP);
+}
+
+#[test]
+fn test_progress_never_exceeds_100_percent() {
+    let mut tracker = ProgressTracker::new("1.1.0".to_string());
+    let progress = tracker.advance(2000, Some(1000)).unwrap();
+    assert_eq!(progress.percent, Some(100));
+}
+
+#[test]
+fn test_update_state_serialization() {
+    let state = UpdateState::Downloading {
+        version: "1.1.0".to_string(),
+        downloaded: 42,
+        total: Some(100),
+    };
+    let json = serde_json::to_string(&state).unwrap();
+    assert!(json.contains("\"status\":\"downloading\""));
+    
+    let restored: UpdateState = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, state);
+    
+    let idle: UpdateState = serde_json::from_str(r#"{"status":"idle"}"#).unwrap();
+    assert_eq!(idle, UpdateState::Idle);
+}
+
+#[test]
+fn test_interrupted_download_resets_on_startup() {
+    let state = UpdateState::Downloading {
+        version: "1.1.0".to_string(),
+        downloaded: 42,
+        total: None,
+    };
+    assert_eq!(state.on_startup(), UpdateState::Idle);
+}
+
+#[test]
+fn test_ready_update_survives_startup() {
+    let dir = TempDir::new().unwrap();
+    let bundle = bundle_path(dir.path(), "1.1.0");
+    std::fs::create_dir_all(bundle.parent().unwrap()).unwrap();
+    std::fs::write(&bundle, b"bundle").unwrap();
+    
+    let ready = UpdateState::Ready {
+        version: "1.1.0".to_string(),
+        bundle_path: bundle.clone(),
+        signature: "c2lnbmF0dXJl".to_string(),
+    };
+    assert_eq!(ready.clone().on_startup(), ready);
+    
+    // Without a signature the bundle cannot be verified before installing
+    let unsigned = UpdateState::Ready {
+        version: "1.1.0".to_string(),
+        bundle_path: bundle.clone(),
+        signature: String::new(),
+    };
+    assert_eq!(unsigned.on_startup(), UpdateState::Idle);
+    
+    // A missing bundle cannot be installed
+    std::fs::remove_file(&bundle).unwrap();
+    assert_eq!(ready.on_startup(), UpdateState::Idle);
+}
+
+#[test]
+fn test_bundle_path() {
+    let path = bundle_path(Path::new("/cache"), "1.2.3");
+    assert_eq!(path, PathBuf::from("/cache/updates/notive-1.2.3.update"));
+}
+
+#[test]
+fn test_cancel_without_download() {
+    assert!(!cancel());
+}
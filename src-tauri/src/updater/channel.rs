@@ -0,0 +1,102 @@
+//! Update channel endpoints and version filtering.
+
+use crate::config::UpdateChannel;
+use semver::Version;
+use url::Url;
+
+/// Manifest published with every stable release, relative to the releases page.
+const STABLE_MANIFEST: &str = "latest/download/latest.json";
+
+/// Manifest attached to the rolling `beta` release tag.
+const BETA_MANIFEST: &str = "download/beta/latest.json";
+
+/// Manifest attached to the rolling `nightly` release tag.
+const NIGHTLY_MANIFEST: &str = "download/nightly/latest.json";
+
+/// Returns the update endpoints for a channel, most specific first.
+///
+/// `stable` is the endpoint configured in `tauri.conf.json`. When it points
+/// at the stable manifest of a releases page, the pre-release manifests are
+/// found next to it; any other endpoint serves every channel on its own.
+///
+/// Pre-release channels fall back to the more stable manifests so that a
+/// missing beta or nightly build never leaves the updater without a source.
+pub fn endpoints(stable: &Url, channel: &UpdateChannel) -> Result<Vec<Url>, String> {
+    let Some(releases) = stable.as_str().strip_suffix(STABLE_MANIFEST) else {
+        return Ok(vec![stable.clone()]);
+    };
+
+    let manifests: &[&str] = match channel {
+        UpdateChannel::Stable => &[STABLE_MANIFEST],
+        UpdateChannel::Beta => &[BETA_MANIFEST, STABLE_MANIFEST],
+        UpdateChannel::Nightly => &[NIGHTLY_MANIFEST, BETA_MANIFEST, STABLE_MANIFEST],
+    };
+
+    manifests
+        .iter()
+        .map(|manifest| {
+            Url::parse(&format!("{}{}", releases, manifest))
+                .map_err(|e| format!("Invalid update endpoint: {}", e))
+        })
+        .collect()
+}
+
+/// Returns the manifest paths of a channel inside a local update mirror.
+///
+/// Mirrors hold the stable manifest at the root and the pre-release
+/// manifests in `beta/` and `nightly/`, with the same fallback order as
+/// [`endpoints`].
+pub fn mirror_manifests(channel: &UpdateChannel) -> &'static [&'static str] {
+    match channel {
+        UpdateChannel::Stable => &["latest.json"],
+        UpdateChannel::Beta => &["beta/latest.json", "latest.json"],
+        UpdateChannel::Nightly => &["nightly/latest.json", "beta/latest.json", "latest.json"],
+    }
+}
+
+/// Returns the channel a version was published on.
+///
+/// Versions without a pre-release tag are stable, `-beta.N` and `-rc.N` are
+/// beta builds, and any other pre-release tag is treated as a nightly build.
+pub fn channel_of(version: &Version) -> UpdateChannel {
+    let pre = version.pre.as_str();
+    if pre.is_empty() {
+        UpdateChannel::Stable
+    } else if pre.starts_with("beta") || pre.starts_with("rc") {
+        UpdateChannel::Beta
+    } else {
+        UpdateChannel::Nightly
+    }
+}
+
+/// Checks whether a channel accepts builds of the given version.
+pub fn accepts(channel: &UpdateChannel, version: &Version) -> bool {
+    matches!(
+        (channel, channel_of(version)),
+        (UpdateChannel::Nightly, _)
+            | (UpdateChannel::Beta, UpdateChannel::Stable | UpdateChannel::Beta)
+            | (UpdateChannel::Stable, UpdateChannel::Stable)
+    )
+}
+
+/// Decides whether the remote version should be offered as an update.
+///
+/// Newer versions are offered if the channel accepts them. Older versions are
+/// only offered when a downgrade was explicitly requested and the running
+/// build does not belong to the selected channel (e.g. Nightly -> Stable).
+pub fn should_update(
+    channel: &UpdateChannel,
+    current: &Version,
+    remote: &Version,
+    allow_downgrade: bool,
+) -> bool {
+    if !accepts(channel, remote) {
+        return false;
+    }
+
+    if remote > current {
+        return true;
+    }
+
+    allow_downgrade && remote < current && !accepts(channel, current)
+}
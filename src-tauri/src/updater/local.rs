@@ -0,0 +1,378 @@
+//! Offline updates from local bundles and directory mirrors.
+//!
+//! The updater plugin only fetches updates over HTTPS, so bundles on disk are
+//! verified against the updater public key here and installed directly: an
+//! AppImage is replaced in place, and `.deb` and `.rpm` packages are handed
+//! to the package manager.
+
+#[cfg(test)]
+mod tests;
+
+use super::channel;
+use crate::config::UpdateChannel;
+use base64::Engine;
+use minisign_verify::{PublicKey, Signature};
+use semver::Version;
+use serde_json::Value;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tauri::{AppHandle, Runtime};
+use url::Url;
+
+/// How the running copy of Notive was installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallKind {
+    AppImage,
+    Deb,
+    Rpm,
+}
+
+impl InstallKind {
+    /// Detects how the running copy was installed.
+    ///
+    /// AppImages set `$APPIMAGE`; otherwise the system's package manager
+    /// decides between `.deb` and `.rpm`.
+    pub fn current() -> Option<Self> {
+        if std::env::var_os("APPIMAGE").is_some() {
+            Some(Self::AppImage)
+        } else if Path::new("/usr/bin/dpkg").exists() {
+            Some(Self::Deb)
+        } else if Path::new("/usr/bin/rpm").exists() {
+            Some(Self::Rpm)
+        } else {
+            None
+        }
+    }
+
+    /// Detects the kind of an update bundle from its first bytes.
+    pub fn of_bundle(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(b"\x7fELF") {
+            Some(Self::AppImage)
+        } else if bytes.starts_with(b"!<arch>\ndebian-binary") {
+            Some(Self::Deb)
+        } else if bytes.starts_with(&[0xed, 0xab, 0xee, 0xdb]) {
+            Some(Self::Rpm)
+        } else {
+            None
+        }
+    }
+
+    /// Suffix of the bundle-specific platform keys in update manifests.
+    fn manifest_suffix(self) -> &'static str {
+        match self {
+            Self::AppImage => "appimage",
+            Self::Deb => "deb",
+            Self::Rpm => "rpm",
+        }
+    }
+}
+
+/// A release offered by an update mirror.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MirrorRelease {
+    pub version: String,
+    pub notes: Option<String>,
+    /// Bundle file inside the mirror.
+    pub bundle: PathBuf,
+    pub signature: String,
+}
+
+/// Resolves a relative path below `root`, rejecting anything that escapes it.
+pub fn resolve_path(root: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative);
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+    let path = root.join(relative);
+    path.is_file().then_some(path)
+}
+
+/// Converts a mirror setting (`file://` URL or plain path) into a directory.
+pub fn mirror_dir(mirror: &str) -> Result<PathBuf, String> {
+    let path = if mirror.starts_with("file:") {
+        Url::parse(mirror)
+            .map_err(|e| format!("Invalid mirror URL: {}", e))?
+            .to_file_path()
+            .map_err(|_| format!("Invalid mirror URL: {}", mirror))?
+    } else {
+        PathBuf::from(mirror)
+    };
+
+    if !path.is_dir() {
+        return Err(format!("Update mirror {} is not a directory", path.display()));
+    }
+    Ok(path)
+}
+
+/// Guesses the version from a bundle name such as `notive_1.3.0_amd64.AppImage`.
+pub fn version_from_file_name(file_name: &str) -> Option<String> {
+    let parse = |token: &str| {
+        let token = token.trim_start_matches('v');
+        semver::Version::parse(token).ok().map(|v| v.to_string())
+    };
+
+    // Underscores first, so pre-release versions like 1.3.0-beta.1 stay intact
+    file_name
+        .split('_')
+        .find_map(parse)
+        .or_else(|| file_name.split('-').find_map(parse))
+}
+
+/// Gets the signature for a bundle, reading `<bundle>.sig` by default.
+pub fn read_signature(bundle: &Path, signature: Option<&Path>) -> Result<String, String> {
+    let signature_path = match signature {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let mut path = bundle.as_os_str().to_owned();
+            path.push(".sig");
+            PathBuf::from(path)
+        }
+    };
+
+    std::fs::read_to_string(&signature_path)
+        .map(|s| s.trim().to_string())
+        .map_err(|e| format!("Failed to read signature {}: {}", signature_path.display(), e))
+}
+
+/// Verifies a bundle against the updater public key.
+///
+/// Both values use the updater's format: base64-encoded minisign files.
+pub fn verify_bundle(bytes: &[u8], signature: &str, pubkey: &str) -> Result<(), String> {
+    let decode = |value: &str, what: &str| {
+        base64::engine::general_purpose::STANDARD
+            .decode(value.trim())
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(|| format!("The {} is not valid base64", what))
+    };
+
+    let public_key = PublicKey::decode(&decode(pubkey, "update public key")?)
+        .map_err(|e| format!("Invalid update public key: {}", e))?;
+    let signature = Signature::decode(&decode(signature, "signature")?)
+        .map_err(|e| format!("Invalid signature file: {}", e))?;
+
+    public_key
+        .verify(bytes, &signature, true)
+        .map_err(|_| "The update bundle does not match its signature or was not signed with the Notive release key".to_string())
+}
+
+/// Gets the updater public key from `tauri.conf.json`.
+pub fn configured_pubkey<R: Runtime>(app: &AppHandle<R>) -> Result<String, String> {
+    app.config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("pubkey"))
+        .and_then(|pubkey| pubkey.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| "No updater public key is configured".to_string())
+}
+
+/// Gets the manifest platform keys for this machine, most specific first.
+pub fn platform_keys(kind: Option<InstallKind>) -> Vec<String> {
+    let arch = match std::env::consts::ARCH {
+        "x86" => "i686",
+        "arm" => "armv7",
+        arch => arch,
+    };
+
+    let mut keys = Vec::new();
+    if let Some(kind) = kind {
+        keys.push(format!("linux-{}-{}", arch, kind.manifest_suffix()));
+    }
+    keys.push(format!("linux-{}", arch));
+    keys
+}
+
+/// Reads the release described by a mirror manifest.
+///
+/// Both the static format (with `platforms`) and the single-bundle format
+/// are accepted. Only the file name of the bundle URL is used, so manifests
+/// copied verbatim from the release page work against a flat mirror.
+pub fn read_release(dir: &Path, manifest: &Value, keys: &[String]) -> Result<MirrorRelease, String> {
+    let version = manifest
+        .get("version")
+        .and_then(Value::as_str)
+        .ok_or("The mirror manifest has no version")?
+        .trim_start_matches('v')
+        .to_string();
+
+    let entry = match manifest.get("platforms") {
+        Some(platforms) => keys
+            .iter()
+            .find_map(|key| platforms.get(key))
+            .ok_or_else(|| format!("The mirror has no update for {}", keys.join(" or ")))?,
+        None => manifest,
+    };
+    let url = entry
+        .get("url")
+        .and_then(Value::as_str)
+        .ok_or("The mirror manifest has no bundle URL")?;
+    let signature = entry
+        .get("signature")
+        .and_then(Value::as_str)
+        .ok_or("The mirror manifest has no signature")?;
+
+    let file_name = url.rsplit('/').next().unwrap_or(url);
+    let bundle = resolve_path(dir, file_name)
+        .ok_or_else(|| format!("{} is missing from the update mirror", file_name))?;
+
+    Ok(MirrorRelease {
+        version,
+        notes: manifest.get("notes").and_then(Value::as_str).map(str::to_string),
+        bundle,
+        signature: signature.to_string(),
+    })
+}
+
+/// Finds the release a mirror offers on a channel.
+///
+/// The channel's manifests are tried in fallback order and the first one
+/// present decides, like the endpoints of an online check.
+pub fn find_release(
+    mirror: &str,
+    channel: &UpdateChannel,
+    current: &Version,
+    allow_downgrade: bool,
+) -> Result<Option<MirrorRelease>, String> {
+    let dir = mirror_dir(mirror)?;
+    let keys = platform_keys(InstallKind::current());
+
+    for manifest in channel::mirror_manifests(channel) {
+        let Some(path) = resolve_path(&dir, manifest) else {
+            continue;
+        };
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let manifest: Value = serde_json::from_str(&contents)
+            .map_err(|e| format!("Invalid update manifest {}: {}", path.display(), e))?;
+
+        let release = read_release(&dir, &manifest, &keys)?;
+        let version = Version::parse(&release.version)
+            .map_err(|e| format!("Invalid version in {}: {}", path.display(), e))?;
+        return Ok(channel::should_update(channel, current, &version, allow_downgrade).then_some(release));
+    }
+
+    Err(format!("No update manifest found in {}", dir.display()))
+}
+
+/// Installs the release a mirror offers on a channel.
+pub async fn install_from_mirror<R: Runtime>(
+    app: &AppHandle<R>,
+    mirror: &str,
+    channel: &UpdateChannel,
+    allow_downgrade: bool,
+) -> Result<String, String> {
+    let release = find_release(mirror, channel, &app.package_info().version, allow_downgrade)?
+        .ok_or("No update available")?;
+    let bytes = fs::read(&release.bundle)
+        .map_err(|e| format!("Failed to read update bundle {}: {}", release.bundle.display(), e))?;
+
+    install_verified(app, bytes, &release.signature, &release.version).await?;
+    Ok(release.version)
+}
+
+/// Installs an update bundle from a local file.
+pub async fn install_from_file<R: Runtime>(
+    app: &AppHandle<R>,
+    bundle: &Path,
+    signature: Option<&Path>,
+    version: Option<String>,
+) -> Result<String, String> {
+    let file_name = bundle
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("Invalid update bundle path: {}", bundle.display()))?
+        .to_string();
+    let version = version
+        .or_else(|| version_from_file_name(&file_name))
+        .ok_or_else(|| format!("Could not determine the version of {}", file_name))?;
+
+    let bytes = fs::read(bundle)
+        .map_err(|e| format!("Failed to read update bundle {}: {}", bundle.display(), e))?;
+    let signature = read_signature(bundle, signature)?;
+
+    install_verified(app, bytes, &signature, &version).await?;
+    Ok(version)
+}
+
+/// Verifies a bundle, snapshots the running version and installs the bundle.
+async fn install_verified<R: Runtime>(
+    app: &AppHandle<R>,
+    bytes: Vec<u8>,
+    signature: &str,
+    version: &str,
+) -> Result<(), String> {
+    verify_bundle(&bytes, signature, &configured_pubkey(app)?)?;
+    log::info!("Verified local update bundle for {}", version);
+
+    let current_version = app.package_info().version.to_string();
+    if let Err(e) = super::snapshot_before_install(app, &current_version, version) {
+        log::warn!("Failed to create rollback snapshot: {}", e);
+    }
+
+    // Package managers wait for the user to authenticate
+    tauri::async_runtime::spawn_blocking(move || install_bundle(&bytes))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Installs a verified update bundle.
+pub fn install_bundle(bytes: &[u8]) -> Result<(), String> {
+    match InstallKind::of_bundle(bytes) {
+        Some(InstallKind::AppImage) => {
+            let appimage = std::env::var_os("APPIMAGE")
+                .ok_or("An AppImage update can only be installed when Notive runs as an AppImage")?;
+            replace_file(Path::new(&appimage), bytes)
+        }
+        Some(InstallKind::Deb) => install_package(bytes, "deb", &["dpkg", "-i"]),
+        Some(InstallKind::Rpm) => install_package(bytes, "rpm", &["rpm", "-U"]),
+        None => Err("The update bundle is not an AppImage, .deb or .rpm package".to_string()),
+    }
+}
+
+/// Replaces a file with `bytes`, keeping its permissions.
+///
+/// The new contents are written next to the target and renamed over it, so
+/// a running AppImage is replaced atomically.
+pub fn replace_file(target: &Path, bytes: &[u8]) -> Result<(), String> {
+    let staged = target.with_extension("update");
+    fs::write(&staged, bytes).map_err(|e| format!("Failed to stage update: {}", e))?;
+    if let Ok(metadata) = fs::metadata(target) {
+        let _ = fs::set_permissions(&staged, metadata.permissions());
+    }
+    fs::rename(&staged, target).map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        format!("Failed to replace {}: {}", target.display(), e)
+    })
+}
+
+/// Installs a package with the system package manager through `pkexec`.
+fn install_package(bytes: &[u8], extension: &str, command: &[&str]) -> Result<(), String> {
+    let dir = std::env::temp_dir().join(format!("notive-update-{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to stage update: {}", e))?;
+    let package = dir.join(format!("notive.{}", extension));
+
+    let result = fs::write(&package, bytes)
+        .map_err(|e| format!("Failed to stage update: {}", e))
+        .and_then(|()| {
+            std::process::Command::new("pkexec")
+                .args(command)
+                .arg(&package)
+                .status()
+                .map_err(|e| format!("Failed to run pkexec: {}", e))
+        })
+        .and_then(|status| {
+            if status.success() {
+                Ok(())
+            } else {
+                Err(format!("{} failed to install the update ({})", command[0], status))
+            }
+        });
+
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
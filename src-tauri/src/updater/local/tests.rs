@@ -0,0 +1,244 @@
+//! Tests for local update bundles and mirrors.
+
+use super::*;
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+// Test vector from minisign-verify: the message "test" signed with this key
+const PUBLIC_KEY: &str = "untrusted comment: minisign public key E7620F1842B4E81F
+RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+
+const SIGNATURE: &str = "untrusted comment: signature from minisign secret key
+RWQf6LRCGA9i59SLOFxz6NxvASXDJeRtuZykwQepbDEGt87ig1BNpWaVWuNrm73YiIiJbq71Wi+dP9eKL8OC351vwIasSSbXxwA=
+trusted comment: timestamp:1555779966\tfile:test
+QtKMXWyYcwdpZAlPF7tE2ENJkRd1ujvKjlj1m9RtHTBnZPa5WKU5uWRs5GoP5M/VqE81QFuMKI5k/SfNQUaOAA==";
+
+fn encode(text: &str) -> String {
+    base64::engine::general_purpose::STANDARD.encode(text)
+}
+
+#[test]
+fn test_verify_bundle_accepts_valid_signature() {
+    assert!(verify_bundle(b"test", &encode(SIGNATURE), &encode(PUBLIC_KEY)).is_ok());
+}
+
+#[test]
+fn test_verify_bundle_rejects_modified_bundle() {
+    let err = verify_bundle(b"Test", &encode(SIGNATURE), &encode(PUBLIC_KEY)).unwrap_err();
+    assert!(err.contains("does not match its signature"));
+}
+
+#[test]
+fn test_verify_bundle_rejects_other_key() {
+    let other_key = "untrusted comment: minisign public key 0000000000000000
+RWQAAAAAAAAAAHmlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3";
+    assert!(verify_bundle(b"test", &encode(SIGNATURE), &encode(other_key)).is_err());
+}
+
+#[test]
+fn test_verify_bundle_reports_malformed_input() {
+    let err = verify_bundle(b"test", &encode(SIGNATURE), "YOUR_PUBLIC_KEY_HERE").unwrap_err();
+    assert!(err.contains("public key"));
+
+    let err = verify_bundle(b"test", "not base64!", &encode(PUBLIC_KEY)).unwrap_err();
+    assert!(err.contains("signature"));
+
+    let err = verify_bundle(b"test", &encode("garbage"), &encode(PUBLIC_KEY)).unwrap_err();
+    assert!(err.contains("Invalid signature file"));
+}
+
+#[test]
+fn test_read_signature_defaults_to_sig_file() {
+    let dir = TempDir::new().unwrap();
+    let bundle = dir.path().join("notive_1.3.0_amd64.AppImage");
+    fs::write(&bundle, b"bundle").unwrap();
+    fs::write(dir.path().join("notive_1.3.0_amd64.AppImage.sig"), "c2ln\n").unwrap();
+
+    assert_eq!(read_signature(&bundle, None).unwrap(), "c2ln");
+}
+
+#[test]
+fn test_read_signature_uses_explicit_path() {
+    let dir = TempDir::new().unwrap();
+    let bundle = dir.path().join("bundle.AppImage");
+    let signature = dir.path().join("other.sig");
+    fs::write(&signature, "b3RoZXI=").unwrap();
+
+    assert_eq!(read_signature(&bundle, Some(&signature)).unwrap(), "b3RoZXI=");
+    assert!(read_signature(&bundle, None).unwrap_err().contains("bundle.AppImage.sig"));
+}
+
+#[test]
+fn test_version_from_file_name() {
+    assert_eq!(
+        version_from_file_name("notive_1.3.0_amd64.AppImage").as_deref(),
+        Some("1.3.0")
+    );
+    assert_eq!(
+        version_from_file_name("notive_1.4.0-beta.2_amd64.AppImage").as_deref(),
+        Some("1.4.0-beta.2")
+    );
+    assert_eq!(
+        version_from_file_name("notive-1.3.0-1.x86_64.rpm").as_deref(),
+        Some("1.3.0")
+    );
+    assert_eq!(version_from_file_name("notive_v2.0.0_amd64.deb").as_deref(), Some("2.0.0"));
+    assert_eq!(version_from_file_name("notive.AppImage"), None);
+}
+
+#[test]
+fn test_resolve_path_rejects_traversal() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("beta")).unwrap();
+    fs::write(dir.path().join("beta/latest.json"), "{}").unwrap();
+
+    assert_eq!(
+        resolve_path(dir.path(), "beta/latest.json"),
+        Some(dir.path().join("beta/latest.json"))
+    );
+    assert_eq!(resolve_path(dir.path(), "../etc/passwd"), None);
+    assert_eq!(resolve_path(dir.path(), "beta/../../secret"), None);
+    assert_eq!(resolve_path(dir.path(), "/etc/passwd"), None);
+    assert_eq!(resolve_path(dir.path(), "beta"), None);
+    assert_eq!(resolve_path(dir.path(), "missing.json"), None);
+}
+
+#[test]
+fn test_mirror_dir_accepts_paths_and_file_urls() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().to_string_lossy().to_string();
+    let file_url = Url::from_directory_path(dir.path()).unwrap().to_string();
+
+    assert_eq!(mirror_dir(&path).unwrap(), dir.path());
+    assert_eq!(mirror_dir(&file_url).unwrap(), dir.path());
+    assert!(mirror_dir("/nonexistent/notive-mirror").is_err());
+    assert!(mirror_dir("file://remote-host/share").is_err());
+}
+
+#[test]
+fn test_platform_keys_prefer_bundle_type() {
+    let keys = platform_keys(Some(InstallKind::Deb));
+    assert_eq!(keys.len(), 2);
+    assert!(keys[0].starts_with("linux-") && keys[0].ends_with("-deb"));
+    assert_eq!(keys[1], platform_keys(None)[0]);
+}
+
+#[test]
+fn test_bundle_kind_from_contents() {
+    assert_eq!(InstallKind::of_bundle(b"\x7fELF\x02\x01"), Some(InstallKind::AppImage));
+    assert_eq!(InstallKind::of_bundle(b"!<arch>\ndebian-binary   "), Some(InstallKind::Deb));
+    assert_eq!(InstallKind::of_bundle(&[0xed, 0xab, 0xee, 0xdb, 0x03]), Some(InstallKind::Rpm));
+    assert_eq!(InstallKind::of_bundle(b"PK\x03\x04"), None);
+}
+
+#[test]
+fn test_read_release_static_format() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("notive_1.3.0_amd64.AppImage"), b"bundle").unwrap();
+    let keys = platform_keys(Some(InstallKind::AppImage));
+    let manifest = json!({
+        "version": "v1.3.0",
+        "notes": "Bug fixes",
+        "platforms": {
+            (keys[1].clone()): {
+                "url": "https://github.com/owner/notive/releases/download/v1.3.0/notive_1.3.0_amd64.AppImage",
+                "signature": "sig"
+            }
+        }
+    });
+
+    let release = read_release(dir.path(), &manifest, &keys).unwrap();
+    assert_eq!(release.version, "1.3.0");
+    assert_eq!(release.notes.as_deref(), Some("Bug fixes"));
+    assert_eq!(release.bundle, dir.path().join("notive_1.3.0_amd64.AppImage"));
+    assert_eq!(release.signature, "sig");
+}
+
+#[test]
+fn test_read_release_single_bundle_format() {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("notive_1.3.0_amd64.deb"), b"bundle").unwrap();
+    let manifest = json!({
+        "version": "1.3.0",
+        "url": "notive_1.3.0_amd64.deb",
+        "signature": "sig"
+    });
+
+    let release = read_release(dir.path(), &manifest, &platform_keys(None)).unwrap();
+    assert_eq!(release.bundle, dir.path().join("notive_1.3.0_amd64.deb"));
+    assert_eq!(release.notes, None);
+}
+
+#[test]
+fn test_read_release_reports_missing_parts() {
+    let dir = TempDir::new().unwrap();
+    let keys = platform_keys(None);
+
+    let err = read_release(dir.path(), &json!({"version": "1.3.0", "platforms": {}}), &keys).unwrap_err();
+    assert!(err.contains("no update for"));
+
+    let manifest = json!({"version": "1.3.0", "url": "notive.AppImage", "signature": "sig"});
+    let err = read_release(dir.path(), &manifest, &keys).unwrap_err();
+    assert!(err.contains("missing from the update mirror"));
+
+    // Bundle names never escape the mirror
+    let manifest = json!({"version": "1.3.0", "url": "..", "signature": "sig"});
+    assert!(read_release(dir.path(), &manifest, &keys).is_err());
+}
+
+#[test]
+fn test_find_release_follows_channel() {
+    let dir = TempDir::new().unwrap();
+    let mirror = dir.path().to_string_lossy().to_string();
+    fs::create_dir_all(dir.path().join("beta")).unwrap();
+    fs::write(dir.path().join("notive_1.3.0_amd64.AppImage"), b"stable").unwrap();
+    fs::write(dir.path().join("notive_1.4.0-beta.1_amd64.AppImage"), b"beta").unwrap();
+    let manifest = |version: &str| {
+        json!({
+            "version": version,
+            "url": format!("notive_{}_amd64.AppImage", version),
+            "signature": "sig"
+        })
+        .to_string()
+    };
+    fs::write(dir.path().join("latest.json"), manifest("1.3.0")).unwrap();
+    fs::write(dir.path().join("beta/latest.json"), manifest("1.4.0-beta.1")).unwrap();
+
+    let current = Version::parse("1.2.0").unwrap();
+    let stable = find_release(&mirror, &UpdateChannel::Stable, &current, false).unwrap().unwrap();
+    assert_eq!(stable.version, "1.3.0");
+    let beta = find_release(&mirror, &UpdateChannel::Beta, &current, false).unwrap().unwrap();
+    assert_eq!(beta.version, "1.4.0-beta.1");
+
+    // Nightly falls back to the beta manifest
+    let nightly = find_release(&mirror, &UpdateChannel::Nightly, &current, false).unwrap().unwrap();
+    assert_eq!(nightly.version, "1.4.0-beta.1");
+
+    // Nothing newer than the running version
+    let current = Version::parse("1.3.0").unwrap();
+    assert!(find_release(&mirror, &UpdateChannel::Stable, &current, false).unwrap().is_none());
+}
+
+#[test]
+fn test_find_release_without_manifest() {
+    let dir = TempDir::new().unwrap();
+    let current = Version::parse("1.2.0").unwrap();
+    let err = find_release(&dir.path().to_string_lossy(), &UpdateChannel::Stable, &current, false).unwrap_err();
+    assert!(err.contains("No update manifest"));
+}
+
+#[test]
+fn test_replace_file_keeps_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TempDir::new().unwrap();
+    let target = dir.path().join("Notive.AppImage");
+    fs::write(&target, b"old").unwrap();
+    fs::set_permissions(&target, fs::Permissions::from_mode(0o755)).unwrap();
+
+    replace_file(&target, b"new").unwrap();
+    assert_eq!(fs::read(&target).unwrap(), b"new");
+    assert_eq!(fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o755);
+    assert!(!dir.path().join("Notive.update").exists());
+}
@@ -1,15 +1,53 @@
 //! Auto-update functionality.
 
+pub mod background;
+pub mod channel;
+pub mod local;
+mod persistence;
+pub mod release_notes;
+pub mod rollback;
+
 #[cfg(test)]
 mod tests;
 
-use tauri::{AppHandle, Runtime};
-use tauri_plugin_updater::UpdaterExt;
+use crate::config::UpdateChannel;
+use background::ProgressTracker;
+use persistence::{load_release_notes, save_release_notes};
+use release_notes::ReleaseNotes;
+use rollback::StartupCheck;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_updater::{Update, Updater, UpdaterExt};
+use url::Url;
+
+/// An update offered by the update server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AvailableUpdate {
+    pub version: String,
+    pub notes: Option<ReleaseNotes>,
+}
+
+/// Release notes kept for the "What's new" dialog after updating.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredReleaseNotes {
+    pub version: String,
+    pub body: String,
+    pub shown: bool,
+}
 
 /// Sets up the auto-updater with periodic checks.
 pub async fn setup<R: Runtime>(app: &AppHandle<R>) {
     log::debug!("Setting up auto-updater...");
 
+    // Re-arm an update that was downloaded before the last restart
+    background::resume(app).await;
+
+    // Show what changed if this is the first start after an update
+    show_whats_new(app).await;
+
     // Delay initial check to allow app to fully load
     tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
 
@@ -42,31 +80,82 @@ pub async fn setup<R: Runtime>(app: &AppHandle<R>) {
     });
 }
 
-/// Checks for available updates.
+/// Gets the stable update endpoint from `tauri.conf.json`.
+fn configured_endpoint<R: Runtime>(app: &AppHandle<R>) -> Result<Url, String> {
+    app.config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("endpoints"))
+        .and_then(|endpoints| endpoints.get(0))
+        .and_then(|endpoint| endpoint.as_str())
+        .ok_or_else(|| "No update endpoint is configured".to_string())
+        .and_then(|endpoint| Url::parse(endpoint).map_err(|e| format!("Invalid update endpoint: {}", e)))
+}
+
+/// Builds an updater for the given channel.
+///
+/// The channel's manifests are derived from the stable endpoint in
+/// `tauri.conf.json`, and the channel also decides which versions are
+/// accepted. Update mirrors are read from disk by [`local`] instead.
+fn build_updater<R: Runtime>(
+    app: &AppHandle<R>,
+    channel: &UpdateChannel,
+    allow_downgrade: bool,
+) -> Result<Updater, String> {
+    let endpoints = channel::endpoints(&configured_endpoint(app)?, channel)?;
+    let channel = channel.clone();
+
+    app.updater_builder()
+        .endpoints(endpoints)
+        .map_err(|e| e.to_string())?
+        .version_comparator(move |current, release| {
+            channel::should_update(&channel, &current, &release.version, allow_downgrade)
+        })
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Checks for available updates on the configured channel.
 pub async fn check<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
-    log::debug!("Checking for updates...");
+    check_with(app, false).await
+}
+
+/// Checks for available updates, optionally accepting older versions.
+///
+/// Downgrades are only offered when the running build is not part of the
+/// configured channel, e.g. after switching from Nightly back to Stable.
+pub async fn check_with<R: Runtime>(app: &AppHandle<R>, allow_downgrade: bool) -> Result<bool, String> {
+    find_update(app, allow_downgrade).await.map(|update| update.is_some())
+}
 
-    let updater = app.updater().map_err(|e| e.to_string())?;
+/// Checks for an available update and returns its version and release notes.
+pub async fn find_update<R: Runtime>(
+    app: &AppHandle<R>,
+    allow_downgrade: bool,
+) -> Result<Option<AvailableUpdate>, String> {
+    let settings = crate::config::load(app).unwrap_or_default();
+    let channel = settings.update_channel;
+    log::debug!("Checking for updates on {:?} channel...", channel);
+
+    if let Some(mirror) = settings.update_mirror {
+        let release = local::find_release(&mirror, &channel, &app.package_info().version, allow_downgrade)?;
+        return Ok(release.map(|release| offer(app, release.version, release.notes.unwrap_or_default())));
+    }
+
+    let updater = build_updater(app, &channel, allow_downgrade)?;
 
     match updater.check().await {
         Ok(Some(update)) => {
-            log::info!("Update available: {}", update.version);
-            
-            // Check if notifications are enabled
-            let settings = crate::config::load(app).unwrap_or_default();
-            if settings.notifications_enabled && settings.auto_update {
-                // Show notification
-                let _ = app.notification().builder()
-                    .title("Update Available")
-                    .body(&format!("Notive {} is available. Click to install.", update.version))
-                    .show();
-            }
+            // Arm a bundle that was downloaded before an offline restart
+            background::rearm(app, &update);
             
-            Ok(true)
+            let body = update.body.clone().unwrap_or_default();
+            Ok(Some(offer(app, update.version, body)))
         }
         Ok(None) => {
             log::debug!("No updates available");
-            Ok(false)
+            Ok(None)
         }
         Err(e) => {
             log::error!("Update check failed: {}", e);
@@ -75,46 +164,188 @@ pub async fn check<R: Runtime>(app: &AppHandle<R>) -> Result<bool, String> {
     }
 }
 
-/// Downloads and installs an update.
+/// Announces an available update and keeps its release notes.
+fn offer<R: Runtime>(app: &AppHandle<R>, version: String, body: String) -> AvailableUpdate {
+    log::info!("Update available: {}", version);
+    
+    // Check if notifications are enabled
+    let settings = crate::config::load(app).unwrap_or_default();
+    if settings.notifications_enabled && settings.auto_update {
+        // Show notification
+        let _ = app.notification().builder()
+            .title("Update Available")
+            .body(&format!("Notive {} is available. Click to install.", version))
+            .show();
+    }
+    
+    // Keep the notes around for the "What's new" dialog after updating
+    let stored = StoredReleaseNotes {
+        version: version.clone(),
+        body: body.clone(),
+        shown: false,
+    };
+    if let Err(e) = save_release_notes(app, &stored) {
+        log::warn!("Failed to save release notes: {}", e);
+    }
+    
+    AvailableUpdate {
+        notes: release_notes::parse_release_notes(&body, &version),
+        version,
+    }
+}
+
+/// Downloads and installs an update from the configured channel.
 pub async fn install<R: Runtime>(app: &AppHandle<R>) -> Result<(), String> {
-    log::info!("Installing update...");
+    install_with(app, false).await
+}
+
+/// Downloads and installs an update, optionally accepting older versions.
+pub async fn install_with<R: Runtime>(app: &AppHandle<R>, allow_downgrade: bool) -> Result<(), String> {
+    let settings = crate::config::load(app).unwrap_or_default();
+    let channel = settings.update_channel;
+    log::info!("Installing update from {:?} channel...", channel);
 
-    let updater = app.updater().map_err(|e| e.to_string())?;
+    if let Some(mirror) = settings.update_mirror {
+        return local::install_from_mirror(app, &mirror, &channel, allow_downgrade)
+            .await
+            .map(|_| ());
+    }
+
+    let updater = build_updater(app, &channel, allow_downgrade)?;
 
     match updater.check().await {
-        Ok(Some(update)) => {
-            update
-                .download_and_install(
-                    |downloaded, total| {
-                        // Emit progress events
-                        let progress = if total > 0 {
-                            (downloaded as f64 / total as f64 * 100.0) as u32
-                        } else {
-                            0
-                        };
-                        log::debug!("Update download progress: {}% ({}/{} bytes)", progress, downloaded, total);
-                        
-                        // Optionally show progress notification for large updates
-                        if total > 10_000_000 && progress % 25 == 0 {
-                            // Show progress every 25% for large downloads
-                            let _ = app.notification().builder()
-                                .title("Updating Notive")
-                                .body(&format!("Downloading update: {}%", progress))
-                                .show();
-                        }
-                    },
-                    || {
-                        log::info!("Update ready, preparing to restart...");
-                        let _ = app.notification().builder()
-                            .title("Update Ready")
-                            .body("Update downloaded. The application will restart shortly.")
-                            .show();
-                    },
-                )
-                .await
-                .map_err(|e| e.to_string())
-        }
+        Ok(Some(update)) => install_update(app, update).await,
         Ok(None) => Err("No update available".to_string()),
         Err(e) => Err(e.to_string()),
     }
 }
+
+/// Snapshots the running version, then downloads and installs `update`.
+async fn install_update<R: Runtime>(app: &AppHandle<R>, update: Update) -> Result<(), String> {
+    if let Err(e) = snapshot_before_install(app, &update.current_version, &update.version) {
+        log::warn!("Failed to create rollback snapshot: {}", e);
+    }
+    
+    let mut tracker = ProgressTracker::new(update.version.clone());
+    update
+        .download_and_install(
+            |chunk_len, total| {
+                if let Some(progress) = tracker.advance(chunk_len, total) {
+                    log::debug!("Update download progress: {:?}%", progress.percent);
+                    let _ = app.emit(background::PROGRESS_EVENT, &progress);
+                }
+            },
+            || {
+                log::info!("Update ready, preparing to restart...");
+                let _ = app.notification().builder()
+                    .title("Update Ready")
+                    .body("Update downloaded. The application will restart shortly.")
+                    .show();
+            },
+        )
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Snapshots the running version so the update can be rolled back.
+fn snapshot_before_install<R: Runtime>(
+    app: &AppHandle<R>,
+    previous_version: &str,
+    installed_version: &str,
+) -> Result<(), String> {
+    let rollback_dir = rollback::rollback_dir().ok_or("Could not determine data directory")?;
+    let store_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let binary_path = rollback::current_binary_path()?;
+    
+    rollback::create_snapshot(&rollback_dir, &binary_path, &store_dir, previous_version, installed_version)?;
+    Ok(())
+}
+
+/// Checks whether a freshly installed update keeps failing to start.
+///
+/// Returns the restored binary path if the previous version was rolled back
+/// into `store_dir` and the process should be relaunched.
+pub fn check_startup_health(current_version: &str, store_dir: &Path) -> Option<PathBuf> {
+    let rollback_dir = rollback::rollback_dir()?;
+    
+    match rollback::record_startup(&rollback_dir, current_version) {
+        StartupCheck::Normal => None,
+        StartupCheck::Probation(attempt) => {
+            log::info!(
+                "Starting updated version {} (attempt {}/{})",
+                current_version,
+                attempt,
+                rollback::MAX_FAILED_STARTUPS
+            );
+            None
+        }
+        StartupCheck::RollbackNeeded => {
+            log::error!(
+                "Version {} failed to start {} times, rolling back",
+                current_version,
+                rollback::MAX_FAILED_STARTUPS
+            );
+            match rollback::rollback(store_dir) {
+                Ok(snapshot) => Some(snapshot.binary_path),
+                Err(e) => {
+                    log::error!("Automatic rollback failed: {}", e);
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Marks the running version as healthy once it has stayed up for a while.
+pub async fn confirm_startup_after_delay() {
+    tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+    confirm_startup();
+}
+
+/// Marks the running version as healthy.
+///
+/// Also called on a clean exit, so quitting a new version soon after it
+/// started is not mistaken for a failed startup.
+pub fn confirm_startup() {
+    if let Some(rollback_dir) = rollback::rollback_dir() {
+        if let Err(e) = rollback::confirm_startup(&rollback_dir) {
+            log::warn!("Failed to confirm startup: {}", e);
+        }
+    }
+}
+
+/// Gets the release notes stored for `version`, if any.
+pub fn stored_release_notes<R: Runtime>(app: &AppHandle<R>, version: &str) -> Option<ReleaseNotes> {
+    let stored = load_release_notes(app).ok().flatten()?;
+    if stored.version.trim_start_matches('v') != version.trim_start_matches('v') {
+        return None;
+    }
+    release_notes::parse_release_notes(&stored.body, &stored.version)
+}
+
+/// Shows the release notes of the running version once after an update.
+async fn show_whats_new<R: Runtime>(app: &AppHandle<R>) {
+    let current_version = app.package_info().version.to_string();
+    let Some(mut stored) = load_release_notes(app).ok().flatten() else {
+        return;
+    };
+    if stored.shown || stored.version.trim_start_matches('v') != current_version {
+        return;
+    }
+    
+    stored.shown = true;
+    if let Err(e) = save_release_notes(app, &stored) {
+        log::warn!("Failed to save release notes: {}", e);
+    }
+    
+    let Some(notes) = release_notes::parse_release_notes(&stored.body, &stored.version) else {
+        return;
+    };
+    let _ = app.dialog()
+        .message(&release_notes::format_for_dialog(&notes))
+        .title(&format!("What's new in Notive {}", current_version))
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::Ok)
+        .show()
+        .await;
+}
@@ -0,0 +1,79 @@
+//! Update state persistence to settings store.
+
+use super::background::UpdateState;
+use super::StoredReleaseNotes;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_store::{with_store, StoreCollection};
+
+const UPDATER_STORE_PATH: &str = "updater.json";
+
+/// Saves the update state to persistent storage.
+pub fn save_update_state<R: Runtime>(
+    app: &AppHandle<R>,
+    state: &UpdateState,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+
+    with_store(app, stores, UPDATER_STORE_PATH, |store| {
+        let data = serde_json::to_value(state)
+            .map_err(|e| tauri_plugin_store::Error::Serialize(e.to_string()))?;
+        store.insert("state".to_string(), data)?;
+        store.save()?;
+        log::debug!("Update state saved to store");
+        Ok(())
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
+
+/// Loads the update state from persistent storage.
+pub fn load_update_state<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<UpdateState, Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+
+    with_store(app, stores, UPDATER_STORE_PATH, |store| {
+        if let Some(data) = store.get("state") {
+            match serde_json::from_value::<UpdateState>(data.clone()) {
+                Ok(state) => return Ok(state),
+                Err(e) => log::warn!("Failed to deserialize update state: {}", e),
+            }
+        }
+        Ok(UpdateState::default())
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
+
+/// Saves the release notes of the most recently offered update.
+pub fn save_release_notes<R: Runtime>(
+    app: &AppHandle<R>,
+    notes: &StoredReleaseNotes,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+
+    with_store(app, stores, UPDATER_STORE_PATH, |store| {
+        let data = serde_json::to_value(notes)
+            .map_err(|e| tauri_plugin_store::Error::Serialize(e.to_string()))?;
+        store.insert("release_notes".to_string(), data)?;
+        store.save()?;
+        Ok(())
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
+
+/// Loads the release notes of the most recently offered update.
+pub fn load_release_notes<R: Runtime>(
+    app: &AppHandle<R>,
+) -> Result<Option<StoredReleaseNotes>, Box<dyn std::error::Error>> {
+    let stores = app.state::<StoreCollection<R>>();
+
+    with_store(app, stores, UPDATER_STORE_PATH, |store| {
+        if let Some(data) = store.get("release_notes") {
+            match serde_json::from_value::<StoredReleaseNotes>(data.clone()) {
+                Ok(notes) => return Ok(Some(notes)),
+                Err(e) => log::warn!("Failed to deserialize release notes: {}", e),
+            }
+        }
+        Ok(None)
+    })
+    .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())) as Box<dyn std::error::Error>)
+}
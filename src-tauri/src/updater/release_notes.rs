@@ -0,0 +1,226 @@
+//! Release notes parsing and display.
+//!
+//! Release notes come from the `notes` field of the update manifest, which is
+//! generated by git-cliff (see `cliff.toml`): one `## [version] - date` heading
+//! per release, `### Group` headings per commit group, and one bullet per
+//! commit in the form `- **scope:** Message by @user`.
+
+#[cfg(test)]
+mod tests;
+
+use serde::{Deserialize, Serialize};
+
+/// Section used for bullets that appear before any group heading.
+const DEFAULT_SECTION: &str = "Changes";
+
+/// Entries listed per section in a dialog before the rest is summarized.
+const MAX_DIALOG_ENTRIES: usize = 8;
+
+/// A single changelog bullet.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NotesEntry {
+    pub scope: Option<String>,
+    pub message: String,
+    pub author: Option<String>,
+}
+
+/// A group of changelog bullets, e.g. "Features" or "Bug Fixes".
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NotesSection {
+    pub title: String,
+    pub entries: Vec<NotesEntry>,
+}
+
+/// Release notes for a single version.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReleaseNotes {
+    /// Version without the leading `v`; `None` for unreleased or untitled notes.
+    pub version: Option<String>,
+    pub date: Option<String>,
+    pub sections: Vec<NotesSection>,
+}
+
+impl ReleaseNotes {
+    /// Returns true if the notes contain no entries.
+    pub fn is_empty(&self) -> bool {
+        self.sections.iter().all(|s| s.entries.is_empty())
+    }
+
+    fn section_mut(&mut self, title: &str) -> &mut NotesSection {
+        if self.sections.last().map(|s| s.title != title).unwrap_or(true) {
+            self.sections.push(NotesSection {
+                title: title.to_string(),
+                entries: Vec::new(),
+            });
+        }
+        self.sections.last_mut().unwrap()
+    }
+}
+
+/// Parses a git-cliff changelog into one [`ReleaseNotes`] per release.
+///
+/// Notes without any `##` heading (e.g. a manifest body holding a single
+/// release) are returned as one release without a version.
+pub fn parse_changelog(text: &str) -> Vec<ReleaseNotes> {
+    let mut releases: Vec<ReleaseNotes> = Vec::new();
+    let mut in_comment = false;
+
+    for raw_line in text.lines() {
+        let line = raw_line.trim();
+
+        // Skip HTML comments such as the git-cliff footer
+        if in_comment || line.starts_with("<!--") {
+            in_comment = !line.contains("-->");
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(heading) = line.strip_prefix("### ") {
+            current(&mut releases).section_mut(heading.trim());
+        } else if let Some(heading) = line.strip_prefix("## ") {
+            releases.push(parse_release_heading(heading));
+        } else if line.starts_with("# ") {
+            // Document title
+        } else if let Some(bullet) = line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")) {
+            let release = current(&mut releases);
+            let title = release
+                .sections
+                .last()
+                .map(|s| s.title.clone())
+                .unwrap_or_else(|| DEFAULT_SECTION.to_string());
+            release.section_mut(&title).entries.push(parse_entry(bullet));
+        } else if raw_line.starts_with(char::is_whitespace) {
+            // Continuation of a wrapped bullet
+            if let Some(entry) = releases
+                .last_mut()
+                .and_then(|r| r.sections.last_mut())
+                .and_then(|s| s.entries.last_mut())
+            {
+                entry.message.push(' ');
+                entry.message.push_str(line);
+            }
+        }
+    }
+
+    releases.retain(|r| r.version.is_some() || !r.is_empty());
+    releases
+}
+
+/// Parses release notes and returns the entry for `version`.
+///
+/// Notes without a release heading (such as a manifest body) are taken to
+/// describe `version`. A changelog that does not mention `version` yields
+/// nothing rather than the notes of another release.
+pub fn parse_release_notes(text: &str, version: &str) -> Option<ReleaseNotes> {
+    let version = version.trim_start_matches('v');
+    let mut releases = parse_changelog(text);
+    let index = releases
+        .iter()
+        .position(|r| r.version.as_deref() == Some(version))
+        .or_else(|| (releases.len() == 1 && releases[0].version.is_none()).then_some(0))?;
+
+    let mut notes = releases.swap_remove(index);
+    if notes.version.is_none() {
+        notes.version = Some(version.to_string());
+    }
+    Some(notes)
+}
+
+fn current(releases: &mut Vec<ReleaseNotes>) -> &mut ReleaseNotes {
+    if releases.is_empty() {
+        releases.push(ReleaseNotes::default());
+    }
+    releases.last_mut().unwrap()
+}
+
+/// Parses `[1.3.0] - 2025-01-10`, `[Unreleased]` or `v1.3.0`.
+fn parse_release_heading(heading: &str) -> ReleaseNotes {
+    let heading = heading.trim();
+    let (name, rest) = match heading.strip_prefix('[') {
+        Some(bracketed) => match bracketed.split_once(']') {
+            Some((name, rest)) => (name, rest),
+            None => (bracketed, ""),
+        },
+        None => match heading.split_once(' ') {
+            Some((name, rest)) => (name, rest),
+            None => (heading, ""),
+        },
+    };
+
+    let name = name.trim();
+    let version = if name.eq_ignore_ascii_case("unreleased") {
+        None
+    } else {
+        Some(name.trim_start_matches('v').to_string())
+    };
+    let date = rest
+        .trim()
+        .trim_start_matches('-')
+        .trim()
+        .to_string();
+
+    ReleaseNotes {
+        version,
+        date: (!date.is_empty()).then_some(date),
+        sections: Vec::new(),
+    }
+}
+
+/// Parses `**scope:** Message by @user`.
+fn parse_entry(bullet: &str) -> NotesEntry {
+    let mut text = bullet.trim();
+
+    let mut scope = None;
+    if let Some(rest) = text.strip_prefix("**") {
+        if let Some((name, after)) = rest.split_once(":**") {
+            scope = Some(name.trim().to_string());
+            text = after.trim_start();
+        }
+    }
+
+    let mut author = None;
+    if let Some((message, user)) = text.rsplit_once(" by @") {
+        if !user.is_empty() && !user.contains(char::is_whitespace) {
+            author = Some(user.to_string());
+            text = message;
+        }
+    }
+
+    NotesEntry {
+        scope,
+        message: text.trim().to_string(),
+        author,
+    }
+}
+
+/// Formats release notes as plain text for a native dialog.
+pub fn format_for_dialog(notes: &ReleaseNotes) -> String {
+    let mut out = String::new();
+
+    for section in notes.sections.iter().filter(|s| !s.entries.is_empty()) {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&section.title);
+        out.push('\n');
+
+        for entry in section.entries.iter().take(MAX_DIALOG_ENTRIES) {
+            out.push_str("• ");
+            if let Some(scope) = &entry.scope {
+                out.push_str(scope);
+                out.push_str(": ");
+            }
+            out.push_str(&entry.message);
+            out.push('\n');
+        }
+
+        let hidden = section.entries.len().saturating_sub(MAX_DIALOG_ENTRIES);
+        if hidden > 0 {
+            out.push_str(&format!("…and {} more\n", hidden));
+        }
+    }
+
+    out.trim_end().to_string()
+}
@@ -0,0 +1,141 @@
+//! Tests for release notes parsing.
+
+use super::*;
+
+const CHANGELOG: &str = r#"# Changelog
+
+All notable changes to this project will be documented in this file.
+
+## [Unreleased]
+
+### Features
+
+- Work in progress
+
+## [1.3.0] - 2026-03-02
+
+### Features
+
+- **updater:** Honor the configured update channel by @alice
+- Add release notes viewer
+
+### Bug Fixes
+
+- **tray:** Fix duplicate menu entries
+- Keep window geometry after restart by @bob
+
+## [1.2.0] - 2026-01-15
+
+### Performance
+
+- Faster startup
+
+<!-- generated by git-cliff -->
+"#;
+
+#[test]
+fn test_parse_changelog_releases() {
+    let releases = parse_changelog(CHANGELOG);
+    assert_eq!(releases.len(), 3);
+    
+    assert_eq!(releases[0].version, None);
+    assert_eq!(releases[1].version.as_deref(), Some("1.3.0"));
+    assert_eq!(releases[1].date.as_deref(), Some("2026-03-02"));
+    assert_eq!(releases[2].version.as_deref(), Some("1.2.0"));
+}
+
+#[test]
+fn test_parse_changelog_sections() {
+    let releases = parse_changelog(CHANGELOG);
+    let release = &releases[1];
+    
+    assert_eq!(release.sections.len(), 2);
+    assert_eq!(release.sections[0].title, "Features");
+    assert_eq!(release.sections[0].entries.len(), 2);
+    assert_eq!(release.sections[1].title, "Bug Fixes");
+    assert_eq!(release.sections[1].entries.len(), 2);
+}
+
+#[test]
+fn test_parse_entry_scope_and_author() {
+    let releases = parse_changelog(CHANGELOG);
+    let entry = &releases[1].sections[0].entries[0];
+    
+    assert_eq!(entry.scope.as_deref(), Some("updater"));
+    assert_eq!(entry.message, "Honor the configured update channel");
+    assert_eq!(entry.author.as_deref(), Some("alice"));
+    
+    let plain = &releases[1].sections[0].entries[1];
+    assert_eq!(plain.scope, None);
+    assert_eq!(plain.message, "Add release notes viewer");
+    assert_eq!(plain.author, None);
+}
+
+#[test]
+fn test_parse_indented_template_output() {
+    let notes = "    ## [1.1.0] - 2025-12-01\n\n    ### Bug Fixes\n        - **sync:** Handle empty files\n          across devices\n";
+    let releases = parse_changelog(notes);
+    
+    assert_eq!(releases.len(), 1);
+    let entry = &releases[0].sections[0].entries[0];
+    assert_eq!(entry.scope.as_deref(), Some("sync"));
+    assert_eq!(entry.message, "Handle empty files across devices");
+}
+
+#[test]
+fn test_parse_manifest_body_without_release_heading() {
+    let body = "### Features\n- Pop-out windows\n\n### Bug Fixes\n- Fix crash on quit\n";
+    let notes = parse_release_notes(body, "v1.4.0").unwrap();
+    
+    assert_eq!(notes.version.as_deref(), Some("1.4.0"));
+    assert_eq!(notes.sections.len(), 2);
+    assert_eq!(notes.sections[1].entries[0].message, "Fix crash on quit");
+}
+
+#[test]
+fn test_parse_bullets_without_section() {
+    let notes = parse_release_notes("- Something changed\n* Another change", "1.0.1").unwrap();
+    assert_eq!(notes.sections.len(), 1);
+    assert_eq!(notes.sections[0].title, "Changes");
+    assert_eq!(notes.sections[0].entries.len(), 2);
+}
+
+#[test]
+fn test_parse_release_notes_selects_version() {
+    let notes = parse_release_notes(CHANGELOG, "1.2.0").unwrap();
+    assert_eq!(notes.version.as_deref(), Some("1.2.0"));
+    assert_eq!(notes.sections[0].title, "Performance");
+}
+
+#[test]
+fn test_parse_release_notes_unknown_version() {
+    // Another release's notes must not be shown under this version
+    assert!(parse_release_notes(CHANGELOG, "1.4.0").is_none());
+}
+
+#[test]
+fn test_parse_empty_notes() {
+    assert!(parse_changelog("").is_empty());
+    assert!(parse_release_notes("# Changelog\n\n<!-- generated by git-cliff -->", "1.0.0").is_none());
+}
+
+#[test]
+fn test_format_for_dialog() {
+    let notes = parse_release_notes(CHANGELOG, "1.3.0").unwrap();
+    let text = format_for_dialog(&notes);
+    
+    assert!(text.starts_with("Features\n• updater: Honor the configured update channel"));
+    assert!(text.contains("\nBug Fixes\n• tray: Fix duplicate menu entries"));
+    assert!(!text.contains("@alice"));
+}
+
+#[test]
+fn test_format_for_dialog_truncates_long_sections() {
+    let body: String = (0..12).map(|i| format!("- Change {}\n", i)).collect();
+    let notes = parse_release_notes(&body, "1.0.0").unwrap();
+    let text = format_for_dialog(&notes);
+    
+    assert!(text.contains("• Change 7"));
+    assert!(!text.contains("• Change 8"));
+    assert!(text.ends_with("…and 4 more"));
+}
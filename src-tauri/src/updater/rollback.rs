@@ -0,0 +1,208 @@
+//! Update rollback snapshots.
+//!
+//! Before an update is installed, the running binary (or AppImage) and the
+//! persisted store files are copied into a rollback directory. The snapshot
+//! can be restored manually, or automatically when the new version fails to
+//! start several times in a row.
+
+#[cfg(test)]
+mod tests;
+
+use crate::utils::paths;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Consecutive failed startups of a new version before rolling back.
+pub const MAX_FAILED_STARTUPS: u32 = 3;
+
+const SNAPSHOT_FILE: &str = "snapshot.json";
+const BINARY_BACKUP: &str = "binary";
+const STORES_DIR: &str = "stores";
+
+/// Metadata describing a rollback snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackSnapshot {
+    pub previous_version: String,
+    pub installed_version: String,
+    pub created_at: String, // ISO 8601 format
+    pub binary_path: PathBuf,
+    pub store_files: Vec<String>,
+    pub failed_startups: u32,
+    pub confirmed: bool,
+}
+
+/// Result of checking a startup against the rollback snapshot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StartupCheck {
+    /// No unconfirmed update is pending.
+    Normal,
+    /// The freshly installed version is starting; the attempt was recorded.
+    Probation(u32),
+    /// The installed version failed to start too many times.
+    RollbackNeeded,
+}
+
+/// Gets the directory holding the rollback snapshot.
+pub fn rollback_dir() -> Option<PathBuf> {
+    paths::app_data_dir().map(|p| p.join("rollback"))
+}
+
+/// Gets the file that gets replaced when an update is installed.
+///
+/// AppImages replace the image itself, so `$APPIMAGE` takes precedence over
+/// the path of the running executable.
+pub fn current_binary_path() -> Result<PathBuf, String> {
+    if let Some(appimage) = std::env::var_os("APPIMAGE") {
+        return Ok(PathBuf::from(appimage));
+    }
+    std::env::current_exe().map_err(|e| format!("Failed to locate executable: {}", e))
+}
+
+/// Copies the binary and store files into `rollback_dir`.
+///
+/// Any previous snapshot is replaced.
+pub fn create_snapshot(
+    rollback_dir: &Path,
+    binary_path: &Path,
+    store_dir: &Path,
+    previous_version: &str,
+    installed_version: &str,
+) -> Result<RollbackSnapshot, String> {
+    if rollback_dir.exists() {
+        fs::remove_dir_all(rollback_dir)
+            .map_err(|e| format!("Failed to clear old snapshot: {}", e))?;
+    }
+    let stores_backup = rollback_dir.join(STORES_DIR);
+    fs::create_dir_all(&stores_backup)
+        .map_err(|e| format!("Failed to create rollback directory: {}", e))?;
+
+    fs::copy(binary_path, rollback_dir.join(BINARY_BACKUP))
+        .map_err(|e| format!("Failed to back up binary: {}", e))?;
+
+    let mut store_files = Vec::new();
+    for name in paths::STORE_FILES {
+        let source = store_dir.join(name);
+        if source.is_file() {
+            fs::copy(&source, stores_backup.join(name))
+                .map_err(|e| format!("Failed to back up {}: {}", name, e))?;
+            store_files.push(name.to_string());
+        }
+    }
+
+    let snapshot = RollbackSnapshot {
+        previous_version: previous_version.to_string(),
+        installed_version: installed_version.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        binary_path: binary_path.to_path_buf(),
+        store_files,
+        failed_startups: 0,
+        confirmed: false,
+    };
+    save_snapshot(rollback_dir, &snapshot)?;
+
+    log::info!(
+        "Rollback snapshot created: {} -> {} ({} store files)",
+        previous_version,
+        installed_version,
+        snapshot.store_files.len()
+    );
+    Ok(snapshot)
+}
+
+/// Loads the snapshot metadata, if a snapshot exists.
+pub fn load_snapshot(rollback_dir: &Path) -> Option<RollbackSnapshot> {
+    let contents = fs::read_to_string(rollback_dir.join(SNAPSHOT_FILE)).ok()?;
+    match serde_json::from_str(&contents) {
+        Ok(snapshot) => Some(snapshot),
+        Err(e) => {
+            log::warn!("Failed to deserialize rollback snapshot: {}", e);
+            None
+        }
+    }
+}
+
+fn save_snapshot(rollback_dir: &Path, snapshot: &RollbackSnapshot) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(snapshot)
+        .map_err(|e| format!("Failed to serialize snapshot: {}", e))?;
+    fs::write(rollback_dir.join(SNAPSHOT_FILE), json)
+        .map_err(|e| format!("Failed to write snapshot: {}", e))
+}
+
+/// Restores the binary and store files from the snapshot.
+///
+/// The snapshot is removed afterwards so a restored version is never rolled
+/// back a second time.
+pub fn restore_snapshot(rollback_dir: &Path, store_dir: &Path) -> Result<RollbackSnapshot, String> {
+    let snapshot = load_snapshot(rollback_dir)
+        .ok_or_else(|| "No rollback snapshot available".to_string())?;
+
+    // Copy next to the target and rename, so a running binary is replaced atomically
+    let staged = snapshot.binary_path.with_extension("rollback");
+    fs::copy(rollback_dir.join(BINARY_BACKUP), &staged)
+        .map_err(|e| format!("Failed to stage previous binary: {}", e))?;
+    if let Ok(metadata) = fs::metadata(&snapshot.binary_path) {
+        let _ = fs::set_permissions(&staged, metadata.permissions());
+    }
+    fs::rename(&staged, &snapshot.binary_path)
+        .map_err(|e| format!("Failed to restore previous binary: {}", e))?;
+
+    fs::create_dir_all(store_dir).map_err(|e| format!("Failed to create store directory: {}", e))?;
+    for name in &snapshot.store_files {
+        fs::copy(rollback_dir.join(STORES_DIR).join(name), store_dir.join(name))
+            .map_err(|e| format!("Failed to restore {}: {}", name, e))?;
+    }
+
+    fs::remove_dir_all(rollback_dir).map_err(|e| format!("Failed to remove snapshot: {}", e))?;
+
+    log::info!(
+        "Rolled back from {} to {}",
+        snapshot.installed_version,
+        snapshot.previous_version
+    );
+    Ok(snapshot)
+}
+
+/// Records a startup attempt of `current_version`.
+///
+/// Only the first runs of a freshly installed, unconfirmed version are
+/// counted; any other startup leaves the snapshot untouched.
+pub fn record_startup(rollback_dir: &Path, current_version: &str) -> StartupCheck {
+    let Some(mut snapshot) = load_snapshot(rollback_dir) else {
+        return StartupCheck::Normal;
+    };
+    if snapshot.confirmed || snapshot.installed_version != current_version {
+        return StartupCheck::Normal;
+    }
+
+    if snapshot.failed_startups >= MAX_FAILED_STARTUPS {
+        return StartupCheck::RollbackNeeded;
+    }
+
+    snapshot.failed_startups += 1;
+    if let Err(e) = save_snapshot(rollback_dir, &snapshot) {
+        log::warn!("Failed to record startup attempt: {}", e);
+    }
+    StartupCheck::Probation(snapshot.failed_startups)
+}
+
+/// Marks the installed version as healthy so it is no longer rolled back automatically.
+pub fn confirm_startup(rollback_dir: &Path) -> Result<(), String> {
+    let Some(mut snapshot) = load_snapshot(rollback_dir) else {
+        return Ok(());
+    };
+    if snapshot.confirmed {
+        return Ok(());
+    }
+    snapshot.confirmed = true;
+    snapshot.failed_startups = 0;
+    save_snapshot(rollback_dir, &snapshot)?;
+    log::info!("Update to {} confirmed healthy", snapshot.installed_version);
+    Ok(())
+}
+
+/// Restores the last snapshot into `store_dir`.
+pub fn rollback(store_dir: &Path) -> Result<RollbackSnapshot, String> {
+    let rollback_dir = rollback_dir().ok_or("Could not determine data directory")?;
+    restore_snapshot(&rollback_dir, store_dir)
+}
@@ -0,0 +1,108 @@
+//! Tests for update rollback snapshots.
+
+use super::*;
+use std::fs;
+use tempfile::TempDir;
+
+struct Fixture {
+    _root: TempDir,
+    rollback_dir: PathBuf,
+    store_dir: PathBuf,
+    binary_path: PathBuf,
+}
+
+fn fixture() -> Fixture {
+    let root = TempDir::new().unwrap();
+    let rollback_dir = root.path().join("rollback");
+    let store_dir = root.path().join("stores");
+    let binary_path = root.path().join("notive");
+    
+    fs::create_dir_all(&store_dir).unwrap();
+    fs::write(&binary_path, b"binary v1").unwrap();
+    fs::write(store_dir.join("settings.json"), r#"{"settings":{"zoom_level":1.0}}"#).unwrap();
+    fs::write(store_dir.join("bookmarks.json"), r#"{"bookmarks":[]}"#).unwrap();
+    
+    Fixture {
+        _root: root,
+        rollback_dir,
+        store_dir,
+        binary_path,
+    }
+}
+
+#[test]
+fn test_create_snapshot() {
+    let f = fixture();
+    let snapshot = create_snapshot(&f.rollback_dir, &f.binary_path, &f.store_dir, "1.0.0", "1.1.0").unwrap();
+    
+    assert_eq!(snapshot.previous_version, "1.0.0");
+    assert_eq!(snapshot.installed_version, "1.1.0");
+    assert_eq!(snapshot.store_files.len(), 2);
+    assert!(!snapshot.confirmed);
+    assert!(load_snapshot(&f.rollback_dir).is_some());
+}
+
+#[test]
+fn test_restore_snapshot() {
+    let f = fixture();
+    create_snapshot(&f.rollback_dir, &f.binary_path, &f.store_dir, "1.0.0", "1.1.0").unwrap();
+    
+    // Simulate the update replacing the binary and migrating data
+    fs::write(&f.binary_path, b"binary v2").unwrap();
+    fs::write(f.store_dir.join("settings.json"), r#"{"settings":{"zoom_level":2.0}}"#).unwrap();
+    
+    let snapshot = restore_snapshot(&f.rollback_dir, &f.store_dir).unwrap();
+    assert_eq!(snapshot.previous_version, "1.0.0");
+    assert_eq!(fs::read(&f.binary_path).unwrap(), b"binary v1");
+    assert!(fs::read_to_string(f.store_dir.join("settings.json")).unwrap().contains("1.0"));
+    
+    // Snapshot is consumed by the restore
+    assert!(load_snapshot(&f.rollback_dir).is_none());
+}
+
+#[test]
+fn test_restore_without_snapshot() {
+    let f = fixture();
+    assert!(restore_snapshot(&f.rollback_dir, &f.store_dir).is_err());
+}
+
+#[test]
+fn test_record_startup_counts_attempts() {
+    let f = fixture();
+    create_snapshot(&f.rollback_dir, &f.binary_path, &f.store_dir, "1.0.0", "1.1.0").unwrap();
+    
+    for attempt in 1..=MAX_FAILED_STARTUPS {
+        assert_eq!(record_startup(&f.rollback_dir, "1.1.0"), StartupCheck::Probation(attempt));
+    }
+    assert_eq!(record_startup(&f.rollback_dir, "1.1.0"), StartupCheck::RollbackNeeded);
+}
+
+#[test]
+fn test_record_startup_other_version() {
+    let f = fixture();
+    create_snapshot(&f.rollback_dir, &f.binary_path, &f.store_dir, "1.0.0", "1.1.0").unwrap();
+    
+    assert_eq!(record_startup(&f.rollback_dir, "1.0.0"), StartupCheck::Normal);
+}
+
+#[test]
+fn test_confirm_startup_stops_rollback() {
+    let f = fixture();
+    create_snapshot(&f.rollback_dir, &f.binary_path, &f.store_dir, "1.0.0", "1.1.0").unwrap();
+    
+    record_startup(&f.rollback_dir, "1.1.0");
+    confirm_startup(&f.rollback_dir).unwrap();
+    
+    for _ in 0..=MAX_FAILED_STARTUPS {
+        assert_eq!(record_startup(&f.rollback_dir, "1.1.0"), StartupCheck::Normal);
+    }
+    
+    // The snapshot stays available for a manual rollback
+    assert!(load_snapshot(&f.rollback_dir).unwrap().confirmed);
+}
+
+#[test]
+fn test_record_startup_without_snapshot() {
+    let f = fixture();
+    assert_eq!(record_startup(&f.rollback_dir, "1.1.0"), StartupCheck::Normal);
+}
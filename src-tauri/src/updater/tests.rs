@@ -3,6 +3,11 @@
 // Note: Full updater tests would require a Tauri app instance and network access
 // These tests verify the module structure and logic
 
+use super::channel::{accepts, channel_of, endpoints, mirror_manifests, should_update};
+use crate::config::UpdateChannel;
+use semver::Version;
+use url::Url;
+
 #[test]
 fn test_updater_module_exists() {
     // Verify module compiles
@@ -27,3 +32,91 @@ fn test_install_logic() {
     
     assert!(should_install);
 }
+
+fn v(version: &str) -> Version {
+    Version::parse(version).unwrap()
+}
+
+fn releases_endpoint() -> Url {
+    Url::parse("https://github.com/notive/notive/releases/latest/download/latest.json").unwrap()
+}
+
+#[test]
+fn test_channel_endpoints() {
+    let stable = endpoints(&releases_endpoint(), &UpdateChannel::Stable).unwrap();
+    assert_eq!(stable, vec![releases_endpoint()]);
+    
+    let beta = endpoints(&releases_endpoint(), &UpdateChannel::Beta).unwrap();
+    assert_eq!(beta.len(), 2);
+    assert_eq!(
+        beta[0].as_str(),
+        "https://github.com/notive/notive/releases/download/beta/latest.json"
+    );
+    assert_eq!(beta[1], stable[0]);
+    
+    let nightly = endpoints(&releases_endpoint(), &UpdateChannel::Nightly).unwrap();
+    assert_eq!(nightly.len(), 3);
+    assert_eq!(
+        nightly[0].as_str(),
+        "https://github.com/notive/notive/releases/download/nightly/latest.json"
+    );
+}
+
+#[test]
+fn test_custom_endpoint_serves_every_channel() {
+    let custom = Url::parse("https://updates.example.com/notive/{{target}}/{{current_version}}").unwrap();
+    assert_eq!(endpoints(&custom, &UpdateChannel::Nightly).unwrap(), vec![custom]);
+}
+
+#[test]
+fn test_channel_of_version() {
+    assert_eq!(channel_of(&v("1.2.0")), UpdateChannel::Stable);
+    assert_eq!(channel_of(&v("1.3.0-beta.2")), UpdateChannel::Beta);
+    assert_eq!(channel_of(&v("1.3.0-rc.1")), UpdateChannel::Beta);
+    assert_eq!(channel_of(&v("1.3.0-nightly.20260101")), UpdateChannel::Nightly);
+    assert_eq!(channel_of(&v("1.3.0-alpha.1")), UpdateChannel::Nightly);
+}
+
+#[test]
+fn test_channel_accepts() {
+    assert!(accepts(&UpdateChannel::Stable, &v("1.2.0")));
+    assert!(!accepts(&UpdateChannel::Stable, &v("1.3.0-beta.1")));
+    assert!(accepts(&UpdateChannel::Beta, &v("1.2.0")));
+    assert!(accepts(&UpdateChannel::Beta, &v("1.3.0-beta.1")));
+    assert!(!accepts(&UpdateChannel::Beta, &v("1.3.0-nightly.1")));
+    assert!(accepts(&UpdateChannel::Nightly, &v("1.3.0-nightly.1")));
+}
+
+#[test]
+fn test_should_update_newer_version() {
+    assert!(should_update(&UpdateChannel::Stable, &v("1.0.0"), &v("1.1.0"), false));
+    assert!(!should_update(&UpdateChannel::Stable, &v("1.1.0"), &v("1.1.0"), false));
+    assert!(!should_update(&UpdateChannel::Stable, &v("1.0.0"), &v("1.1.0-beta.1"), false));
+    assert!(should_update(&UpdateChannel::Beta, &v("1.0.0"), &v("1.1.0-beta.1"), false));
+    assert!(should_update(&UpdateChannel::Beta, &v("1.1.0-beta.1"), &v("1.1.0"), false));
+}
+
+#[test]
+fn test_should_update_downgrade() {
+    let nightly = v("1.3.0-nightly.20260101");
+    let stable = v("1.2.0");
+    
+    // Older versions are never offered without an explicit request
+    assert!(!should_update(&UpdateChannel::Stable, &nightly, &stable, false));
+    
+    // Leaving the nightly channel may downgrade to stable
+    assert!(should_update(&UpdateChannel::Stable, &nightly, &stable, true));
+    
+    // A stable build never downgrades within its own channel
+    assert!(!should_update(&UpdateChannel::Stable, &v("1.2.1"), &stable, true));
+}
+
+#[test]
+fn test_mirror_manifests_follow_channel_fallback() {
+    assert_eq!(mirror_manifests(&UpdateChannel::Stable), ["latest.json"]);
+    assert_eq!(mirror_manifests(&UpdateChannel::Beta), ["beta/latest.json", "latest.json"]);
+    assert_eq!(
+        mirror_manifests(&UpdateChannel::Nightly),
+        ["nightly/latest.json", "beta/latest.json", "latest.json"]
+    );
+}
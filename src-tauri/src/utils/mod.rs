@@ -2,6 +2,8 @@
 
 pub mod paths;
 pub mod platform;
+#[cfg(test)]
+pub mod test_server;
 
 #[cfg(test)]
 mod tests;
@@ -17,7 +17,42 @@ pub fn app_cache_dir() -> Option<PathBuf> {
     dirs::cache_dir().map(|p| p.join("notive"))
 }
 
+/// Gets the per-user runtime directory for sockets and other transient files.
+///
+/// Falls back to the cache directory when `$XDG_RUNTIME_DIR` is not set.
+pub fn app_runtime_dir() -> Option<PathBuf> {
+    dirs::runtime_dir()
+        .map(|p| p.join("notive"))
+        .or_else(app_cache_dir)
+}
+
 /// Gets the downloads directory.
 pub fn downloads_dir() -> Option<PathBuf> {
     dirs::download_dir()
 }
+
+/// Store files written by the persistence modules.
+pub const STORE_FILES: &[&str] = &[
+    "settings.json",
+    "workspaces.json",
+    "tabs.json",
+    "bookmarks.json",
+    "history.json",
+    "templates.json",
+    "sessions.json",
+    "analytics.json",
+    "windows.json",
+    "popouts.json",
+    "mini.json",
+    "downloads.json",
+    "sync.json",
+];
+
+/// Gets the directory holding the persisted store files of the app with
+/// the given identifier.
+///
+/// This matches where the store plugin resolves relative paths, but can be
+/// used before the Tauri app has started (e.g. from command-line flags).
+pub fn store_dir(identifier: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|p| p.join(identifier))
+}
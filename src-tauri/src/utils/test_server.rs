@@ -0,0 +1,171 @@
+//! Loopback HTTP server for tests of code that talks to web servers.
+//!
+//! Every connection carries one request, answered by a handler function and
+//! closed, so tests only have to decide what to respond.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// A request received by a [`TestServer`].
+#[derive(Debug, Clone)]
+pub struct Request {
+    /// Request line and headers.
+    pub head: String,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn method(&self) -> &str {
+        self.head.split_whitespace().next().unwrap_or("")
+    }
+
+    pub fn path(&self) -> &str {
+        self.head.split_whitespace().nth(1).unwrap_or("/")
+    }
+
+    /// Gets a header by case-insensitive name.
+    pub fn header(&self, name: &str) -> Option<String> {
+        self.head.lines().skip(1).find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.eq_ignore_ascii_case(name).then(|| value.trim().to_string())
+        })
+    }
+}
+
+/// A response for a [`TestServer`] to send.
+#[derive(Debug, Clone)]
+pub struct Response {
+    status: &'static str,
+    headers: String,
+    body: Vec<u8>,
+    chunk_delay: Duration,
+}
+
+impl Response {
+    /// An empty response with a status such as `"200 OK"`.
+    pub fn new(status: &'static str) -> Self {
+        Self {
+            status,
+            headers: String::new(),
+            body: Vec::new(),
+            chunk_delay: Duration::ZERO,
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push_str(&format!("{}: {}\r\n", name, value));
+        self
+    }
+
+    pub fn body(mut self, body: Vec<u8>) -> Self {
+        self.body = body;
+        self
+    }
+
+    /// Pauses between 4 KiB chunks of the body, so transfers can be interrupted.
+    pub fn chunk_delay(mut self, delay: Duration) -> Self {
+        self.chunk_delay = delay;
+        self
+    }
+}
+
+/// Loopback HTTP server recording the requests it gets.
+pub struct TestServer {
+    addr: SocketAddr,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl TestServer {
+    /// Starts answering every request with `handler`.
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = requests.clone();
+        let handler = Arc::new(handler);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let recorded = recorded.clone();
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let Some(request) = read_request(&mut stream).await else {
+                        return;
+                    };
+                    recorded.lock().unwrap().push(request.clone());
+                    write_response(&mut stream, handler(&request)).await;
+                });
+            }
+        });
+
+        Self { addr, requests }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}/{}", self.addr, path.trim_start_matches('/'))
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// Reads one request: the head and a body of `Content-Length` bytes.
+async fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+    let mut request = Request {
+        head: String::from_utf8_lossy(&buf[..head_end]).into_owned(),
+        body: Vec::new(),
+    };
+    let length = request
+        .header("content-length")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    while buf.len() < head_end + length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    request.body = buf[head_end..head_end + length].to_vec();
+    Some(request)
+}
+
+async fn write_response(stream: &mut TcpStream, response: Response) {
+    let head = format!(
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.headers,
+        response.body.len()
+    );
+    if stream.write_all(head.as_bytes()).await.is_err() {
+        return;
+    }
+    for chunk in response.body.chunks(4096) {
+        if stream.write_all(chunk).await.is_err() || stream.flush().await.is_err() {
+            return;
+        }
+        if !response.chunk_delay.is_zero() {
+            tokio::time::sleep(response.chunk_delay).await;
+        }
+    }
+    let _ = stream.shutdown().await;
+}
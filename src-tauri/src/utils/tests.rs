@@ -28,6 +28,13 @@ fn test_app_cache_dir() {
     }
 }
 
+#[test]
+fn test_app_runtime_dir() {
+    if let Some(path) = app_runtime_dir() {
+        assert!(path.to_string_lossy().contains("notive"));
+    }
+}
+
 #[test]
 fn test_downloads_dir() {
     let dir = downloads_dir();
@@ -35,3 +42,16 @@ fn test_downloads_dir() {
     // Just verify it doesn't panic
     let _ = dir;
 }
+
+#[test]
+fn test_store_dir() {
+    if let Some(path) = store_dir("io.github.notive") {
+        assert!(path.ends_with("io.github.notive"));
+    }
+}
+
+#[test]
+fn test_store_files_include_settings() {
+    assert!(STORE_FILES.contains(&"settings.json"));
+    assert!(STORE_FILES.iter().all(|f| f.ends_with(".json")));
+}
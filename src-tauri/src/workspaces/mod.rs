@@ -1,4 +1,18 @@
 //! Workspace management for multiple Notion instances.
+//!
+//! Each workspace window keeps its cookies, local storage and IndexedDB in
+//! its own directory under `workspaces/` in the app data directory, so
+//! several accounts can be signed in at once. Workspaces marked as shared
+//! use the main window's browser storage instead.
+//!
+//! Workspaces whose window was open when Notive quit are reopened at
+//! startup on the page they last showed, where the window placement module
+//! puts them back in place.
+//!
+//! The saved order of the workspaces is the order of the tray menu, the
+//! switcher and the Ctrl+1..9 shortcuts. A workspace's color and icon are
+//! shown in front of its name wherever it appears, the color as a colored
+//! circle where only text can be shown.
 
 #[cfg(test)]
 mod tests;
@@ -8,7 +22,71 @@
 use persistence::{load_workspaces, save_workspaces};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use tauri::{AppHandle, Manager, Runtime, WindowBuilder, WindowUrl};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow, WindowBuilder, WindowEvent, WindowUrl};
+
+/// Label prefix of workspace windows, followed by the workspace ID.
+pub const LABEL_PREFIX: &str = "workspace-";
+
+/// Directory under the app data directory holding the browser storage of
+/// isolated workspaces.
+const DATA_DIR: &str = "workspaces";
+
+/// Longest icon accepted, in characters; enough for an emoji sequence.
+const MAX_ICON_CHARS: usize = 8;
+
+/// Color of a workspace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkspaceColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Brown,
+    Gray,
+}
+
+impl WorkspaceColor {
+    /// Gets the colored circle standing in for the color in window titles
+    /// and menus.
+    pub fn marker(self) -> &'static str {
+        match self {
+            Self::Red => "🔴",
+            Self::Orange => "🟠",
+            Self::Yellow => "🟡",
+            Self::Green => "🟢",
+            Self::Blue => "🔵",
+            Self::Purple => "🟣",
+            Self::Brown => "🟤",
+            Self::Gray => "🔘",
+        }
+    }
+}
+
+/// Changes to a workspace; fields left out stay as they are.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceUpdate {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    /// New color; `null` removes it.
+    #[serde(default, deserialize_with = "present")]
+    pub color: Option<Option<WorkspaceColor>>,
+    /// New icon, e.g. an emoji; an empty string removes it.
+    pub icon: Option<String>,
+}
+
+/// Deserializes a field that is present, even as `null`, into `Some`.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
 
 /// Workspace configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +95,26 @@ pub struct Workspace {
     pub name: String,
     pub url: String,
     pub zoom_level: f64,
+    /// Shares the main window's browser storage (and so its login) instead
+    /// of having its own. Workspaces saved before storage was isolated keep
+    /// sharing it.
+    #[serde(default = "shared_by_default")]
+    pub shared_data: bool,
+    /// Whether the workspace's window is open.
+    #[serde(default)]
+    pub open: bool,
+    /// Page the window showed last.
+    #[serde(default)]
+    pub last_url: Option<String>,
+    #[serde(default)]
+    pub color: Option<WorkspaceColor>,
+    /// Short icon shown before the name, e.g. an emoji.
+    #[serde(default)]
+    pub icon: Option<String>,
+}
+
+fn shared_by_default() -> bool {
+    true
 }
 
 impl Default for Workspace {
@@ -26,10 +124,206 @@ fn default() -> Self {
             name: "Default Workspace".to_string(),
             url: "https://www.notion.so".to_string(),
             zoom_level: 1.0,
+            shared_data: false,
+            open: false,
+            last_url: None,
+            color: None,
+            icon: None,
         }
     }
 }
 
+impl Workspace {
+    /// Gets the label of the workspace's window.
+    pub fn label(&self) -> String {
+        format!("{}{}", LABEL_PREFIX, self.id)
+    }
+
+    /// Gets the page the window opens on: the one it showed last, or the
+    /// workspace URL.
+    pub fn start_url(&self) -> &str {
+        self.last_url.as_deref().unwrap_or(&self.url)
+    }
+
+    /// Gets the directory holding this workspace's browser storage under
+    /// `base`; `None` for shared workspaces.
+    pub fn data_dir_in(&self, base: &Path) -> Option<PathBuf> {
+        if self.shared_data || !is_valid_id(&self.id) {
+            return None;
+        }
+        Some(base.join(DATA_DIR).join(&self.id))
+    }
+
+    /// Gets the directory holding this workspace's browser storage; `None`
+    /// for shared workspaces.
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        crate::utils::paths::app_data_dir().and_then(|base| self.data_dir_in(&base))
+    }
+
+    /// Gets the name with the color marker and icon in front, as shown in
+    /// window titles and the tray menu.
+    pub fn display_name(&self) -> String {
+        let mut parts: Vec<&str> = Vec::new();
+        parts.extend(self.color.map(WorkspaceColor::marker));
+        parts.extend(self.icon.as_deref());
+        parts.push(&self.name);
+        parts.join(" ")
+    }
+
+    /// Gets the title of the workspace's window.
+    pub fn window_title(&self) -> String {
+        format!("Notive - {}", self.display_name())
+    }
+
+    /// Applies changes made by the user. Returns true if the URL changed.
+    pub fn apply_update(&mut self, update: WorkspaceUpdate) -> Result<bool, String> {
+        let name = update.name.map(|name| name.trim().to_string());
+        if name.as_deref() == Some("") {
+            return Err("The workspace name cannot be empty".to_string());
+        }
+        let url = match update.url {
+            Some(url) => Some(parse_url(&url)?),
+            None => None,
+        };
+        let icon = update.icon.map(|icon| icon.trim().to_string());
+        if icon.as_deref().is_some_and(|icon| icon.chars().count() > MAX_ICON_CHARS) {
+            return Err("The workspace icon must be a few characters at most, e.g. an emoji".to_string());
+        }
+
+        if let Some(name) = name {
+            self.name = name;
+        }
+        if let Some(color) = update.color {
+            self.color = color;
+        }
+        if let Some(icon) = icon {
+            self.icon = Some(icon).filter(|icon| !icon.is_empty());
+        }
+        let url_changed = url.as_ref().is_some_and(|url| *url != self.url);
+        if let Some(url) = url.filter(|_| url_changed) {
+            // Start over from the new URL rather than the last page
+            self.url = url;
+            self.last_url = None;
+        }
+        Ok(url_changed)
+    }
+}
+
+/// Checks a workspace URL.
+fn parse_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    let parsed = url.parse::<url::Url>().map_err(|e| format!("Invalid URL: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Invalid URL: {}", url));
+    }
+    Ok(url.to_string())
+}
+
+/// Moves the workspaces with the given IDs to the front, in that order.
+/// Workspaces left out keep their relative order after them; unknown IDs
+/// are ignored.
+pub fn reorder(workspaces: &mut Vec<Workspace>, ids: &[String]) {
+    let mut ordered = Vec::with_capacity(workspaces.len());
+    for id in ids {
+        if let Some(index) = workspaces.iter().position(|w| &w.id == id) {
+            ordered.push(workspaces.remove(index));
+        }
+    }
+    ordered.append(workspaces);
+    *workspaces = ordered;
+}
+
+/// Gets the workspace ID from a window label, if it is a workspace window.
+pub fn id_from_label(label: &str) -> Option<&str> {
+    label.strip_prefix(LABEL_PREFIX)
+}
+
+/// Records whether a workspace's window is open. Returns true if it changed.
+pub fn set_open(workspaces: &mut [Workspace], id: &str, open: bool) -> bool {
+    match workspaces.iter_mut().find(|w| w.id == id) {
+        Some(workspace) if workspace.open != open => {
+            workspace.open = open;
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Records the page a workspace window navigated to. Returns true if it
+/// changed.
+pub fn apply_page_change(workspaces: &mut [Workspace], id: &str, url: &str) -> bool {
+    match workspaces.iter_mut().find(|w| w.id == id) {
+        Some(workspace) if workspace.last_url.as_deref() != Some(url) => {
+            workspace.last_url = Some(url.to_string());
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Checks that a workspace ID is safe to use as a directory name.
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// Deletes the browser storage of a workspace.
+fn remove_data(workspace: &Workspace) {
+    let Some(dir) = workspace.data_dir() else {
+        return;
+    };
+    match fs::remove_dir_all(&dir) {
+        Ok(()) => log::info!("Removed browser data of workspace {}", workspace.id),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        // Cleaned up at the next start
+        Err(e) => log::warn!("Failed to remove {}: {}", dir.display(), e),
+    }
+}
+
+/// Deletes the browser storage directories under `base` that no longer
+/// belong to a workspace. Returns the removed directories.
+pub fn prune_data_dirs(base: &Path, workspaces: &[Workspace]) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(base.join(DATA_DIR)) else {
+        return Vec::new();
+    };
+
+    let mut removed = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let owned = workspaces
+            .iter()
+            .any(|workspace| workspace.data_dir_in(base).as_deref() == Some(path.as_path()));
+        if owned || !path.is_dir() {
+            continue;
+        }
+        match fs::remove_dir_all(&path) {
+            Ok(()) => removed.push(path),
+            Err(e) => log::warn!("Failed to remove {}: {}", path.display(), e),
+        }
+    }
+    removed
+}
+
+/// Removes browser storage left behind by deleted workspaces, e.g. when
+/// the window still held it open at the time.
+pub fn cleanup<R: Runtime>(app: &AppHandle<R>) {
+    let Some(base) = crate::utils::paths::app_data_dir() else {
+        return;
+    };
+    let workspaces = match load_workspaces(app) {
+        // An unreadable store also loads as an empty list; never take that
+        // as a reason to delete every workspace's data
+        Ok(workspaces) if workspaces.is_empty() => return,
+        Ok(workspaces) => workspaces,
+        Err(e) => {
+            log::warn!("Failed to load workspaces: {}", e);
+            return;
+        }
+    };
+    for dir in prune_data_dirs(&base, &workspaces) {
+        log::info!("Removed browser data of deleted workspace: {}", dir.display());
+    }
+}
+
 /// Manages multiple workspaces.
 pub struct WorkspaceManager {
     workspaces: HashMap<String, Workspace>,
@@ -71,12 +365,99 @@ pub fn get_active(&self) -> Option<&Workspace> {
     }
 }
 
+fn focus<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    window.show().map_err(|e| e.to_string())?;
+    let _ = window.unminimize();
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+/// Creates the window of a workspace.
+fn open_window<R: Runtime>(
+    app: &AppHandle<R>,
+    workspace: &Workspace,
+    visible: bool,
+) -> Result<WebviewWindow<R>, String> {
+    let url = workspace
+        .start_url()
+        .parse::<url::Url>()
+        .map_err(|e| format!("Invalid URL: {}", e))?;
+
+    let mut builder = WindowBuilder::new(app, &workspace.label(), WindowUrl::External(url))
+        .title(&workspace.window_title())
+        .inner_size(1200.0, 800.0)
+        .min_inner_size(800.0, 600.0)
+        .resizable(true)
+        .center()
+        .visible(visible);
+    if let Some(dir) = workspace.data_dir() {
+        builder = builder.data_directory(dir);
+    }
+    let window = builder.build().map_err(|e| e.to_string())?;
+
+    crate::handlers::title::watch_titles(&window);
+    crate::handlers::downloads::watch_downloads(&window);
+    crate::monitor::restore_window(&window);
+    Ok(window)
+}
+
+/// Reopens the workspace windows that were open when Notive last quit.
+pub fn restore<R: Runtime>(app: &AppHandle<R>, visible: bool) {
+    let workspaces = load_workspaces(app).unwrap_or_else(|e| {
+        log::warn!("Failed to load workspaces: {}", e);
+        Vec::new()
+    });
+
+    let mut restored = 0;
+    for workspace in workspaces.iter().filter(|w| w.open) {
+        match open_window(app, workspace, visible) {
+            Ok(_) => restored += 1,
+            Err(e) => log::warn!("Failed to restore workspace {}: {}", workspace.name, e),
+        }
+    }
+    if restored > 0 {
+        log::info!("Restored {} workspace windows", restored);
+    }
+}
+
+/// Keeps a workspace's last page in step with where its window navigated.
+pub fn on_page_changed<R: Runtime>(app: &AppHandle<R>, label: &str, url: &str) {
+    let Some(id) = id_from_label(label) else {
+        return;
+    };
+
+    let mut workspaces = load_workspaces(app).unwrap_or_default();
+    if apply_page_change(&mut workspaces, id, url) {
+        if let Err(e) = save_workspaces(app, &workspaces) {
+            log::warn!("Failed to save workspaces: {}", e);
+        }
+    }
+}
+
+/// Records that the user closed a workspace window, keeping its page and
+/// placement for the next time it is opened.
+pub fn on_closed<R: Runtime>(app: &AppHandle<R>, label: &str) {
+    let Some(id) = id_from_label(label) else {
+        return;
+    };
+
+    let mut workspaces = load_workspaces(app).unwrap_or_default();
+    if set_open(&mut workspaces, id, false) {
+        if let Err(e) = save_workspaces(app, &workspaces) {
+            log::warn!("Failed to save workspaces: {}", e);
+        }
+    }
+    log::debug!("Workspace window closed: {}", id);
+}
+
 /// Creates a new workspace window.
+///
+/// The workspace gets its own browser storage unless `shared` is set.
 #[tauri::command]
 pub async fn create_workspace<R: Runtime>(
     app: AppHandle<R>,
     name: String,
     url: Option<String>,
+    shared: Option<bool>,
 ) -> Result<String, String> {
     log::debug!("Creating workspace: {} -> {:?}", name, url);
     
@@ -85,27 +466,15 @@ pub async fn create_workspace<R: Runtime>(
         name: name.clone(),
         url: url.unwrap_or_else(|| "https://www.notion.so".to_string()),
         zoom_level: 1.0,
+        shared_data: shared.unwrap_or(false),
+        open: true,
+        last_url: None,
+        color: None,
+        icon: None,
     };
     
     // Create window for workspace
-    let window_label = format!("workspace-{}", workspace.id);
-    let workspace_url = workspace.url.clone();
-    let window = WindowBuilder::new(
-        &app,
-        &window_label,
-        WindowUrl::External(
-            workspace_url
-                .parse::<url::Url>()
-                .map_err(|e| format!("Invalid URL: {}", e))?,
-        ),
-    )
-    .title(&format!("Notive - {}", name))
-    .inner_size(1200.0, 800.0)
-    .min_inner_size(800.0, 600.0)
-    .resizable(true)
-    .center()
-    .build()
-    .map_err(|e| e.to_string())?;
+    open_window(&app, &workspace, true)?;
     
     // Persist workspace
     let mut workspaces = load_workspaces(&app).unwrap_or_default();
@@ -114,6 +483,7 @@ pub async fn create_workspace<R: Runtime>(
         log::warn!("Failed to save workspace: {}", e);
     }
     
+    crate::tray::refresh_menu(&app);
     log::info!("Workspace created: {} ({})", name, workspace.id);
     
     Ok(workspace.id)
@@ -125,7 +495,7 @@ pub fn list_workspaces<R: Runtime>(app: AppHandle<R>) -> Result<Vec<Workspace>,
     load_workspaces(&app).map_err(|e| e.to_string())
 }
 
-/// Switches to a different workspace.
+/// Switches to a different workspace, opening its window if it is closed.
 #[tauri::command]
 pub async fn switch_workspace<R: Runtime>(
     app: AppHandle<R>,
@@ -134,17 +504,26 @@ pub async fn switch_workspace<R: Runtime>(
     log::debug!("Switching to workspace: {}", workspace_id);
     
     // Find workspace window
-    let window_label = format!("workspace-{}", workspace_id);
+    let window_label = format!("{}{}", LABEL_PREFIX, workspace_id);
     if let Some(window) = app.get_webview_window(&window_label) {
-        window.show().map_err(|e| e.to_string())?;
-        window.set_focus().map_err(|e| e.to_string())?;
-        Ok(())
-    } else {
-        Err(format!("Workspace {} not found", workspace_id))
+        return focus(&window);
     }
+
+    let mut workspaces = load_workspaces(&app).map_err(|e| e.to_string())?;
+    let workspace = workspaces
+        .iter()
+        .find(|w| w.id == workspace_id)
+        .ok_or_else(|| format!("Workspace {} not found", workspace_id))?;
+    let window = open_window(&app, workspace, true)?;
+    focus(&window)?;
+
+    set_open(&mut workspaces, &workspace_id, true);
+    save_workspaces(&app, &workspaces).map_err(|e| e.to_string())?;
+    log::info!("Workspace opened: {}", workspace_id);
+    Ok(())
 }
 
-/// Closes a workspace window.
+/// Closes a workspace window, keeping the workspace.
 #[tauri::command]
 pub async fn close_workspace<R: Runtime>(
     app: AppHandle<R>,
@@ -152,19 +531,91 @@ pub async fn close_workspace<R: Runtime>(
 ) -> Result<(), String> {
     log::debug!("Closing workspace: {}", workspace_id);
     
-    let window_label = format!("workspace-{}", workspace_id);
+    let window_label = format!("{}{}", LABEL_PREFIX, workspace_id);
     if let Some(window) = app.get_webview_window(&window_label) {
-        window.close().map_err(|e| e.to_string())?;
-        
-        // Remove from persistent storage
-        let mut workspaces = load_workspaces(&app).unwrap_or_default();
-        workspaces.retain(|w| w.id != workspace_id);
-        if let Err(e) = save_workspaces(&app, &workspaces) {
-            log::warn!("Failed to update workspaces: {}", e);
-        }
-        
-        Ok(())
-    } else {
-        Err(format!("Workspace {} not found", workspace_id))
+        crate::monitor::remember_window(window.as_ref().window(), true);
+        window.destroy().map_err(|e| e.to_string())?;
+    }
+    on_closed(&app, &window_label);
+    Ok(())
+}
+
+/// Deletes a workspace, closing its window and removing its browser data.
+#[tauri::command]
+pub async fn delete_workspace<R: Runtime>(
+    app: AppHandle<R>,
+    workspace_id: String,
+) -> Result<(), String> {
+    log::debug!("Deleting workspace: {}", workspace_id);
+
+    let mut workspaces = load_workspaces(&app).map_err(|e| e.to_string())?;
+    let index = workspaces
+        .iter()
+        .position(|w| w.id == workspace_id)
+        .ok_or_else(|| format!("Workspace {} not found", workspace_id))?;
+    let workspace = workspaces.remove(index);
+
+    let window = app.get_webview_window(&workspace.label());
+    if let Some(window) = &window {
+        // The webview keeps its storage open until the window is gone
+        let deleted = workspace.clone();
+        window.on_window_event(move |event| {
+            if matches!(event, WindowEvent::Destroyed) {
+                remove_data(&deleted);
+            }
+        });
+        window.destroy().map_err(|e| e.to_string())?;
     }
+    crate::monitor::forget_window(&app, &workspace.label());
+    save_workspaces(&app, &workspaces).map_err(|e| e.to_string())?;
+    if window.is_none() {
+        remove_data(&workspace);
+    }
+    crate::tray::refresh_menu(&app);
+
+    log::info!("Workspace deleted: {} ({})", workspace.name, workspace.id);
+    Ok(())
+}
+
+/// Renames a workspace, or changes its URL, color or icon.
+#[tauri::command]
+pub async fn update_workspace<R: Runtime>(
+    app: AppHandle<R>,
+    workspace_id: String,
+    update: WorkspaceUpdate,
+) -> Result<Workspace, String> {
+    let mut workspaces = load_workspaces(&app).map_err(|e| e.to_string())?;
+    let workspace = workspaces
+        .iter_mut()
+        .find(|w| w.id == workspace_id)
+        .ok_or_else(|| format!("Workspace {} not found", workspace_id))?;
+    let url_changed = workspace.apply_update(update)?;
+    let workspace = workspace.clone();
+    save_workspaces(&app, &workspaces).map_err(|e| e.to_string())?;
+
+    if let Some(window) = app.get_webview_window(&workspace.label()) {
+        let _ = window.set_title(&workspace.window_title());
+        if url_changed {
+            let url = workspace.url.parse::<url::Url>().map_err(|e| format!("Invalid URL: {}", e))?;
+            window.navigate(url).map_err(|e| e.to_string())?;
+        }
+    }
+    crate::tray::refresh_menu(&app);
+
+    log::info!("Workspace updated: {} ({})", workspace.name, workspace.id);
+    Ok(workspace)
+}
+
+/// Puts the workspaces in the given order, which the tray menu, the
+/// switcher and the Ctrl+1..9 shortcuts follow.
+#[tauri::command]
+pub async fn reorder_workspaces<R: Runtime>(
+    app: AppHandle<R>,
+    workspace_ids: Vec<String>,
+) -> Result<Vec<Workspace>, String> {
+    let mut workspaces = load_workspaces(&app).map_err(|e| e.to_string())?;
+    reorder(&mut workspaces, &workspace_ids);
+    save_workspaces(&app, &workspaces).map_err(|e| e.to_string())?;
+    crate::tray::refresh_menu(&app);
+    Ok(workspaces)
 }
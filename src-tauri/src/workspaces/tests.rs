@@ -1,6 +1,6 @@
 //! Tests for workspace management.
 
-use super::{Workspace, WorkspaceManager};
+use super::*;
 
 #[test]
 fn test_workspace_default() {
@@ -26,6 +26,11 @@ fn test_workspace_manager_add() {
         name: "Test Workspace".to_string(),
         url: "https://www.notion.so/test".to_string(),
         zoom_level: 1.0,
+        shared_data: false,
+        open: false,
+        last_url: None,
+        color: None,
+        icon: None,
     };
     
     manager.add_workspace(workspace);
@@ -42,6 +47,11 @@ fn test_workspace_manager_remove() {
         name: "Test".to_string(),
         url: "https://example.com".to_string(),
         zoom_level: 1.0,
+        shared_data: false,
+        open: false,
+        last_url: None,
+        color: None,
+        icon: None,
     };
     
     manager.add_workspace(workspace);
@@ -60,6 +70,11 @@ fn test_workspace_manager_set_active() {
         name: "Test".to_string(),
         url: "https://example.com".to_string(),
         zoom_level: 1.0,
+        shared_data: false,
+        open: false,
+        last_url: None,
+        color: None,
+        icon: None,
     };
     
     manager.add_workspace(workspace);
@@ -79,6 +94,11 @@ fn test_workspace_manager_multiple_workspaces() {
             name: format!("Workspace {}", i),
             url: format!("https://example.com/{}", i),
             zoom_level: 1.0,
+            shared_data: false,
+            open: false,
+            last_url: None,
+            color: None,
+            icon: None,
         };
         manager.add_workspace(workspace);
     }
@@ -98,3 +118,183 @@ fn test_workspace_manager_remove_nonexistent() {
     let removed = manager.remove_workspace("nonexistent");
     assert!(removed.is_none());
 }
+
+fn workspace(id: &str, shared_data: bool) -> Workspace {
+    Workspace {
+        id: id.to_string(),
+        shared_data,
+        ..Workspace::default()
+    }
+}
+
+#[test]
+fn test_workspace_data_dir() {
+    let base = Path::new("/data/notive");
+    assert_eq!(
+        workspace("work-1", false).data_dir_in(base),
+        Some(PathBuf::from("/data/notive/workspaces/work-1"))
+    );
+    assert_eq!(workspace("work-1", true).data_dir_in(base), None);
+    assert_eq!(workspace("../settings", false).data_dir_in(base), None);
+    assert_eq!(workspace("", false).data_dir_in(base), None);
+    assert!(!Workspace::default().shared_data);
+}
+
+#[test]
+fn test_saved_workspaces_keep_sharing_storage() {
+    let saved = r#"{ "id": "work-1", "name": "Work", "url": "https://www.notion.so", "zoom_level": 1.0 }"#;
+    let workspace: Workspace = serde_json::from_str(saved).unwrap();
+    assert!(workspace.shared_data);
+}
+
+#[test]
+fn test_prune_data_dirs_keeps_current_workspaces() {
+    let base = tempfile::tempdir().unwrap();
+    let workspaces = vec![workspace("kept", false), workspace("shared", true)];
+    for id in ["kept", "shared", "deleted"] {
+        fs::create_dir_all(base.path().join(DATA_DIR).join(id).join("storage")).unwrap();
+    }
+
+    let removed = prune_data_dirs(base.path(), &workspaces);
+    assert_eq!(removed.len(), 2);
+    assert!(base.path().join(DATA_DIR).join("kept").exists());
+    assert!(!base.path().join(DATA_DIR).join("shared").exists());
+    assert!(!base.path().join(DATA_DIR).join("deleted").exists());
+
+    // Nothing to do without the directory
+    assert!(prune_data_dirs(&base.path().join("missing"), &workspaces).is_empty());
+}
+
+#[test]
+fn test_workspace_labels() {
+    let workspace = workspace("work-1", false);
+    assert_eq!(workspace.label(), "workspace-work-1");
+    assert_eq!(id_from_label(&workspace.label()), Some("work-1"));
+    assert_eq!(id_from_label("main"), None);
+    assert_eq!(id_from_label("popout-work-1"), None);
+}
+
+#[test]
+fn test_open_state_and_last_page() {
+    let mut workspaces = vec![workspace("work-1", false), workspace("work-2", false)];
+    assert_eq!(workspaces[0].start_url(), "https://www.notion.so");
+
+    assert!(set_open(&mut workspaces, "work-1", true));
+    assert!(!set_open(&mut workspaces, "work-1", true));
+    assert!(!set_open(&mut workspaces, "missing", true));
+    assert!(workspaces[0].open && !workspaces[1].open);
+
+    let page = "https://www.notion.so/acme/Roadmap-0123456789abcdef0123456789abcdef";
+    assert!(apply_page_change(&mut workspaces, "work-1", page));
+    assert!(!apply_page_change(&mut workspaces, "work-1", page));
+    assert_eq!(workspaces[0].start_url(), page);
+    assert_eq!(workspaces[1].start_url(), "https://www.notion.so");
+
+    // Closing keeps the page for the next time
+    assert!(set_open(&mut workspaces, "work-1", false));
+    assert_eq!(workspaces[0].last_url.as_deref(), Some(page));
+}
+
+#[test]
+fn test_saved_workspaces_start_closed() {
+    let saved = r#"{ "id": "work-1", "name": "Work", "url": "https://www.notion.so", "zoom_level": 1.0 }"#;
+    let workspace: Workspace = serde_json::from_str(saved).unwrap();
+    assert!(!workspace.open);
+    assert_eq!(workspace.last_url, None);
+}
+
+#[test]
+fn test_display_name_shows_color_and_icon() {
+    let mut workspace = workspace("work-1", false);
+    workspace.name = "Work".to_string();
+    assert_eq!(workspace.display_name(), "Work");
+
+    workspace.icon = Some("🚀".to_string());
+    assert_eq!(workspace.display_name(), "🚀 Work");
+    workspace.color = Some(WorkspaceColor::Blue);
+    assert_eq!(workspace.display_name(), "🔵 🚀 Work");
+    assert_eq!(workspace.window_title(), "Notive - 🔵 🚀 Work");
+}
+
+#[test]
+fn test_color_markers_are_distinct() {
+    let colors = [
+        WorkspaceColor::Red,
+        WorkspaceColor::Orange,
+        WorkspaceColor::Yellow,
+        WorkspaceColor::Green,
+        WorkspaceColor::Blue,
+        WorkspaceColor::Purple,
+        WorkspaceColor::Brown,
+        WorkspaceColor::Gray,
+    ];
+    let markers: std::collections::HashSet<&str> = colors.iter().map(|c| c.marker()).collect();
+    assert_eq!(markers.len(), colors.len());
+    // A white circle would read as "no color" rather than gray
+    assert!(!markers.contains("⚪"));
+}
+
+#[test]
+fn test_apply_update() {
+    let mut workspace = workspace("work-1", false);
+    workspace.last_url = Some("https://www.notion.so/acme/Old-page".to_string());
+
+    let update: WorkspaceUpdate = serde_json::from_str(
+        r#"{ "name": " Work ", "url": "https://www.notion.so/acme", "color": "green", "icon": "📚" }"#,
+    )
+    .unwrap();
+    assert!(workspace.apply_update(update).unwrap());
+    assert_eq!(workspace.name, "Work");
+    assert_eq!(workspace.url, "https://www.notion.so/acme");
+    assert_eq!(workspace.last_url, None);
+    assert_eq!(workspace.color, Some(WorkspaceColor::Green));
+    assert_eq!(workspace.icon.as_deref(), Some("📚"));
+
+    // Left-out fields stay, null and empty remove the color and icon
+    let update: WorkspaceUpdate = serde_json::from_str(r#"{ "color": null, "icon": "" }"#).unwrap();
+    assert!(!workspace.apply_update(update).unwrap());
+    assert_eq!(workspace.name, "Work");
+    assert_eq!((workspace.color, workspace.icon.as_deref()), (None, None));
+
+    // The same URL is no change
+    let update = WorkspaceUpdate {
+        url: Some("https://www.notion.so/acme".to_string()),
+        ..WorkspaceUpdate::default()
+    };
+    assert!(!workspace.apply_update(update).unwrap());
+}
+
+#[test]
+fn test_apply_update_rejects_invalid_changes() {
+    let mut workspace = workspace("work-1", false);
+    let invalid = [
+        WorkspaceUpdate { name: Some("  ".to_string()), ..WorkspaceUpdate::default() },
+        WorkspaceUpdate { url: Some("not a url".to_string()), ..WorkspaceUpdate::default() },
+        WorkspaceUpdate { url: Some("file:///etc/passwd".to_string()), ..WorkspaceUpdate::default() },
+        WorkspaceUpdate { icon: Some("a long label".to_string()), ..WorkspaceUpdate::default() },
+        // Valid name, invalid URL: nothing is applied
+        WorkspaceUpdate {
+            name: Some("Renamed".to_string()),
+            url: Some("ftp://example.com".to_string()),
+            ..WorkspaceUpdate::default()
+        },
+    ];
+    for update in invalid {
+        assert!(workspace.apply_update(update).is_err());
+    }
+    assert_eq!(workspace.name, "Default Workspace");
+    assert_eq!(workspace.url, "https://www.notion.so");
+}
+
+#[test]
+fn test_reorder() {
+    let mut workspaces = vec![
+        workspace("a", false),
+        workspace("b", false),
+        workspace("c", false),
+        workspace("d", false),
+    ];
+    reorder(&mut workspaces, &["c".to_string(), "missing".to_string(), "a".to_string()]);
+    let ids: Vec<&str> = workspaces.iter().map(|w| w.id.as_str()).collect();
+    assert_eq!(ids, vec!["c", "a", "b", "d"]);
+}
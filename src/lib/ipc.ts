@@ -129,12 +129,33 @@ export async function switchWorkspace(workspaceId: string): Promise<void> {
 }
 
 /**
- * Closes a workspace window.
+ * Closes a workspace window, keeping the workspace.
  */
 export async function closeWorkspace(workspaceId: string): Promise<void> {
   await invoke('close_workspace', { workspaceId });
 }
 
+/**
+ * Deletes a workspace, closing its window and removing its browser data.
+ */
+export async function deleteWorkspace(workspaceId: string): Promise<void> {
+  await invoke('delete_workspace', { workspaceId });
+}
+
+/**
+ * Renames a workspace, or changes its URL, color or icon.
+ */
+export async function updateWorkspace(workspaceId: string, update: WorkspaceUpdate): Promise<Workspace> {
+  return await invoke('update_workspace', { workspaceId, update });
+}
+
+/**
+ * Puts the workspaces in the given order.
+ */
+export async function reorderWorkspaces(workspaceIds: string[]): Promise<Workspace[]> {
+  return await invoke('reorder_workspaces', { workspaceIds });
+}
+
 // ============================================================================
 // Tabs
 // ============================================================================
@@ -369,6 +390,27 @@ export interface Workspace {
   name: string;
   url: string;
   zoom_level: number;
+  shared_data?: boolean;
+  open?: boolean;
+  last_url?: string | null;
+  color?: WorkspaceColor | null;
+  icon?: string | null;
+}
+
+/**
+ * Workspace color.
+ */
+export type WorkspaceColor = 'red' | 'orange' | 'yellow' | 'green' | 'blue' | 'purple' | 'brown' | 'gray';
+
+/**
+ * Changes to a workspace; fields left out stay as they are. A `null` color
+ * or an empty icon removes it.
+ */
+export interface WorkspaceUpdate {
+  name?: string;
+  url?: string;
+  color?: WorkspaceColor | null;
+  icon?: string;
 }
 
 // ============================================================================
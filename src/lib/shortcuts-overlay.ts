@@ -84,6 +84,11 @@ export class ShortcutsOverlay {
               shortcut: 'Ctrl+Shift+W / Cmd+Shift+W',
               description: 'Switch between workspaces',
             },
+            {
+              action: 'Switch to Workspace',
+              shortcut: 'Ctrl+1 … Ctrl+9',
+              description: 'Open the workspaces in their saved order',
+            },
             {
               action: 'Shortcuts Help',
               shortcut: '? / Ctrl+? / Cmd+?',
@@ -5,10 +5,30 @@
 
 import * as ipc from './ipc';
 
+/**
+ * Number of workspaces reachable with Ctrl+1..9.
+ */
+const SHORTCUT_COUNT = 9;
+
+/**
+ * Swatches for the workspace colors.
+ */
+export const WORKSPACE_COLORS: Record<ipc.WorkspaceColor, string> = {
+  red: '#e03e3e',
+  orange: '#d9730d',
+  yellow: '#dfab01',
+  green: '#0f7b6c',
+  blue: '#0b6e99',
+  purple: '#6940a5',
+  brown: '#64473a',
+  gray: '#9b9a97',
+};
+
 export class WorkspaceSwitcher {
   private overlay: HTMLElement | null = null;
   private isVisible = false;
   private workspaces: ipc.Workspace[] = [];
+  private listenersAttached = false;
 
   init(): void {
     this.createOverlay();
@@ -96,24 +116,36 @@ export class WorkspaceSwitcher {
     }
 
     return workspaces
-      .map(
-        (workspace) => `
-      <div class="workspace-item" data-workspace-id="${this.escapeHtml(workspace.id)}">
+      .map((workspace, index) => {
+        const id = this.escapeHtml(workspace.id);
+        const color = workspace.color ? WORKSPACE_COLORS[workspace.color] : null;
+        const shortcut = index < SHORTCUT_COUNT ? `<kbd class="workspace-shortcut">Ctrl+${index + 1}</kbd>` : '';
+        return `
+      <div class="workspace-item${workspace.open ? ' workspace-open' : ''}" data-workspace-id="${id}">
+        <span class="workspace-color" style="background: ${color ?? 'transparent'}"></span>
         <div class="workspace-info">
-          <div class="workspace-name">${this.escapeHtml(workspace.name)}</div>
+          <div class="workspace-name">
+            ${workspace.icon ? `<span class="workspace-icon">${this.escapeHtml(workspace.icon)}</span>` : ''}
+            ${this.escapeHtml(workspace.name)}
+            ${shortcut}
+          </div>
           <div class="workspace-url">${this.escapeHtml(workspace.url)}</div>
         </div>
         <div class="workspace-actions">
-          <button class="btn btn-small btn-primary switch-workspace-btn" data-workspace-id="${this.escapeHtml(workspace.id)}">
-            Switch
+          <button class="btn btn-small btn-primary switch-workspace-btn" data-workspace-id="${id}">
+            ${workspace.open ? 'Switch' : 'Open'}
           </button>
-          <button class="btn btn-small btn-danger remove-workspace-btn" data-workspace-id="${this.escapeHtml(workspace.id)}">
-            Remove
+          <button class="btn btn-small edit-workspace-btn" data-workspace-id="${id}">Edit</button>
+          <button class="btn btn-small move-workspace-btn" data-workspace-id="${id}" data-offset="-1"${index === 0 ? ' disabled' : ''} title="Move up">↑</button>
+          <button class="btn btn-small move-workspace-btn" data-workspace-id="${id}" data-offset="1"${index === workspaces.length - 1 ? ' disabled' : ''} title="Move down">↓</button>
+          ${workspace.open ? `<button class="btn btn-small close-workspace-btn" data-workspace-id="${id}">Close</button>` : ''}
+          <button class="btn btn-small btn-danger delete-workspace-btn" data-workspace-id="${id}">
+            Delete
           </button>
         </div>
       </div>
-    `,
-      )
+    `;
+      })
       .join('');
   }
 
@@ -132,36 +164,11 @@ export class WorkspaceSwitcher {
       createBtn.addEventListener('click', () => this.showCreateWorkspaceDialog());
     }
 
-    // Switch workspace buttons
-    this.overlay.addEventListener('click', async (e) => {
-      const target = e.target as HTMLElement;
-      
-      if (target.classList.contains('switch-workspace-btn')) {
-        const workspaceId = target.dataset.workspaceId;
-        if (workspaceId) {
-          try {
-            await ipc.switchWorkspace(workspaceId);
-            this.hide();
-          } catch (error) {
-            console.error('Failed to switch workspace:', error);
-            alert('Failed to switch workspace. Please try again.');
-          }
-        }
-      }
-      
-      if (target.classList.contains('remove-workspace-btn')) {
-        const workspaceId = target.dataset.workspaceId;
-        if (workspaceId && confirm('Remove this workspace? The window will be closed.')) {
-          try {
-            await ipc.closeWorkspace(workspaceId);
-            await this.show(); // Refresh list
-          } catch (error) {
-            console.error('Failed to remove workspace:', error);
-            alert('Failed to remove workspace. Please try again.');
-          }
-        }
-      }
-    });
+    // Workspace buttons; the overlay outlives re-renders, so listen once
+    if (!this.listenersAttached) {
+      this.listenersAttached = true;
+      this.overlay.addEventListener('click', (e) => this.handleWorkspaceAction(e));
+    }
 
     // Search functionality
     const searchInput = this.overlay.querySelector('#workspace-search-input') as HTMLInputElement;
@@ -187,6 +194,95 @@ export class WorkspaceSwitcher {
     });
   }
 
+  private async handleWorkspaceAction(e: Event): Promise<void> {
+    const target = e.target as HTMLElement;
+    const workspaceId = target.dataset.workspaceId;
+    if (!workspaceId) return;
+
+    if (target.classList.contains('switch-workspace-btn')) {
+      try {
+        await ipc.switchWorkspace(workspaceId);
+        this.hide();
+      } catch (error) {
+        console.error('Failed to switch workspace:', error);
+        alert('Failed to switch workspace. Please try again.');
+      }
+    }
+
+    if (target.classList.contains('edit-workspace-btn')) {
+      await this.showEditWorkspaceDialog(workspaceId);
+    }
+
+    if (target.classList.contains('move-workspace-btn')) {
+      const ids = this.workspaces.map((workspace) => workspace.id);
+      const from = ids.indexOf(workspaceId);
+      const to = from + Number(target.dataset.offset);
+      if (from < 0 || to < 0 || to >= ids.length) return;
+      ids.splice(to, 0, ...ids.splice(from, 1));
+      try {
+        this.workspaces = await ipc.reorderWorkspaces(ids);
+        this.render();
+      } catch (error) {
+        console.error('Failed to reorder workspaces:', error);
+      }
+    }
+
+    if (target.classList.contains('close-workspace-btn')) {
+      try {
+        await ipc.closeWorkspace(workspaceId);
+        await this.show(); // Refresh list
+      } catch (error) {
+        console.error('Failed to close workspace:', error);
+      }
+    }
+
+    if (target.classList.contains('delete-workspace-btn')) {
+      if (confirm('Delete this workspace? Its window will be closed and its logins and browser data removed.')) {
+        try {
+          await ipc.deleteWorkspace(workspaceId);
+          await this.show(); // Refresh list
+        } catch (error) {
+          console.error('Failed to delete workspace:', error);
+          alert('Failed to delete workspace. Please try again.');
+        }
+      }
+    }
+  }
+
+  private async showEditWorkspaceDialog(workspaceId: string): Promise<void> {
+    const workspace = this.workspaces.find((w) => w.id === workspaceId);
+    if (!workspace) return;
+
+    const name = prompt('Workspace name:', workspace.name);
+    if (name === null) return;
+    const url = prompt('Notion URL:', workspace.url);
+    if (url === null) return;
+    const icon = prompt('Icon, e.g. an emoji (leave empty for none):', workspace.icon ?? '');
+    if (icon === null) return;
+    const colors = Object.keys(WORKSPACE_COLORS).join(', ');
+    const color = prompt(`Color (${colors}; leave empty for none):`, workspace.color ?? '');
+    if (color === null) return;
+
+    const colorValue = color.trim().toLowerCase();
+    if (colorValue && !(colorValue in WORKSPACE_COLORS)) {
+      alert(`Unknown color: ${color}`);
+      return;
+    }
+
+    try {
+      await ipc.updateWorkspace(workspaceId, {
+        name,
+        url,
+        icon,
+        color: colorValue ? (colorValue as ipc.WorkspaceColor) : null,
+      });
+      await this.show(); // Refresh list
+    } catch (error) {
+      console.error('Failed to update workspace:', error);
+      alert(`Failed to update workspace: ${error}`);
+    }
+  }
+
   private filterWorkspaces(query: string): void {
     const list = this.overlay?.querySelector('#workspace-list');
     if (!list) return;
@@ -222,10 +318,30 @@ export class WorkspaceSwitcher {
       if ((e.ctrlKey || e.metaKey) && e.shiftKey && e.key === 'w') {
         e.preventDefault();
         this.toggle();
+        return;
+      }
+
+      // Ctrl+1..9 or Cmd+1..9 to switch to a workspace by position
+      const position = workspaceShortcutPosition(e);
+      if (position !== null) {
+        e.preventDefault();
+        void this.switchToPosition(position);
       }
     });
   }
 
+  private async switchToPosition(position: number): Promise<void> {
+    try {
+      const workspaces = await ipc.listWorkspaces();
+      const workspace = workspaces[position - 1];
+      if (workspace) {
+        await ipc.switchWorkspace(workspace.id);
+      }
+    } catch (error) {
+      console.error('Failed to switch workspace:', error);
+    }
+  }
+
   private escapeHtml(text: string): string {
     const div = document.createElement('div');
     div.textContent = text;
@@ -233,6 +349,23 @@ export class WorkspaceSwitcher {
   }
 }
 
+/**
+ * Gets the workspace position (from 1) a Ctrl+1..9 or Cmd+1..9 key press
+ * switches to, or null for any other key.
+ */
+export function workspaceShortcutPosition(
+  event: Pick<KeyboardEvent, 'code' | 'ctrlKey' | 'metaKey' | 'shiftKey' | 'altKey'>
+): number | null {
+  if (!(event.ctrlKey || event.metaKey) || event.shiftKey || event.altKey) return null;
+
+  // The physical digit keys, whatever the keyboard layout prints on them
+  const match = /^Digit([1-9])$/.exec(event.code);
+  if (!match) return null;
+
+  const position = Number(match[1]);
+  return position <= SHORTCUT_COUNT ? position : null;
+}
+
 /**
  * Gets the global workspace switcher instance.
  */
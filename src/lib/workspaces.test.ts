@@ -4,6 +4,7 @@
 
 import { describe, it, expect, vi, beforeEach } from 'vitest';
 import * as ipc from './ipc';
+import { workspaceShortcutPosition } from './workspace-switcher';
 
 // Mock Tauri API
 vi.mock('@tauri-apps/api/core', () => ({
@@ -80,6 +81,69 @@ describe('Workspaces', () => {
     });
   });
 
+  it('should delete workspace', async () => {
+    const { invoke } = await import('@tauri-apps/api/core');
+    vi.mocked(invoke).mockResolvedValue(undefined);
+
+    await ipc.deleteWorkspace('workspace-id');
+
+    expect(invoke).toHaveBeenCalledWith('delete_workspace', {
+      workspaceId: 'workspace-id',
+    });
+  });
+
+  it('should update workspace', async () => {
+    const { invoke } = await import('@tauri-apps/api/core');
+    const updated: ipc.Workspace = {
+      id: 'workspace-id',
+      name: 'Work',
+      url: 'https://www.notion.so/work',
+      zoom_level: 1.0,
+      color: 'blue',
+      icon: '🚀',
+    };
+    vi.mocked(invoke).mockResolvedValue(updated);
+
+    const workspace = await ipc.updateWorkspace('workspace-id', { name: 'Work', color: 'blue', icon: '🚀' });
+
+    expect(workspace).toEqual(updated);
+    expect(invoke).toHaveBeenCalledWith('update_workspace', {
+      workspaceId: 'workspace-id',
+      update: { name: 'Work', color: 'blue', icon: '🚀' },
+    });
+  });
+
+  it('should reorder workspaces', async () => {
+    const { invoke } = await import('@tauri-apps/api/core');
+    vi.mocked(invoke).mockResolvedValue([]);
+
+    await ipc.reorderWorkspaces(['b', 'a']);
+
+    expect(invoke).toHaveBeenCalledWith('reorder_workspaces', {
+      workspaceIds: ['b', 'a'],
+    });
+  });
+
+  it('should map Ctrl+1..9 to workspace positions', () => {
+    const key = (code: string, modifiers: Partial<KeyboardEvent> = {}) => ({
+      code,
+      ctrlKey: true,
+      metaKey: false,
+      shiftKey: false,
+      altKey: false,
+      ...modifiers,
+    });
+
+    expect(workspaceShortcutPosition(key('Digit1'))).toBe(1);
+    expect(workspaceShortcutPosition(key('Digit9', { ctrlKey: false, metaKey: true }))).toBe(9);
+    expect(workspaceShortcutPosition(key('Digit0'))).toBeNull();
+    expect(workspaceShortcutPosition(key('KeyW'))).toBeNull();
+    expect(workspaceShortcutPosition(key('Digit2', { ctrlKey: false }))).toBeNull();
+    // Notion uses Ctrl+Alt+digit and Ctrl+Shift+digit for block types
+    expect(workspaceShortcutPosition(key('Digit1', { altKey: true }))).toBeNull();
+    expect(workspaceShortcutPosition(key('Digit1', { shiftKey: true }))).toBeNull();
+  });
+
   it('should handle empty workspace list', async () => {
     const { invoke } = await import('@tauri-apps/api/core');
     vi.mocked(invoke).mockResolvedValue([]);